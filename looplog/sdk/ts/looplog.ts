@@ -65,7 +65,7 @@ export class LoopLogClient {
     });
   }
 
-  private async request<T = unknown>(path: string, init: RequestInit): Promise<T> {
+  private async request<T = unknown>(path: string, init: RequestInit, attempt = 0): Promise<T> {
     const controller = new AbortController();
     const timer = setTimeout(() => controller.abort(), this.timeoutMs);
     try {
@@ -73,6 +73,12 @@ export class LoopLogClient {
         ...init,
         signal: controller.signal,
       });
+      // The server's write queue is full; back off as told and resend a few times.
+      if (response.status === 429 && attempt < 3) {
+        const seconds = Number(response.headers.get("retry-after") ?? "1");
+        await new Promise((resolve) => setTimeout(resolve, seconds * 1000));
+        return this.request<T>(path, init, attempt + 1);
+      }
       if (!response.ok) {
         throw new Error(`looplog request failed: ${response.status} ${await response.text()}`);
       }
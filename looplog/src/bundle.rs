@@ -0,0 +1,213 @@
+use crate::db::{ImportStats, SqliteStorage};
+use crate::error::{Error, Result};
+use crate::protocol::SourceLocation;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+const BUNDLE_FORMAT: &str = "looplog-bundle";
+const BUNDLE_VERSION: u32 = 1;
+
+/// A run with everything needed to recreate it on another machine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleRun {
+    pub id: String,
+    pub started_at_ms: i64,
+    pub ended_at_ms: Option<i64>,
+    pub status: String,
+    pub exit_code: Option<i32>,
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub argv: Vec<String>,
+    pub tag: Option<String>,
+    pub source: Option<String>,
+    pub client_id: Option<String>,
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub meta: BTreeMap<String, Value>,
+    /// Written as separate `line` records in NDJSON bundles.
+    #[serde(skip)]
+    pub lines: Vec<BundleLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleLine {
+    pub seq: i64,
+    pub ts_ms: i64,
+    pub stream: String,
+    pub level: String,
+    pub event: Option<String>,
+    pub text: String,
+    #[serde(default)]
+    pub meta: BTreeMap<String, Value>,
+    pub location: Option<SourceLocation>,
+}
+
+/// NDJSON bundle layout: one `header`, then each `run` followed by its `line`s.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum BundleRecord {
+    Header { format: String, version: u32 },
+    Run(BundleRun),
+    Line { run_id: String, line: BundleLine },
+}
+
+/// `.db`, `.sqlite` and `.sqlite3` bundles are SQLite files with the normal looplog
+/// schema; anything else is NDJSON.
+fn is_sqlite_bundle(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("db" | "sqlite" | "sqlite3")
+    )
+}
+
+pub fn export(storage: &mut SqliteStorage, run_ids: &[String], out: &Path) -> Result<usize> {
+    let runs = storage.export_runs(run_ids)?;
+    if is_sqlite_bundle(out) {
+        if out.exists() {
+            return Err(Error::Message(format!(
+                "refusing to overwrite existing bundle `{}`",
+                out.display()
+            )));
+        }
+        SqliteStorage::open(out)?.import_runs(&runs)?;
+    } else {
+        write_ndjson(out, &runs)?;
+    }
+    Ok(runs.len())
+}
+
+pub fn import(storage: &mut SqliteStorage, path: &Path) -> Result<ImportStats> {
+    let runs = if is_sqlite_bundle(path) {
+        let mut bundle = SqliteStorage::open(path)?;
+        let ids = bundle.all_run_ids()?;
+        bundle.export_runs(&ids)?
+    } else {
+        read_ndjson(path)?
+    };
+    storage.import_runs(&runs)
+}
+
+fn write_ndjson(path: &Path, runs: &[BundleRun]) -> Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    let header = BundleRecord::Header {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+    };
+    writeln!(out, "{}", serde_json::to_string(&header)?)?;
+    for run in runs {
+        writeln!(
+            out,
+            "{}",
+            serde_json::to_string(&BundleRecord::Run(run.clone()))?
+        )?;
+        for line in &run.lines {
+            let record = BundleRecord::Line {
+                run_id: run.id.clone(),
+                line: line.clone(),
+            };
+            writeln!(out, "{}", serde_json::to_string(&record)?)?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn read_ndjson(path: &Path) -> Result<Vec<BundleRun>> {
+    let mut runs: Vec<BundleRun> = Vec::new();
+    let mut saw_header = false;
+    for (index, raw) in BufReader::new(File::open(path)?).lines().enumerate() {
+        let raw = raw?;
+        if raw.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<BundleRecord>(&raw)? {
+            BundleRecord::Header { format, version } => {
+                if format != BUNDLE_FORMAT || version > BUNDLE_VERSION {
+                    return Err(Error::Message(format!(
+                        "unsupported bundle `{}` version {}",
+                        format, version
+                    )));
+                }
+                saw_header = true;
+            }
+            BundleRecord::Run(run) => runs.push(run),
+            BundleRecord::Line { run_id, line } => match runs.last_mut() {
+                Some(run) if run.id == run_id => run.lines.push(line),
+                _ => {
+                    return Err(Error::Message(format!(
+                        "bundle line {} belongs to run `{}` but does not follow it",
+                        index + 1,
+                        run_id
+                    )))
+                }
+            },
+        }
+    }
+    if !saw_header {
+        return Err(Error::Message(format!(
+            "`{}` is not a looplog bundle",
+            path.display()
+        )));
+    }
+    Ok(runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::NewRun;
+    use crate::protocol::AppendLine;
+    use tempfile::TempDir;
+
+    #[test]
+    fn ndjson_and_sqlite_bundles_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let mut source = SqliteStorage::open(&dir.path().join("source.db")).unwrap();
+        let mut meta = BTreeMap::new();
+        meta.insert("appid".to_string(), Value::String("wx123".to_string()));
+        let run_id = source
+            .start_run(NewRun {
+                tag: Some("repro".to_string()),
+                source: None,
+                cwd: None,
+                argv: vec!["npm".to_string(), "test".to_string()],
+                client_id: None,
+                kind: None,
+                meta,
+            })
+            .unwrap();
+        source
+            .append_lines(
+                &run_id,
+                &[AppendLine {
+                    level: Some("error".to_string()),
+                    text: "TypeError: boom".to_string(),
+                    ..Default::default()
+                }],
+            )
+            .unwrap();
+
+        for name in ["bundle.ndjson", "bundle.db"] {
+            let path = dir.path().join(name);
+            assert_eq!(
+                export(&mut source, std::slice::from_ref(&run_id), &path).unwrap(),
+                1
+            );
+
+            let mut target =
+                SqliteStorage::open(&dir.path().join(format!("{name}.target"))).unwrap();
+            let stats = import(&mut target, &path).unwrap();
+            assert_eq!((stats.imported_runs, stats.imported_lines), (1, 1));
+            let run = target.get_run(&run_id).unwrap().unwrap();
+            assert!(run.pinned);
+            assert_eq!(run.appid.as_deref(), Some("wx123"));
+            assert_eq!(target.run_signatures(&run_id).unwrap().len(), 1);
+
+            assert_eq!(import(&mut target, &path).unwrap().skipped_runs, 1);
+        }
+    }
+}
@@ -1,18 +1,31 @@
+use crate::bundle::{BundleLine, BundleRun};
 use crate::error::{Error, Result};
+use crate::fingerprint;
 use crate::meta;
-use crate::protocol::{AppendLine, FinishRunRequest};
+use crate::parser::{self, Level};
+use crate::protocol::{AppendLine, FinishRunRequest, SourceLocation};
+use crate::redact::Redactor;
 use rusqlite::types::Value as SqlValue;
-use rusqlite::{params, params_from_iter, Connection};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
 use serde::Serialize;
 use serde_json::Value;
+use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
 pub const DEFAULT_KEEP_HOURS: u64 = 24;
+/// Reported as `search_backend` by `grep --json` and `GET /v1/search`.
+pub const SEARCH_BACKEND: &str = "like";
+/// Upper bound for any retention window, so a typo cannot keep logs forever.
+pub const MAX_KEEP_HOURS: u64 = 24 * 30;
+/// Automatic cleanup sums every stored line to enforce `max_bytes`, so across all
+/// commands sharing a database it does so at most this often. `clean` always checks.
+const SIZE_CHECK_INTERVAL_MS: i64 = 60_000;
 
 #[derive(Debug, Clone)]
 pub struct NewRun {
@@ -36,6 +49,8 @@ pub struct QueryFilter {
     pub session: Option<String>,
     pub trace: Option<String>,
     pub since_ms: Option<i64>,
+    /// Only match lines at or above this severity; `list` keeps runs that have any.
+    pub min_level: Option<Level>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -54,6 +69,7 @@ pub struct RunRecord {
     pub session: Option<String>,
     pub trace_id: Option<String>,
     pub line_count: i64,
+    pub pinned: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -65,6 +81,7 @@ pub struct LineRecord {
     pub level: String,
     pub event: Option<String>,
     pub text: String,
+    pub location: Option<SourceLocation>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -73,16 +90,102 @@ pub struct SearchHit {
     pub line: LineRecord,
 }
 
+/// One error signature within a run, as compared by `looplog diff`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSignature {
+    pub fingerprint: String,
+    pub signature: String,
+    pub count: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+/// One error signature aggregated across runs, as listed by `looplog top-errors`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorStat {
+    pub fingerprint: String,
+    pub signature: String,
+    pub count: i64,
+    pub runs: i64,
+    pub first_seen: String,
+    pub last_seen: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct CleanStats {
     pub deleted_runs: usize,
     pub deleted_lines: usize,
     pub keep_hours: u64,
+    /// Runs deleted (oldest first) to get back under the size cap.
+    pub evicted_for_size: usize,
+}
+
+/// Counts for the runs matching a filter, plus whole-database size figures.
+#[derive(Debug, Clone, Serialize)]
+pub struct StoreStats {
+    pub runs: i64,
+    pub running_runs: i64,
+    pub pinned_runs: i64,
+    pub lines: i64,
+    pub lines_by_level: BTreeMap<String, i64>,
+    pub oldest_run: Option<String>,
+    pub newest_run: Option<String>,
+    pub stored_bytes: u64,
+    pub db_bytes: u64,
+    pub fts_available: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionPolicy {
+    pub default_keep_hours: u64,
+    pub tag_keep_hours: BTreeMap<String, u64>,
+    pub max_bytes: Option<u64>,
+    pub pinned_runs: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct ImportStats {
+    pub imported_runs: usize,
+    pub skipped_runs: usize,
+    pub imported_lines: usize,
+}
+
+/// Runs sharing a session or trace id, and their lines in time order with the
+/// timestamp in milliseconds; see [`SqliteStorage::correlated`].
+#[derive(Debug, Clone, Default)]
+pub struct Correlated {
+    pub runs: Vec<RunRecord>,
+    pub lines: Vec<(i64, LineRecord)>,
+}
+
+/// One write handled by the server's writer thread; see [`SqliteStorage::apply_batch`].
+#[derive(Debug, Clone)]
+pub enum WriteOp {
+    StartRun(NewRun),
+    AppendLines {
+        run_id: String,
+        lines: Vec<AppendLine>,
+    },
+    FinishRun {
+        run_id: String,
+        finish: FinishRunRequest,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteOutput {
+    RunId(String),
+    Lines(usize),
+    Finished,
 }
 
 pub struct SqliteStorage {
     conn: Connection,
     fts_available: bool,
+    auto_clean: bool,
+    redactor: Option<Redactor>,
 }
 
 impl SqliteStorage {
@@ -96,9 +199,13 @@ impl SqliteStorage {
         let conn = Connection::open(path)?;
         conn.pragma_update(None, "journal_mode", "WAL")?;
         conn.pragma_update(None, "foreign_keys", "ON")?;
+        // `serve` writes from its own thread while CLI commands read and clean.
+        conn.busy_timeout(Duration::from_secs(5))?;
         let mut storage = Self {
             conn,
             fts_available: false,
+            auto_clean: true,
+            redactor: None,
         };
         storage.migrate()?;
         Ok(storage)
@@ -124,7 +231,8 @@ impl SqliteStorage {
                 page TEXT,
                 session TEXT,
                 trace_id TEXT,
-                line_count INTEGER NOT NULL DEFAULT 0
+                line_count INTEGER NOT NULL DEFAULT 0,
+                pinned INTEGER NOT NULL DEFAULT 0
             );
 
             CREATE TABLE IF NOT EXISTS log_lines (
@@ -136,10 +244,25 @@ impl SqliteStorage {
                 event TEXT,
                 text TEXT NOT NULL,
                 meta_json TEXT NOT NULL,
+                src_file TEXT,
+                src_line INTEGER,
+                src_col INTEGER,
+                signature TEXT,
+                fingerprint TEXT,
                 PRIMARY KEY (run_id, seq),
                 FOREIGN KEY (run_id) REFERENCES runs(id) ON DELETE CASCADE
             );
 
+            CREATE TABLE IF NOT EXISTS retention_rules (
+                tag TEXT PRIMARY KEY,
+                keep_hours INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+
             CREATE TABLE IF NOT EXISTS run_meta (
                 run_id TEXT NOT NULL,
                 key TEXT NOT NULL,
@@ -153,9 +276,21 @@ impl SqliteStorage {
             CREATE INDEX IF NOT EXISTS idx_runs_kind_appid_started ON runs(kind, appid, started_at_ms);
             CREATE INDEX IF NOT EXISTS idx_runs_page_started ON runs(page, started_at_ms);
             CREATE INDEX IF NOT EXISTS idx_lines_run_seq ON log_lines(run_id, seq);
+            CREATE INDEX IF NOT EXISTS idx_lines_run_level ON log_lines(run_id, level);
             "#,
         )?;
 
+        // Databases created before structured parsing lack the source location columns.
+        self.ensure_column("log_lines", "src_file", "TEXT")?;
+        self.ensure_column("log_lines", "src_line", "INTEGER")?;
+        self.ensure_column("log_lines", "src_col", "INTEGER")?;
+        self.ensure_column("log_lines", "signature", "TEXT")?;
+        self.ensure_column("log_lines", "fingerprint", "TEXT")?;
+        self.ensure_column("runs", "pinned", "INTEGER NOT NULL DEFAULT 0")?;
+        self.conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_lines_fingerprint ON log_lines(fingerprint, ts_ms)",
+        )?;
+
         self.fts_available = self
             .conn
             .execute(
@@ -166,45 +301,26 @@ impl SqliteStorage {
         Ok(())
     }
 
-    pub fn start_run(&mut self, run: NewRun) -> Result<String> {
-        self.clean(DEFAULT_KEEP_HOURS, false)?;
-
-        let id = Uuid::new_v4().to_string();
-        let meta = meta::normalize_map(&run.meta);
-        let common = meta::extract_common(run.kind.as_deref(), &meta);
-        let argv_json = serde_json::to_string(&run.argv)?;
+    fn ensure_column(&mut self, table: &str, column: &str, decl: &str) -> Result<()> {
+        let exists = {
+            let mut stmt = self.conn.prepare(&format!("PRAGMA table_info({table})"))?;
+            let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+            names
+                .collect::<std::result::Result<Vec<_>, _>>()?
+                .iter()
+                .any(|name| name == column)
+        };
+        if !exists {
+            self.conn
+                .execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {column} {decl}"))?;
+        }
+        Ok(())
+    }
 
+    pub fn start_run(&mut self, run: NewRun) -> Result<String> {
+        self.auto_clean()?;
         let tx = self.conn.transaction()?;
-        tx.execute(
-            r#"
-            INSERT INTO runs (
-                id, started_at_ms, status, cwd, argv_json, tag, source, client_id,
-                kind, project_path, appid, page, session, trace_id
-            )
-            VALUES (?1, ?2, 'running', ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
-            "#,
-            params![
-                id,
-                now_ms(),
-                run.cwd,
-                argv_json,
-                run.tag,
-                run.source,
-                run.client_id,
-                common.kind,
-                common.project_path,
-                common.appid,
-                common.page,
-                common.session,
-                common.trace_id,
-            ],
-        )?;
-        for (key, value) in meta {
-            tx.execute(
-                "INSERT OR REPLACE INTO run_meta (run_id, key, value_json) VALUES (?1, ?2, ?3)",
-                params![id, key, serde_json::to_string(&value)?],
-            )?;
-        }
+        let id = insert_run(&tx, self.redactor.as_ref(), run)?;
         tx.commit()?;
         Ok(id)
     }
@@ -213,62 +329,77 @@ impl SqliteStorage {
         if lines.is_empty() {
             return Ok(0);
         }
-
-        let mut next_seq: i64 = self.conn.query_row(
-            "SELECT COALESCE(MAX(seq), 0) + 1 FROM log_lines WHERE run_id = ?1",
-            [run_id],
-            |row| row.get(0),
+        let tx = self.conn.transaction()?;
+        let count = insert_lines(
+            &tx,
+            self.fts_available,
+            self.redactor.as_ref(),
+            run_id,
+            lines,
         )?;
+        tx.commit()?;
+        Ok(count)
+    }
+
+    pub fn finish_run(&mut self, run_id: &str, finish: &FinishRunRequest) -> Result<()> {
+        update_finished(&self.conn, run_id, finish)
+    }
 
+    /// Apply many writes in one transaction. Each op runs in its own savepoint, so a
+    /// bad op (unknown run, bad timestamp) fails alone without rolling back the rest.
+    pub fn apply_batch(&mut self, ops: Vec<WriteOp>) -> Result<Vec<Result<WriteOutput>>> {
         let fts_available = self.fts_available;
-        let tx = self.conn.transaction()?;
-        for line in lines {
-            let ts_ms = match line.ts.as_deref() {
-                Some(ts) => parse_rfc3339_ms(ts)?,
-                None => now_ms(),
+        let redactor = self.redactor.as_ref();
+        let mut tx = self.conn.transaction()?;
+        let mut results = Vec::with_capacity(ops.len());
+        for op in ops {
+            let sp = tx.savepoint()?;
+            let result = match op {
+                WriteOp::StartRun(run) => insert_run(&sp, redactor, run).map(WriteOutput::RunId),
+                WriteOp::AppendLines { run_id, lines } => {
+                    insert_lines(&sp, fts_available, redactor, &run_id, &lines)
+                        .map(WriteOutput::Lines)
+                }
+                WriteOp::FinishRun { run_id, finish } => {
+                    update_finished(&sp, &run_id, &finish).map(|()| WriteOutput::Finished)
+                }
             };
-            let stream = line.stream.as_deref().unwrap_or("stdout");
-            let level = line.level.as_deref().unwrap_or("info");
-            let meta_json = serde_json::to_string(&line.meta)?;
-            tx.execute(
-                r#"
-                INSERT INTO log_lines (run_id, seq, ts_ms, stream, level, event, text, meta_json)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
-                "#,
-                params![run_id, next_seq, ts_ms, stream, level, line.event, line.text, meta_json],
-            )?;
-            if fts_available {
-                tx.execute(
-                    "INSERT INTO log_fts (run_id, seq, text) VALUES (?1, ?2, ?3)",
-                    params![run_id, next_seq, line.text],
-                )?;
+            if result.is_ok() {
+                sp.commit()?;
             }
-            next_seq += 1;
+            results.push(result);
         }
-        tx.execute(
-            "UPDATE runs SET line_count = line_count + ?2 WHERE id = ?1",
-            params![run_id, lines.len() as i64],
-        )?;
         tx.commit()?;
-        Ok(lines.len())
+        Ok(results)
     }
 
-    pub fn finish_run(&mut self, run_id: &str, finish: &FinishRunRequest) -> Result<()> {
-        let status = finish.status.as_deref().unwrap_or("ok");
-        self.conn.execute(
-            "UPDATE runs SET ended_at_ms = ?2, status = ?3, exit_code = ?4 WHERE id = ?1",
-            params![run_id, now_ms(), status, finish.exit_code],
-        )?;
-        Ok(())
+    /// Mask secrets in run argv and meta, and in appended line text, events and meta.
+    /// Intake paths set this unless `--no-redact` was given; bundle imports are stored
+    /// as exported.
+    pub fn set_redactor(&mut self, redactor: Option<Redactor>) {
+        self.redactor = redactor;
+    }
+
+    /// Stop read and write calls from cleaning expired runs. The server does this for
+    /// its connections and cleans on a timer from the writer thread instead.
+    pub fn disable_auto_clean(&mut self) {
+        self.auto_clean = false;
     }
 
     pub fn list_runs(&mut self, filter: &QueryFilter, limit: usize) -> Result<Vec<RunRecord>> {
-        self.clean(DEFAULT_KEEP_HOURS, false)?;
-        let (where_sql, mut values) = filter_sql(filter, "runs");
+        self.auto_clean()?;
+        let (mut where_sql, mut values) = filter_sql(filter, "runs");
+        if let Some(level) = filter.min_level {
+            let clause = format!(
+                "EXISTS (SELECT 1 FROM log_lines l WHERE l.run_id = runs.id AND {})",
+                level_clause("l", level, &mut values)
+            );
+            where_sql = and_where(where_sql, &clause);
+        }
         values.push(SqlValue::Integer(limit as i64));
         let sql = format!(
             "SELECT id, started_at_ms, ended_at_ms, status, exit_code, tag, source, kind, \
-             project_path, appid, page, session, trace_id, line_count FROM runs {where_sql} \
+             project_path, appid, page, session, trace_id, line_count, pinned FROM runs {where_sql} \
              ORDER BY started_at_ms DESC LIMIT ?"
         );
         let mut stmt = self.conn.prepare(&sql)?;
@@ -277,30 +408,83 @@ impl SqliteStorage {
             .map_err(Into::into)
     }
 
+    pub fn get_run(&mut self, run_id: &str) -> Result<Option<RunRecord>> {
+        self.conn
+            .query_row(
+                "SELECT id, started_at_ms, ended_at_ms, status, exit_code, tag, source, kind, \
+                 project_path, appid, page, session, trace_id, line_count, pinned FROM runs \
+                 WHERE id = ?1",
+                [run_id],
+                row_to_run,
+            )
+            .optional()
+            .map_err(Into::into)
+    }
+
+    /// Lines with `seq > after_seq`, oldest first. Used by follow mode to poll for new output.
+    pub fn lines_after(
+        &mut self,
+        run_id: &str,
+        after_seq: i64,
+        stream: Option<&str>,
+        min_level: Option<Level>,
+    ) -> Result<Vec<LineRecord>> {
+        let mut values = vec![
+            SqlValue::Text(run_id.to_string()),
+            SqlValue::Integer(after_seq),
+        ];
+        let mut extra = String::new();
+        if let Some(stream) = stream {
+            values.push(SqlValue::Text(stream.to_string()));
+            extra.push_str(" AND stream = ?");
+        }
+        if let Some(level) = min_level {
+            extra = format!(
+                "{extra} AND {}",
+                level_clause("log_lines", level, &mut values)
+            );
+        }
+        let sql = format!(
+            "SELECT {LINE_COLUMNS} FROM log_lines WHERE run_id = ? AND seq > ? {extra} \
+             ORDER BY seq ASC"
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), row_to_line)?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
     pub fn show_lines(
         &mut self,
         run_id: &str,
         tail: Option<usize>,
         stream: Option<&str>,
+        min_level: Option<Level>,
     ) -> Result<Vec<LineRecord>> {
-        self.clean(DEFAULT_KEEP_HOURS, false)?;
+        self.auto_clean()?;
         let mut values = vec![SqlValue::Text(run_id.to_string())];
-        let stream_clause = if let Some(stream) = stream {
+        let mut stream_clause = if let Some(stream) = stream {
             values.push(SqlValue::Text(stream.to_string()));
-            " AND stream = ?"
+            " AND stream = ?".to_string()
         } else {
-            ""
+            String::new()
         };
+        if let Some(level) = min_level {
+            stream_clause = format!(
+                "{stream_clause} AND {}",
+                level_clause("log_lines", level, &mut values)
+            );
+        }
 
         let sql = if let Some(tail) = tail {
             values.push(SqlValue::Integer(tail as i64));
             format!(
-                "SELECT * FROM (SELECT run_id, seq, ts_ms, stream, level, event, text FROM log_lines \
+                "SELECT * FROM (SELECT {LINE_COLUMNS} FROM log_lines \
                  WHERE run_id = ? {stream_clause} ORDER BY seq DESC LIMIT ?) ORDER BY seq ASC"
             )
         } else {
             format!(
-                "SELECT run_id, seq, ts_ms, stream, level, event, text FROM log_lines \
+                "SELECT {LINE_COLUMNS} FROM log_lines \
                  WHERE run_id = ? {stream_clause} ORDER BY seq ASC"
             )
         };
@@ -317,7 +501,7 @@ impl SqliteStorage {
         filter: &QueryFilter,
         limit: usize,
     ) -> Result<Vec<SearchHit>> {
-        self.clean(DEFAULT_KEEP_HOURS, false)?;
+        self.auto_clean()?;
         let mut local_filter = filter.clone();
         let (where_sql, mut values) = filter_sql(&local_filter, "r");
         let terms = pattern
@@ -336,17 +520,18 @@ impl SqliteStorage {
             values.push(SqlValue::Text(format!("%{}%", term)));
         }
         let term_sql = term_clauses.join(" OR ");
-        let combined_where = if where_sql.is_empty() {
-            format!("WHERE ({term_sql})")
-        } else {
-            format!("{where_sql} AND ({term_sql})")
-        };
+        let mut combined_where = and_where(where_sql, &format!("({term_sql})"));
+        if let Some(level) = filter.min_level {
+            let clause = level_clause("l", level, &mut values);
+            combined_where = and_where(combined_where, &clause);
+        }
         values.push(SqlValue::Integer(limit as i64));
 
         let sql = format!(
             "SELECT r.id, r.started_at_ms, r.ended_at_ms, r.status, r.exit_code, r.tag, r.source, \
-             r.kind, r.project_path, r.appid, r.page, r.session, r.trace_id, r.line_count, \
-             l.run_id, l.seq, l.ts_ms, l.stream, l.level, l.event, l.text \
+             r.kind, r.project_path, r.appid, r.page, r.session, r.trace_id, r.line_count, r.pinned, \
+             l.run_id, l.seq, l.ts_ms, l.stream, l.level, l.event, l.text, \
+             l.src_file, l.src_line, l.src_col \
              FROM log_lines l JOIN runs r ON r.id = l.run_id {combined_where} \
              ORDER BY l.ts_ms DESC LIMIT ?"
         );
@@ -354,7 +539,7 @@ impl SqliteStorage {
         let rows = stmt.query_map(params_from_iter(values.iter()), |row| {
             Ok(SearchHit {
                 run: row_to_run_offset(row, 0)?,
-                line: row_to_line_offset(row, 14)?,
+                line: row_to_line_offset(row, 15)?,
             })
         })?;
         local_filter.run_id.take();
@@ -362,20 +547,298 @@ impl SqliteStorage {
             .map_err(Into::into)
     }
 
+    /// Runs and lines that share a session or trace id, through the run columns or
+    /// `session`/`trace_id` in line meta (OTLP records carry their own trace ids).
+    pub fn correlated(
+        &mut self,
+        session: Option<&str>,
+        trace: Option<&str>,
+        min_level: Option<Level>,
+    ) -> Result<Correlated> {
+        self.auto_clean()?;
+        let mut run_clauses = Vec::new();
+        let mut line_clauses = Vec::new();
+        let mut keys = Vec::new();
+        for (column, value) in [("session", session), ("trace_id", trace)] {
+            if let Some(value) = value {
+                run_clauses.push(format!("r.{column} = ?"));
+                line_clauses.push(format!("json_extract(l.meta_json, '$.{column}') = ?"));
+                keys.push(SqlValue::Text(value.to_string()));
+            }
+        }
+        if keys.is_empty() {
+            return Ok(Correlated::default());
+        }
+        let run_match = run_clauses.join(" OR ");
+        let line_match = line_clauses.join(" OR ");
+
+        let mut values = keys.clone();
+        values.extend(keys.iter().cloned());
+        let mut where_sql = format!("WHERE (({run_match}) OR ({line_match}))");
+        if let Some(level) = min_level {
+            where_sql = and_where(where_sql, &level_clause("l", level, &mut values));
+        }
+        let sql = format!(
+            "SELECT l.run_id, l.seq, l.ts_ms, l.stream, l.level, l.event, l.text, \
+             l.src_file, l.src_line, l.src_col \
+             FROM log_lines l JOIN runs r ON r.id = l.run_id {where_sql} \
+             ORDER BY l.ts_ms ASC, l.run_id ASC, l.seq ASC"
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), |row| {
+            Ok((row.get::<_, i64>(2)?, row_to_line(row)?))
+        })?;
+        let lines = rows.collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut values = keys.clone();
+        values.extend(keys);
+        let sql = format!(
+            "SELECT r.id, r.started_at_ms, r.ended_at_ms, r.status, r.exit_code, r.tag, r.source, \
+             r.kind, r.project_path, r.appid, r.page, r.session, r.trace_id, r.line_count, r.pinned \
+             FROM runs r WHERE ({run_match}) OR EXISTS (SELECT 1 FROM log_lines l \
+             WHERE l.run_id = r.id AND ({line_match})) ORDER BY r.started_at_ms ASC"
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), row_to_run)?;
+        let runs = rows.collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(Correlated { runs, lines })
+    }
+
+    /// Accepts a full run id or an unambiguous prefix such as the 8-char id `list` prints.
+    pub fn resolve_run_id(&mut self, prefix: &str) -> Result<String> {
+        // `%` and `_` in the prefix are literal, not wildcards.
+        let pattern = prefix
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM runs WHERE id LIKE ?1 || '%' ESCAPE '\\' LIMIT 2")?;
+        let ids = stmt
+            .query_map([pattern], |row| row.get::<_, String>(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        match ids.as_slice() {
+            [id] => Ok(id.clone()),
+            [] => Err(Error::Message(format!("run `{}` not found", prefix))),
+            _ => Err(Error::Message(format!("run id `{}` is ambiguous", prefix))),
+        }
+    }
+
+    pub fn run_signatures(&mut self, run_id: &str) -> Result<Vec<RunSignature>> {
+        self.auto_clean()?;
+        // Signature text and location come from each fingerprint's first occurrence,
+        // joined back explicitly by (run_id, seq).
+        let mut stmt = self.conn.prepare(
+            "SELECT g.fingerprint, f.signature, g.count, f.src_file, f.src_line, f.src_col \
+             FROM (SELECT fingerprint, COUNT(*) AS count, MIN(seq) AS first_seq \
+                   FROM log_lines WHERE run_id = ?1 AND fingerprint IS NOT NULL \
+                   GROUP BY fingerprint) g \
+             JOIN log_lines f ON f.run_id = ?1 AND f.seq = g.first_seq \
+             ORDER BY g.count DESC, g.first_seq ASC",
+        )?;
+        let rows = stmt.query_map([run_id], |row| {
+            Ok(RunSignature {
+                fingerprint: row.get(0)?,
+                signature: row.get(1)?,
+                count: row.get(2)?,
+                location: location_string(row, 3)?,
+            })
+        })?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    pub fn top_errors(&mut self, filter: &QueryFilter, limit: usize) -> Result<Vec<ErrorStat>> {
+        self.auto_clean()?;
+        let (where_sql, mut values) = filter_sql(filter, "r");
+        let where_sql = and_where(where_sql, "l.fingerprint IS NOT NULL");
+        values.push(SqlValue::Integer(limit as i64));
+        // As in `run_signatures`, signature text and location come from each
+        // fingerprint's first occurrence, joined back explicitly by (run_id, seq).
+        let sql = format!(
+            "WITH m AS (SELECT l.run_id, l.seq, l.ts_ms, l.fingerprint \
+                        FROM log_lines l JOIN runs r ON r.id = l.run_id {where_sql}), \
+             g AS (SELECT fingerprint, COUNT(*) AS count, COUNT(DISTINCT run_id) AS runs, \
+                          MIN(ts_ms) AS first_ms, MAX(ts_ms) AS last_ms \
+                   FROM m GROUP BY fingerprint), \
+             o AS (SELECT fingerprint, run_id, seq, ROW_NUMBER() OVER ( \
+                          PARTITION BY fingerprint ORDER BY ts_ms, run_id, seq) AS n \
+                   FROM m) \
+             SELECT g.fingerprint, f.signature, g.count, g.runs, g.first_ms, g.last_ms, \
+                    f.src_file, f.src_line, f.src_col \
+             FROM g JOIN o ON o.fingerprint = g.fingerprint AND o.n = 1 \
+             JOIN log_lines f ON f.run_id = o.run_id AND f.seq = o.seq \
+             ORDER BY g.count DESC, g.last_ms DESC LIMIT ?"
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), |row| {
+            Ok(ErrorStat {
+                fingerprint: row.get(0)?,
+                signature: row.get(1)?,
+                count: row.get(2)?,
+                runs: row.get(3)?,
+                first_seen: format_ms(row.get(4)?),
+                last_seen: format_ms(row.get(5)?),
+                location: location_string(row, 6)?,
+            })
+        })?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    pub fn stats(&mut self, filter: &QueryFilter) -> Result<StoreStats> {
+        self.auto_clean()?;
+        let (where_sql, values) = filter_sql(filter, "r");
+        let (runs, running_runs, pinned_runs, lines, oldest, newest) = self.conn.query_row(
+            &format!(
+                "SELECT COUNT(*), COALESCE(SUM(r.status = 'running'), 0), \
+                 COALESCE(SUM(r.pinned), 0), COALESCE(SUM(r.line_count), 0), \
+                 MIN(r.started_at_ms), MAX(r.started_at_ms) FROM runs r {where_sql}"
+            ),
+            params_from_iter(values.iter()),
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, Option<i64>>(4)?,
+                    row.get::<_, Option<i64>>(5)?,
+                ))
+            },
+        )?;
+
+        let mut lines_by_level = BTreeMap::new();
+        let mut stored_bytes = 0u64;
+        {
+            let mut stmt = self.conn.prepare(&format!(
+                "SELECT l.level, COUNT(*), COALESCE(SUM(LENGTH(CAST(l.text AS BLOB)) + LENGTH(CAST(l.meta_json AS BLOB))), 0) \
+                 FROM log_lines l JOIN runs r ON r.id = l.run_id {where_sql} GROUP BY l.level"
+            ))?;
+            let rows = stmt.query_map(params_from_iter(values.iter()), |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, i64>(2)?,
+                ))
+            })?;
+            for row in rows {
+                let (level, count, bytes) = row?;
+                lines_by_level.insert(level, count);
+                stored_bytes += bytes as u64;
+            }
+        }
+
+        let db_bytes: i64 = self.conn.query_row(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+            [],
+            |row| row.get(0),
+        )?;
+
+        Ok(StoreStats {
+            runs,
+            running_runs,
+            pinned_runs,
+            lines,
+            lines_by_level,
+            oldest_run: oldest.map(format_ms),
+            newest_run: newest.map(format_ms),
+            stored_bytes,
+            db_bytes: db_bytes as u64,
+            fts_available: self.fts_available,
+        })
+    }
+
+    /// Cleanup run implicitly by writes and queries, using the stored default window.
+    fn auto_clean(&mut self) -> Result<()> {
+        if self.auto_clean {
+            let keep_hours = self.default_keep_hours()?;
+            self.clean_with(keep_hours, false, false)?;
+        }
+        Ok(())
+    }
+
+    /// Delete expired runs, then evict the oldest finished runs while over the size cap.
+    ///
+    /// `keep_hours` applies to runs whose tag has no rule of its own. Pinned runs are
+    /// never deleted. A run without an end (tail, syslog, OTLP) ages from its newest
+    /// line (or its start, if later: client timestamps may predate the run), so a
+    /// long-lived intake is only expired once it has gone quiet.
     pub fn clean(&mut self, keep_hours: u64, vacuum: bool) -> Result<CleanStats> {
-        let cutoff = now_ms() - (keep_hours as i64 * 60 * 60 * 1000);
+        self.clean_with(keep_hours, vacuum, true)
+    }
+
+    fn clean_with(
+        &mut self,
+        keep_hours: u64,
+        vacuum: bool,
+        force_size_check: bool,
+    ) -> Result<CleanStats> {
+        let now = now_ms();
         let run_ids = {
-            let mut stmt = self
-                .conn
-                .prepare("SELECT id FROM runs WHERE COALESCE(ended_at_ms, started_at_ms) < ?1")?;
-            let rows = stmt.query_map([cutoff], |row| row.get::<_, String>(0))?;
+            let mut stmt = self.conn.prepare(
+                "SELECT r.id FROM runs r LEFT JOIN retention_rules t ON t.tag = r.tag \
+                 WHERE r.pinned = 0 AND COALESCE(r.ended_at_ms, MAX(r.started_at_ms, \
+                 COALESCE((SELECT l.ts_ms FROM log_lines l WHERE l.run_id = r.id \
+                 ORDER BY l.seq DESC LIMIT 1), 0))) \
+                 < ?1 - COALESCE(t.keep_hours, ?2) * 3600000",
+            )?;
+            let rows = stmt.query_map(params![now, keep_hours as i64], |row| {
+                row.get::<_, String>(0)
+            })?;
             rows.collect::<std::result::Result<Vec<_>, _>>()?
         };
+        let mut deleted_lines = self.delete_runs(&run_ids)?;
+
+        let mut evicted = Vec::new();
+        let max_bytes = match self.max_bytes()? {
+            Some(max_bytes) if self.size_check_due(now, force_size_check)? => Some(max_bytes),
+            _ => None,
+        };
+        if let Some(max_bytes) = max_bytes {
+            let mut total = self.stored_bytes()?;
+            if total > max_bytes {
+                let mut stmt = self.conn.prepare(
+                    "SELECT r.id, COALESCE(SUM(LENGTH(CAST(l.text AS BLOB)) + LENGTH(CAST(l.meta_json AS BLOB))), 0) \
+                     FROM runs r LEFT JOIN log_lines l ON l.run_id = r.id \
+                     WHERE r.pinned = 0 AND r.status != 'running' \
+                     GROUP BY r.id ORDER BY r.started_at_ms ASC",
+                )?;
+                let rows = stmt.query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+                })?;
+                for row in rows {
+                    if total <= max_bytes {
+                        break;
+                    }
+                    let (id, bytes) = row?;
+                    total = total.saturating_sub(bytes as u64);
+                    evicted.push(id);
+                }
+            }
+            deleted_lines += self.delete_runs(&evicted)?;
+        }
 
+        if vacuum {
+            self.conn.execute_batch("VACUUM")?;
+        }
+
+        Ok(CleanStats {
+            deleted_runs: run_ids.len() + evicted.len(),
+            deleted_lines,
+            keep_hours,
+            evicted_for_size: evicted.len(),
+        })
+    }
+
+    fn delete_runs(&mut self, run_ids: &[String]) -> Result<usize> {
+        if run_ids.is_empty() {
+            return Ok(0);
+        }
         let mut deleted_lines = 0usize;
         let fts_available = self.fts_available;
         let tx = self.conn.transaction()?;
-        for run_id in &run_ids {
+        for run_id in run_ids {
             deleted_lines += tx.execute("DELETE FROM log_lines WHERE run_id = ?1", [run_id])?;
             tx.execute("DELETE FROM run_meta WHERE run_id = ?1", [run_id])?;
             if fts_available {
@@ -384,17 +847,430 @@ impl SqliteStorage {
             tx.execute("DELETE FROM runs WHERE id = ?1", [run_id])?;
         }
         tx.commit()?;
+        Ok(deleted_lines)
+    }
 
-        if vacuum {
-            self.conn.execute_batch("VACUUM")?;
+    /// Record a size check at `now` unless one ran within `SIZE_CHECK_INTERVAL_MS`.
+    /// The stamp lives in `settings` so short-lived CLI commands share it.
+    fn size_check_due(&mut self, now: i64, force: bool) -> Result<bool> {
+        let last = self
+            .setting("size_checked_at_ms")?
+            .and_then(|v| v.parse::<i64>().ok());
+        if !force && last.is_some_and(|at| (0..SIZE_CHECK_INTERVAL_MS).contains(&(now - at))) {
+            return Ok(false);
         }
+        self.set_setting("size_checked_at_ms", Some(now.to_string()))?;
+        Ok(true)
+    }
 
-        Ok(CleanStats {
-            deleted_runs: run_ids.len(),
-            deleted_lines,
-            keep_hours,
+    /// Logical size of stored logs: line text plus line metadata, in UTF-8 bytes.
+    fn stored_bytes(&mut self) -> Result<u64> {
+        let bytes: i64 = self.conn.query_row(
+            "SELECT COALESCE(SUM(LENGTH(CAST(text AS BLOB)) + LENGTH(CAST(meta_json AS BLOB))), 0) \
+             FROM log_lines",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(bytes as u64)
+    }
+
+    fn setting(&mut self, key: &str) -> Result<Option<String>> {
+        self.conn
+            .query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(Into::into)
+    }
+
+    fn set_setting(&mut self, key: &str, value: Option<String>) -> Result<()> {
+        match value {
+            Some(value) => self.conn.execute(
+                "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+                params![key, value],
+            )?,
+            None => self
+                .conn
+                .execute("DELETE FROM settings WHERE key = ?1", [key])?,
+        };
+        Ok(())
+    }
+
+    pub fn default_keep_hours(&mut self) -> Result<u64> {
+        Ok(self
+            .setting("default_keep_hours")?
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_KEEP_HOURS))
+    }
+
+    pub fn set_default_keep_hours(&mut self, keep_hours: u64) -> Result<()> {
+        self.set_setting("default_keep_hours", Some(keep_hours.to_string()))
+    }
+
+    pub fn max_bytes(&mut self) -> Result<Option<u64>> {
+        Ok(self.setting("max_bytes")?.and_then(|v| v.parse().ok()))
+    }
+
+    pub fn set_max_bytes(&mut self, max_bytes: Option<u64>) -> Result<()> {
+        // A new cap takes effect on the next automatic cleanup, not a minute later.
+        self.set_setting("size_checked_at_ms", None)?;
+        self.set_setting("max_bytes", max_bytes.map(|v| v.to_string()))
+    }
+
+    pub fn set_tag_retention(&mut self, tag: &str, keep_hours: Option<u64>) -> Result<()> {
+        match keep_hours {
+            Some(hours) => self.conn.execute(
+                "INSERT OR REPLACE INTO retention_rules (tag, keep_hours) VALUES (?1, ?2)",
+                params![tag, hours as i64],
+            )?,
+            None => self
+                .conn
+                .execute("DELETE FROM retention_rules WHERE tag = ?1", [tag])?,
+        };
+        Ok(())
+    }
+
+    pub fn retention_policy(&mut self) -> Result<RetentionPolicy> {
+        let tag_keep_hours = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT tag, keep_hours FROM retention_rules ORDER BY tag")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64))
+            })?;
+            rows.collect::<std::result::Result<BTreeMap<_, _>, _>>()?
+        };
+        let pinned_runs =
+            self.conn
+                .query_row("SELECT COUNT(*) FROM runs WHERE pinned = 1", [], |row| {
+                    row.get(0)
+                })?;
+        Ok(RetentionPolicy {
+            default_keep_hours: self.default_keep_hours()?,
+            tag_keep_hours,
+            max_bytes: self.max_bytes()?,
+            pinned_runs,
         })
     }
+
+    /// Every run id, oldest first, without running cleanup.
+    pub fn all_run_ids(&mut self) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM runs ORDER BY started_at_ms ASC")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    pub fn set_pinned(&mut self, run_id: &str, pinned: bool) -> Result<()> {
+        self.conn.execute(
+            "UPDATE runs SET pinned = ?2 WHERE id = ?1",
+            params![run_id, pinned],
+        )?;
+        Ok(())
+    }
+
+    /// Full copies of runs, including metadata and every line, for a portable bundle.
+    /// Does not run cleanup, so it is safe on bundle databases holding old runs.
+    pub fn export_runs(&mut self, run_ids: &[String]) -> Result<Vec<BundleRun>> {
+        let mut runs = Vec::with_capacity(run_ids.len());
+        for run_id in run_ids {
+            let mut run = self
+                .conn
+                .query_row(
+                    "SELECT id, started_at_ms, ended_at_ms, status, exit_code, cwd, argv_json, \
+                     tag, source, client_id, kind FROM runs WHERE id = ?1",
+                    [run_id],
+                    |row| {
+                        let argv_json: String = row.get(6)?;
+                        Ok(BundleRun {
+                            id: row.get(0)?,
+                            started_at_ms: row.get(1)?,
+                            ended_at_ms: row.get(2)?,
+                            status: row.get(3)?,
+                            exit_code: row.get(4)?,
+                            cwd: row.get(5)?,
+                            argv: serde_json::from_str(&argv_json).unwrap_or_default(),
+                            tag: row.get(7)?,
+                            source: row.get(8)?,
+                            client_id: row.get(9)?,
+                            kind: row.get(10)?,
+                            meta: BTreeMap::new(),
+                            lines: Vec::new(),
+                        })
+                    },
+                )
+                .optional()?
+                .ok_or_else(|| Error::Message(format!("run `{}` not found", run_id)))?;
+
+            let mut stmt = self
+                .conn
+                .prepare("SELECT key, value_json FROM run_meta WHERE run_id = ?1")?;
+            let meta = stmt.query_map([run_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?;
+            for entry in meta {
+                let (key, value_json) = entry?;
+                run.meta.insert(key, serde_json::from_str(&value_json)?);
+            }
+
+            let mut stmt = self.conn.prepare(
+                "SELECT seq, ts_ms, stream, level, event, text, meta_json, src_file, src_line, \
+                 src_col FROM log_lines WHERE run_id = ?1 ORDER BY seq ASC",
+            )?;
+            let lines = stmt.query_map([run_id], |row| {
+                let meta_json: String = row.get(6)?;
+                let location = match (row.get(7)?, row.get(8)?) {
+                    (Some(file), Some(line)) => Some(SourceLocation {
+                        file,
+                        line,
+                        col: row.get(9)?,
+                    }),
+                    _ => None,
+                };
+                Ok(BundleLine {
+                    seq: row.get(0)?,
+                    ts_ms: row.get(1)?,
+                    stream: row.get(2)?,
+                    level: row.get(3)?,
+                    event: row.get(4)?,
+                    text: row.get(5)?,
+                    meta: serde_json::from_str(&meta_json).unwrap_or_default(),
+                    location,
+                })
+            })?;
+            run.lines = lines.collect::<std::result::Result<Vec<_>, _>>()?;
+            runs.push(run);
+        }
+        Ok(runs)
+    }
+
+    /// Load bundled runs under their original ids. Imported runs are pinned, since
+    /// they are usually older than the retention window. Existing ids are skipped. Each
+    /// run is written with its meta and lines in one transaction, so a failed import
+    /// leaves nothing behind and can simply be retried.
+    pub fn import_runs(&mut self, runs: &[BundleRun]) -> Result<ImportStats> {
+        let mut stats = ImportStats::default();
+        for run in runs {
+            if self.get_run(&run.id)?.is_some() {
+                stats.skipped_runs += 1;
+                continue;
+            }
+            let meta = meta::normalize_map(&run.meta);
+            let common = meta::extract_common(run.kind.as_deref(), &meta);
+            let tx = self.conn.transaction()?;
+            tx.execute(
+                r#"
+                INSERT INTO runs (
+                    id, started_at_ms, ended_at_ms, status, exit_code, cwd, argv_json, tag,
+                    source, client_id, kind, project_path, appid, page, session, trace_id,
+                    pinned
+                )
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, 1)
+                "#,
+                params![
+                    run.id,
+                    run.started_at_ms,
+                    run.ended_at_ms,
+                    run.status,
+                    run.exit_code,
+                    run.cwd,
+                    serde_json::to_string(&run.argv)?,
+                    run.tag,
+                    run.source,
+                    run.client_id,
+                    common.kind,
+                    common.project_path,
+                    common.appid,
+                    common.page,
+                    common.session,
+                    common.trace_id,
+                ],
+            )?;
+            for (key, value) in &meta {
+                tx.execute(
+                    "INSERT OR REPLACE INTO run_meta (run_id, key, value_json) VALUES (?1, ?2, ?3)",
+                    params![run.id, key, serde_json::to_string(value)?],
+                )?;
+            }
+
+            let lines = run
+                .lines
+                .iter()
+                .map(|line| AppendLine {
+                    ts: Some(format_ms(line.ts_ms)),
+                    stream: Some(line.stream.clone()),
+                    level: Some(line.level.clone()),
+                    event: line.event.clone(),
+                    text: line.text.clone(),
+                    location: line.location.clone(),
+                    meta: line.meta.clone(),
+                })
+                .collect::<Vec<_>>();
+            let imported = insert_lines(
+                &tx,
+                self.fts_available,
+                self.redactor.as_ref(),
+                &run.id,
+                &lines,
+            )?;
+            tx.commit()?;
+            stats.imported_lines += imported;
+            stats.imported_runs += 1;
+        }
+        Ok(stats)
+    }
+}
+
+fn insert_run(conn: &Connection, redactor: Option<&Redactor>, run: NewRun) -> Result<String> {
+    let id = Uuid::new_v4().to_string();
+    let mut meta = meta::normalize_map(&run.meta);
+    let mut argv = run.argv;
+    if let Some(redactor) = redactor {
+        let mut redacted = Vec::new();
+        for arg in &mut argv {
+            if let Cow::Owned(masked) = redactor.redact_into(arg, &mut redacted) {
+                *arg = masked;
+            }
+        }
+        for value in meta.values_mut() {
+            redactor.redact_value(value, &mut redacted);
+        }
+        if !redacted.is_empty() {
+            meta.insert("redacted".to_string(), Value::from(redacted));
+        }
+    }
+    let common = meta::extract_common(run.kind.as_deref(), &meta);
+    let argv_json = serde_json::to_string(&argv)?;
+
+    conn.execute(
+        r#"
+        INSERT INTO runs (
+            id, started_at_ms, status, cwd, argv_json, tag, source, client_id,
+            kind, project_path, appid, page, session, trace_id
+        )
+        VALUES (?1, ?2, 'running', ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+        "#,
+        params![
+            id,
+            now_ms(),
+            run.cwd,
+            argv_json,
+            run.tag,
+            run.source,
+            run.client_id,
+            common.kind,
+            common.project_path,
+            common.appid,
+            common.page,
+            common.session,
+            common.trace_id,
+        ],
+    )?;
+    for (key, value) in meta {
+        conn.execute(
+            "INSERT OR REPLACE INTO run_meta (run_id, key, value_json) VALUES (?1, ?2, ?3)",
+            params![id, key, serde_json::to_string(&value)?],
+        )?;
+    }
+    Ok(id)
+}
+
+fn insert_lines(
+    conn: &Connection,
+    fts_available: bool,
+    redactor: Option<&Redactor>,
+    run_id: &str,
+    lines: &[AppendLine],
+) -> Result<usize> {
+    let first_seq: i64 = conn
+        .query_row(
+            "SELECT (SELECT COALESCE(MAX(seq), 0) + 1 FROM log_lines WHERE run_id = ?1) \
+             FROM runs WHERE id = ?1",
+            [run_id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| Error::RunNotFound(run_id.to_string()))?;
+
+    let mut insert_line = conn.prepare_cached(
+        r#"
+        INSERT INTO log_lines (
+            run_id, seq, ts_ms, stream, level, event, text, meta_json,
+            src_file, src_line, src_col, signature, fingerprint
+        )
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+        "#,
+    )?;
+    for (seq, line) in (first_seq..).zip(lines) {
+        let ts_ms = match line.ts.as_deref() {
+            Some(ts) => parse_rfc3339_ms(ts)?,
+            None => now_ms(),
+        };
+        let stream = line.stream.as_deref().unwrap_or("stdout");
+        let level = parser::normalize_level(line.level.as_deref().unwrap_or("info"));
+        let mut text = Cow::Borrowed(line.text.as_str());
+        let mut event = line.event.as_deref().map(Cow::Borrowed);
+        let mut meta = Cow::Borrowed(&line.meta);
+        if let Some(redactor) = redactor {
+            let mut redacted = Vec::new();
+            text = redactor.redact_into(&line.text, &mut redacted);
+            event = line
+                .event
+                .as_deref()
+                .map(|event| redactor.redact_into(event, &mut redacted));
+            let mut masked = line.meta.clone();
+            for value in masked.values_mut() {
+                redactor.redact_value(value, &mut redacted);
+            }
+            if !redacted.is_empty() {
+                masked.insert("redacted".to_string(), Value::from(redacted));
+                meta = Cow::Owned(masked);
+            }
+        }
+        let meta_json = serde_json::to_string(&meta)?;
+        let location = line.location.as_ref();
+        let signature = level
+            .parse::<Level>()
+            .is_ok_and(|l| l >= Level::Error)
+            .then(|| fingerprint::signature(&text))
+            .filter(|sig| !sig.is_empty());
+        let fingerprint = signature.as_deref().map(fingerprint::fingerprint);
+        insert_line.execute(params![
+            run_id,
+            seq,
+            ts_ms,
+            stream,
+            level,
+            event.as_deref(),
+            text.as_ref(),
+            meta_json,
+            location.map(|l| &l.file),
+            location.map(|l| l.line),
+            location.and_then(|l| l.col),
+            signature,
+            fingerprint,
+        ])?;
+        if fts_available {
+            conn.prepare_cached("INSERT INTO log_fts (run_id, seq, text) VALUES (?1, ?2, ?3)")?
+                .execute(params![run_id, seq, text.as_ref()])?;
+        }
+    }
+    conn.execute(
+        "UPDATE runs SET line_count = line_count + ?2 WHERE id = ?1",
+        params![run_id, lines.len() as i64],
+    )?;
+    Ok(lines.len())
+}
+
+fn update_finished(conn: &Connection, run_id: &str, finish: &FinishRunRequest) -> Result<()> {
+    let status = finish.status.as_deref().unwrap_or("ok");
+    conn.execute(
+        "UPDATE runs SET ended_at_ms = ?2, status = ?3, exit_code = ?4 WHERE id = ?1",
+        params![run_id, now_ms(), status, finish.exit_code],
+    )?;
+    Ok(())
 }
 
 fn filter_sql(filter: &QueryFilter, table: &str) -> (String, Vec<SqlValue>) {
@@ -426,6 +1302,40 @@ fn filter_sql(filter: &QueryFilter, table: &str) -> (String, Vec<SqlValue>) {
     }
 }
 
+const LINE_COLUMNS: &str =
+    "run_id, seq, ts_ms, stream, level, event, text, src_file, src_line, src_col";
+
+fn and_where(where_sql: String, clause: &str) -> String {
+    if where_sql.is_empty() {
+        format!("WHERE {clause}")
+    } else {
+        format!("{where_sql} AND {clause}")
+    }
+}
+
+fn level_clause(table: &str, level: Level, values: &mut Vec<SqlValue>) -> String {
+    let names = level.at_least();
+    let placeholders = vec!["?"; names.len()].join(", ");
+    values.extend(
+        names
+            .into_iter()
+            .map(|name| SqlValue::Text(name.to_string())),
+    );
+    format!("{table}.level IN ({placeholders})")
+}
+
+/// `file:line[:col]` from three consecutive `src_*` columns.
+fn location_string(row: &rusqlite::Row<'_>, offset: usize) -> rusqlite::Result<Option<String>> {
+    let file: Option<String> = row.get(offset)?;
+    let line: Option<u32> = row.get(offset + 1)?;
+    let col: Option<u32> = row.get(offset + 2)?;
+    Ok(match (file, line, col) {
+        (Some(file), Some(line), Some(col)) => Some(format!("{file}:{line}:{col}")),
+        (Some(file), Some(line), None) => Some(format!("{file}:{line}")),
+        _ => None,
+    })
+}
+
 fn row_to_run(row: &rusqlite::Row<'_>) -> rusqlite::Result<RunRecord> {
     row_to_run_offset(row, 0)
 }
@@ -448,6 +1358,7 @@ fn row_to_run_offset(row: &rusqlite::Row<'_>, offset: usize) -> rusqlite::Result
         session: row.get(offset + 11)?,
         trace_id: row.get(offset + 12)?,
         line_count: row.get(offset + 13)?,
+        pinned: row.get(offset + 14)?,
     })
 }
 
@@ -457,6 +1368,14 @@ fn row_to_line(row: &rusqlite::Row<'_>) -> rusqlite::Result<LineRecord> {
 
 fn row_to_line_offset(row: &rusqlite::Row<'_>, offset: usize) -> rusqlite::Result<LineRecord> {
     let ts_ms: i64 = row.get(offset + 2)?;
+    let location = match (row.get(offset + 7)?, row.get(offset + 8)?) {
+        (Some(file), Some(line)) => Some(SourceLocation {
+            file,
+            line,
+            col: row.get(offset + 9)?,
+        }),
+        _ => None,
+    };
     Ok(LineRecord {
         run_id: row.get(offset)?,
         seq: row.get(offset + 1)?,
@@ -465,6 +1384,7 @@ fn row_to_line_offset(row: &rusqlite::Row<'_>, offset: usize) -> rusqlite::Resul
         level: row.get(offset + 4)?,
         event: row.get(offset + 5)?,
         text: row.get(offset + 6)?,
+        location,
     })
 }
 
@@ -509,7 +1429,7 @@ pub fn default_db_path() -> std::path::PathBuf {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tempfile::NamedTempFile;
+    use tempfile::{NamedTempFile, TempDir};
 
     #[test]
     fn stores_and_queries_wechat_meta() {
@@ -555,4 +1475,445 @@ mod tests {
         assert_eq!(hits.len(), 1);
         assert_eq!(hits[0].run.id, run_id);
     }
+
+    #[test]
+    fn redacts_runs_and_lines_and_marks_meta() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut db = SqliteStorage::open(tmp.path()).unwrap();
+        // An empty config file keeps the test independent of the user's own rules.
+        let config = NamedTempFile::new().unwrap();
+        fs::write(config.path(), "{}").unwrap();
+        db.set_redactor(Some(Redactor::load(Some(config.path())).unwrap()));
+        let run_id = db
+            .start_run(NewRun {
+                tag: None,
+                source: None,
+                cwd: None,
+                argv: vec![
+                    "curl".to_string(),
+                    "-H".to_string(),
+                    "Authorization: Bearer abcdefgh12345".to_string(),
+                ],
+                client_id: None,
+                kind: None,
+                meta: BTreeMap::from([(
+                    "http".to_string(),
+                    serde_json::json!({ "url": "/login?password=hunter2345" }),
+                )]),
+            })
+            .unwrap();
+        let line = |text: &str| AppendLine {
+            text: text.to_string(),
+            ..Default::default()
+        };
+        db.append_lines(
+            &run_id,
+            &[
+                line("login ok token=abcdef123456"),
+                line("plain"),
+                AppendLine {
+                    event: Some("callback token=abcdef123456".to_string()),
+                    meta: BTreeMap::from([(
+                        "otel.attrs".to_string(),
+                        serde_json::json!(["mail dev@example.com"]),
+                    )]),
+                    ..line("request done")
+                },
+            ],
+        )
+        .unwrap();
+
+        assert!(db
+            .search("abcdef123456", &QueryFilter::default(), 10)
+            .unwrap()
+            .is_empty());
+        let runs = db.export_runs(std::slice::from_ref(&run_id)).unwrap();
+        let run = &runs[0];
+        assert_eq!(
+            run.argv[2],
+            "Authorization: [REDACTED:secret] [REDACTED:bearer_token]"
+        );
+        assert_eq!(
+            run.meta["http"],
+            serde_json::json!({ "url": "/login?password=[REDACTED:secret]" })
+        );
+        assert_eq!(
+            run.meta["redacted"],
+            serde_json::json!(["bearer_token", "secret"])
+        );
+        let lines = &run.lines;
+        assert_eq!(lines[0].text, "login ok token=[REDACTED:secret]");
+        assert_eq!(lines[0].meta["redacted"], serde_json::json!(["secret"]));
+        assert!(!lines[1].meta.contains_key("redacted"));
+        assert_eq!(
+            lines[2].event.as_deref(),
+            Some("callback token=[REDACTED:secret]")
+        );
+        assert_eq!(
+            lines[2].meta["otel.attrs"],
+            serde_json::json!(["mail [REDACTED:email]"])
+        );
+        assert_eq!(
+            lines[2].meta["redacted"],
+            serde_json::json!(["secret", "email"])
+        );
+    }
+
+    #[test]
+    fn failed_import_leaves_no_partial_run() {
+        let dir = TempDir::new().unwrap();
+        let mut source = SqliteStorage::open(&dir.path().join("source.db")).unwrap();
+        let run_id = source
+            .start_run(NewRun {
+                tag: None,
+                source: None,
+                cwd: None,
+                argv: vec![],
+                client_id: None,
+                kind: None,
+                meta: BTreeMap::new(),
+            })
+            .unwrap();
+        let line = |text: &str| AppendLine {
+            text: text.to_string(),
+            ..Default::default()
+        };
+        source
+            .append_lines(&run_id, &[line("first"), line("second")])
+            .unwrap();
+        let runs = source.export_runs(std::slice::from_ref(&run_id)).unwrap();
+
+        let mut target = SqliteStorage::open(&dir.path().join("target.db")).unwrap();
+        target
+            .conn
+            .execute_batch(
+                "CREATE TRIGGER fail_second BEFORE INSERT ON log_lines \
+                 WHEN NEW.text = 'second' BEGIN SELECT RAISE(ABORT, 'boom'); END",
+            )
+            .unwrap();
+        assert!(target.import_runs(&runs).is_err());
+        assert!(target.get_run(&run_id).unwrap().is_none());
+
+        target
+            .conn
+            .execute_batch("DROP TRIGGER fail_second")
+            .unwrap();
+        let stats = target.import_runs(&runs).unwrap();
+        assert_eq!((stats.imported_runs, stats.imported_lines), (1, 2));
+        assert_eq!(target.get_run(&run_id).unwrap().unwrap().line_count, 2);
+    }
+
+    #[test]
+    fn correlates_runs_and_lines_by_session_and_trace() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut db = SqliteStorage::open(tmp.path()).unwrap();
+        let mut start = |key: Option<(&str, &str)>| {
+            let meta = key
+                .map(|(k, v)| BTreeMap::from([(k.to_string(), Value::from(v))]))
+                .unwrap_or_default();
+            db.start_run(NewRun {
+                tag: None,
+                source: None,
+                cwd: None,
+                argv: vec![],
+                client_id: None,
+                kind: None,
+                meta,
+            })
+            .unwrap()
+        };
+        let build = start(Some(("session", "s1")));
+        let backend = start(None);
+        let other = start(Some(("session", "s2")));
+        let line = |ts: &str, text: &str, trace: Option<&str>| AppendLine {
+            ts: Some(ts.to_string()),
+            text: text.to_string(),
+            meta: trace
+                .map(|t| BTreeMap::from([("trace_id".to_string(), Value::from(t))]))
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+        db.append_lines(&build, &[line("2026-10-01T10:00:02Z", "compiled", None)])
+            .unwrap();
+        db.append_lines(
+            &backend,
+            &[
+                line("2026-10-01T10:00:01Z", "GET /login", Some("t1")),
+                line("2026-10-01T10:00:03Z", "GET /health", None),
+            ],
+        )
+        .unwrap();
+        db.append_lines(&other, &[line("2026-10-01T10:00:04Z", "unrelated", None)])
+            .unwrap();
+
+        let Correlated { runs, lines } = db.correlated(Some("s1"), Some("t1"), None).unwrap();
+        let mut run_ids: Vec<_> = runs.into_iter().map(|run| run.id).collect();
+        run_ids.sort();
+        let mut expected = vec![build, backend];
+        expected.sort();
+        assert_eq!(run_ids, expected);
+        let texts: Vec<_> = lines.iter().map(|(_, line)| line.text.as_str()).collect();
+        assert_eq!(texts, ["GET /login", "compiled"]);
+        assert_eq!(lines[1].0 - lines[0].0, 1000);
+    }
+
+    #[test]
+    fn filters_by_minimum_level_and_keeps_location() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut db = SqliteStorage::open(tmp.path()).unwrap();
+        let run_id = db
+            .start_run(NewRun {
+                tag: None,
+                source: None,
+                cwd: None,
+                argv: vec![],
+                client_id: None,
+                kind: None,
+                meta: BTreeMap::new(),
+            })
+            .unwrap();
+        db.append_lines(
+            &run_id,
+            &[
+                AppendLine {
+                    level: Some("warning".to_string()),
+                    text: "slow".to_string(),
+                    ..Default::default()
+                },
+                AppendLine {
+                    level: Some("error".to_string()),
+                    text: "TypeError: boom".to_string(),
+                    location: Some(SourceLocation {
+                        file: "index.js".to_string(),
+                        line: 12,
+                        col: Some(3),
+                    }),
+                    ..Default::default()
+                },
+            ],
+        )
+        .unwrap();
+
+        let warn_and_up = db
+            .show_lines(&run_id, None, None, Some(Level::Warn))
+            .unwrap();
+        assert_eq!(warn_and_up.len(), 2);
+        assert_eq!(warn_and_up[0].level, "warn");
+
+        let errors = db
+            .show_lines(&run_id, None, None, Some(Level::Error))
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].location.as_ref().unwrap().line, 12);
+
+        let fatal = QueryFilter {
+            min_level: Some(Level::Fatal),
+            ..Default::default()
+        };
+        assert!(db.list_runs(&fatal, 10).unwrap().is_empty());
+        assert!(db.search("boom", &fatal, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn signatures_and_top_errors_point_at_first_occurrence() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut db = SqliteStorage::open(tmp.path()).unwrap();
+        let run_id = db
+            .start_run(NewRun {
+                tag: None,
+                source: None,
+                cwd: None,
+                argv: vec![],
+                client_id: None,
+                kind: None,
+                meta: BTreeMap::new(),
+            })
+            .unwrap();
+        let error = |text: &str, line: Option<u32>| AppendLine {
+            level: Some("error".to_string()),
+            text: text.to_string(),
+            location: line.map(|line| SourceLocation {
+                file: "index.js".to_string(),
+                line,
+                col: None,
+            }),
+            ..Default::default()
+        };
+        db.append_lines(
+            &run_id,
+            &[
+                error("RangeError: once", Some(1)),
+                error("TypeError: boom 1", Some(12)),
+                error("TypeError: boom 2", None),
+                error("TypeError: boom 3", Some(40)),
+            ],
+        )
+        .unwrap();
+
+        let signatures = db.run_signatures(&run_id).unwrap();
+        assert_eq!(signatures.len(), 2);
+        assert_eq!(signatures[0].count, 3);
+        assert_eq!(signatures[0].location.as_deref(), Some("index.js:12"));
+        assert_eq!(signatures[1].location.as_deref(), Some("index.js:1"));
+
+        let top = db.top_errors(&QueryFilter::default(), 10).unwrap();
+        assert_eq!(top[0].count, 3);
+        assert_eq!(top[0].location.as_deref(), Some("index.js:12"));
+
+        assert_eq!(db.resolve_run_id(&run_id[..8]).unwrap(), run_id);
+        assert!(db.resolve_run_id("%").is_err());
+        assert!(db.resolve_run_id(&format!("{}_", &run_id[..7])).is_err());
+    }
+
+    #[test]
+    fn clean_respects_pins_tag_rules_and_size_cap() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut db = SqliteStorage::open(tmp.path()).unwrap();
+        let start = |db: &mut SqliteStorage, tag: &str, text: &str| {
+            let id = db
+                .start_run(NewRun {
+                    tag: Some(tag.to_string()),
+                    source: None,
+                    cwd: None,
+                    argv: vec![],
+                    client_id: None,
+                    kind: None,
+                    meta: BTreeMap::new(),
+                })
+                .unwrap();
+            db.append_lines(
+                &id,
+                &[AppendLine {
+                    text: text.to_string(),
+                    ..Default::default()
+                }],
+            )
+            .unwrap();
+            db.finish_run(&id, &FinishRunRequest::default()).unwrap();
+            id
+        };
+        let plain = start(&mut db, "build", "old");
+        let pinned = start(&mut db, "build", "rare repro");
+        let kept_by_tag = start(&mut db, "nightly", "old but kept");
+        let fresh = start(&mut db, "build", &"x".repeat(4096));
+
+        let two_days_ago = now_ms() - 48 * 3600 * 1000;
+        for id in [&plain, &pinned, &kept_by_tag] {
+            db.conn
+                .execute(
+                    "UPDATE runs SET started_at_ms = ?2, ended_at_ms = ?2 WHERE id = ?1",
+                    params![id, two_days_ago],
+                )
+                .unwrap();
+        }
+        db.set_pinned(&pinned, true).unwrap();
+        db.set_tag_retention("nightly", Some(72)).unwrap();
+
+        let stats = db.clean(24, false).unwrap();
+        assert_eq!((stats.deleted_runs, stats.evicted_for_size), (1, 0));
+        assert!(db.get_run(&plain).unwrap().is_none());
+        assert!(db.get_run(&pinned).unwrap().is_some());
+        assert!(db.get_run(&kept_by_tag).unwrap().is_some());
+
+        db.set_max_bytes(Some(1024)).unwrap();
+        let stats = db.clean(24, false).unwrap();
+        assert_eq!(stats.evicted_for_size, 2);
+        assert!(db.get_run(&pinned).unwrap().is_some());
+        assert!(db.get_run(&fresh).unwrap().is_none());
+    }
+
+    #[test]
+    fn size_cap_counts_utf8_bytes_and_auto_check_is_throttled() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut db = SqliteStorage::open(tmp.path()).unwrap();
+        let start = |db: &mut SqliteStorage, text: &str| {
+            let id = db
+                .start_run(NewRun {
+                    tag: None,
+                    source: None,
+                    cwd: None,
+                    argv: vec![],
+                    client_id: None,
+                    kind: None,
+                    meta: BTreeMap::new(),
+                })
+                .unwrap();
+            db.append_lines(
+                &id,
+                &[AppendLine {
+                    text: text.to_string(),
+                    ..Default::default()
+                }],
+            )
+            .unwrap();
+            db.finish_run(&id, &FinishRunRequest::default()).unwrap();
+            id
+        };
+        // 3 bytes per char in UTF-8, plus `{}` of line meta.
+        let first = start(&mut db, &"错".repeat(100));
+        let stats = db.stats(&QueryFilter::default()).unwrap();
+        assert_eq!(stats.stored_bytes, 302);
+
+        db.set_max_bytes(Some(400)).unwrap();
+        db.list_runs(&QueryFilter::default(), 10).unwrap();
+        assert!(db.get_run(&first).unwrap().is_some());
+
+        // Over the cap now, but the automatic check ran moments ago.
+        let second = start(&mut db, &"错".repeat(100));
+        db.list_runs(&QueryFilter::default(), 10).unwrap();
+        assert!(db.get_run(&first).unwrap().is_some());
+
+        let stats = db.clean(24, false).unwrap();
+        assert_eq!(stats.evicted_for_size, 1);
+        assert!(db.get_run(&first).unwrap().is_none());
+        assert!(db.get_run(&second).unwrap().is_some());
+    }
+
+    #[test]
+    fn clean_ages_unfinished_runs_by_newest_line() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut db = SqliteStorage::open(tmp.path()).unwrap();
+        let two_days_ago = now_ms() - 48 * 3600 * 1000;
+        let mut start = |text: &str| {
+            let id = db
+                .start_run(NewRun {
+                    tag: None,
+                    source: Some("looplog-tail".to_string()),
+                    cwd: None,
+                    argv: vec![],
+                    client_id: None,
+                    kind: None,
+                    meta: BTreeMap::new(),
+                })
+                .unwrap();
+            db.append_lines(
+                &id,
+                &[AppendLine {
+                    text: text.to_string(),
+                    ..Default::default()
+                }],
+            )
+            .unwrap();
+            db.conn
+                .execute(
+                    "UPDATE runs SET started_at_ms = ?2 WHERE id = ?1",
+                    params![id, two_days_ago],
+                )
+                .unwrap();
+            id
+        };
+        let live = start("still writing");
+        let quiet = start("last words");
+        db.conn
+            .execute(
+                "UPDATE log_lines SET ts_ms = ?2 WHERE run_id = ?1",
+                params![quiet, two_days_ago],
+            )
+            .unwrap();
+
+        let stats = db.clean(24, false).unwrap();
+        assert_eq!(stats.deleted_runs, 1);
+        assert!(db.get_run(&live).unwrap().is_some());
+        assert!(db.get_run(&quiet).unwrap().is_none());
+    }
 }
@@ -14,8 +14,14 @@ pub enum Error {
     TimeParse(String),
     #[error("invalid metadata entry `{0}`, expected key=value")]
     InvalidMeta(String),
+    #[error("invalid level `{0}`, expected trace, debug, info, warn, error or fatal")]
+    InvalidLevel(String),
     #[error("missing command after `--`")]
     MissingCommand,
+    #[error("run `{0}` not found")]
+    RunNotFound(String),
+    #[error("write queue is full, retry later")]
+    QueueFull,
     #[error("http error: {0}")]
     Http(String),
     #[error("{0}")]
@@ -0,0 +1,164 @@
+use crate::db::RunSignature;
+use crate::parser::strip_devtools_prefix;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Normalized one-line signature of an error record.
+///
+/// The header line is kept and volatile parts are replaced: numbers become `<n>`,
+/// hex/uuid-like ids become `<id>`, URLs become `<url>` and paths are reduced to
+/// their file name, so the same error from two runs (or two machines) matches.
+pub fn signature(text: &str) -> String {
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    let Some(first) = lines.next() else {
+        return String::new();
+    };
+    let first = strip_devtools_prefix(first);
+
+    // Headers that carry no message of their own: new-style Rust panics
+    // (`thread 'main' panicked at src/main.rs:1:1:`) and bare WeChat markers
+    // (`thirdScriptError`). The message is on the next line.
+    let bare_header = (first.contains(" panicked at ") && first.ends_with(':'))
+        || !first.contains(char::is_whitespace);
+    let header = match lines.next() {
+        Some(next) if bare_header => format!("{first} {next}"),
+        _ => first.to_string(),
+    };
+
+    header
+        .split_whitespace()
+        .map(normalize_token)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Stable 64-bit FNV-1a of the signature, hex encoded. `DefaultHasher` is not
+/// guaranteed stable across Rust releases, and fingerprints live in SQLite.
+pub fn fingerprint(signature: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in signature.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
+fn normalize_token(token: &str) -> String {
+    if token.contains("://") {
+        return "<url>".to_string();
+    }
+    let token = match token.rfind(['/', '\\']) {
+        Some(index) => &token[index + 1..],
+        None => token,
+    };
+
+    let mut out = String::with_capacity(token.len());
+    let mut word = String::new();
+    for c in token.chars() {
+        if c.is_ascii_alphanumeric() || c == '-' {
+            word.push(c);
+        } else {
+            out.push_str(&normalize_word(&word));
+            word.clear();
+            out.push(c);
+        }
+    }
+    out.push_str(&normalize_word(&word));
+    out
+}
+
+fn normalize_word(word: &str) -> String {
+    let is_id = word.len() >= 8
+        && word.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+        && word.chars().any(|c| c.is_ascii_digit());
+    if is_id {
+        return "<id>".to_string();
+    }
+    // Plain numbers and numbers with a unit suffix (`35ms`, `2s`, `10px`).
+    word.split('-')
+        .map(|part| {
+            let unit = part.trim_start_matches(|c: char| c.is_ascii_digit());
+            if unit.len() < part.len() && unit.chars().all(|c| c.is_ascii_alphabetic()) {
+                format!("<n>{unit}")
+            } else {
+                part.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PersistingSignature {
+    pub fingerprint: String,
+    pub signature: String,
+    pub count_a: i64,
+    pub count_b: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct SignatureDiff {
+    pub new: Vec<RunSignature>,
+    pub resolved: Vec<RunSignature>,
+    pub persisting: Vec<PersistingSignature>,
+}
+
+/// Compare the error signatures of a baseline run `a` with a later run `b`.
+pub fn diff(a: Vec<RunSignature>, b: Vec<RunSignature>) -> SignatureDiff {
+    let mut before: BTreeMap<String, RunSignature> = a
+        .into_iter()
+        .map(|sig| (sig.fingerprint.clone(), sig))
+        .collect();
+    let mut out = SignatureDiff::default();
+    for sig in b {
+        match before.remove(&sig.fingerprint) {
+            Some(old) => out.persisting.push(PersistingSignature {
+                fingerprint: sig.fingerprint,
+                signature: sig.signature,
+                count_a: old.count,
+                count_b: sig.count,
+            }),
+            None => out.new.push(sig),
+        }
+    }
+    out.resolved = before.into_values().collect();
+    out.resolved.sort_by_key(|sig| std::cmp::Reverse(sig.count));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_ignores_volatile_parts() {
+        let a = signature(
+            "TypeError: Cannot read property 'x' of undefined at /Users/a/app/pages/index.js:12:7 \
+             (req 550e8400-e29b-41d4-a716-446655440000, took 35ms)",
+        );
+        let b = signature(
+            "TypeError: Cannot read property 'x' of undefined at C:\\work\\app\\pages\\index.js:40:1 \
+             (req 123e4567-e89b-12d3-a456-426614174000, took 120ms)",
+        );
+        assert_eq!(a, b);
+        assert_eq!(fingerprint(&a), fingerprint(&b));
+        assert!(a.contains("index.js:<n>:<n>"), "{a}");
+        assert_ne!(
+            signature("TypeError: Cannot read property 'y' of undefined"),
+            signature("TypeError: Cannot read property 'x' of undefined")
+        );
+    }
+
+    #[test]
+    fn signature_uses_message_after_bare_headers() {
+        let panic = signature("thread 'main' panicked at src/main.rs:10:5:\nindex out of bounds: the len is 3 but the index is 7\nnote: run with `RUST_BACKTRACE=1`");
+        assert_eq!(
+            panic,
+            "thread 'main' panicked at main.rs:<n>:<n>: index out of bounds: the len is <n> but the index is <n>"
+        );
+        assert_eq!(
+            signature("VM12:1 thirdScriptError\nx is not defined"),
+            "thirdScriptError x is not defined"
+        );
+    }
+}
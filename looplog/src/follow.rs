@@ -0,0 +1,141 @@
+use crate::db::{LineRecord, RunRecord, SqliteStorage};
+use crate::error::{Error, Result};
+use crate::parser::Level;
+use serde::Serialize;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// One NDJSON record of a follow stream. The last record is always `finished`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FollowEvent {
+    Line(LineRecord),
+    Finished(RunRecord),
+}
+
+/// Polls SQLite for lines appended to one run until the run leaves `running`.
+///
+/// Both `looplog follow` and `GET /v1/runs/{id}/follow` are built on this, so the CLI
+/// and HTTP subscribers see the same records in the same order.
+pub struct Follower {
+    storage: SqliteStorage,
+    run_id: String,
+    after_seq: i64,
+    stream: Option<String>,
+    min_level: Option<Level>,
+    done: bool,
+}
+
+impl Follower {
+    pub fn new(
+        storage: SqliteStorage,
+        run_id: String,
+        after_seq: i64,
+        stream: Option<String>,
+        min_level: Option<Level>,
+    ) -> Self {
+        Self {
+            storage,
+            run_id,
+            after_seq,
+            stream,
+            min_level,
+            done: false,
+        }
+    }
+
+    /// Wait up to `wait` for new records. Returns an empty batch on timeout and `None`
+    /// once the `finished` record has been returned.
+    pub fn next_events(&mut self, wait: Duration) -> Result<Option<Vec<FollowEvent>>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let deadline = Instant::now() + wait;
+        loop {
+            // Read the status before the lines: a run is only finished after its last
+            // append, so seeing `finished` here means no line can be missed below.
+            let run = self
+                .storage
+                .get_run(&self.run_id)?
+                .ok_or_else(|| Error::Message(format!("run `{}` not found", self.run_id)))?;
+            let lines = self.storage.lines_after(
+                &self.run_id,
+                self.after_seq,
+                self.stream.as_deref(),
+                self.min_level,
+            )?;
+
+            if let Some(last) = lines.last() {
+                self.after_seq = last.seq;
+                return Ok(Some(lines.into_iter().map(FollowEvent::Line).collect()));
+            }
+            if run.status != "running" {
+                self.done = true;
+                return Ok(Some(vec![FollowEvent::Finished(run)]));
+            }
+            if Instant::now() >= deadline {
+                return Ok(Some(Vec::new()));
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::NewRun;
+    use crate::protocol::{AppendLine, FinishRunRequest};
+    use std::collections::BTreeMap;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn streams_new_lines_then_finishes() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut writer = SqliteStorage::open(tmp.path()).unwrap();
+        let run_id = writer
+            .start_run(NewRun {
+                tag: None,
+                source: None,
+                cwd: None,
+                argv: vec![],
+                client_id: None,
+                kind: None,
+                meta: BTreeMap::new(),
+            })
+            .unwrap();
+        let line = |text: &str| AppendLine {
+            text: text.to_string(),
+            ..Default::default()
+        };
+        writer.append_lines(&run_id, &[line("one")]).unwrap();
+
+        let mut follower = Follower::new(
+            SqliteStorage::open(tmp.path()).unwrap(),
+            run_id.clone(),
+            0,
+            None,
+            None,
+        );
+        let first = follower.next_events(Duration::ZERO).unwrap().unwrap();
+        assert!(matches!(&first[..], [FollowEvent::Line(l)] if l.text == "one"));
+        assert!(follower
+            .next_events(Duration::ZERO)
+            .unwrap()
+            .unwrap()
+            .is_empty());
+
+        writer.append_lines(&run_id, &[line("two")]).unwrap();
+        writer
+            .finish_run(&run_id, &FinishRunRequest::default())
+            .unwrap();
+        let second = follower.next_events(Duration::ZERO).unwrap().unwrap();
+        assert!(matches!(&second[..], [FollowEvent::Line(l)] if l.text == "two"));
+        let last = follower.next_events(Duration::ZERO).unwrap().unwrap();
+        assert!(matches!(&last[..], [FollowEvent::Finished(run)] if run.status == "ok"));
+        assert!(follower.next_events(Duration::ZERO).unwrap().is_none());
+    }
+}
@@ -1,28 +1,59 @@
 use crate::db::{NewRun, SqliteStorage};
 use crate::error::{Error, Result};
+use crate::follow::POLL_INTERVAL;
+use crate::parser::{Level, LineParser};
 use crate::protocol::{AppendLine, FinishRunRequest};
+use crate::redact::Redactor;
+use crate::tail::TailFile;
 use serde_json::Value;
 use std::collections::BTreeMap;
 use std::io::{self, BufRead, BufReader, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::sync::mpsc;
 use std::thread;
+use std::time::{Duration, Instant};
 
 pub struct IngestOptions {
     pub tag: Option<String>,
     pub source: Option<String>,
     pub kind: Option<String>,
     pub meta: BTreeMap<String, Value>,
+    /// Mirror child output to our stdout/stderr and let the child read our stdin.
+    /// Off for `looplog mcp`, where stdio carries the protocol.
+    pub passthrough: bool,
+    /// `None` only with an explicit `--no-redact`.
+    pub redactor: Option<Redactor>,
+    /// `run_command` kills the child once this has elapsed. `None` waits for it to exit.
+    pub timeout: Option<Duration>,
 }
 
-pub fn run_command(db_path: &Path, opts: IngestOptions, command: &[String]) -> Result<i32> {
+pub struct RunOutcome {
+    pub run_id: String,
+    pub exit_code: i32,
+    /// The child was killed at `IngestOptions::timeout`; the run holds its output so far.
+    pub timed_out: bool,
+}
+
+impl RunOutcome {
+    /// Stored run status: `ok`, `failed`, or `timeout`.
+    pub fn status(&self) -> &'static str {
+        match (self.timed_out, self.exit_code) {
+            (true, _) => "timeout",
+            (false, 0) => "ok",
+            (false, _) => "failed",
+        }
+    }
+}
+
+pub fn run_command(db_path: &Path, opts: IngestOptions, command: &[String]) -> Result<RunOutcome> {
     if command.is_empty() {
         return Err(Error::MissingCommand);
     }
 
     let cwd = std::env::current_dir()?.display().to_string();
     let mut storage = SqliteStorage::open(db_path)?;
+    storage.set_redactor(opts.redactor);
     let run_id = storage.start_run(NewRun {
         tag: opts.tag,
         source: opts.source.or_else(|| Some("looplog-run".to_string())),
@@ -35,6 +66,11 @@ pub fn run_command(db_path: &Path, opts: IngestOptions, command: &[String]) -> R
 
     let mut child = Command::new(&command[0])
         .args(&command[1..])
+        .stdin(if opts.passthrough {
+            Stdio::inherit()
+        } else {
+            Stdio::null()
+        })
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()?;
@@ -48,29 +84,71 @@ pub fn run_command(db_path: &Path, opts: IngestOptions, command: &[String]) -> R
         .take()
         .ok_or_else(|| Error::Message("could not capture stderr".to_string()))?;
 
-    let (tx, rx) = mpsc::channel::<AppendLine>();
-    spawn_reader(stdout, "stdout", tx.clone());
-    spawn_reader(stderr, "stderr", tx);
+    let (tx, rx) = mpsc::channel::<(Stream, String)>();
+    spawn_reader(stdout, Stream::Stdout, opts.passthrough, tx.clone());
+    spawn_reader(stderr, Stream::Stderr, opts.passthrough, tx);
 
-    for line in rx {
-        storage.append_lines(&run_id, &[line])?;
+    let mut parsers = [
+        LineParser::new("stdout", Level::Info),
+        LineParser::new("stderr", Level::Info),
+    ];
+    let mut deadline = opts.timeout.map(|timeout| Instant::now() + timeout);
+    let mut timed_out = false;
+    loop {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            if timed_out {
+                // Grandchildren may still hold the pipes open; stop waiting for them.
+                break;
+            }
+            timed_out = true;
+            let _ = child.kill();
+            // Give the readers a moment to deliver what the child wrote before it died.
+            deadline = Some(Instant::now() + POLL_INTERVAL);
+        }
+        let wait = deadline.map_or(POLL_INTERVAL, |deadline| {
+            deadline
+                .saturating_duration_since(Instant::now())
+                .min(POLL_INTERVAL)
+        });
+        let batch = match rx.recv_timeout(wait) {
+            Ok((stream, text)) => parsers[stream as usize].push(text),
+            // Quiet output: a pending multi-line record (stack trace) is complete, so
+            // `follow` sees it now rather than when the child writes again.
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                parsers.iter_mut().filter_map(LineParser::finish).collect()
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+        if !batch.is_empty() {
+            storage.append_lines(&run_id, &batch)?;
+        }
+    }
+    let rest: Vec<AppendLine> = parsers.iter_mut().filter_map(LineParser::finish).collect();
+    if !rest.is_empty() {
+        storage.append_lines(&run_id, &rest)?;
     }
 
     let status = child.wait()?;
-    let exit_code = status.code().unwrap_or(1);
+    let outcome = RunOutcome {
+        run_id,
+        exit_code: status.code().unwrap_or(1),
+        timed_out,
+    };
     storage.finish_run(
-        &run_id,
+        &outcome.run_id,
         &FinishRunRequest {
-            status: Some(if exit_code == 0 { "ok" } else { "failed" }.to_string()),
-            exit_code: Some(exit_code),
+            status: Some(outcome.status().to_string()),
+            exit_code: Some(outcome.exit_code),
         },
     )?;
-    Ok(exit_code)
+    Ok(outcome)
 }
 
-pub fn push_stdin(db_path: &Path, opts: IngestOptions, level: Option<String>) -> Result<String> {
+/// `level` is the fallback for lines without a recognizable level marker.
+pub fn push_stdin(db_path: &Path, opts: IngestOptions, level: Option<Level>) -> Result<String> {
     let cwd = std::env::current_dir()?.display().to_string();
     let mut storage = SqliteStorage::open(db_path)?;
+    storage.set_redactor(opts.redactor);
     let run_id = storage.start_run(NewRun {
         tag: opts.tag,
         source: opts.source.or_else(|| Some("looplog-push".to_string())),
@@ -82,19 +160,16 @@ pub fn push_stdin(db_path: &Path, opts: IngestOptions, level: Option<String>) ->
     })?;
 
     let stdin = io::stdin();
+    let mut parser = LineParser::new("stdin", level.unwrap_or(Level::Info));
     let mut batch = Vec::new();
     for line in stdin.lock().lines() {
-        batch.push(AppendLine {
-            stream: Some("stdin".to_string()),
-            level: level.clone().or_else(|| Some("info".to_string())),
-            text: line?,
-            ..Default::default()
-        });
+        batch.extend(parser.push(line?));
         if batch.len() >= 100 {
             storage.append_lines(&run_id, &batch)?;
             batch.clear();
         }
     }
+    batch.extend(parser.finish());
     if !batch.is_empty() {
         storage.append_lines(&run_id, &batch)?;
     }
@@ -108,27 +183,107 @@ pub fn push_stdin(db_path: &Path, opts: IngestOptions, level: Option<String>) ->
     Ok(run_id)
 }
 
-fn spawn_reader<R>(reader: R, stream: &'static str, tx: mpsc::Sender<AppendLine>)
+/// Follow files like `tail -F`, one run per file, until the process is killed.
+/// The runs stay `running`: a tailed file has no natural end.
+pub fn tail_files(
+    db_path: &Path,
+    opts: IngestOptions,
+    paths: &[PathBuf],
+    from_start: bool,
+    level: Option<Level>,
+) -> Result<()> {
+    let cwd = std::env::current_dir()?.display().to_string();
+    let mut storage = SqliteStorage::open(db_path)?;
+    storage.set_redactor(opts.redactor);
+    let new_run = |path: &Path| {
+        let mut meta = opts.meta.clone();
+        meta.insert("file".to_string(), Value::from(path.display().to_string()));
+        NewRun {
+            tag: opts.tag.clone(),
+            source: opts
+                .source
+                .clone()
+                .or_else(|| Some("looplog-tail".to_string())),
+            cwd: Some(cwd.clone()),
+            argv: vec!["tail".to_string(), path.display().to_string()],
+            client_id: None,
+            kind: opts.kind.clone(),
+            meta,
+        }
+    };
+    let mut tails = Vec::new();
+    for path in paths {
+        let run_id = storage.start_run(new_run(path))?;
+        eprintln!("looplog: tailing {} into run {}", path.display(), run_id);
+        let parser = LineParser::new("file", level.unwrap_or(Level::Info));
+        tails.push((run_id, TailFile::new(path, from_start), parser));
+    }
+
+    loop {
+        let mut idle = true;
+        for (run_id, file, parser) in &mut tails {
+            let lines = file.poll().map_err(|e| {
+                Error::Message(format!("cannot read {}: {}", file.path().display(), e))
+            })?;
+            let mut batch = Vec::new();
+            if lines.is_empty() {
+                // Nothing new: a pending multi-line record (stack trace) is complete.
+                batch.extend(parser.finish());
+            } else {
+                idle = false;
+                for line in lines {
+                    batch.extend(parser.push(line));
+                }
+            }
+            match storage.append_lines(run_id, &batch) {
+                // Retention removed the run after the file sat quiet past its window;
+                // carry on in a fresh one.
+                Err(Error::RunNotFound(_)) => {
+                    *run_id = storage.start_run(new_run(file.path()))?;
+                    eprintln!(
+                        "looplog: tailing {} into new run {}",
+                        file.path().display(),
+                        run_id
+                    );
+                    storage.append_lines(run_id, &batch)?;
+                }
+                result => {
+                    result?;
+                }
+            }
+        }
+        if idle {
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Stream {
+    Stdout = 0,
+    Stderr = 1,
+}
+
+/// Echo and forward raw lines; parsing happens on the receiving side so that a
+/// pending record can be flushed when the child goes quiet.
+fn spawn_reader<R>(reader: R, stream: Stream, echo: bool, tx: mpsc::Sender<(Stream, String)>)
 where
     R: std::io::Read + Send + 'static,
 {
     thread::spawn(move || {
-        let mut out = if stream == "stderr" {
-            Box::new(io::stderr()) as Box<dyn Write + Send>
-        } else {
-            Box::new(io::stdout()) as Box<dyn Write + Send>
+        let mut out = match (echo, stream) {
+            (false, _) => Box::new(io::sink()) as Box<dyn Write + Send>,
+            (true, Stream::Stderr) => Box::new(io::stderr()) as Box<dyn Write + Send>,
+            (true, Stream::Stdout) => Box::new(io::stdout()) as Box<dyn Write + Send>,
         };
         for line in BufReader::new(reader).lines() {
             let Ok(text) = line else {
                 break;
             };
             let _ = writeln!(out, "{}", text);
-            let _ = tx.send(AppendLine {
-                stream: Some(stream.to_string()),
-                level: Some(if stream == "stderr" { "error" } else { "info" }.to_string()),
-                text,
-                ..Default::default()
-            });
+            if tx.send((stream, text)).is_err() {
+                break;
+            }
         }
     });
 }
@@ -1,17 +1,36 @@
+mod bundle;
 mod db;
 mod error;
+mod fingerprint;
+mod follow;
 mod ingest;
+mod mcp;
 mod meta;
+mod otlp;
 mod output;
+mod parser;
 mod protocol;
+mod redact;
 mod server;
 mod storage;
+mod syslog;
+mod tail;
+mod timeline;
+mod writer;
 
 use clap::{Parser, Subcommand};
-use db::{default_db_path, parse_since, QueryFilter, SqliteStorage, DEFAULT_KEEP_HOURS};
+use db::{
+    default_db_path, parse_since, QueryFilter, SqliteStorage, MAX_KEEP_HOURS, SEARCH_BACKEND,
+};
 use error::{Error, Result};
+use follow::{FollowEvent, Follower};
 use ingest::IngestOptions;
-use std::path::PathBuf;
+use parser::Level;
+use protocol::{LinesResponse, RunsResponse, SearchResponse, StatsResponse};
+use redact::Redactor;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(
@@ -26,8 +45,16 @@ use std::path::PathBuf;
     after_help = "Examples:\n  \
         looplog serve --addr 127.0.0.1:3768\n  \
         looplog run --tag miniprogram-build --meta appid=wx123 -- npm run build\n  \
+        looplog tail ./logs/app.log --tag dev-server\n  \
         looplog list --kind wechat_miniprogram --appid wx123 --json\n  \
-        looplog grep TypeError --appid wx123 --page pages/index/index --since 2h --json"
+        looplog grep TypeError --appid wx123 --page pages/index/index --since 2h --json\n  \
+        looplog show <run_id> --errors-only --json\n  \
+        looplog follow --tag miniprogram-build --errors-only\n  \
+        looplog diff <run_a> <run_b> --json\n  \
+        looplog top-errors --since 2h --json\n  \
+        looplog timeline --session s-42 --before 30s\n  \
+        looplog pin <run_id> && looplog export <run_id> --out repro.ndjson\n  \
+        looplog mcp"
 )]
 struct Cli {
     /// Output machine-readable JSON.
@@ -49,9 +76,21 @@ enum Commands {
         /// Address to bind. Must be loopback for the MVP.
         #[arg(long, default_value = "127.0.0.1:3768")]
         addr: String,
-        /// Retention window in hours. Values above 24 are capped to 24.
-        #[arg(long, default_value_t = DEFAULT_KEEP_HOURS)]
-        keep_hours: u64,
+        /// Retention window in hours. Defaults to the stored default (24 unless
+        /// changed with `retention default`). Capped to 720 (30 days).
+        #[arg(long)]
+        keep_hours: Option<u64>,
+        /// HTTP worker threads. All writes still go through one SQLite writer thread.
+        #[arg(long, default_value_t = 4)]
+        workers: usize,
+        /// Pending write requests before new writes get `429 Too Many Requests`.
+        #[arg(long, default_value_t = 1024)]
+        queue: usize,
+        /// Also receive syslog (RFC 5424/3164) over UDP, e.g. `127.0.0.1:5514`.
+        #[arg(long, value_name = "ADDR")]
+        syslog: Option<String>,
+        #[command(flatten)]
+        redact: RedactArgs,
     },
     /// Run a command, capture stdout/stderr, and return the child exit code.
     Run {
@@ -64,6 +103,8 @@ enum Commands {
         /// Metadata as key=value. Useful keys: appid, project_path, page, session, trace_id.
         #[arg(long = "meta", value_name = "KEY=VALUE")]
         meta: Vec<String>,
+        #[command(flatten)]
+        redact: RedactArgs,
         #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
         command: Vec<String>,
     },
@@ -75,10 +116,34 @@ enum Commands {
         source: Option<String>,
         #[arg(long)]
         kind: Option<String>,
+        /// Level for lines without a recognizable level marker. Defaults to info.
         #[arg(long)]
-        level: Option<String>,
+        level: Option<Level>,
         #[arg(long = "meta", value_name = "KEY=VALUE")]
         meta: Vec<String>,
+        #[command(flatten)]
+        redact: RedactArgs,
+    },
+    /// Follow log files (surviving rotation and truncation) and store each as a run.
+    Tail {
+        #[arg(required = true, value_name = "PATH")]
+        paths: Vec<PathBuf>,
+        #[arg(long)]
+        tag: Option<String>,
+        #[arg(long)]
+        source: Option<String>,
+        #[arg(long)]
+        kind: Option<String>,
+        /// Level for lines without a recognizable level marker. Defaults to info.
+        #[arg(long)]
+        level: Option<Level>,
+        #[arg(long = "meta", value_name = "KEY=VALUE")]
+        meta: Vec<String>,
+        /// Read existing content too, not just lines written from now on.
+        #[arg(long)]
+        from_start: bool,
+        #[command(flatten)]
+        redact: RedactArgs,
     },
     /// List recent runs.
     List {
@@ -94,6 +159,22 @@ enum Commands {
         tail: Option<usize>,
         #[arg(long)]
         stream: Option<String>,
+        #[command(flatten)]
+        levels: LevelArgs,
+    },
+    /// Follow a run like `tail -f` until it is finished.
+    Follow {
+        /// Run to follow. Defaults to the newest run matching the filter, waiting for
+        /// one to start if none matches yet.
+        #[arg(value_name = "RUN_ID")]
+        run: Option<String>,
+        #[command(flatten)]
+        filter: FilterArgs,
+        /// Start with the last N existing lines instead of the whole run.
+        #[arg(long)]
+        tail: Option<usize>,
+        #[arg(long)]
+        stream: Option<String>,
     },
     /// Search log lines.
     Grep {
@@ -103,13 +184,101 @@ enum Commands {
         #[arg(long, default_value_t = 50)]
         limit: usize,
     },
-    /// Delete expired records. Defaults to 24 hours retention.
+    /// Summarize stored runs and lines, optionally for a filter.
+    Stats {
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+    /// Compare error signatures of two runs: new, resolved, and persisting.
+    Diff {
+        /// Baseline run id (or unique prefix).
+        run_a: String,
+        /// Later run id (or unique prefix).
+        run_b: String,
+    },
+    /// Most frequent error signatures with counts and first/last seen.
+    TopErrors {
+        #[command(flatten)]
+        filter: FilterArgs,
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Merge lines from all runs sharing a session or trace id into one time-ordered view.
+    Timeline {
+        /// Correlate by this run's session and trace id.
+        #[arg(value_name = "RUN_ID")]
+        run: Option<String>,
+        #[arg(long)]
+        session: Option<String>,
+        #[arg(long = "trace")]
+        trace: Option<String>,
+        /// Only lines this long before the anchor (the first fatal, else first error line).
+        #[arg(long, value_parser = humantime::parse_duration)]
+        before: Option<Duration>,
+        /// Only lines this long after the anchor.
+        #[arg(long, value_parser = humantime::parse_duration)]
+        after: Option<Duration>,
+        /// Mark silences at least this long.
+        #[arg(long, value_parser = humantime::parse_duration, default_value = "5s")]
+        gap: Duration,
+        /// Keep at most this many lines, nearest the anchor.
+        #[arg(long, default_value_t = 500)]
+        limit: usize,
+        #[command(flatten)]
+        levels: LevelArgs,
+    },
+    /// Delete expired records. Pinned runs and per-tag rules are respected.
     Clean {
-        #[arg(long, default_value_t = DEFAULT_KEEP_HOURS)]
-        keep_hours: u64,
+        /// Window for runs without a tag rule. Defaults to the stored default.
+        #[arg(long)]
+        keep_hours: Option<u64>,
         #[arg(long)]
         vacuum: bool,
     },
+    /// Exempt a run from retention cleanup.
+    Pin { run_id: String },
+    /// Make a pinned run subject to retention again.
+    Unpin { run_id: String },
+    /// Show or change retention rules.
+    Retention {
+        #[command(subcommand)]
+        action: Option<RetentionAction>,
+    },
+    /// Write runs to a portable bundle (`.ndjson`, or `.db`/`.sqlite` for SQLite).
+    Export {
+        /// Run ids or unique prefixes. Defaults to the runs matching the filter.
+        run_ids: Vec<String>,
+        #[command(flatten)]
+        filter: FilterArgs,
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        #[arg(long, value_name = "PATH")]
+        out: PathBuf,
+    },
+    /// Load runs from a bundle written by `export`. Imported runs are pinned.
+    Import { path: PathBuf },
+    /// Serve MCP (Model Context Protocol) tools over stdio for AI agents.
+    Mcp {
+        /// Approximate size limit for one tool result; longer results are paginated.
+        #[arg(long, default_value_t = mcp::DEFAULT_MAX_BYTES)]
+        max_bytes: usize,
+        #[command(flatten)]
+        redact: RedactArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum RetentionAction {
+    /// Print the current policy.
+    Show,
+    /// Set the window for runs without a tag rule.
+    Default { keep_hours: u64 },
+    /// Keep runs with this tag for a custom window.
+    Tag { tag: String, keep_hours: u64 },
+    /// Remove a per-tag rule.
+    Untag { tag: String },
+    /// Cap the total stored log size in MiB; oldest finished runs are evicted. 0 disables.
+    MaxSize { mib: u64 },
 }
 
 #[derive(clap::Args, Debug, Clone, Default)]
@@ -133,6 +302,47 @@ struct FilterArgs {
     /// Relative duration like 2h/30m or RFC3339 timestamp.
     #[arg(long)]
     since: Option<String>,
+    #[command(flatten)]
+    levels: LevelArgs,
+}
+
+#[derive(clap::Args, Debug, Clone, Default)]
+struct LevelArgs {
+    /// Minimum severity: trace, debug, info, warn, error or fatal.
+    #[arg(long)]
+    level: Option<Level>,
+    /// Shorthand for `--level error`.
+    #[arg(long, conflicts_with = "level")]
+    errors_only: bool,
+}
+
+#[derive(clap::Args, Debug, Clone, Default)]
+struct RedactArgs {
+    /// Store captured text as-is, without masking tokens, secrets and personal data.
+    #[arg(long)]
+    no_redact: bool,
+    /// Extra redaction rules. Defaults to `<config dir>/looplog/redact.json` if present.
+    #[arg(long, value_name = "PATH", conflicts_with = "no_redact")]
+    redact_config: Option<PathBuf>,
+}
+
+impl RedactArgs {
+    fn redactor(&self) -> Result<Option<Redactor>> {
+        if self.no_redact {
+            return Ok(None);
+        }
+        Redactor::load(self.redact_config.as_deref()).map(Some)
+    }
+}
+
+impl LevelArgs {
+    fn min_level(&self) -> Option<Level> {
+        if self.errors_only {
+            Some(Level::Error)
+        } else {
+            self.level
+        }
+    }
 }
 
 fn main() {
@@ -147,28 +357,52 @@ fn run() -> Result<()> {
     let db_path = cli.db.unwrap_or_else(default_db_path);
 
     match cli.command {
-        Commands::Serve { addr, keep_hours } => {
+        Commands::Serve {
+            addr,
+            keep_hours,
+            workers,
+            queue,
+            syslog,
+            redact,
+        } => {
             ensure_loopback(&addr)?;
-            server::serve(&addr, db_path, cap_keep_hours(keep_hours))
+            if let Some(syslog) = &syslog {
+                ensure_loopback(syslog)?;
+            }
+            server::serve(
+                &addr,
+                db_path,
+                server::ServeOptions {
+                    keep_hours: keep_hours.map(cap_keep_hours),
+                    workers,
+                    queue,
+                    redactor: redact.redactor()?,
+                    syslog,
+                },
+            )
         }
         Commands::Run {
             tag,
             source,
             kind,
             meta,
+            redact,
             command,
         } => {
-            let exit_code = ingest::run_command(
+            let outcome = ingest::run_command(
                 &db_path,
                 IngestOptions {
                     tag,
                     source,
-                    kind: normalize_kind(kind),
+                    kind: meta::normalize_kind(kind),
                     meta: meta::parse_cli_meta(&meta)?,
+                    passthrough: true,
+                    redactor: redact.redactor()?,
+                    timeout: None,
                 },
                 &command,
             )?;
-            std::process::exit(exit_code);
+            std::process::exit(outcome.exit_code);
         }
         Commands::Push {
             tag,
@@ -176,14 +410,18 @@ fn run() -> Result<()> {
             kind,
             level,
             meta,
+            redact,
         } => {
             let run_id = ingest::push_stdin(
                 &db_path,
                 IngestOptions {
                     tag,
                     source,
-                    kind: normalize_kind(kind),
+                    kind: meta::normalize_kind(kind),
                     meta: meta::parse_cli_meta(&meta)?,
+                    passthrough: true,
+                    redactor: redact.redactor()?,
+                    timeout: None,
                 },
                 level,
             )?;
@@ -194,11 +432,35 @@ fn run() -> Result<()> {
             }
             Ok(())
         }
+        Commands::Tail {
+            paths,
+            tag,
+            source,
+            kind,
+            level,
+            meta,
+            from_start,
+            redact,
+        } => ingest::tail_files(
+            &db_path,
+            IngestOptions {
+                tag,
+                source,
+                kind: meta::normalize_kind(kind),
+                meta: meta::parse_cli_meta(&meta)?,
+                passthrough: true,
+                redactor: redact.redactor()?,
+                timeout: None,
+            },
+            &paths,
+            from_start,
+            level,
+        ),
         Commands::List { filter, limit } => {
             let mut storage = SqliteStorage::open(&db_path)?;
             let runs = storage.list_runs(&filter.try_into()?, limit)?;
             if cli.json {
-                output::print_json(&serde_json::json!({"status": "ok", "runs": runs}))?;
+                output::print_json(&RunsResponse { status: "ok", runs })?;
             } else {
                 output::print_runs(&runs);
             }
@@ -208,20 +470,27 @@ fn run() -> Result<()> {
             run_id,
             tail,
             stream,
+            levels,
         } => {
             let mut storage = SqliteStorage::open(&db_path)?;
-            let lines = storage.show_lines(&run_id, tail, stream.as_deref())?;
+            let lines = storage.show_lines(&run_id, tail, stream.as_deref(), levels.min_level())?;
             if cli.json {
-                output::print_json(&serde_json::json!({
-                    "status": "ok",
-                    "run_id": run_id,
-                    "lines": lines
-                }))?;
+                output::print_json(&LinesResponse {
+                    status: "ok",
+                    run_id,
+                    lines,
+                })?;
             } else {
                 output::print_lines(&lines);
             }
             Ok(())
         }
+        Commands::Follow {
+            run,
+            filter,
+            tail,
+            stream,
+        } => follow_run(&db_path, run, filter, tail, stream, cli.json),
         Commands::Grep {
             pattern,
             filter,
@@ -230,19 +499,119 @@ fn run() -> Result<()> {
             let mut storage = SqliteStorage::open(&db_path)?;
             let hits = storage.search(&pattern, &filter.try_into()?, limit)?;
             if cli.json {
-                output::print_json(&serde_json::json!({
+                output::print_json(&SearchResponse {
+                    status: "ok",
+                    search_backend: SEARCH_BACKEND,
+                    hits,
+                })?;
+            } else {
+                output::print_hits(&hits);
+            }
+            Ok(())
+        }
+        Commands::Stats { filter } => {
+            let mut storage = SqliteStorage::open(&db_path)?;
+            let stats = storage.stats(&filter.try_into()?)?;
+            if cli.json {
+                output::print_json(&StatsResponse {
+                    status: "ok",
+                    stats,
+                })?;
+            } else {
+                output::print_stats(&stats);
+            }
+            Ok(())
+        }
+        Commands::Diff { run_a, run_b } => {
+            let mut storage = SqliteStorage::open(&db_path)?;
+            let run_a = storage.resolve_run_id(&run_a)?;
+            let run_b = storage.resolve_run_id(&run_b)?;
+            let diff = fingerprint::diff(
+                storage.run_signatures(&run_a)?,
+                storage.run_signatures(&run_b)?,
+            );
+            // Compact single-line JSON: meant to be pasted into an agent's context.
+            if cli.json {
+                output::print_json_line(&serde_json::json!({
                     "status": "ok",
-                    "search_backend": "like",
-                    "hits": hits
+                    "run_a": run_a,
+                    "run_b": run_b,
+                    "diff": diff
                 }))?;
             } else {
-                output::print_hits(&hits);
+                output::print_signature_diff(&diff);
+            }
+            Ok(())
+        }
+        Commands::TopErrors { filter, limit } => {
+            let mut storage = SqliteStorage::open(&db_path)?;
+            let errors = storage.top_errors(&filter.try_into()?, limit)?;
+            if cli.json {
+                output::print_json_line(&serde_json::json!({"status": "ok", "errors": errors}))?;
+            } else {
+                output::print_top_errors(&errors);
+            }
+            Ok(())
+        }
+        Commands::Timeline {
+            run,
+            session,
+            trace,
+            before,
+            after,
+            gap,
+            limit,
+            levels,
+        } => {
+            let mut storage = SqliteStorage::open(&db_path)?;
+            let (session, trace) = match run {
+                Some(run) => {
+                    let run_id = storage.resolve_run_id(&run)?;
+                    let record = storage
+                        .get_run(&run_id)?
+                        .ok_or(Error::RunNotFound(run_id))?;
+                    (session.or(record.session), trace.or(record.trace_id))
+                }
+                None => (session, trace),
+            };
+            if session.is_none() && trace.is_none() {
+                return Err(Error::Message(
+                    "timeline needs --session, --trace, or a run with a session or trace_id"
+                        .to_string(),
+                ));
+            }
+            let correlated =
+                storage.correlated(session.as_deref(), trace.as_deref(), levels.min_level())?;
+            let timeline = timeline::build(
+                correlated,
+                &timeline::TimelineOptions {
+                    before_ms: before.map(|d| d.as_millis() as i64),
+                    after_ms: after.map(|d| d.as_millis() as i64),
+                    gap_ms: gap.as_millis() as i64,
+                    limit,
+                },
+            );
+            if cli.json {
+                output::print_json_line(&serde_json::json!({
+                    "status": "ok",
+                    "session": session,
+                    "trace": trace,
+                    "timeline": timeline
+                }))?;
+            } else {
+                let color =
+                    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+                output::print_timeline(&timeline, color);
             }
             Ok(())
         }
         Commands::Clean { keep_hours, vacuum } => {
             let mut storage = SqliteStorage::open(&db_path)?;
-            let stats = storage.clean(cap_keep_hours(keep_hours), vacuum)?;
+            let keep_hours = match keep_hours {
+                Some(hours) => cap_keep_hours(hours),
+                None => storage.default_keep_hours()?,
+            };
+            let stats = storage.clean(keep_hours, vacuum)?;
             if cli.json {
                 output::print_json(&serde_json::json!({"status": "ok", "clean": stats}))?;
             } else {
@@ -250,7 +619,152 @@ fn run() -> Result<()> {
             }
             Ok(())
         }
+        Commands::Pin { run_id } => set_pinned(&db_path, &run_id, true, cli.json),
+        Commands::Unpin { run_id } => set_pinned(&db_path, &run_id, false, cli.json),
+        Commands::Retention { action } => {
+            let mut storage = SqliteStorage::open(&db_path)?;
+            match action.unwrap_or(RetentionAction::Show) {
+                RetentionAction::Show => {}
+                RetentionAction::Default { keep_hours } => {
+                    storage.set_default_keep_hours(cap_keep_hours(keep_hours))?
+                }
+                RetentionAction::Tag { tag, keep_hours } => {
+                    storage.set_tag_retention(&tag, Some(cap_keep_hours(keep_hours)))?
+                }
+                RetentionAction::Untag { tag } => storage.set_tag_retention(&tag, None)?,
+                RetentionAction::MaxSize { mib } => {
+                    storage.set_max_bytes((mib > 0).then_some(mib * 1024 * 1024))?
+                }
+            }
+            let policy = storage.retention_policy()?;
+            if cli.json {
+                output::print_json(&serde_json::json!({"status": "ok", "retention": policy}))?;
+            } else {
+                output::print_retention(&policy);
+            }
+            Ok(())
+        }
+        Commands::Export {
+            run_ids,
+            filter,
+            limit,
+            out,
+        } => {
+            let mut storage = SqliteStorage::open(&db_path)?;
+            let run_ids = if run_ids.is_empty() {
+                storage
+                    .list_runs(&filter.try_into()?, limit)?
+                    .into_iter()
+                    .map(|run| run.id)
+                    .collect()
+            } else {
+                run_ids
+                    .iter()
+                    .map(|id| storage.resolve_run_id(id))
+                    .collect::<Result<Vec<_>>>()?
+            };
+            if run_ids.is_empty() {
+                return Err(Error::Message(
+                    "no runs matched; nothing to export".to_string(),
+                ));
+            }
+            let count = bundle::export(&mut storage, &run_ids, &out)?;
+            if cli.json {
+                output::print_json(&serde_json::json!({
+                    "status": "ok",
+                    "runs": count,
+                    "path": out.display().to_string()
+                }))?;
+            } else {
+                println!("exported {} run(s) to {}", count, out.display());
+            }
+            Ok(())
+        }
+        Commands::Import { path } => {
+            let mut storage = SqliteStorage::open(&db_path)?;
+            let stats = bundle::import(&mut storage, &path)?;
+            if cli.json {
+                output::print_json(&serde_json::json!({"status": "ok", "import": stats}))?;
+            } else {
+                println!(
+                    "imported {} run(s), {} line(s); skipped {} existing run(s)",
+                    stats.imported_runs, stats.imported_lines, stats.skipped_runs
+                );
+            }
+            Ok(())
+        }
+        Commands::Mcp { max_bytes, redact } => mcp::serve(db_path, max_bytes, redact.redactor()?),
+    }
+}
+
+fn follow_run(
+    db_path: &Path,
+    run_id: Option<String>,
+    filter: FilterArgs,
+    tail: Option<usize>,
+    stream: Option<String>,
+    json: bool,
+) -> Result<()> {
+    let mut storage = SqliteStorage::open(db_path)?;
+    let min_level = filter.levels.min_level();
+    let run_id = match run_id.or_else(|| filter.run_id.clone()) {
+        Some(run_id) => run_id,
+        None => {
+            // The level filter applies to the followed lines, not to run selection.
+            let mut query: QueryFilter = filter.try_into()?;
+            query.min_level = None;
+            let mut announced = false;
+            loop {
+                if let Some(run) = storage.list_runs(&query, 1)?.pop() {
+                    break run.id;
+                }
+                if !announced && !json {
+                    eprintln!("looplog: waiting for a matching run...");
+                    announced = true;
+                }
+                std::thread::sleep(follow::POLL_INTERVAL);
+            }
+        }
+    };
+
+    let mut after_seq = 0;
+    if let Some(tail) = tail {
+        let lines = storage.show_lines(&run_id, Some(tail), stream.as_deref(), min_level)?;
+        if let Some(first) = lines.first() {
+            after_seq = first.seq - 1;
+        }
+    }
+
+    let mut follower = Follower::new(storage, run_id, after_seq, stream, min_level);
+    while let Some(events) = follower.next_events(Duration::from_secs(60))? {
+        for event in &events {
+            if json {
+                output::print_json_line(event)?;
+            } else {
+                match event {
+                    FollowEvent::Line(line) => output::print_lines(std::slice::from_ref(line)),
+                    FollowEvent::Finished(run) => output::print_finished(run),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn set_pinned(db_path: &Path, run_id: &str, pinned: bool, json: bool) -> Result<()> {
+    let mut storage = SqliteStorage::open(db_path)?;
+    let run_id = storage.resolve_run_id(run_id)?;
+    storage.set_pinned(&run_id, pinned)?;
+    if json {
+        output::print_json(&serde_json::json!({
+            "status": "ok",
+            "run_id": run_id,
+            "pinned": pinned
+        }))?;
+    } else {
+        println!("{} {}", if pinned { "pinned" } else { "unpinned" }, run_id);
     }
+    Ok(())
 }
 
 impl TryFrom<FilterArgs> for QueryFilter {
@@ -260,29 +774,20 @@ impl TryFrom<FilterArgs> for QueryFilter {
         Ok(QueryFilter {
             run_id: value.run_id,
             tag: value.tag,
-            kind: normalize_kind(value.kind),
+            kind: meta::normalize_kind(value.kind),
             appid: value.appid,
             project: value.project,
             page: value.page,
             session: value.session,
             trace: value.trace,
             since_ms: value.since.as_deref().map(parse_since).transpose()?,
+            min_level: value.levels.min_level(),
         })
     }
 }
 
-fn normalize_kind(kind: Option<String>) -> Option<String> {
-    kind.map(|k| {
-        if k == "wechat" || k == "wx" {
-            meta::DEFAULT_KIND.to_string()
-        } else {
-            k
-        }
-    })
-}
-
 fn cap_keep_hours(keep_hours: u64) -> u64 {
-    keep_hours.min(DEFAULT_KEEP_HOURS)
+    keep_hours.min(MAX_KEEP_HOURS)
 }
 
 fn ensure_loopback(addr: &str) -> Result<()> {
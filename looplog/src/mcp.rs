@@ -0,0 +1,591 @@
+use crate::db::{parse_since, QueryFilter, SqliteStorage, SEARCH_BACKEND};
+use crate::error::{Error, Result};
+use crate::fingerprint;
+use crate::ingest::{self, IngestOptions};
+use crate::meta;
+use crate::parser::Level;
+use crate::redact::Redactor;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// MCP revision implemented here: newline-delimited JSON-RPC 2.0 over stdio, tools only.
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+/// Default budget for one tool result, roughly 4k tokens.
+pub const DEFAULT_MAX_BYTES: usize = 16 * 1024;
+/// Longer line texts (minified bundles, base64 blobs) are clipped in tool results.
+const MAX_TEXT_CHARS: usize = 2000;
+const MAX_PAGE: usize = 500;
+/// Tool calls are served one at a time, so `run_command` must not block forever.
+const DEFAULT_RUN_TIMEOUT_SECS: u64 = 300;
+const MAX_RUN_TIMEOUT_SECS: u64 = 3600;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Serve MCP on stdin/stdout until stdin closes. Nothing else may write to stdout.
+/// `redactor` applies to output captured by `run_command`.
+pub fn serve(db_path: PathBuf, max_bytes: usize, redactor: Option<Redactor>) -> Result<()> {
+    let server = McpServer {
+        db_path,
+        max_bytes: max_bytes.max(1024),
+        redactor,
+    };
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => server.handle(message),
+            Err(e) => Some(rpc_error(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+        if let Some(response) = response {
+            writeln!(stdout, "{}", response)?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+struct McpServer {
+    db_path: PathBuf,
+    max_bytes: usize,
+    redactor: Option<Redactor>,
+}
+
+impl McpServer {
+    /// Returns `None` for notifications, which get no response.
+    fn handle(&self, message: Value) -> Option<Value> {
+        let id = message.get("id").cloned();
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            return Some(rpc_error(
+                id.unwrap_or(Value::Null),
+                INVALID_REQUEST,
+                "missing method",
+            ));
+        };
+        let id = id?;
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "initialize" => json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": "looplog", "version": env!("CARGO_PKG_VERSION")},
+                "instructions": "Query local debugging logs captured by looplog. Start with \
+                    list_runs or error_summary, then page through show_run or search_logs \
+                    with next_cursor."
+            }),
+            "ping" => json!({}),
+            "tools/list" => json!({ "tools": tool_definitions() }),
+            "tools/call" => {
+                let name = params.get("name").and_then(Value::as_str).unwrap_or("");
+                let empty = Map::new();
+                let args = params
+                    .get("arguments")
+                    .and_then(Value::as_object)
+                    .unwrap_or(&empty);
+                // Tool failures are results the model can read, not protocol errors.
+                let (text, is_error) = match self.call_tool(name, Args(args)) {
+                    Ok(text) => (text, false),
+                    Err(e) => (e.to_string(), true),
+                };
+                json!({
+                    "content": [{"type": "text", "text": text}],
+                    "isError": is_error
+                })
+            }
+            _ => {
+                return Some(rpc_error(
+                    id,
+                    METHOD_NOT_FOUND,
+                    &format!("method `{}` not found", method),
+                ))
+            }
+        };
+        Some(json!({"jsonrpc": "2.0", "id": id, "result": result}))
+    }
+
+    fn call_tool(&self, name: &str, args: Args) -> Result<String> {
+        let value = match name {
+            "list_runs" => self.list_runs(&args)?,
+            "show_run" => self.show_run(&args)?,
+            "search_logs" => self.search_logs(&args)?,
+            "run_command" => self.run_command(&args)?,
+            "error_summary" => self.error_summary(&args)?,
+            _ => return Err(Error::Message(format!("unknown tool `{}`", name))),
+        };
+        Ok(serde_json::to_string(&value)?)
+    }
+
+    fn list_runs(&self, args: &Args) -> Result<Value> {
+        let limit = args.limit(20)?;
+        let offset = args.offset_cursor()?;
+        let mut storage = SqliteStorage::open(&self.db_path)?;
+        let runs = storage.list_runs(&args.filter()?, offset + limit + 1)?;
+        let (runs, next) = self.page(runs.into_iter().skip(offset), limit);
+        let next_cursor = next.then(|| (offset + runs.len()).to_string());
+        Ok(json!({"runs": runs, "next_cursor": next_cursor}))
+    }
+
+    fn show_run(&self, args: &Args) -> Result<Value> {
+        let limit = args.limit(200)?;
+        let stream = args.text("stream")?;
+        let min_level = args.min_level()?;
+        let mut storage = SqliteStorage::open(&self.db_path)?;
+        let run_id = storage.resolve_run_id(&args.required_text("run_id")?)?;
+        let run = storage.get_run(&run_id)?;
+
+        let lines = match (args.text("cursor")?, args.usize("tail")?) {
+            (Some(cursor), _) => {
+                let after_seq = cursor
+                    .parse()
+                    .map_err(|_| Error::Message(format!("invalid cursor `{}`", cursor)))?;
+                storage.lines_after(&run_id, after_seq, stream.as_deref(), min_level)?
+            }
+            (None, Some(tail)) => {
+                storage.show_lines(&run_id, Some(tail), stream.as_deref(), min_level)?
+            }
+            (None, None) => storage.lines_after(&run_id, 0, stream.as_deref(), min_level)?,
+        };
+        let lines = lines.into_iter().map(|mut line| {
+            clip_text(&mut line.text);
+            line
+        });
+        let (lines, next) = self.page(lines, limit);
+        let next_cursor = next
+            .then(|| lines.last().map(|line| line.seq.to_string()))
+            .flatten();
+        Ok(json!({"run": run, "lines": lines, "next_cursor": next_cursor}))
+    }
+
+    fn search_logs(&self, args: &Args) -> Result<Value> {
+        let query = args.required_text("query")?;
+        let limit = args.limit(50)?;
+        let offset = args.offset_cursor()?;
+        let mut storage = SqliteStorage::open(&self.db_path)?;
+        let hits = storage.search(&query, &args.filter()?, offset + limit + 1)?;
+        // Run records are dropped from hits: every line already carries its run_id,
+        // and list_runs/show_run give the details when they are needed.
+        let lines = hits.into_iter().skip(offset).map(|hit| {
+            let mut line = hit.line;
+            clip_text(&mut line.text);
+            line
+        });
+        let (hits, next) = self.page(lines, limit);
+        let next_cursor = next.then(|| (offset + hits.len()).to_string());
+        Ok(json!({
+            "search_backend": SEARCH_BACKEND,
+            "hits": hits,
+            "next_cursor": next_cursor
+        }))
+    }
+
+    fn run_command(&self, args: &Args) -> Result<Value> {
+        let command = args
+            .0
+            .get("command")
+            .and_then(Value::as_array)
+            .and_then(|argv| {
+                argv.iter()
+                    .map(|arg| arg.as_str().map(ToOwned::to_owned))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .ok_or_else(|| Error::Message("`command` must be an array of strings".to_string()))?;
+        let meta = match args.0.get("meta") {
+            Some(Value::Object(map)) => map
+                .iter()
+                .map(|(k, v)| (meta::normalize_key(k), v.clone()))
+                .collect(),
+            Some(_) => return Err(Error::Message("`meta` must be an object".to_string())),
+            None => Default::default(),
+        };
+        let timeout_secs = args
+            .usize("timeout_secs")?
+            .map_or(DEFAULT_RUN_TIMEOUT_SECS, |secs| secs as u64)
+            .clamp(1, MAX_RUN_TIMEOUT_SECS);
+        let outcome = ingest::run_command(
+            &self.db_path,
+            IngestOptions {
+                tag: args.text("tag")?,
+                source: Some("looplog-mcp".to_string()),
+                kind: meta::normalize_kind(args.text("kind")?),
+                meta,
+                passthrough: false,
+                redactor: self.redactor.clone(),
+                timeout: Some(Duration::from_secs(timeout_secs)),
+            },
+            &command,
+        )?;
+
+        let mut storage = SqliteStorage::open(&self.db_path)?;
+        let mut errors = storage.run_signatures(&outcome.run_id)?;
+        errors.truncate(10);
+        let tail = storage
+            .show_lines(
+                &outcome.run_id,
+                Some(args.usize("tail")?.unwrap_or(20)),
+                None,
+                None,
+            )?
+            .into_iter()
+            .map(|mut line| {
+                clip_text(&mut line.text);
+                line
+            });
+        // The tail keeps its newest lines when the budget runs out.
+        let mut tail: Vec<_> = tail.rev().collect();
+        tail = self.page(tail.into_iter(), usize::MAX).0;
+        tail.reverse();
+        Ok(json!({
+            "run_id": outcome.run_id,
+            "exit_code": outcome.exit_code,
+            "status": outcome.status(),
+            "timed_out": outcome.timed_out,
+            "errors": errors,
+            "tail": tail
+        }))
+    }
+
+    fn error_summary(&self, args: &Args) -> Result<Value> {
+        let limit = args.limit(20)?;
+        let mut storage = SqliteStorage::open(&self.db_path)?;
+        let Some(run_id) = args.text("run_id")? else {
+            let errors = storage.top_errors(&args.filter()?, limit)?;
+            let (errors, _) = self.page(errors.into_iter(), limit);
+            return Ok(json!({ "errors": errors }));
+        };
+
+        let run_id = storage.resolve_run_id(&run_id)?;
+        let signatures = storage.run_signatures(&run_id)?;
+        match args.text("baseline_run_id")? {
+            Some(baseline) => {
+                let baseline = storage.resolve_run_id(&baseline)?;
+                let diff = fingerprint::diff(storage.run_signatures(&baseline)?, signatures);
+                Ok(json!({"run_a": baseline, "run_b": run_id, "diff": diff}))
+            }
+            None => {
+                let (errors, _) = self.page(signatures.into_iter(), limit);
+                Ok(json!({"run_id": run_id, "errors": errors}))
+            }
+        }
+    }
+
+    /// Take up to `limit` items while the serialized size stays within the byte budget.
+    /// The first item is always kept so paging cannot stall. Returns whether more remain.
+    fn page<T: Serialize>(&self, items: impl Iterator<Item = T>, limit: usize) -> (Vec<T>, bool) {
+        let mut out = Vec::new();
+        let mut used = 0;
+        for item in items {
+            if out.len() == limit {
+                return (out, true);
+            }
+            used += serde_json::to_string(&item).map_or(0, |s| s.len() + 1);
+            if used > self.max_bytes && !out.is_empty() {
+                return (out, true);
+            }
+            out.push(item);
+        }
+        (out, false)
+    }
+}
+
+/// Tool arguments as sent by the client.
+struct Args<'a>(&'a Map<String, Value>);
+
+impl Args<'_> {
+    fn text(&self, key: &str) -> Result<Option<String>> {
+        match self.0.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s.clone())),
+            // Clients sometimes send ids and cursors as numbers.
+            Some(Value::Number(n)) => Ok(Some(n.to_string())),
+            Some(_) => Err(Error::Message(format!("`{}` must be a string", key))),
+        }
+    }
+
+    fn required_text(&self, key: &str) -> Result<String> {
+        self.text(key)?
+            .ok_or_else(|| Error::Message(format!("missing required argument `{}`", key)))
+    }
+
+    fn usize(&self, key: &str) -> Result<Option<usize>> {
+        match self.0.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => value
+                .as_u64()
+                .map(|n| Some(n as usize))
+                .ok_or_else(|| Error::Message(format!("`{}` must be a non-negative integer", key))),
+        }
+    }
+
+    fn limit(&self, default: usize) -> Result<usize> {
+        Ok(self.usize("limit")?.unwrap_or(default).clamp(1, MAX_PAGE))
+    }
+
+    fn offset_cursor(&self) -> Result<usize> {
+        self.text("cursor")?
+            .map(|cursor| {
+                cursor
+                    .parse()
+                    .map_err(|_| Error::Message(format!("invalid cursor `{}`", cursor)))
+            })
+            .transpose()
+            .map(Option::unwrap_or_default)
+    }
+
+    fn min_level(&self) -> Result<Option<Level>> {
+        if self.0.get("errors_only").and_then(Value::as_bool) == Some(true) {
+            return Ok(Some(Level::Error));
+        }
+        self.text("level")?.map(|level| level.parse()).transpose()
+    }
+
+    fn filter(&self) -> Result<QueryFilter> {
+        Ok(QueryFilter {
+            run_id: self.text("run")?,
+            tag: self.text("tag")?,
+            kind: meta::normalize_kind(self.text("kind")?),
+            appid: self.text("appid")?,
+            project: self.text("project")?,
+            page: self.text("page")?,
+            session: self.text("session")?,
+            trace: self.text("trace")?,
+            since_ms: self
+                .text("since")?
+                .as_deref()
+                .map(parse_since)
+                .transpose()?,
+            min_level: self.min_level()?,
+        })
+    }
+}
+
+fn clip_text(text: &mut String) {
+    if let Some((index, _)) = text.char_indices().nth(MAX_TEXT_CHARS) {
+        let rest = text[index..].chars().count();
+        text.truncate(index);
+        text.push_str(&format!("… [{} more chars]", rest));
+    }
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+fn tool_definitions() -> Value {
+    let filters = json!({
+        "run": {"type": "string", "description": "Run id"},
+        "tag": {"type": "string"},
+        "kind": {"type": "string", "description": "e.g. wechat_miniprogram (alias: wx)"},
+        "appid": {"type": "string"},
+        "project": {"type": "string", "description": "Project path"},
+        "page": {"type": "string"},
+        "session": {"type": "string"},
+        "trace": {"type": "string", "description": "Trace id"},
+        "since": {"type": "string", "description": "Relative duration like 2h/30m or RFC3339"},
+        "level": {"type": "string", "enum": ["trace", "debug", "info", "warn", "error", "fatal"],
+                  "description": "Minimum severity"},
+        "errors_only": {"type": "boolean", "description": "Shorthand for level=error"}
+    });
+    let with = |extra: Value| {
+        let mut props = filters.as_object().cloned().unwrap_or_default();
+        props.extend(extra.as_object().cloned().unwrap_or_default());
+        Value::Object(props)
+    };
+    let cursor = json!({"type": "string", "description": "next_cursor from the previous page"});
+
+    json!([
+        {
+            "name": "list_runs",
+            "description": "List recent captured runs, newest first.",
+            "inputSchema": {"type": "object", "properties": with(json!({
+                "limit": {"type": "integer", "default": 20},
+                "cursor": cursor
+            }))}
+        },
+        {
+            "name": "show_run",
+            "description": "Show a run's status and its log lines, oldest first. Page with \
+                next_cursor, or pass tail to get only the last lines.",
+            "inputSchema": {"type": "object", "required": ["run_id"], "properties": {
+                "run_id": {"type": "string", "description": "Run id or unique prefix"},
+                "tail": {"type": "integer"},
+                "stream": {"type": "string", "description": "stdout, stderr, console, ..."},
+                "level": filters["level"],
+                "errors_only": filters["errors_only"],
+                "limit": {"type": "integer", "default": 200},
+                "cursor": cursor
+            }}
+        },
+        {
+            "name": "search_logs",
+            "description": "Search log lines by substring, newest first. Separate alternatives \
+                with `|`.",
+            "inputSchema": {"type": "object", "required": ["query"], "properties": with(json!({
+                "query": {"type": "string"},
+                "limit": {"type": "integer", "default": 50},
+                "cursor": cursor
+            }))}
+        },
+        {
+            "name": "run_command",
+            "description": "Run a command in the server's working directory, capture its \
+                output as a new run, and return the exit code, error signatures and the \
+                last lines. A command still running after timeout_secs is killed and its \
+                partial output returned with timed_out=true.",
+            "inputSchema": {"type": "object", "required": ["command"], "properties": {
+                "command": {"type": "array", "items": {"type": "string"},
+                            "description": "Program and arguments, e.g. [\"npm\", \"test\"]"},
+                "tag": {"type": "string"},
+                "kind": {"type": "string"},
+                "meta": {"type": "object", "description": "e.g. {\"appid\": \"wx123\"}"},
+                "tail": {"type": "integer", "default": 20},
+                "timeout_secs": {"type": "integer", "default": DEFAULT_RUN_TIMEOUT_SECS,
+                                 "maximum": MAX_RUN_TIMEOUT_SECS}
+            }}
+        },
+        {
+            "name": "error_summary",
+            "description": "Error signatures. With run_id: the run's errors, or its diff \
+                (new/resolved/persisting) against baseline_run_id. Without: the most frequent \
+                errors across runs matching the filters.",
+            "inputSchema": {"type": "object", "properties": with(json!({
+                "run_id": {"type": "string"},
+                "baseline_run_id": {"type": "string"},
+                "limit": {"type": "integer", "default": 20}
+            }))}
+        }
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::NewRun;
+    use std::collections::BTreeMap;
+    use tempfile::NamedTempFile;
+
+    fn call(server: &McpServer, id: i64, method: &str, params: Value) -> Value {
+        server
+            .handle(json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}))
+            .unwrap()
+    }
+
+    #[test]
+    fn lists_tools_and_pages_results() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut storage = SqliteStorage::open(tmp.path()).unwrap();
+        for tag in ["first", "second"] {
+            storage
+                .start_run(NewRun {
+                    tag: Some(tag.to_string()),
+                    source: None,
+                    cwd: None,
+                    argv: vec![],
+                    client_id: None,
+                    kind: None,
+                    meta: BTreeMap::new(),
+                })
+                .unwrap();
+        }
+        let server = McpServer {
+            db_path: tmp.path().to_path_buf(),
+            max_bytes: DEFAULT_MAX_BYTES,
+            redactor: None,
+        };
+
+        let init = call(&server, 1, "initialize", json!({}));
+        assert_eq!(init["result"]["protocolVersion"], PROTOCOL_VERSION);
+        assert!(server
+            .handle(json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .is_none());
+        let tools = call(&server, 2, "tools/list", json!({}));
+        assert_eq!(tools["result"]["tools"].as_array().unwrap().len(), 5);
+
+        let page = |cursor: Value| {
+            let response = call(
+                &server,
+                3,
+                "tools/call",
+                json!({"name": "list_runs", "arguments": {"limit": 1, "cursor": cursor}}),
+            );
+            assert_eq!(response["result"]["isError"], false);
+            let text = response["result"]["content"][0]["text"].as_str().unwrap();
+            serde_json::from_str::<Value>(text).unwrap()
+        };
+        let first = page(Value::Null);
+        assert_eq!(first["runs"].as_array().unwrap().len(), 1);
+        assert_eq!(first["next_cursor"], "1");
+        let second = page(first["next_cursor"].clone());
+        assert_eq!(second["runs"][0]["tag"], "first");
+        assert!(second["next_cursor"].is_null());
+
+        let failed = call(
+            &server,
+            4,
+            "tools/call",
+            json!({"name": "show_run", "arguments": {}}),
+        );
+        assert_eq!(failed["result"]["isError"], true);
+        let unknown = call(&server, 5, "resources/list", json!({}));
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_command_times_out_with_partial_output() {
+        let tmp = NamedTempFile::new().unwrap();
+        let server = McpServer {
+            db_path: tmp.path().to_path_buf(),
+            max_bytes: DEFAULT_MAX_BYTES,
+            redactor: None,
+        };
+        let started = std::time::Instant::now();
+        let response = call(
+            &server,
+            1,
+            "tools/call",
+            json!({"name": "run_command", "arguments": {
+                "command": ["sh", "-c", "echo started; sleep 30"],
+                "timeout_secs": 1
+            }}),
+        );
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+        assert_eq!(response["result"]["isError"], false);
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        let result: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(result["timed_out"], true);
+        assert_eq!(result["status"], "timeout");
+        assert_eq!(result["tail"][0]["text"], "started");
+
+        let mut storage = SqliteStorage::open(tmp.path()).unwrap();
+        let run = storage
+            .get_run(result["run_id"].as_str().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(run.status, "timeout");
+    }
+
+    #[test]
+    fn page_respects_byte_budget_but_keeps_one_item() {
+        let server = McpServer {
+            db_path: PathBuf::new(),
+            max_bytes: 10,
+            redactor: None,
+        };
+        let items = vec!["aaaaaaaaaaaa".to_string(), "b".to_string()];
+        let (kept, more) = server.page(items.into_iter(), 10);
+        assert_eq!(kept.len(), 1);
+        assert!(more);
+
+        let mut long = "é".repeat(MAX_TEXT_CHARS + 5);
+        clip_text(&mut long);
+        assert!(long.ends_with("… [5 more chars]"));
+    }
+}
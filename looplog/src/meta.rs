@@ -29,6 +29,17 @@ pub fn parse_cli_meta(entries: &[String]) -> Result<BTreeMap<String, Value>> {
     Ok(meta)
 }
 
+/// `wechat`/`wx` are accepted as shorthands for the default WeChat kind.
+pub fn normalize_kind(kind: Option<String>) -> Option<String> {
+    kind.map(|k| {
+        if k == "wechat" || k == "wx" {
+            DEFAULT_KIND.to_string()
+        } else {
+            k
+        }
+    })
+}
+
 pub fn normalize_key(key: &str) -> String {
     key.trim().to_ascii_lowercase().replace('-', "_")
 }
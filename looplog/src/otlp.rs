@@ -0,0 +1,281 @@
+use crate::db::{format_ms, NewRun};
+use crate::error::Result;
+use crate::meta;
+use crate::parser::{self, Level};
+use crate::protocol::{AppendLine, SourceLocation};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+// OTLP/HTTP logs, JSON encoding only (`OTEL_EXPORTER_OTLP_LOGS_PROTOCOL=http/json`).
+// Field names follow the protobuf JSON mapping: camelCase, 64-bit ints as strings.
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportLogsServiceRequest {
+    #[serde(default)]
+    resource_logs: Vec<ResourceLogs>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResourceLogs {
+    #[serde(default)]
+    resource: Resource,
+    #[serde(default)]
+    scope_logs: Vec<ScopeLogs>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Resource {
+    #[serde(default)]
+    attributes: Vec<KeyValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScopeLogs {
+    #[serde(default)]
+    log_records: Vec<LogRecord>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KeyValue {
+    key: String,
+    #[serde(default)]
+    value: Value,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LogRecord {
+    time_unix_nano: Option<Value>,
+    observed_time_unix_nano: Option<Value>,
+    severity_number: Option<i32>,
+    severity_text: Option<String>,
+    #[serde(default)]
+    body: Value,
+    #[serde(default)]
+    attributes: Vec<KeyValue>,
+    trace_id: Option<String>,
+    span_id: Option<String>,
+}
+
+/// Lines from one OTLP resource (one service instance), which share a run.
+#[derive(Debug, Clone)]
+pub struct OtlpBatch {
+    pub key: String,
+    pub run: NewRun,
+    pub lines: Vec<AppendLine>,
+}
+
+pub fn parse_logs(body: &str) -> Result<Vec<OtlpBatch>> {
+    let request: ExportLogsServiceRequest = serde_json::from_str(body)?;
+    let mut batches = Vec::new();
+    for resource_logs in request.resource_logs {
+        let resource = attributes_map(&resource_logs.resource.attributes);
+        let text_attr = |key: &str| resource.get(key).and_then(Value::as_str);
+        let service = text_attr("service.name").unwrap_or("unknown_service");
+        let key = format!(
+            "otlp\u{0}{}\u{0}{}",
+            service,
+            text_attr("service.instance.id").unwrap_or("-")
+        );
+
+        let mut run_meta: BTreeMap<String, Value> = resource
+            .iter()
+            .map(|(k, v)| (meta::normalize_key(k), v.clone()))
+            .collect();
+        run_meta.insert("service".to_string(), Value::from(service));
+        let run = NewRun {
+            tag: Some("otlp".to_string()),
+            source: Some("otlp".to_string()),
+            cwd: None,
+            argv: Vec::new(),
+            client_id: text_attr("service.instance.id").map(ToOwned::to_owned),
+            kind: Some("otlp".to_string()),
+            meta: run_meta,
+        };
+
+        let mut lines: Vec<AppendLine> = resource_logs
+            .scope_logs
+            .into_iter()
+            .flat_map(|scope| scope.log_records)
+            .map(record_to_line)
+            .collect();
+        parser::annotate(&mut lines);
+        batches.push(OtlpBatch { key, run, lines });
+    }
+    Ok(batches)
+}
+
+fn record_to_line(record: LogRecord) -> AppendLine {
+    let mut attrs = attributes_map(&record.attributes);
+    let location = take_location(&mut attrs);
+    let event = attrs
+        .remove("event.name")
+        .and_then(|v| v.as_str().map(ToOwned::to_owned));
+    let mut meta: BTreeMap<String, Value> = attrs.into_iter().collect();
+    for (key, id) in [("trace_id", record.trace_id), ("span_id", record.span_id)] {
+        if let Some(id) = id.filter(|id| !id.is_empty()) {
+            meta.insert(key.to_string(), Value::from(id));
+        }
+    }
+
+    let ts = record
+        .time_unix_nano
+        .as_ref()
+        .and_then(nanos)
+        .filter(|n| *n > 0)
+        .or_else(|| record.observed_time_unix_nano.as_ref().and_then(nanos))
+        .filter(|n| *n > 0)
+        .map(|n| format_ms((n / 1_000_000) as i64));
+    let level = record
+        .severity_number
+        .and_then(severity_level)
+        .map(|level| level.as_str().to_string())
+        .or(record.severity_text);
+    let text = match any_value(&record.body) {
+        Value::String(text) => text,
+        Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    AppendLine {
+        ts,
+        stream: Some("otlp".to_string()),
+        level,
+        event,
+        text,
+        location,
+        meta,
+    }
+}
+
+/// OTLP severity numbers come in bands of four per level.
+fn severity_level(number: i32) -> Option<Level> {
+    Some(match number {
+        1..=4 => Level::Trace,
+        5..=8 => Level::Debug,
+        9..=12 => Level::Info,
+        13..=16 => Level::Warn,
+        17..=20 => Level::Error,
+        21..=24 => Level::Fatal,
+        _ => return None,
+    })
+}
+
+/// Semantic-convention code attributes, old (`code.filepath`) and new (`code.file.path`).
+fn take_location(attrs: &mut BTreeMap<String, Value>) -> Option<SourceLocation> {
+    let find = |keys: [&'static str; 2]| keys.into_iter().find(|key| attrs.contains_key(*key));
+    let file_key = find(["code.file.path", "code.filepath"])?;
+    let line_key = find(["code.line.number", "code.lineno"])?;
+    let col_key = find(["code.column.number", "code.column"]);
+    let location = SourceLocation {
+        file: attrs[file_key].as_str()?.to_string(),
+        line: attrs[line_key].as_u64()? as u32,
+        col: col_key
+            .and_then(|key| attrs[key].as_u64())
+            .map(|col| col as u32),
+    };
+    for key in [Some(file_key), Some(line_key), col_key]
+        .into_iter()
+        .flatten()
+    {
+        attrs.remove(key);
+    }
+    Some(location)
+}
+
+fn attributes_map(attributes: &[KeyValue]) -> BTreeMap<String, Value> {
+    attributes
+        .iter()
+        .map(|kv| (kv.key.clone(), any_value(&kv.value)))
+        .collect()
+}
+
+/// Flatten an OTLP `AnyValue` (`{"stringValue": "x"}`, `{"intValue": "3"}`, ...) to JSON.
+fn any_value(value: &Value) -> Value {
+    let Some(object) = value.as_object() else {
+        return value.clone();
+    };
+    if let Some(v) = object.get("stringValue").or(object.get("boolValue")) {
+        return v.clone();
+    }
+    if let Some(v) = object.get("intValue") {
+        return match v {
+            Value::String(s) => s.parse::<i64>().map(Value::from).unwrap_or(v.clone()),
+            _ => v.clone(),
+        };
+    }
+    if let Some(v) = object.get("doubleValue").or(object.get("bytesValue")) {
+        return v.clone();
+    }
+    if let Some(values) = object
+        .get("arrayValue")
+        .and_then(|a| a.get("values"))
+        .and_then(Value::as_array)
+    {
+        return Value::Array(values.iter().map(any_value).collect());
+    }
+    if let Some(values) = object.get("kvlistValue").and_then(|k| k.get("values")) {
+        let pairs: Vec<KeyValue> = serde_json::from_value(values.clone()).unwrap_or_default();
+        return Value::Object(attributes_map(&pairs).into_iter().collect());
+    }
+    Value::Null
+}
+
+fn nanos(value: &Value) -> Option<u64> {
+    match value {
+        Value::String(s) => s.parse().ok(),
+        other => other.as_u64(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_resource_logs_to_runs_and_lines() {
+        let batches = parse_logs(
+            r#"{"resourceLogs": [{
+                "resource": {"attributes": [
+                    {"key": "service.name", "value": {"stringValue": "checkout"}}
+                ]},
+                "scopeLogs": [{"scope": {"name": "app"}, "logRecords": [
+                    {"timeUnixNano": "1760220855003000000", "severityNumber": 17,
+                     "body": {"stringValue": "payment failed"},
+                     "traceId": "5b8efff798038103d269b633813fc60c",
+                     "attributes": [
+                        {"key": "code.filepath", "value": {"stringValue": "src/pay.ts"}},
+                        {"key": "code.lineno", "value": {"intValue": "42"}},
+                        {"key": "order", "value": {"intValue": "7"}}
+                     ]},
+                    {"body": {"kvlistValue": {"values": [
+                        {"key": "ok", "value": {"boolValue": true}}
+                    ]}}}
+                ]}]
+            }]}"#,
+        )
+        .unwrap();
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.run.meta["service"], "checkout");
+        let [error, info] = &batch.lines[..] else {
+            panic!("expected two lines");
+        };
+        assert_eq!(error.level.as_deref(), Some("error"));
+        assert_eq!(error.ts.as_deref(), Some("2025-10-11T22:14:15.003Z"));
+        assert_eq!(
+            error.location,
+            Some(SourceLocation {
+                file: "src/pay.ts".to_string(),
+                line: 42,
+                col: None
+            })
+        );
+        assert_eq!(error.meta["order"], 7);
+        assert_eq!(error.meta["trace_id"], "5b8efff798038103d269b633813fc60c");
+        assert_eq!(info.text, r#"{"ok":true}"#);
+    }
+}
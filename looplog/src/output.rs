@@ -1,55 +1,263 @@
-use crate::db::{CleanStats, LineRecord, RunRecord, SearchHit};
+use crate::db::{
+    CleanStats, ErrorStat, LineRecord, RetentionPolicy, RunRecord, SearchHit, StoreStats,
+};
 use crate::error::Result;
+use crate::fingerprint::SignatureDiff;
+use crate::timeline::Timeline;
 use serde::Serialize;
+use std::io::Write;
 
 pub fn print_json<T: Serialize>(value: &T) -> Result<()> {
     println!("{}", serde_json::to_string_pretty(value)?);
     Ok(())
 }
 
+/// Compact single-line JSON for streamed (NDJSON) output.
+pub fn print_json_line<T: Serialize>(value: &T) -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "{}", serde_json::to_string(value)?)?;
+    stdout.flush()?;
+    Ok(())
+}
+
 pub fn print_runs(runs: &[RunRecord]) {
     for run in runs {
         println!(
-            "{}  {}  tag={} appid={} page={} lines={} status={}",
+            "{}  {}  tag={} appid={} page={} lines={} status={}{}",
             run.started_at,
             short_id(&run.id),
             run.tag.as_deref().unwrap_or("-"),
             run.appid.as_deref().unwrap_or("-"),
             run.page.as_deref().unwrap_or("-"),
             run.line_count,
-            run.status
+            run.status,
+            if run.pinned { " pinned" } else { "" }
         );
     }
 }
 
 pub fn print_lines(lines: &[LineRecord]) {
     for line in lines {
-        println!("{} [{} {}] {}", line.ts, line.stream, line.level, line.text);
+        println!(
+            "{} [{} {}{}] {}",
+            line.ts,
+            line.stream,
+            line.level,
+            location_suffix(line),
+            line.text
+        );
     }
 }
 
+pub fn print_finished(run: &RunRecord) {
+    println!(
+        "run {} finished: status={} exit_code={}",
+        short_id(&run.id),
+        run.status,
+        run.exit_code
+            .map(|code| code.to_string())
+            .unwrap_or_else(|| "-".to_string())
+    );
+}
+
 pub fn print_hits(hits: &[SearchHit]) {
     for hit in hits {
         println!(
-            "{} {} appid={} page={} [{} {}] {}",
+            "{} {} appid={} page={} [{} {}{}] {}",
             hit.line.ts,
             short_id(&hit.run.id),
             hit.run.appid.as_deref().unwrap_or("-"),
             hit.run.page.as_deref().unwrap_or("-"),
             hit.line.stream,
             hit.line.level,
+            location_suffix(&hit.line),
             hit.line.text
         );
     }
 }
 
+pub fn print_stats(stats: &StoreStats) {
+    println!(
+        "runs={} running={} pinned={} lines={}",
+        stats.runs, stats.running_runs, stats.pinned_runs, stats.lines
+    );
+    let levels = stats
+        .lines_by_level
+        .iter()
+        .map(|(level, count)| format!("{}={}", level, count))
+        .collect::<Vec<_>>();
+    println!(
+        "levels: {}",
+        if levels.is_empty() {
+            "-".to_string()
+        } else {
+            levels.join(" ")
+        }
+    );
+    println!(
+        "oldest={} newest={}",
+        stats.oldest_run.as_deref().unwrap_or("-"),
+        stats.newest_run.as_deref().unwrap_or("-")
+    );
+    println!(
+        "stored_bytes={} db_bytes={} fts={}",
+        stats.stored_bytes, stats.db_bytes, stats.fts_available
+    );
+}
+
+pub fn print_signature_diff(diff: &SignatureDiff) {
+    for sig in &diff.new {
+        println!(
+            "+ {} x{}  {}",
+            short_fp(&sig.fingerprint),
+            sig.count,
+            sig.signature
+        );
+    }
+    for sig in &diff.resolved {
+        println!(
+            "- {} x{}  {}",
+            short_fp(&sig.fingerprint),
+            sig.count,
+            sig.signature
+        );
+    }
+    for sig in &diff.persisting {
+        println!(
+            "= {} x{}->{}  {}",
+            short_fp(&sig.fingerprint),
+            sig.count_a,
+            sig.count_b,
+            sig.signature
+        );
+    }
+    println!(
+        "new={} resolved={} persisting={}",
+        diff.new.len(),
+        diff.resolved.len(),
+        diff.persisting.len()
+    );
+}
+
+pub fn print_top_errors(errors: &[ErrorStat]) {
+    for err in errors {
+        println!(
+            "{} x{} runs={} first={} last={} at={}  {}",
+            short_fp(&err.fingerprint),
+            err.count,
+            err.runs,
+            err.first_seen,
+            err.last_seen,
+            err.location.as_deref().unwrap_or("-"),
+            err.signature
+        );
+    }
+}
+
 pub fn print_clean(stats: &CleanStats) {
     println!(
-        "deleted {} run(s), {} line(s); keep_hours={}",
-        stats.deleted_runs, stats.deleted_lines, stats.keep_hours
+        "deleted {} run(s), {} line(s); keep_hours={} evicted_for_size={}",
+        stats.deleted_runs, stats.deleted_lines, stats.keep_hours, stats.evicted_for_size
+    );
+}
+
+pub fn print_retention(policy: &RetentionPolicy) {
+    println!("default keep_hours={}", policy.default_keep_hours);
+    for (tag, hours) in &policy.tag_keep_hours {
+        println!("tag {} keep_hours={}", tag, hours);
+    }
+    match policy.max_bytes {
+        Some(bytes) => println!("max_size={} MiB", bytes / (1024 * 1024)),
+        None => println!("max_size=off"),
+    }
+    println!("pinned runs={}", policy.pinned_runs);
+}
+
+/// ANSI colors cycled per run so interleaved sources stay distinguishable.
+const RUN_COLORS: [&str; 6] = ["36", "33", "35", "32", "34", "96"];
+
+pub fn print_timeline(timeline: &Timeline, color: bool) {
+    let paint = |run: &str, text: &str| {
+        let index = timeline.runs.iter().position(|r| r.id == run);
+        match index {
+            Some(index) if color => format!(
+                "\x1b[{}m{}\x1b[0m",
+                RUN_COLORS[index % RUN_COLORS.len()],
+                text
+            ),
+            _ => text.to_string(),
+        }
+    };
+    for run in &timeline.runs {
+        println!(
+            "{}  started={} tag={} source={} status={}",
+            paint(&run.id, &run.id),
+            run.started_at,
+            run.tag.as_deref().unwrap_or("-"),
+            run.source.as_deref().unwrap_or("-"),
+            run.status
+        );
+    }
+    let Some(anchor) = &timeline.anchor else {
+        println!("no lines");
+        return;
+    };
+    println!(
+        "anchor: {} {} #{} [{}]",
+        anchor.ts, anchor.run, anchor.seq, anchor.level
     );
+    if timeline.omitted > 0 {
+        println!("({} line(s) omitted; raise --limit)", timeline.omitted);
+    }
+    for line in &timeline.lines {
+        if let Some(gap) = line.gap_ms {
+            println!(
+                "{:>12}  ... {} gap ...",
+                "",
+                format_offset(gap).trim_start_matches('+')
+            );
+        }
+        println!(
+            "{:>12}  {} [{} {}{}] {}",
+            format_offset(line.rel_ms),
+            paint(&line.run, &line.run),
+            line.stream,
+            line.level,
+            line.location
+                .as_deref()
+                .map(|loc| format!(" {}", loc))
+                .unwrap_or_default(),
+            line.text
+        );
+    }
+}
+
+/// `+1.250s`, `-2m03.400s`: signed offset from the timeline anchor.
+fn format_offset(ms: i64) -> String {
+    let sign = if ms < 0 { '-' } else { '+' };
+    let ms = ms.unsigned_abs();
+    let (minutes, rest) = (ms / 60_000, ms % 60_000);
+    if minutes > 0 {
+        format!("{}{}m{:02}.{:03}s", sign, minutes, rest / 1000, rest % 1000)
+    } else {
+        format!("{}{}.{:03}s", sign, rest / 1000, rest % 1000)
+    }
+}
+
+fn location_suffix(line: &LineRecord) -> String {
+    match &line.location {
+        Some(loc) => match loc.col {
+            Some(col) => format!(" {}:{}:{}", loc.file, loc.line, col),
+            None => format!(" {}:{}", loc.file, loc.line),
+        },
+        None => String::new(),
+    }
 }
 
 fn short_id(id: &str) -> &str {
     id.get(..8).unwrap_or(id)
 }
+
+fn short_fp(fingerprint: &str) -> &str {
+    fingerprint.get(..10).unwrap_or(fingerprint)
+}
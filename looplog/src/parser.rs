@@ -0,0 +1,459 @@
+use crate::error::{Error, Result};
+use crate::protocol::{AppendLine, SourceLocation};
+use std::str::FromStr;
+
+/// Canonical severities, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl Level {
+    pub const ALL: [Level; 6] = [
+        Level::Trace,
+        Level::Debug,
+        Level::Info,
+        Level::Warn,
+        Level::Error,
+        Level::Fatal,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Level::Trace => "trace",
+            Level::Debug => "debug",
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+            Level::Fatal => "fatal",
+        }
+    }
+
+    /// Stored level names at or above this severity, for SQL `IN (...)` filters.
+    pub fn at_least(self) -> Vec<&'static str> {
+        Self::ALL
+            .iter()
+            .filter(|level| **level >= self)
+            .map(|level| level.as_str())
+            .collect()
+    }
+
+    fn from_word(word: &str) -> Option<Self> {
+        match word.to_ascii_lowercase().as_str() {
+            "trace" | "verbose" => Some(Level::Trace),
+            "debug" | "dbg" => Some(Level::Debug),
+            "info" | "log" | "notice" => Some(Level::Info),
+            "warn" | "warning" => Some(Level::Warn),
+            "error" | "err" => Some(Level::Error),
+            "fatal" | "critical" | "crit" | "panic" => Some(Level::Fatal),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Level {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::from_word(s.trim()).ok_or_else(|| Error::InvalidLevel(s.to_string()))
+    }
+}
+
+/// Map client-supplied level aliases (`warning`, `log`, `ERR`) onto canonical names.
+/// Unknown levels are kept, lowercased, so custom levels still round-trip.
+pub fn normalize_level(level: &str) -> String {
+    Level::from_word(level.trim())
+        .map(|level| level.as_str().to_string())
+        .unwrap_or_else(|| level.trim().to_ascii_lowercase())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockKind {
+    Plain,
+    RustPanic { expect_message: bool },
+}
+
+struct Pending {
+    line: AppendLine,
+    kind: BlockKind,
+}
+
+/// Turns raw process output into structured records for one stream.
+///
+/// Each line is classified (WeChat devtools console, Node/V8 errors, Rust panics and
+/// compiler diagnostics, generic `[LEVEL]` prefixes). Recognised warnings and errors
+/// are held back until the next line so that stack frames and other continuation
+/// lines can be merged into a single record; everything else is emitted immediately.
+pub struct LineParser {
+    stream: String,
+    default_level: Level,
+    pending: Option<Pending>,
+}
+
+impl LineParser {
+    pub fn new(stream: &str, default_level: Level) -> Self {
+        Self {
+            stream: stream.to_string(),
+            default_level,
+            pending: None,
+        }
+    }
+
+    /// Feed one raw line; returns the records that are complete after it.
+    pub fn push(&mut self, text: String) -> Vec<AppendLine> {
+        if let Some(pending) = self.pending.as_mut() {
+            if is_continuation(pending.kind, &text) {
+                if let BlockKind::RustPanic { expect_message } = &mut pending.kind {
+                    *expect_message = false;
+                }
+                if pending.line.location.is_none() {
+                    pending.line.location = extract_location(&text);
+                }
+                pending.line.text.push('\n');
+                pending.line.text.push_str(&text);
+                return Vec::new();
+            }
+        }
+
+        let mut out = Vec::new();
+        out.extend(self.finish());
+
+        // Only a recognised header opens a multi-line record; an unmarked line at a
+        // warn default (`push --level warn`) must not swallow indented output after it.
+        let header = classify(&text);
+        let (level, kind) = header.unwrap_or((self.default_level, BlockKind::Plain));
+        let line = AppendLine {
+            stream: Some(self.stream.clone()),
+            level: Some(level.as_str().to_string()),
+            location: extract_location(&text),
+            text,
+            ..Default::default()
+        };
+        if header.is_some() && level >= Level::Warn {
+            self.pending = Some(Pending { line, kind });
+        } else {
+            out.push(line);
+        }
+        out
+    }
+
+    /// Flush the record still waiting for continuation lines, if any.
+    pub fn finish(&mut self) -> Option<AppendLine> {
+        self.pending.take().map(|pending| pending.line)
+    }
+}
+
+/// Fill in level and source location for lines that arrive without them
+/// (HTTP intake), without merging or reordering anything.
+pub fn annotate(lines: &mut [AppendLine]) {
+    for line in lines {
+        match line.level.as_deref() {
+            Some(level) => line.level = Some(normalize_level(level)),
+            None => line.level = classify(&line.text).map(|(level, _)| level.as_str().to_string()),
+        }
+        if line.location.is_none() {
+            line.location = line.text.lines().find_map(extract_location);
+        }
+    }
+}
+
+fn classify(text: &str) -> Option<(Level, BlockKind)> {
+    let trimmed = strip_devtools_prefix(text.trim_start());
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    if trimmed.starts_with("thread '") && trimmed.contains("' panicked at ") {
+        let expect_message = trimmed.ends_with(':');
+        return Some((Level::Error, BlockKind::RustPanic { expect_message }));
+    }
+
+    if let Some(level) = wechat_level(trimmed) {
+        return Some((level, BlockKind::Plain));
+    }
+
+    if let Some(level) = prefix_level(trimmed) {
+        return Some((level, BlockKind::Plain));
+    }
+
+    if is_js_error_header(trimmed) {
+        return Some((Level::Error, BlockKind::Plain));
+    }
+
+    None
+}
+
+/// WeChat devtools console copies prefix lines with the VM script they came from,
+/// e.g. `VM123:1 TypeError: ...`.
+pub(crate) fn strip_devtools_prefix(text: &str) -> &str {
+    let Some(rest) = text.strip_prefix("VM") else {
+        return text;
+    };
+    let Some((head, tail)) = rest.split_once(' ') else {
+        return text;
+    };
+    if !head.is_empty() && head.chars().all(|c| c.is_ascii_digit() || c == ':') {
+        tail.trim_start()
+    } else {
+        text
+    }
+}
+
+fn wechat_level(text: &str) -> Option<Level> {
+    const ERROR_MARKERS: [&str; 5] = [
+        "thirdScriptError",
+        "MiniProgramError",
+        "appServiceSDKScriptError",
+        "[渲染层错误]",
+        "[渲染层网络层错误]",
+    ];
+    const WARN_MARKERS: [&str; 3] = ["[WXML Runtime warning]", "[Deprecation]", "[Violation]"];
+
+    if ERROR_MARKERS.iter().any(|marker| text.starts_with(marker)) {
+        Some(Level::Error)
+    } else if WARN_MARKERS.iter().any(|marker| text.starts_with(marker)) {
+        Some(Level::Warn)
+    } else {
+        None
+    }
+}
+
+/// Generic level markers: `[ERROR] ...`, `ERROR: ...`, `error[E0308]: ...`,
+/// `warning: ...`, and bare uppercase level words within the first few tokens so that
+/// timestamped formats like `2024-01-01T00:00:00Z  WARN app: ...` are recognised.
+fn prefix_level(text: &str) -> Option<Level> {
+    for (index, token) in text.split_whitespace().take(3).enumerate() {
+        if let Some(inner) = token.strip_prefix('[') {
+            let inner = inner.split(']').next().unwrap_or(inner);
+            if let Some(level) = Level::from_word(inner) {
+                return Some(level);
+            }
+            continue;
+        }
+
+        if index == 0 {
+            let word = token.split([':', '[']).next().unwrap_or(token);
+            let followed_by_marker = token.len() > word.len();
+            if followed_by_marker {
+                if let Some(level) = Level::from_word(word) {
+                    return Some(level);
+                }
+            }
+        }
+
+        let bare = token.trim_end_matches(':');
+        if bare.len() > 2 && bare.chars().all(|c| c.is_ascii_uppercase()) {
+            if let Some(level) = Level::from_word(bare) {
+                return Some(level);
+            }
+        }
+    }
+    None
+}
+
+/// `TypeError: ...`, `Uncaught ReferenceError: ...`, `Error: ...`, `SomeException: ...`.
+fn is_js_error_header(text: &str) -> bool {
+    let text = text.strip_prefix("Uncaught ").unwrap_or(text);
+    let Some((name, _)) = text.split_once(':') else {
+        return false;
+    };
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        && (name.ends_with("Error") || name.ends_with("Exception"))
+}
+
+fn is_continuation(kind: BlockKind, text: &str) -> bool {
+    if text.trim().is_empty() {
+        return false;
+    }
+    match kind {
+        BlockKind::RustPanic {
+            expect_message: true,
+        } => true,
+        BlockKind::RustPanic { .. } => {
+            text.starts_with(char::is_whitespace)
+                || text.starts_with("note: ")
+                || text.starts_with("stack backtrace:")
+        }
+        BlockKind::Plain => text.starts_with(char::is_whitespace) || is_diagnostic_gutter(text),
+    }
+}
+
+/// rustc-style source snippets: `12 |     let x = y;` under an `error[E...]` header.
+fn is_diagnostic_gutter(text: &str) -> bool {
+    let Some((number, rest)) = text.split_once(' ') else {
+        return false;
+    };
+    !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit())
+        && rest.trim_start().starts_with('|')
+}
+
+/// Find a `file:line[:col]` reference in a stack frame, compiler pointer or panic header.
+pub fn extract_location(text: &str) -> Option<SourceLocation> {
+    let trimmed = text.trim();
+
+    if let Some(frame) = trimmed.strip_prefix("at ") {
+        let inner = match (frame.rfind('('), frame.rfind(')')) {
+            (Some(open), Some(close)) if open < close => &frame[open + 1..close],
+            _ => frame,
+        };
+        return parse_location(inner);
+    }
+
+    if let Some(pointer) = trimmed.strip_prefix("--> ") {
+        return parse_location(pointer);
+    }
+
+    if let Some((_, rest)) = trimmed.split_once("panicked at ") {
+        // Rust < 1.73 prints `panicked at 'message', src/main.rs:10:5`.
+        let rest = match rest.strip_prefix('\'') {
+            Some(quoted) => quoted
+                .rsplit_once("', ")
+                .map(|(_, loc)| loc)
+                .unwrap_or(rest),
+            None => rest,
+        };
+        return parse_location(rest.trim_end_matches(':'));
+    }
+
+    None
+}
+
+fn parse_location(input: &str) -> Option<SourceLocation> {
+    let (head, last) = input.trim().rsplit_once(':')?;
+    let last = last.parse::<u32>().ok()?;
+    let (file, line, col) = match head
+        .rsplit_once(':')
+        .and_then(|(file, line)| Some((file, line.parse::<u32>().ok()?)))
+    {
+        Some((file, line)) => (file, line, Some(last)),
+        None => (head, last, None),
+    };
+
+    // WeChat devtools decorates paths as `index.js? [sm]` and service files carry
+    // cache-busting query strings.
+    let file = file.split('?').next().unwrap_or(file).trim();
+    if file.is_empty() {
+        return None;
+    }
+    Some(SourceLocation {
+        file: file.to_string(),
+        line,
+        col,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_all(stream: &str, input: &str) -> Vec<AppendLine> {
+        let mut parser = LineParser::new(stream, Level::Info);
+        let mut out = Vec::new();
+        for line in input.lines() {
+            out.extend(parser.push(line.to_string()));
+        }
+        out.extend(parser.finish());
+        out
+    }
+
+    #[test]
+    fn merges_v8_stack_into_one_error() {
+        let lines = parse_all(
+            "stderr",
+            "starting\n\
+             TypeError: Cannot read properties of undefined (reading 'x')\n    \
+             at onLoad (/app/pages/index/index.js:12:7)\n    \
+             at Module._compile (node:internal/modules/cjs/loader:1256:14)\n\
+             done",
+        );
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1].level.as_deref(), Some("error"));
+        assert_eq!(lines[1].text.lines().count(), 3);
+        let location = lines[1].location.as_ref().unwrap();
+        assert_eq!(location.file, "/app/pages/index/index.js");
+        assert_eq!((location.line, location.col), (12, Some(7)));
+        assert_eq!(lines[2].level.as_deref(), Some("info"));
+    }
+
+    #[test]
+    fn merges_rust_panic_with_message_and_note() {
+        let lines = parse_all(
+            "stderr",
+            "thread 'main' panicked at src/main.rs:10:5:\n\
+             index out of bounds\n\
+             note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace",
+        );
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].level.as_deref(), Some("error"));
+        let location = lines[0].location.as_ref().unwrap();
+        assert_eq!(location.file, "src/main.rs");
+        assert_eq!((location.line, location.col), (10, Some(5)));
+    }
+
+    #[test]
+    fn reads_wechat_console_frames() {
+        let lines = parse_all(
+            "console",
+            "[WXML Runtime warning] ./pages/index/index.wxml\n\
+             VM45:1 thirdScriptError\n    \
+             at li.onLoad (index.js? [sm]:12)",
+        );
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].level.as_deref(), Some("warn"));
+        assert_eq!(lines[1].level.as_deref(), Some("error"));
+        let location = lines[1].location.as_ref().unwrap();
+        assert_eq!(location.file, "index.js");
+        assert_eq!((location.line, location.col), (12, None));
+    }
+
+    #[test]
+    fn detects_generic_level_prefixes() {
+        let cases = [
+            ("[ERROR] db down", "error"),
+            ("[warn] slow query", "warn"),
+            ("DEBUG: cache hit", "debug"),
+            ("error[E0308]: mismatched types", "error"),
+            ("warning: unused variable", "warn"),
+            ("2024-01-01T00:00:00Z  WARN app: retrying", "warn"),
+            ("plain output", "info"),
+        ];
+        for (input, expected) in cases {
+            let lines = parse_all("stdout", input);
+            assert_eq!(lines[0].level.as_deref(), Some(expected), "{input}");
+        }
+    }
+
+    #[test]
+    fn unmarked_warn_default_does_not_merge_indented_lines() {
+        let mut parser = LineParser::new("stderr", Level::Warn);
+        let mut lines = Vec::new();
+        for text in [
+            "   Compiling foo v0.1.0",
+            "   Compiling bar v0.2.0",
+            "    Finished dev",
+        ] {
+            lines.extend(parser.push(text.to_string()));
+        }
+        lines.extend(parser.finish());
+        assert_eq!(lines.len(), 3);
+        assert!(lines
+            .iter()
+            .all(|line| line.level.as_deref() == Some("warn")));
+    }
+
+    #[test]
+    fn level_filter_includes_more_severe_levels() {
+        assert_eq!(Level::Warn.at_least(), vec!["warn", "error", "fatal"]);
+        assert_eq!(normalize_level("WARNING"), "warn");
+        assert_eq!(normalize_level("custom"), "custom");
+    }
+}
@@ -1,3 +1,4 @@
+use crate::db::{LineRecord, RunRecord, SearchHit, StoreStats};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::BTreeMap;
@@ -27,10 +28,18 @@ pub struct AppendLine {
     pub level: Option<String>,
     pub event: Option<String>,
     pub text: String,
+    pub location: Option<SourceLocation>,
     #[serde(default)]
     pub meta: BTreeMap<String, Value>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: u32,
+    pub col: Option<u32>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct FinishRunRequest {
     pub status: Option<String>,
@@ -48,3 +57,32 @@ pub struct ErrorResponse {
     pub status: &'static str,
     pub error: String,
 }
+
+// Read responses. The CLI prints these same shapes for `--json`, so clients can
+// move between the CLI and `GET /v1/...` without reshaping anything.
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunsResponse {
+    pub status: &'static str,
+    pub runs: Vec<RunRecord>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LinesResponse {
+    pub status: &'static str,
+    pub run_id: String,
+    pub lines: Vec<LineRecord>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResponse {
+    pub status: &'static str,
+    pub search_backend: &'static str,
+    pub hits: Vec<SearchHit>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsResponse {
+    pub status: &'static str,
+    pub stats: StoreStats,
+}
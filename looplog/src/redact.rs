@@ -0,0 +1,260 @@
+use crate::error::{Error, Result};
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Built-in rules, applied in order. When a pattern has a `secret` group only that
+/// group is replaced, so `token=[REDACTED:secret]` still shows which key leaked.
+const BUILTIN_RULES: &[(&str, &str)] = &[
+    (
+        "private_key",
+        r"-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?(?:-----END [A-Z ]*PRIVATE KEY-----|$)",
+    ),
+    (
+        "jwt",
+        r"\beyJ[A-Za-z0-9_-]{5,}\.[A-Za-z0-9_-]{5,}\.[A-Za-z0-9_-]{5,}",
+    ),
+    (
+        "bearer_token",
+        r"(?i)\bbearer\s+(?P<secret>[A-Za-z0-9._~+/=-]{8,})",
+    ),
+    ("cookie", r"(?i)\b(?:set-)?cookie\s*:\s*(?P<secret>.+)"),
+    (
+        "wechat_appsecret",
+        r#"(?i)\bapp_?secret["']?\s*[:=]\s*["']?(?P<secret>[0-9a-f]{32})\b"#,
+    ),
+    ("aws_access_key", r"\bAKIA[0-9A-Z]{16}\b"),
+    ("github_token", r"\bgh[pousr]_[A-Za-z0-9]{36,}\b"),
+    (
+        "secret",
+        r#"(?i)\b(?:access_token|refresh_token|session_key|token|secret|password|passwd|api_?key|authorization)["']?\s*[:=]\s*["']?(?P<secret>[^\s"'&,;\[]{6,})"#,
+    ),
+    (
+        "email",
+        r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}\b",
+    ),
+    ("cn_id_card", r"\b\d{17}[\dXx]\b"),
+    ("phone", r"\b1[3-9]\d{9}\b"),
+];
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RedactConfig {
+    /// Extra rules, applied after the built-in ones.
+    #[serde(default)]
+    rules: Vec<RuleConfig>,
+    /// Names of built-in rules to turn off, e.g. `email`.
+    #[serde(default)]
+    disable: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleConfig {
+    name: String,
+    pattern: String,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    name: String,
+    regex: Regex,
+    marker: String,
+}
+
+/// Masks secrets and personal data in log text before it is stored.
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    rules: Vec<Rule>,
+}
+
+impl Redactor {
+    /// Built-in rules plus the rules in `config`, or in the default config file when
+    /// no path is given and that file exists.
+    pub fn load(config: Option<&Path>) -> Result<Self> {
+        let config = match config {
+            Some(path) => read_config(path)?,
+            None => match default_config_path().filter(|path| path.exists()) {
+                Some(path) => read_config(&path)?,
+                None => RedactConfig::default(),
+            },
+        };
+        if let Some(unknown) = config
+            .disable
+            .iter()
+            .find(|name| !BUILTIN_RULES.iter().any(|(builtin, _)| builtin == name))
+        {
+            return Err(Error::Message(format!(
+                "unknown built-in redaction rule `{}`",
+                unknown
+            )));
+        }
+
+        let builtin = BUILTIN_RULES
+            .iter()
+            .filter(|(name, _)| !config.disable.iter().any(|d| d == name))
+            .map(|(name, pattern)| (name.to_string(), pattern.to_string()));
+        let custom = config.rules.into_iter().map(|r| (r.name, r.pattern));
+        let rules = builtin
+            .chain(custom)
+            .map(|(name, pattern)| {
+                let regex = Regex::new(&pattern).map_err(|e| {
+                    Error::Message(format!("invalid redaction rule `{}`: {}", name, e))
+                })?;
+                Ok(Rule {
+                    marker: format!("[REDACTED:{}]", name),
+                    name,
+                    regex,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Returns the masked text and the names of the rules that matched.
+    pub fn redact<'a>(&self, text: &'a str) -> (Cow<'a, str>, Vec<String>) {
+        let mut out = Cow::Borrowed(text);
+        let mut hits = Vec::new();
+        for rule in &self.rules {
+            if !rule.regex.is_match(&out) {
+                continue;
+            }
+            let replaced = rule
+                .regex
+                .replace_all(&out, |caps: &Captures| {
+                    let whole = &caps[0];
+                    match caps.name("secret") {
+                        Some(secret) => {
+                            let start = secret.start() - caps.get(0).map_or(0, |m| m.start());
+                            let end = start + secret.len();
+                            format!("{}{}{}", &whole[..start], rule.marker, &whole[end..])
+                        }
+                        None => rule.marker.clone(),
+                    }
+                })
+                .into_owned();
+            out = Cow::Owned(replaced);
+            hits.push(rule.name.clone());
+        }
+        (out, hits)
+    }
+
+    /// Like [`Redactor::redact`], but adds the matched rule names to `hits`, once each.
+    pub fn redact_into<'a>(&self, text: &'a str, hits: &mut Vec<String>) -> Cow<'a, str> {
+        let (out, names) = self.redact(text);
+        for name in names {
+            if !hits.contains(&name) {
+                hits.push(name);
+            }
+        }
+        out
+    }
+
+    /// Masks every string nested in `value` in place. Keys are left alone.
+    pub fn redact_value(&self, value: &mut Value, hits: &mut Vec<String>) {
+        match value {
+            Value::String(text) => {
+                if let Cow::Owned(masked) = self.redact_into(text, hits) {
+                    *text = masked;
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.redact_value(item, hits);
+                }
+            }
+            Value::Object(map) => {
+                for item in map.values_mut() {
+                    self.redact_value(item, hits);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `<config dir>/looplog/redact.json`.
+pub fn default_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("looplog").join("redact.json"))
+}
+
+fn read_config(path: &Path) -> Result<RedactConfig> {
+    let text = fs::read_to_string(path).map_err(|e| {
+        Error::Message(format!(
+            "cannot read redaction config `{}`: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    serde_json::from_str(&text).map_err(|e| {
+        Error::Message(format!(
+            "invalid redaction config `{}`: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn masks_builtin_secrets() {
+        let redactor = Redactor::load(Some(write_config("{}").path())).unwrap();
+        let cases = [
+            (
+                "GET /cgi-bin/token?appid=wx123&secret=0123456789abcdef0123456789abcdef",
+                "GET /cgi-bin/token?appid=wx123&secret=[REDACTED:secret]",
+            ),
+            (
+                r#"{"appsecret": "0123456789abcdef0123456789abcdef"}"#,
+                r#"{"appsecret": "[REDACTED:wechat_appsecret]"}"#,
+            ),
+            (
+                "Authorization: Bearer abc.def-123456",
+                "Authorization: [REDACTED:secret] [REDACTED:bearer_token]",
+            ),
+            ("cookie: sid=abc; uid=7", "cookie: [REDACTED:cookie]"),
+            (
+                "token eyJhbGciOi.eyJzdWIiOi.SflKxwRJSM",
+                "token [REDACTED:jwt]",
+            ),
+            (
+                "call 13812345678 or mail dev@example.com",
+                "call [REDACTED:phone] or mail [REDACTED:email]",
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(redactor.redact(input).0, expected, "{input}");
+        }
+
+        let (text, hits) = redactor.redact("build ok in 1712345678901ms");
+        assert!(matches!(text, Cow::Borrowed(_)));
+        assert!(hits.is_empty());
+    }
+
+    #[test]
+    fn applies_custom_rules_and_disables_builtins() {
+        let config = write_config(
+            r#"{"rules": [{"name": "order", "pattern": "ORD-\\d+"}], "disable": ["email"]}"#,
+        );
+        let redactor = Redactor::load(Some(config.path())).unwrap();
+        let (text, hits) = redactor.redact("ORD-42 for dev@example.com");
+        assert_eq!(text, "[REDACTED:order] for dev@example.com");
+        assert_eq!(hits, vec!["order".to_string()]);
+
+        let bad = write_config(r#"{"rules": [{"name": "x", "pattern": "("}]}"#);
+        assert!(Redactor::load(Some(bad.path())).is_err());
+    }
+
+    fn write_config(json: &str) -> NamedTempFile {
+        let file = NamedTempFile::new().unwrap();
+        fs::write(file.path(), json).unwrap();
+        file
+    }
+}
@@ -1,87 +1,445 @@
-use crate::db::{NewRun, SqliteStorage};
+use crate::db::{parse_since, NewRun, QueryFilter, SqliteStorage, SEARCH_BACKEND};
 use crate::error::{Error, Result};
+use crate::follow::Follower;
+use crate::meta;
+use crate::otlp;
+use crate::parser::{self, Level};
 use crate::protocol::{
-    AppendLine, ErrorResponse, FinishRunRequest, HealthResponse, StartRunRequest, StartRunResponse,
+    AppendLine, ErrorResponse, FinishRunRequest, HealthResponse, LinesResponse, RunsResponse,
+    SearchResponse, StartRunRequest, StartRunResponse, StatsResponse,
 };
+use crate::redact::Redactor;
+use crate::storage::Storage;
+use crate::syslog;
+use crate::writer::{RunGroups, Writer};
 use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 use tiny_http::{Header, Method, Request, Response, Server, StatusCode};
 
-pub fn serve(addr: &str, db_path: PathBuf, keep_hours: u64) -> Result<()> {
+/// Idle follow streams send a blank line this often so closed clients are noticed.
+const FOLLOW_HEARTBEAT: Duration = Duration::from_secs(15);
+
+pub struct ServeOptions {
+    /// Overrides the stored default retention window for this server.
+    pub keep_hours: Option<u64>,
+    /// HTTP worker threads. Reads run on the workers; writes go to the writer thread.
+    pub workers: usize,
+    /// Pending write requests before the server answers 429.
+    pub queue: usize,
+    /// Applied to every appended line; `None` only with `--no-redact`.
+    pub redactor: Option<Redactor>,
+    /// Also accept syslog datagrams on this UDP address.
+    pub syslog: Option<String>,
+}
+
+pub fn serve(addr: &str, db_path: PathBuf, options: ServeOptions) -> Result<()> {
     let server = Server::http(addr)?;
-    eprintln!("looplog serve listening on http://{}", addr);
-    for request in server.incoming_requests() {
-        let result = handle_request(request, &db_path, keep_hours);
-        if let Err(e) = result {
-            eprintln!("[looplog serve] request failed: {}", e);
-        }
+    eprintln!(
+        "looplog serve listening on http://{} ({} workers)",
+        addr, options.workers
+    );
+    run_server(server, db_path, options)
+}
+
+fn run_server(server: Server, db_path: PathBuf, options: ServeOptions) -> Result<()> {
+    let server = Arc::new(server);
+    let writer = Writer::spawn(
+        &db_path,
+        options.queue,
+        options.keep_hours,
+        options.redactor,
+    )?;
+    let groups = RunGroups::new(writer.clone());
+    if let Some(addr) = &options.syslog {
+        syslog::spawn(addr, groups.clone(), None)?;
+    }
+    let mut workers = Vec::new();
+    for _ in 0..options.workers.max(1) {
+        let mut storage = SqliteStorage::open(&db_path)?;
+        storage.disable_auto_clean();
+        let mut worker = Worker {
+            storage,
+            writer: writer.clone(),
+            groups: groups.clone(),
+            db_path: db_path.clone(),
+        };
+        let server = Arc::clone(&server);
+        workers.push(thread::spawn(move || {
+            for request in server.incoming_requests() {
+                if let Err(e) = worker.handle_request(request) {
+                    eprintln!("[looplog serve] request failed: {}", e);
+                }
+            }
+        }));
+    }
+    for worker in workers {
+        let _ = worker.join();
     }
     Ok(())
 }
 
-fn handle_request(mut request: Request, db_path: &PathBuf, keep_hours: u64) -> Result<()> {
-    let method = request.method().clone();
-    let path = request.url().split('?').next().unwrap_or("/").to_string();
-    let mut storage = SqliteStorage::open(db_path)?;
-    storage.clean(keep_hours, false)?;
-
-    match (method.clone(), path.as_str()) {
-        (Method::Get, "/healthz") => respond_json(
-            request,
-            StatusCode(200),
-            &HealthResponse {
-                status: "ok",
-                service: "looplog",
-            },
-        ),
-        (Method::Post, "/v1/runs") => {
-            let req: StartRunRequest = read_json(&mut request)?;
-            let run_id = storage.start_run(NewRun {
-                tag: req.tag,
-                source: req.source,
-                cwd: req.cwd,
-                argv: req.argv,
-                client_id: req.client_id,
-                kind: req.kind,
-                meta: req.meta,
-            })?;
-            respond_json(request, StatusCode(200), &StartRunResponse { run_id })
+/// Per-thread state: a read connection plus a handle to the shared writer.
+struct Worker {
+    storage: SqliteStorage,
+    writer: Writer,
+    /// Runs for intake protocols without explicit run ids (OTLP, syslog).
+    groups: RunGroups,
+    db_path: PathBuf,
+}
+
+impl Worker {
+    fn handle_request(&mut self, mut request: Request) -> Result<()> {
+        let method = request.method().clone();
+        let url = request.url().to_string();
+        let path = url.split('?').next().unwrap_or("/").to_string();
+
+        if method == Method::Get {
+            let params = query_params(&url);
+            if let Some(result) = read_route(&mut self.storage, &path, &params) {
+                return respond_result(request, result);
+            }
+        }
+
+        match (method.clone(), path.as_str()) {
+            (Method::Get, "/healthz") => respond_json(
+                request,
+                StatusCode(200),
+                &HealthResponse {
+                    status: "ok",
+                    service: "looplog",
+                },
+            ),
+            (Method::Post, "/v1/runs") => {
+                let result = read_json::<StartRunRequest>(&mut request).and_then(|req| {
+                    self.writer.start_run(NewRun {
+                        tag: req.tag,
+                        source: req.source,
+                        cwd: req.cwd,
+                        argv: req.argv,
+                        client_id: req.client_id,
+                        kind: req.kind,
+                        meta: req.meta,
+                    })
+                });
+                respond_result(request, result.map(|run_id| StartRunResponse { run_id }))
+            }
+            (Method::Post, "/v1/logs") => self.otlp_logs(request),
+            _ => {
+                if method == Method::Post
+                    && path.starts_with("/v1/runs/")
+                    && path.ends_with("/lines")
+                {
+                    let run_id = path
+                        .trim_start_matches("/v1/runs/")
+                        .trim_end_matches("/lines")
+                        .trim_end_matches('/');
+                    // Parsing happens here on the worker so the writer only does SQL.
+                    let result = read_body(&mut request)
+                        .and_then(|body| parse_ndjson_lines(&body))
+                        .and_then(|mut lines| {
+                            parser::annotate(&mut lines);
+                            self.writer.append_lines(run_id, lines)
+                        });
+                    respond_result(
+                        request,
+                        result.map(|count| serde_json::json!({"status": "ok", "lines": count})),
+                    )
+                } else if method == Method::Get
+                    && path.starts_with("/v1/runs/")
+                    && path.ends_with("/follow")
+                {
+                    let run_id = path
+                        .trim_start_matches("/v1/runs/")
+                        .trim_end_matches("/follow")
+                        .trim_end_matches('/')
+                        .to_string();
+                    self.follow(request, &url, run_id)
+                } else if method == Method::Patch && path.starts_with("/v1/runs/") {
+                    let run_id = path.trim_start_matches("/v1/runs/").trim_end_matches('/');
+                    let result = read_json::<FinishRunRequest>(&mut request)
+                        .and_then(|req| self.writer.finish_run(run_id, req));
+                    respond_result(
+                        request,
+                        result.map(|()| serde_json::json!({"status": "ok"})),
+                    )
+                } else {
+                    respond_json(
+                        request,
+                        StatusCode(404),
+                        &ErrorResponse {
+                            status: "error",
+                            error: "not found".to_string(),
+                        },
+                    )
+                }
+            }
         }
+    }
+
+    /// OTLP/HTTP log export. Only the JSON encoding is supported.
+    fn otlp_logs(&mut self, mut request: Request) -> Result<()> {
+        let protobuf = request
+            .headers()
+            .iter()
+            .any(|h| h.field.equiv("Content-Type") && h.value.as_str().contains("protobuf"));
+        if protobuf {
+            return respond_json(
+                request,
+                StatusCode(415),
+                &ErrorResponse {
+                    status: "error",
+                    error: "only OTLP http/json is supported; set \
+                        OTEL_EXPORTER_OTLP_LOGS_PROTOCOL=http/json"
+                        .to_string(),
+                },
+            );
+        }
+        let result = read_body(&mut request)
+            .and_then(|body| otlp::parse_logs(&body))
+            .and_then(|batches| {
+                for batch in batches {
+                    let run = batch.run;
+                    self.groups
+                        .append(&batch.key, || run.clone(), batch.lines)?;
+                }
+                Ok(serde_json::json!({"partialSuccess": {}}))
+            });
+        respond_result(request, result)
+    }
+
+    fn follow(&mut self, request: Request, url: &str, run_id: String) -> Result<()> {
+        if self.storage.get_run(&run_id)?.is_none() {
+            return respond_json(
+                request,
+                StatusCode(404),
+                &ErrorResponse {
+                    status: "error",
+                    error: format!("run `{}` not found", run_id),
+                },
+            );
+        }
+        let params = query_params(url);
+        let after_seq = parse_param::<i64>(&params, "after")?.unwrap_or(0);
+        let mut storage = SqliteStorage::open(&self.db_path)?;
+        storage.disable_auto_clean();
+        let follower = Follower::new(
+            storage,
+            run_id,
+            after_seq,
+            params.get("stream").cloned(),
+            min_level_param(&params)?,
+        );
+        // Follow streams stay open until the run finishes, so they get their own
+        // thread instead of holding a worker.
+        thread::spawn(move || {
+            if let Err(e) = stream_follow(request, follower) {
+                eprintln!("[looplog serve] follow stream ended: {}", e);
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Chunked NDJSON written by hand: tiny_http's own chunk encoder buffers 8 KiB
+/// before sending, which would stall a live stream.
+fn stream_follow(request: Request, mut follower: Follower) -> Result<()> {
+    let mut writer = request.into_writer();
+    writer.write_all(
+        b"HTTP/1.1 200 OK\r\n\
+          Content-Type: application/x-ndjson; charset=utf-8\r\n\
+          Cache-Control: no-cache\r\n\
+          Transfer-Encoding: chunked\r\n\
+          Connection: close\r\n\r\n",
+    )?;
+    writer.flush()?;
+
+    while let Some(events) = follower.next_events(FOLLOW_HEARTBEAT)? {
+        let mut body = String::new();
+        if events.is_empty() {
+            body.push('\n');
+        }
+        for event in &events {
+            body.push_str(&serde_json::to_string(event)?);
+            body.push('\n');
+        }
+        write!(writer, "{:x}\r\n", body.len())?;
+        writer.write_all(body.as_bytes())?;
+        writer.write_all(b"\r\n")?;
+        writer.flush()?;
+    }
+    writer.write_all(b"0\r\n\r\n")?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ReadResponse {
+    Runs(RunsResponse),
+    Lines(LinesResponse),
+    Search(SearchResponse),
+    Stats(StatsResponse),
+}
+
+/// Read-only routes. Each mirrors a CLI command and returns its `--json` shape:
+/// `GET /v1/runs` (list), `GET /v1/runs/{id}/lines` (show), `GET /v1/search` (grep)
+/// and `GET /v1/stats` (stats).
+fn read_route<S: Storage>(
+    storage: &mut S,
+    path: &str,
+    params: &BTreeMap<String, String>,
+) -> Option<Result<ReadResponse>> {
+    let result = match path {
+        "/v1/runs" => list_route(storage, params),
+        "/v1/search" => search_route(storage, params),
+        "/v1/stats" => stats_route(storage, params),
         _ => {
-            if method == Method::Post && path.starts_with("/v1/runs/") && path.ends_with("/lines") {
-                let run_id = path
-                    .trim_start_matches("/v1/runs/")
-                    .trim_end_matches("/lines")
-                    .trim_end_matches('/');
-                let body = read_body(&mut request)?;
-                let lines = parse_ndjson_lines(&body)?;
-                let count = storage.append_lines(run_id, &lines)?;
-                respond_json(
-                    request,
-                    StatusCode(200),
-                    &serde_json::json!({"status": "ok", "lines": count}),
-                )
-            } else if method == Method::Patch && path.starts_with("/v1/runs/") {
-                let run_id = path.trim_start_matches("/v1/runs/").trim_end_matches('/');
-                let req: FinishRunRequest = read_json(&mut request)?;
-                storage.finish_run(run_id, &req)?;
-                respond_json(
-                    request,
-                    StatusCode(200),
-                    &serde_json::json!({"status": "ok"}),
-                )
-            } else {
-                respond_json(
-                    request,
-                    StatusCode(404),
-                    &ErrorResponse {
-                        status: "error",
-                        error: "not found".to_string(),
-                    },
-                )
+            let run_id = path
+                .strip_prefix("/v1/runs/")?
+                .strip_suffix("/lines")?
+                .trim_end_matches('/');
+            lines_route(storage, run_id, params)
+        }
+    };
+    Some(result)
+}
+
+fn list_route<S: Storage>(
+    storage: &mut S,
+    params: &BTreeMap<String, String>,
+) -> Result<ReadResponse> {
+    let limit = parse_param(params, "limit")?.unwrap_or(20);
+    let runs = storage.list_runs(&filter_from_query(params)?, limit)?;
+    Ok(ReadResponse::Runs(RunsResponse { status: "ok", runs }))
+}
+
+fn lines_route<S: Storage>(
+    storage: &mut S,
+    run_id: &str,
+    params: &BTreeMap<String, String>,
+) -> Result<ReadResponse> {
+    let lines = storage.show_lines(
+        run_id,
+        parse_param(params, "tail")?,
+        params.get("stream").map(String::as_str),
+        min_level_param(params)?,
+    )?;
+    Ok(ReadResponse::Lines(LinesResponse {
+        status: "ok",
+        run_id: run_id.to_string(),
+        lines,
+    }))
+}
+
+fn search_route<S: Storage>(
+    storage: &mut S,
+    params: &BTreeMap<String, String>,
+) -> Result<ReadResponse> {
+    let pattern = params
+        .get("q")
+        .ok_or_else(|| Error::Message("missing `q` search pattern".to_string()))?;
+    let limit = parse_param(params, "limit")?.unwrap_or(50);
+    let hits = storage.search(pattern, &filter_from_query(params)?, limit)?;
+    Ok(ReadResponse::Search(SearchResponse {
+        status: "ok",
+        search_backend: SEARCH_BACKEND,
+        hits,
+    }))
+}
+
+fn stats_route<S: Storage>(
+    storage: &mut S,
+    params: &BTreeMap<String, String>,
+) -> Result<ReadResponse> {
+    let stats = storage.stats(&filter_from_query(params)?)?;
+    Ok(ReadResponse::Stats(StatsResponse {
+        status: "ok",
+        stats,
+    }))
+}
+
+/// Same keys as the CLI filter flags: `run`, `tag`, `kind`, `appid`, `project`, `page`,
+/// `session`, `trace`, `since`, `level`, `errors_only`.
+fn filter_from_query(params: &BTreeMap<String, String>) -> Result<QueryFilter> {
+    let text = |key: &str| params.get(key).cloned();
+    Ok(QueryFilter {
+        run_id: text("run"),
+        tag: text("tag"),
+        kind: meta::normalize_kind(text("kind")),
+        appid: text("appid"),
+        project: text("project"),
+        page: text("page"),
+        session: text("session"),
+        trace: text("trace"),
+        since_ms: params.get("since").map(|v| parse_since(v)).transpose()?,
+        min_level: min_level_param(params)?,
+    })
+}
+
+fn min_level_param(params: &BTreeMap<String, String>) -> Result<Option<Level>> {
+    if params
+        .get("errors_only")
+        .is_some_and(|v| v.is_empty() || v == "1" || v == "true")
+    {
+        return Ok(Some(Level::Error));
+    }
+    params.get("level").map(|v| v.parse()).transpose()
+}
+
+fn parse_param<T: std::str::FromStr>(
+    params: &BTreeMap<String, String>,
+    key: &str,
+) -> Result<Option<T>> {
+    params
+        .get(key)
+        .map(|v| {
+            v.parse()
+                .map_err(|_| Error::Message(format!("invalid `{}` value `{}`", key, v)))
+        })
+        .transpose()
+}
+
+fn query_params(url: &str) -> BTreeMap<String, String> {
+    let Some((_, query)) = url.split_once('?') else {
+        return BTreeMap::new();
+    };
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 2;
+                    }
+                    None => out.push(b'%'),
+                }
             }
+            byte => out.push(byte),
         }
+        i += 1;
     }
+    String::from_utf8_lossy(&out).into_owned()
 }
 
 fn parse_ndjson_lines(body: &str) -> Result<Vec<AppendLine>> {
@@ -107,6 +465,46 @@ fn read_body(request: &mut Request) -> Result<String> {
     Ok(body)
 }
 
+/// Errors become JSON: 429 with `Retry-After` when the write queue is full, 404 for
+/// unknown runs, else 400.
+fn respond_result<T: Serialize>(request: Request, result: Result<T>) -> Result<()> {
+    match result {
+        Ok(body) => respond_json(request, StatusCode(200), &body),
+        Err(Error::QueueFull) => {
+            let body = serde_json::to_string_pretty(&ErrorResponse {
+                status: "error",
+                error: Error::QueueFull.to_string(),
+            })?;
+            let mut response = Response::from_string(body).with_status_code(StatusCode(429));
+            for (name, value) in [
+                ("Content-Type", "application/json; charset=utf-8"),
+                ("Retry-After", "1"),
+            ] {
+                if let Ok(header) = Header::from_bytes(name, value) {
+                    response.add_header(header);
+                }
+            }
+            request
+                .respond(response)
+                .map_err(|e| Error::Http(e.to_string()))
+        }
+        Err(e) => {
+            let status = match e {
+                Error::RunNotFound(_) => 404,
+                _ => 400,
+            };
+            respond_json(
+                request,
+                StatusCode(status),
+                &ErrorResponse {
+                    status: "error",
+                    error: e.to_string(),
+                },
+            )
+        }
+    }
+}
+
 fn respond_json<T: Serialize>(request: Request, status: StatusCode, value: &T) -> Result<()> {
     let body = serde_json::to_string_pretty(value)?;
     let mut response = Response::from_string(body).with_status_code(status);
@@ -122,6 +520,17 @@ fn respond_json<T: Serialize>(request: Request, status: StatusCode, value: &T) -
 mod tests {
     use super::*;
 
+    #[test]
+    fn decodes_query_into_filter() {
+        let params = query_params("/v1/search?q=Type%20Error+x&appid=wx123&errors_only=1&limit=5");
+        assert_eq!(params["q"], "Type Error x");
+        let filter = filter_from_query(&params).unwrap();
+        assert_eq!(filter.appid.as_deref(), Some("wx123"));
+        assert_eq!(filter.min_level, Some(Level::Error));
+        assert_eq!(parse_param::<usize>(&params, "limit").unwrap(), Some(5));
+        assert!(parse_param::<usize>(&query_params("/?limit=x"), "limit").is_err());
+    }
+
     #[test]
     fn parses_ndjson_batch() {
         let lines = parse_ndjson_lines(
@@ -132,4 +541,92 @@ mod tests {
         assert_eq!(lines.len(), 2);
         assert_eq!(lines[0].text, "TypeError");
     }
+
+    fn http(addr: std::net::SocketAddr, method: &str, path: &str, body: &str) -> (u16, String) {
+        use std::io::Read;
+        let mut stream = std::net::TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response[9..12].parse().unwrap();
+        let body = response.split_once("\r\n\r\n").unwrap().1.to_string();
+        (status, body)
+    }
+
+    /// Concurrent clients flooding the intake. Prints throughput; run with
+    /// `cargo test --release ingest_throughput -- --nocapture` for real numbers.
+    #[test]
+    fn ingest_throughput() {
+        const CLIENTS: usize = 4;
+        const BATCHES: usize = 10;
+        const BATCH_LINES: usize = 250;
+
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let db_path = tmp.path().to_path_buf();
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+        let options = ServeOptions {
+            keep_hours: None,
+            workers: 4,
+            queue: 64,
+            redactor: None,
+            syslog: None,
+        };
+        let server_db = db_path.clone();
+        thread::spawn(move || run_server(server, server_db, options));
+
+        let body = (0..BATCH_LINES)
+            .map(|i| format!(r#"{{"stream":"console","level":"info","text":"console.log {i}"}}"#))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let started = std::time::Instant::now();
+        let clients = (0..CLIENTS)
+            .map(|_| {
+                let body = body.clone();
+                thread::spawn(move || {
+                    let (status, created) = http(addr, "POST", "/v1/runs", "{}");
+                    assert_eq!(status, 200, "{created}");
+                    let run_id = serde_json::from_str::<StartRunResponse>(&created)
+                        .unwrap()
+                        .run_id;
+                    let path = format!("/v1/runs/{run_id}/lines");
+                    let mut batch = 0;
+                    while batch < BATCHES {
+                        match http(addr, "POST", &path, &body) {
+                            (200, _) => batch += 1,
+                            (429, _) => thread::sleep(Duration::from_millis(5)),
+                            (status, error) => panic!("{status}: {error}"),
+                        }
+                    }
+                    run_id
+                })
+            })
+            .collect::<Vec<_>>();
+        let run_ids = clients
+            .into_iter()
+            .map(|client| client.join().unwrap())
+            .collect::<Vec<_>>();
+        let elapsed = started.elapsed();
+
+        let total = CLIENTS * BATCHES * BATCH_LINES;
+        eprintln!(
+            "ingested {} lines in {:.2?} ({:.0} lines/sec)",
+            total,
+            elapsed,
+            total as f64 / elapsed.as_secs_f64()
+        );
+        let mut storage = SqliteStorage::open(&db_path).unwrap();
+        for run_id in run_ids {
+            let run = storage.get_run(&run_id).unwrap().unwrap();
+            assert_eq!(run.line_count as usize, BATCHES * BATCH_LINES);
+        }
+        let (status, _) = http(addr, "POST", "/v1/runs/missing/lines", r#"{"text":"x"}"#);
+        assert_eq!(status, 404);
+    }
 }
@@ -1,5 +1,8 @@
-use crate::db::{CleanStats, LineRecord, NewRun, QueryFilter, RunRecord, SearchHit, SqliteStorage};
+use crate::db::{
+    CleanStats, LineRecord, NewRun, QueryFilter, RunRecord, SearchHit, SqliteStorage, StoreStats,
+};
 use crate::error::Result;
+use crate::parser::Level;
 use crate::protocol::{AppendLine, FinishRunRequest};
 use std::path::Path;
 
@@ -12,11 +15,20 @@ pub trait Storage {
     fn append_lines(&mut self, run_id: &str, lines: &[AppendLine]) -> Result<usize>;
     fn finish_run(&mut self, run_id: &str, finish: &FinishRunRequest) -> Result<()>;
     fn list_runs(&mut self, filter: &QueryFilter, limit: usize) -> Result<Vec<RunRecord>>;
+    fn get_run(&mut self, run_id: &str) -> Result<Option<RunRecord>>;
     fn show_lines(
         &mut self,
         run_id: &str,
         tail: Option<usize>,
         stream: Option<&str>,
+        min_level: Option<Level>,
+    ) -> Result<Vec<LineRecord>>;
+    fn lines_after(
+        &mut self,
+        run_id: &str,
+        after_seq: i64,
+        stream: Option<&str>,
+        min_level: Option<Level>,
     ) -> Result<Vec<LineRecord>>;
     fn search(
         &mut self,
@@ -24,6 +36,7 @@ pub trait Storage {
         filter: &QueryFilter,
         limit: usize,
     ) -> Result<Vec<SearchHit>>;
+    fn stats(&mut self, filter: &QueryFilter) -> Result<StoreStats>;
     fn clean(&mut self, keep_hours: u64, vacuum: bool) -> Result<CleanStats>;
 }
 
@@ -48,13 +61,28 @@ impl Storage for SqliteStorage {
         SqliteStorage::list_runs(self, filter, limit)
     }
 
+    fn get_run(&mut self, run_id: &str) -> Result<Option<RunRecord>> {
+        SqliteStorage::get_run(self, run_id)
+    }
+
     fn show_lines(
         &mut self,
         run_id: &str,
         tail: Option<usize>,
         stream: Option<&str>,
+        min_level: Option<Level>,
+    ) -> Result<Vec<LineRecord>> {
+        SqliteStorage::show_lines(self, run_id, tail, stream, min_level)
+    }
+
+    fn lines_after(
+        &mut self,
+        run_id: &str,
+        after_seq: i64,
+        stream: Option<&str>,
+        min_level: Option<Level>,
     ) -> Result<Vec<LineRecord>> {
-        SqliteStorage::show_lines(self, run_id, tail, stream)
+        SqliteStorage::lines_after(self, run_id, after_seq, stream, min_level)
     }
 
     fn search(
@@ -66,6 +94,10 @@ impl Storage for SqliteStorage {
         SqliteStorage::search(self, pattern, filter, limit)
     }
 
+    fn stats(&mut self, filter: &QueryFilter) -> Result<StoreStats> {
+        SqliteStorage::stats(self, filter)
+    }
+
     fn clean(&mut self, keep_hours: u64, vacuum: bool) -> Result<CleanStats> {
         SqliteStorage::clean(self, keep_hours, vacuum)
     }
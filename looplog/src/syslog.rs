@@ -0,0 +1,312 @@
+use crate::db::{parse_rfc3339_ms, NewRun};
+use crate::error::{Error, Result};
+use crate::parser::Level;
+use crate::protocol::AppendLine;
+use crate::writer::RunGroups;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::net::UdpSocket;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Datagrams are grouped per run and flushed this often, or sooner when a batch fills.
+const FLUSH_INTERVAL: Duration = Duration::from_millis(200);
+const MAX_BATCH: usize = 500;
+
+/// One parsed RFC 5424 or RFC 3164 message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyslogMessage {
+    pub facility: u8,
+    pub severity: u8,
+    pub timestamp: Option<String>,
+    pub host: Option<String>,
+    pub app: Option<String>,
+    pub procid: Option<String>,
+    pub msgid: Option<String>,
+    pub text: String,
+}
+
+impl SyslogMessage {
+    /// Messages from one host and app share a run.
+    pub fn run_key(&self) -> String {
+        format!(
+            "syslog\u{0}{}\u{0}{}",
+            self.host.as_deref().unwrap_or("-"),
+            self.app.as_deref().unwrap_or("-")
+        )
+    }
+
+    pub fn level(&self) -> Level {
+        match self.severity {
+            0..=2 => Level::Fatal,
+            3 => Level::Error,
+            4 => Level::Warn,
+            5 | 6 => Level::Info,
+            _ => Level::Debug,
+        }
+    }
+
+    pub fn into_line(self) -> AppendLine {
+        let mut meta = BTreeMap::new();
+        meta.insert("facility".to_string(), Value::from(self.facility));
+        for (key, value) in [("procid", &self.procid), ("msgid", &self.msgid)] {
+            if let Some(value) = value {
+                meta.insert(key.to_string(), Value::from(value.as_str()));
+            }
+        }
+        let level = self.level();
+        AppendLine {
+            ts: self.timestamp,
+            stream: Some("syslog".to_string()),
+            level: Some(level.as_str().to_string()),
+            event: None,
+            text: self.text,
+            location: None,
+            meta,
+        }
+    }
+}
+
+/// Listen for syslog datagrams on `addr` in a background thread.
+pub fn spawn(addr: &str, groups: RunGroups, tag: Option<String>) -> Result<()> {
+    let socket = UdpSocket::bind(addr)?;
+    socket.set_read_timeout(Some(FLUSH_INTERVAL))?;
+    eprintln!("looplog syslog listening on udp://{}", addr);
+    thread::spawn(move || listen(socket, groups, tag));
+    Ok(())
+}
+
+fn listen(socket: UdpSocket, groups: RunGroups, tag: Option<String>) {
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut pending: BTreeMap<String, (SyslogMessage, Vec<AppendLine>)> = BTreeMap::new();
+    let mut count = 0;
+    let mut last_flush = Instant::now();
+    loop {
+        match socket.recv(&mut buf) {
+            Ok(len) => {
+                let datagram = String::from_utf8_lossy(&buf[..len]);
+                // Some senders pack several messages into one datagram, one per line.
+                for raw in datagram.lines().filter(|l| !l.trim().is_empty()) {
+                    let message = parse(raw);
+                    let line = message.clone().into_line();
+                    pending
+                        .entry(message.run_key())
+                        .or_insert_with(|| (message, Vec::new()))
+                        .1
+                        .push(line);
+                    count += 1;
+                }
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) => {
+                eprintln!("[looplog syslog] receive failed: {}", e);
+                return;
+            }
+        }
+
+        if count >= MAX_BATCH || last_flush.elapsed() >= FLUSH_INTERVAL {
+            for (key, (first, lines)) in std::mem::take(&mut pending) {
+                let new_run = || new_run(&first, tag.clone());
+                if let Err(e) = append_with_retry(&groups, &key, new_run, lines) {
+                    eprintln!("[looplog syslog] dropped messages: {}", e);
+                }
+            }
+            count = 0;
+            last_flush = Instant::now();
+        }
+    }
+}
+
+/// UDP has no way to push back on a sender, so a full queue is retried briefly
+/// before the batch is dropped.
+fn append_with_retry(
+    groups: &RunGroups,
+    key: &str,
+    new_run: impl Fn() -> NewRun,
+    lines: Vec<AppendLine>,
+) -> Result<usize> {
+    let mut attempts = 0;
+    loop {
+        match groups.append(key, &new_run, lines.clone()) {
+            Err(Error::QueueFull) if attempts < 10 => {
+                attempts += 1;
+                thread::sleep(Duration::from_millis(50));
+            }
+            result => return result,
+        }
+    }
+}
+
+fn new_run(message: &SyslogMessage, tag: Option<String>) -> NewRun {
+    let mut meta = BTreeMap::new();
+    for (key, value) in [("host", &message.host), ("app", &message.app)] {
+        if let Some(value) = value {
+            meta.insert(key.to_string(), Value::from(value.as_str()));
+        }
+    }
+    NewRun {
+        tag: tag.or_else(|| Some("syslog".to_string())),
+        source: Some("syslog".to_string()),
+        cwd: None,
+        argv: Vec::new(),
+        client_id: message.host.clone(),
+        kind: Some("syslog".to_string()),
+        meta,
+    }
+}
+
+/// Parse RFC 5424, falling back to the looser BSD format (RFC 3164). Anything
+/// unrecognizable is kept whole as a `user.notice` message.
+pub fn parse(raw: &str) -> SyslogMessage {
+    let raw = raw.trim_end_matches(['\r', '\n', '\0']);
+    let Some((pri, rest)) = parse_pri(raw) else {
+        return SyslogMessage {
+            facility: 1,
+            severity: 5,
+            timestamp: None,
+            host: None,
+            app: None,
+            procid: None,
+            msgid: None,
+            text: raw.to_string(),
+        };
+    };
+    let mut message = match rest.strip_prefix("1 ") {
+        Some(rest) => parse_5424(rest),
+        None => parse_3164(rest),
+    };
+    message.facility = pri / 8;
+    message.severity = pri % 8;
+    message
+}
+
+fn parse_pri(raw: &str) -> Option<(u8, &str)> {
+    let rest = raw.strip_prefix('<')?;
+    let end = rest.find('>')?;
+    let pri = rest[..end].parse::<u8>().ok().filter(|pri| *pri < 192)?;
+    Some((pri, &rest[end + 1..]))
+}
+
+fn parse_5424(rest: &str) -> SyslogMessage {
+    let mut fields = rest.splitn(6, ' ');
+    let mut next = || fields.next().filter(|f| *f != "-").map(ToOwned::to_owned);
+    let timestamp = next().filter(|ts| parse_rfc3339_ms(ts).is_ok());
+    let host = next();
+    let app = next();
+    let procid = next();
+    let msgid = next();
+    let rest = fields.next().unwrap_or("");
+
+    let text = match rest.strip_prefix('-') {
+        Some(text) => text,
+        None => skip_structured_data(rest),
+    };
+    SyslogMessage {
+        facility: 0,
+        severity: 0,
+        timestamp,
+        host,
+        app,
+        procid,
+        msgid,
+        text: text.trim_start().trim_start_matches('\u{feff}').to_string(),
+    }
+}
+
+/// Skip `[id key="value"]...` elements, honouring `\]` escapes inside values.
+fn skip_structured_data(rest: &str) -> &str {
+    let mut in_element = false;
+    let mut escaped = false;
+    for (index, c) in rest.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' if !in_element => in_element = true,
+            ']' if in_element => in_element = false,
+            _ if !in_element => return &rest[index..],
+            _ => {}
+        }
+    }
+    ""
+}
+
+fn parse_3164(rest: &str) -> SyslogMessage {
+    // `Mmm dd hh:mm:ss ` has no year or zone, so the receive time is used instead.
+    let rest = match rest.get(..16) {
+        Some(head) if is_bsd_timestamp(head) => &rest[16..],
+        _ => rest,
+    };
+    let (host, rest) = match rest.split_once(' ') {
+        // A token ending in `:` or holding `[pid]` is the tag, not a host name.
+        Some((first, tail)) if !first.ends_with(':') && !first.contains('[') => {
+            (Some(first.to_string()), tail)
+        }
+        _ => (None, rest),
+    };
+    let (app, procid, text) = match rest.split_once(": ") {
+        Some((tag, text)) if !tag.contains(' ') => {
+            let (app, procid) = match tag.split_once('[') {
+                Some((app, pid)) => (app, Some(pid.trim_end_matches(']').to_string())),
+                None => (tag, None),
+            };
+            (Some(app.to_string()), procid, text)
+        }
+        _ => (None, None, rest),
+    };
+    SyslogMessage {
+        facility: 0,
+        severity: 0,
+        timestamp: None,
+        host,
+        app,
+        procid,
+        msgid: None,
+        text: text.to_string(),
+    }
+}
+
+fn is_bsd_timestamp(head: &str) -> bool {
+    let bytes = head.as_bytes();
+    bytes[3] == b' '
+        && bytes[6] == b' '
+        && bytes[9] == b':'
+        && bytes[12] == b':'
+        && bytes[15] == b' '
+        && bytes[..3].iter().all(u8::is_ascii_alphabetic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rfc5424_and_bsd_messages() {
+        let message = parse(
+            "<165>1 2026-10-11T22:14:15.003Z mymachine evntslog 1234 ID47 \
+             [exampleSDID@32473 iut=\"3\" note=\"a \\] b\"] An application event",
+        );
+        assert_eq!(message.facility, 20);
+        assert_eq!(message.level(), Level::Info);
+        assert_eq!(message.host.as_deref(), Some("mymachine"));
+        assert_eq!(message.app.as_deref(), Some("evntslog"));
+        assert_eq!(message.msgid.as_deref(), Some("ID47"));
+        assert_eq!(
+            message.timestamp.as_deref(),
+            Some("2026-10-11T22:14:15.003Z")
+        );
+        assert_eq!(message.text, "An application event");
+
+        let bsd = parse("<11>Oct 11 22:14:15 devbox nginx[812]: upstream timed out");
+        assert_eq!(bsd.level(), Level::Error);
+        assert_eq!(bsd.host.as_deref(), Some("devbox"));
+        assert_eq!(bsd.app.as_deref(), Some("nginx"));
+        assert_eq!(bsd.procid.as_deref(), Some("812"));
+        assert_eq!(bsd.text, "upstream timed out");
+
+        // `logger -n` style without a host name, and plain text without a PRI.
+        let local = parse("<12>myapp: disk almost full");
+        assert_eq!((local.host, local.app.as_deref()), (None, Some("myapp")));
+        assert_eq!(parse("just text").text, "just text");
+    }
+}
@@ -0,0 +1,152 @@
+use std::fs::{self, File, Metadata};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Follows one log file across appends, truncation (`copytruncate`) and rotation
+/// (the path renamed away and recreated, as logrotate and most loggers do).
+pub struct TailFile {
+    path: PathBuf,
+    file: Option<File>,
+    identity: Option<(u64, u64)>,
+    pos: u64,
+    partial: Vec<u8>,
+    start_at_end: bool,
+}
+
+impl TailFile {
+    /// Without `from_start`, only lines written after the file is first opened are read.
+    /// Files that appear later (after rotation) are always read from the start.
+    pub fn new(path: &Path, from_start: bool) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            file: None,
+            identity: None,
+            pos: 0,
+            partial: Vec::new(),
+            start_at_end: !from_start,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Complete lines written since the last poll. A missing file is not an error;
+    /// it is picked up once it appears.
+    pub fn poll(&mut self) -> io::Result<Vec<String>> {
+        let mut lines = Vec::new();
+        let current = fs::metadata(&self.path).ok();
+        if self.file.is_some() {
+            // Drain the open handle first: after a rename it still points at the old
+            // file, which may have received its last lines since the previous poll.
+            self.read_available(&mut lines)?;
+            let rotated = current
+                .as_ref()
+                .is_some_and(|meta| identity(meta) != self.identity);
+            if rotated {
+                if !self.partial.is_empty() {
+                    lines.push(decode(&std::mem::take(&mut self.partial)));
+                }
+                self.file = None;
+            }
+        }
+        if self.file.is_none() {
+            if let Some(meta) = current {
+                self.file = Some(File::open(&self.path)?);
+                self.identity = identity(&meta);
+                self.pos = if self.start_at_end { meta.len() } else { 0 };
+                self.start_at_end = false;
+                self.read_available(&mut lines)?;
+            }
+        }
+        Ok(lines)
+    }
+
+    fn read_available(&mut self, lines: &mut Vec<String>) -> io::Result<()> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+        if file.metadata()?.len() < self.pos {
+            self.pos = 0;
+            self.partial.clear();
+        }
+        file.seek(SeekFrom::Start(self.pos))?;
+        let read = file.read_to_end(&mut self.partial)?;
+        self.pos += read as u64;
+        while let Some(end) = self.partial.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.partial.drain(..=end).collect();
+            lines.push(decode(&line));
+        }
+        Ok(())
+    }
+}
+
+fn decode(line: &[u8]) -> String {
+    String::from_utf8_lossy(line)
+        .trim_end_matches(['\n', '\r'])
+        .to_string()
+}
+
+#[cfg(unix)]
+fn identity(meta: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+/// Without inode numbers only truncation is detected, not rename-and-recreate.
+#[cfg(not(unix))]
+fn identity(_meta: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn append(path: &Path, text: &str) {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn follows_appends_truncation_and_rotation() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("app.log");
+        let mut tail = TailFile::new(&path, true);
+        assert!(tail.poll().unwrap().is_empty());
+
+        append(&path, "a\nb");
+        assert_eq!(tail.poll().unwrap(), ["a"]);
+        append(&path, "\nc\n");
+        assert_eq!(tail.poll().unwrap(), ["b", "c"]);
+
+        if cfg!(unix) {
+            let rotated = dir.path().join("app.log.1");
+            fs::rename(&path, &rotated).unwrap();
+            append(&rotated, "d\n");
+            append(&path, "eeeeeeee\n");
+            assert_eq!(tail.poll().unwrap(), ["d", "eeeeeeee"]);
+        }
+
+        fs::write(&path, "f\n").unwrap();
+        assert_eq!(tail.poll().unwrap(), ["f"]);
+    }
+
+    #[test]
+    fn starts_at_end_unless_asked() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "old\n");
+        let mut tail = TailFile::new(&path, false);
+        assert!(tail.poll().unwrap().is_empty());
+        append(&path, "new\n");
+        assert_eq!(tail.poll().unwrap(), ["new"]);
+    }
+}
@@ -0,0 +1,225 @@
+use crate::db::{Correlated, LineRecord, RunRecord};
+use crate::parser::Level;
+use serde::Serialize;
+
+pub struct TimelineOptions {
+    /// Keep only lines at most this long before the anchor.
+    pub before_ms: Option<i64>,
+    /// Keep only lines at most this long after the anchor.
+    pub after_ms: Option<i64>,
+    /// Silences at least this long are marked as gaps.
+    pub gap_ms: i64,
+    pub limit: usize,
+}
+
+/// Lines from correlated runs merged into one time-ordered view.
+#[derive(Debug, Serialize)]
+pub struct Timeline {
+    pub anchor: Option<TimelineAnchor>,
+    pub runs: Vec<TimelineRun>,
+    pub lines: Vec<TimelineLine>,
+    /// Lines inside the window that were dropped to stay within `limit`.
+    pub omitted: usize,
+}
+
+/// The line relative timestamps count from: the first fatal line, else the first
+/// error, else the first line.
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineAnchor {
+    pub ts: String,
+    pub run: String,
+    pub seq: i64,
+    pub level: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimelineRun {
+    pub id: String,
+    pub started_at: String,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+}
+
+/// Compact on purpose: `run` is the 8-char id prefix and `rel_ms` is the offset
+/// from the anchor, so "what happened right before" reads straight off the JSON.
+#[derive(Debug, Serialize)]
+pub struct TimelineLine {
+    pub rel_ms: i64,
+    pub ts: String,
+    pub run: String,
+    pub seq: i64,
+    pub stream: String,
+    pub level: String,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Silence before this line, when at least the gap threshold.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gap_ms: Option<i64>,
+}
+
+pub fn build(correlated: Correlated, options: &TimelineOptions) -> Timeline {
+    let Correlated { runs, lines } = correlated;
+    let anchor_index = [Level::Fatal, Level::Error]
+        .into_iter()
+        .find_map(|level| {
+            lines
+                .iter()
+                .position(|(_, line)| line.level == level.as_str())
+        })
+        .unwrap_or(0);
+    let Some((anchor_ms, anchor_line)) = lines.get(anchor_index) else {
+        return Timeline {
+            anchor: None,
+            runs: runs.into_iter().map(timeline_run).collect(),
+            lines: Vec::new(),
+            omitted: 0,
+        };
+    };
+    let anchor_ms = *anchor_ms;
+    let anchor_run_id = anchor_line.run_id.clone();
+    let anchor = TimelineAnchor {
+        ts: anchor_line.ts.clone(),
+        run: short_id(&anchor_line.run_id),
+        seq: anchor_line.seq,
+        level: anchor_line.level.clone(),
+    };
+
+    let in_window = |ts_ms: i64| {
+        options
+            .before_ms
+            .is_none_or(|before| ts_ms >= anchor_ms - before)
+            && options
+                .after_ms
+                .is_none_or(|after| ts_ms <= anchor_ms + after)
+    };
+    let mut window: Vec<(i64, LineRecord)> = lines
+        .into_iter()
+        .filter(|(ts_ms, _)| in_window(*ts_ms))
+        .collect();
+
+    // Over the limit, keep the lines nearest the anchor, three quarters before it.
+    let total = window.len();
+    if total > options.limit {
+        let anchor_pos = window
+            .iter()
+            .position(|(_, line)| line.run_id == anchor_run_id && line.seq == anchor.seq)
+            .unwrap_or(0);
+        let end = (anchor_pos.saturating_sub(options.limit * 3 / 4) + options.limit).min(total);
+        window = window
+            .drain(end.saturating_sub(options.limit)..end)
+            .collect();
+    }
+
+    let mut previous_ms = None;
+    let lines = window
+        .into_iter()
+        .map(|(ts_ms, line)| {
+            let gap_ms = previous_ms
+                .map(|prev| ts_ms - prev)
+                .filter(|gap| *gap >= options.gap_ms);
+            previous_ms = Some(ts_ms);
+            TimelineLine {
+                rel_ms: ts_ms - anchor_ms,
+                location: line.location.as_ref().map(|loc| match loc.col {
+                    Some(col) => format!("{}:{}:{}", loc.file, loc.line, col),
+                    None => format!("{}:{}", loc.file, loc.line),
+                }),
+                ts: line.ts,
+                run: short_id(&line.run_id),
+                seq: line.seq,
+                stream: line.stream,
+                level: line.level,
+                text: line.text,
+                gap_ms,
+            }
+        })
+        .collect::<Vec<_>>();
+    Timeline {
+        anchor: Some(anchor),
+        runs: runs.into_iter().map(timeline_run).collect(),
+        omitted: total - lines.len(),
+        lines,
+    }
+}
+
+fn timeline_run(run: RunRecord) -> TimelineRun {
+    TimelineRun {
+        id: short_id(&run.id),
+        started_at: run.started_at,
+        status: run.status,
+        tag: run.tag,
+        source: run.source,
+        kind: run.kind,
+    }
+}
+
+fn short_id(id: &str) -> String {
+    id.get(..8).unwrap_or(id).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(run_id: &str, seq: i64, ts_ms: i64, level: &str, text: &str) -> (i64, LineRecord) {
+        let line = LineRecord {
+            run_id: run_id.to_string(),
+            seq,
+            ts: crate::db::format_ms(ts_ms),
+            stream: "stdout".to_string(),
+            level: level.to_string(),
+            event: None,
+            text: text.to_string(),
+            location: None,
+        };
+        (ts_ms, line)
+    }
+
+    fn correlated(lines: Vec<(i64, LineRecord)>) -> Correlated {
+        Correlated {
+            runs: Vec::new(),
+            lines,
+        }
+    }
+
+    #[test]
+    fn anchors_on_first_error_and_marks_gaps() {
+        let lines = vec![
+            line("build0000", 1, 1_000, "info", "compiled"),
+            line("console0", 1, 9_000, "warn", "slow request"),
+            line("backend0", 1, 9_500, "error", "500 /login"),
+            line("console0", 2, 9_600, "error", "TypeError: boom"),
+            line("backend0", 2, 12_000, "info", "recovered"),
+        ];
+        let options = TimelineOptions {
+            before_ms: None,
+            after_ms: None,
+            gap_ms: 5_000,
+            limit: 100,
+        };
+        let timeline = build(correlated(lines.clone()), &options);
+        let anchor = timeline.anchor.unwrap();
+        assert_eq!((anchor.run.as_str(), anchor.seq), ("backend0", 1));
+        let rel: Vec<_> = timeline.lines.iter().map(|l| l.rel_ms).collect();
+        assert_eq!(rel, [-8_500, -500, 0, 100, 2_500]);
+        assert_eq!(timeline.lines[1].gap_ms, Some(8_000));
+        assert_eq!(timeline.lines[2].gap_ms, None);
+
+        let window = TimelineOptions {
+            before_ms: Some(10_000),
+            after_ms: Some(0),
+            limit: 2,
+            ..options
+        };
+        let timeline = build(correlated(lines), &window);
+        let texts: Vec<_> = timeline.lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(texts, ["slow request", "500 /login"]);
+        assert_eq!(timeline.omitted, 1);
+    }
+}
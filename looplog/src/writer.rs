@@ -0,0 +1,207 @@
+use crate::db::{NewRun, SqliteStorage, WriteOp, WriteOutput};
+use crate::error::{Error, Result};
+use crate::protocol::{AppendLine, FinishRunRequest};
+use crate::redact::Redactor;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Retention cleanup runs this often on the writer thread instead of on every request.
+pub const CLEAN_INTERVAL: Duration = Duration::from_secs(60);
+/// Upper bounds for one transaction; whatever is queued beyond them goes in the next.
+const MAX_BATCH_OPS: usize = 512;
+const MAX_BATCH_LINES: usize = 10_000;
+
+struct Job {
+    op: WriteOp,
+    reply: mpsc::Sender<Result<WriteOutput>>,
+}
+
+/// Handle to the single SQLite writer thread. Cloned into every HTTP worker.
+///
+/// Writes are queued on a bounded channel and committed in batches, so a burst of
+/// console output costs one transaction per batch instead of one per request. When the
+/// queue is full, calls fail fast with [`Error::QueueFull`] and the server answers 429.
+#[derive(Clone)]
+pub struct Writer {
+    jobs: SyncSender<Job>,
+}
+
+impl Writer {
+    /// `keep_hours` overrides the stored default retention window for periodic cleanup.
+    pub fn spawn(
+        db_path: &Path,
+        queue: usize,
+        keep_hours: Option<u64>,
+        redactor: Option<Redactor>,
+    ) -> Result<Self> {
+        let mut storage = SqliteStorage::open(db_path)?;
+        storage.disable_auto_clean();
+        storage.set_redactor(redactor);
+        let (jobs, rx) = mpsc::sync_channel(queue.max(1));
+        thread::spawn(move || run(storage, rx, keep_hours));
+        Ok(Self { jobs })
+    }
+
+    pub fn start_run(&self, run: NewRun) -> Result<String> {
+        match self.submit(WriteOp::StartRun(run))? {
+            WriteOutput::RunId(run_id) => Ok(run_id),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub fn append_lines(&self, run_id: &str, lines: Vec<AppendLine>) -> Result<usize> {
+        let op = WriteOp::AppendLines {
+            run_id: run_id.to_string(),
+            lines,
+        };
+        match self.submit(op)? {
+            WriteOutput::Lines(count) => Ok(count),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub fn finish_run(&self, run_id: &str, finish: FinishRunRequest) -> Result<()> {
+        let op = WriteOp::FinishRun {
+            run_id: run_id.to_string(),
+            finish,
+        };
+        match self.submit(op)? {
+            WriteOutput::Finished => Ok(()),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    fn submit(&self, op: WriteOp) -> Result<WriteOutput> {
+        let (reply, result) = mpsc::channel();
+        self.jobs.try_send(Job { op, reply }).map_err(|e| match e {
+            TrySendError::Full(_) => Error::QueueFull,
+            TrySendError::Disconnected(_) => writer_gone(),
+        })?;
+        result.recv().map_err(|_| writer_gone())?
+    }
+}
+
+fn run(mut storage: SqliteStorage, rx: Receiver<Job>, keep_hours: Option<u64>) {
+    let mut last_clean = None::<Instant>;
+    loop {
+        if last_clean.is_none_or(|at| at.elapsed() >= CLEAN_INTERVAL) {
+            if let Err(e) = clean(&mut storage, keep_hours) {
+                eprintln!("[looplog serve] cleanup failed: {}", e);
+            }
+            last_clean = Some(Instant::now());
+        }
+
+        let first = match rx.recv_timeout(CLEAN_INTERVAL) {
+            Ok(job) => job,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+        let mut lines = op_lines(&first.op);
+        let mut batch = vec![first];
+        while batch.len() < MAX_BATCH_OPS && lines < MAX_BATCH_LINES {
+            let Ok(job) = rx.try_recv() else {
+                break;
+            };
+            lines += op_lines(&job.op);
+            batch.push(job);
+        }
+
+        let (ops, replies): (Vec<_>, Vec<_>) =
+            batch.into_iter().map(|job| (job.op, job.reply)).unzip();
+        match storage.apply_batch(ops) {
+            Ok(results) => {
+                for (reply, result) in replies.into_iter().zip(results) {
+                    let _ = reply.send(result);
+                }
+            }
+            Err(e) => {
+                let message = e.to_string();
+                for reply in replies {
+                    let _ = reply.send(Err(Error::Message(message.clone())));
+                }
+            }
+        }
+    }
+}
+
+fn clean(storage: &mut SqliteStorage, keep_hours: Option<u64>) -> Result<()> {
+    let keep_hours = match keep_hours {
+        Some(hours) => hours,
+        None => storage.default_keep_hours()?,
+    };
+    storage.clean(keep_hours, false)?;
+    Ok(())
+}
+
+fn op_lines(op: &WriteOp) -> usize {
+    match op {
+        WriteOp::AppendLines { lines, .. } => lines.len(),
+        _ => 1,
+    }
+}
+
+fn writer_gone() -> Error {
+    Error::Message("the writer thread has stopped".to_string())
+}
+
+fn unexpected(output: WriteOutput) -> Error {
+    Error::Message(format!("unexpected writer result {:?}", output))
+}
+
+/// Long-lived runs keyed by source, for intake without explicit run boundaries
+/// (syslog senders, OTLP services). Runs are created on first use and stay `running`
+/// for the life of the server.
+#[derive(Clone)]
+pub struct RunGroups {
+    writer: Writer,
+    runs: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl RunGroups {
+    pub fn new(writer: Writer) -> Self {
+        Self {
+            writer,
+            runs: Arc::default(),
+        }
+    }
+
+    /// Append to the run for `key`, starting it with `new_run` if needed. A run that
+    /// retention deleted while the server was up is started again.
+    pub fn append(
+        &self,
+        key: &str,
+        new_run: impl Fn() -> NewRun,
+        lines: Vec<AppendLine>,
+    ) -> Result<usize> {
+        let run_id = self.run_id(key, &new_run)?;
+        match self.writer.append_lines(&run_id, lines.clone()) {
+            Err(Error::RunNotFound(_)) => {
+                self.forget(key);
+                let run_id = self.run_id(key, &new_run)?;
+                self.writer.append_lines(&run_id, lines)
+            }
+            result => result,
+        }
+    }
+
+    fn run_id(&self, key: &str, new_run: &impl Fn() -> NewRun) -> Result<String> {
+        let mut runs = self.runs.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(run_id) = runs.get(key) {
+            return Ok(run_id.clone());
+        }
+        let run_id = self.writer.start_run(new_run())?;
+        runs.insert(key.to_string(), run_id.clone());
+        Ok(run_id)
+    }
+
+    fn forget(&self, key: &str) {
+        self.runs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(key);
+    }
+}
@@ -27,15 +27,22 @@ use maquette::block_meta::{
     hfrog::{HfrogConfig, HfrogProvider},
     BlockMetaProvider, LocalProvider,
 };
-use maquette::export::{self, ExportFormat, ExportOptions, OutlineConfig};
+use maquette::export::{
+    self, ExportFormat, ExportOptions, OutlineConfig, TextureConfig, TextureMode,
+};
 use maquette::grid::{Grid, Palette};
+use maquette::mesher;
+use maquette::obj;
 use maquette::palette_io;
 use maquette::project;
-use maquette::render::{self, RenderOptions};
+use maquette::render::{self, RenderOptions, RenderOutline};
+use maquette::sprites::{self, PitchPreset, SpriteSheetOptions};
+use maquette::stl;
 use maquette::texgen::{
     self, MockProvider, TextureProvider, TextureRequest,
     rustyme::{RustymeConfig, RustymeProvider},
 };
+use maquette::vox::{self, VoxImportOptions};
 
 #[derive(Parser, Debug)]
 #[command(
@@ -52,8 +59,12 @@ struct Cli {
 
 #[derive(Subcommand, Debug)]
 enum Command {
-    /// Export a `.maq` project as glTF / GLB.
+    /// Export a `.maq` project as glTF / GLB, OBJ + MTL, STL or
+    /// MagicaVoxel `.vox`.
     Export(ExportArgs),
+    /// Convert a MagicaVoxel `.vox` model into a `.maq` project,
+    /// reporting everything that had to change to fit the canvas.
+    Import(ImportArgs),
     /// Print a human- or JSON-readable summary of a `.maq` project.
     Info(InfoArgs),
     /// Load a `.maq` file and verify its structure. Non-zero exit on
@@ -63,6 +74,9 @@ enum Command {
     /// pure-CPU rasterizer. Useful for CI thumbnails, docs, and
     /// headless regression testing.
     Render(RenderArgs),
+    /// Bake a multi-direction sprite sheet (packed PNG + JSON frame
+    /// index) with the same CPU rasterizer as `render`.
+    BakeSprites(BakeSpritesArgs),
     /// Share palettes across projects as `colors.json` documents.
     Palette(PaletteArgs),
     /// Generate textures from prompts. Providers: `mock` (offline
@@ -274,8 +288,9 @@ struct PaletteImportArgs {
 struct ExportArgs {
     /// Input `.maq` project file.
     input: PathBuf,
-    /// Output path. Extension (`.glb` / `.gltf`) picks the format
-    /// unless `--format` is given.
+    /// Output path. Extension (`.glb` / `.gltf` / `.obj` / `.stl` /
+    /// `.vox`) picks the format unless `--format` is given. OBJ, STL
+    /// and `.vox` carry neither the outline nor textures.
     #[arg(short, long)]
     out: PathBuf,
     /// Force a specific output format (overrides the extension).
@@ -293,6 +308,32 @@ struct ExportArgs {
     /// to black.
     #[arg(long, default_value = "#000000")]
     outline_color: String,
+    /// Longitude segments per Sphere cell (rings are half that).
+    /// Lower is blockier and cheaper; values below 3 are clamped.
+    #[arg(long, default_value_t = mesher::DEFAULT_SPHERE_SEGMENTS)]
+    sphere_segments: u32,
+    /// Palette-slot textures: `flat` ignores them, `embed` packs the
+    /// cached PNGs into the model, `external` writes them next to it.
+    /// Slots without a cached texture always export flat.
+    #[arg(long, value_enum, default_value_t = TexturesArg::Flat)]
+    textures: TexturesArg,
+    /// Texture cache directory holding `<cache_key>.png`. Defaults to
+    /// the one `texture gen` and the GUI write to.
+    #[arg(long)]
+    texture_dir: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+struct ImportArgs {
+    /// Input MagicaVoxel `.vox` file. Only the first model is read.
+    input: PathBuf,
+    /// Output `.maq` path.
+    #[arg(short, long)]
+    out: PathBuf,
+    /// Palette size limit; rarer colors merge into their nearest
+    /// neighbour. Clamped to 1..=255.
+    #[arg(long, default_value_t = vox::MAX_VOX_COLORS)]
+    max_colors: usize,
 }
 
 #[derive(Parser, Debug)]
@@ -329,19 +370,108 @@ struct RenderArgs {
     /// Ambient luminance floor, 0..=1. Higher values flatten shading.
     #[arg(long, default_value_t = 0.35)]
     ambient: f32,
+    /// Longitude segments per Sphere cell. Match the value given to
+    /// `export` to preview the same tessellation.
+    #[arg(long, default_value_t = mesher::DEFAULT_SPHERE_SEGMENTS)]
+    sphere_segments: u32,
 }
 
-#[derive(Copy, Clone, Debug, ValueEnum)]
+#[derive(Parser, Debug)]
+struct BakeSpritesArgs {
+    /// Input `.maq` project file.
+    input: PathBuf,
+    /// Output sheet PNG path.
+    #[arg(short, long)]
+    out: PathBuf,
+    /// Frame index JSON path. Defaults to the sheet path with a
+    /// `.json` extension.
+    #[arg(long)]
+    index: Option<PathBuf>,
+    /// Number of evenly spaced yaw angles (8 / 16 / 24 are typical).
+    #[arg(long, default_value_t = 8, value_parser = clap::value_parser!(u32).range(1..=360))]
+    directions: u32,
+    /// Yaw of the first direction, in degrees. 0 looks at the model's
+    /// front (+Z) face; -45 matches `render`.
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    start_yaw: f32,
+    /// Camera pitch presets, comma-separated. Each adds one full set
+    /// of directions to the sheet.
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = [PitchArg::Iso])]
+    pitch: Vec<PitchArg>,
+    /// Frame width and height in pixels.
+    #[arg(long, default_value_t = sprites::DEFAULT_FRAME_SIZE)]
+    frame_size: u32,
+    /// Frames per sheet row. Defaults to one row per pitch preset.
+    #[arg(long)]
+    columns: Option<u32>,
+    /// Draw a silhouette outline around each frame's model.
+    #[arg(long, default_value_t = false)]
+    outline: bool,
+    /// Outline thickness in pixels.
+    #[arg(long, default_value_t = 1)]
+    outline_width: u32,
+    /// Outline color in `#RRGGBB` form.
+    #[arg(long, default_value = "#000000")]
+    outline_color: String,
+    /// Quantize shading into this many flat bands (toon look).
+    /// Omit for smooth shading.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(2..=16))]
+    toon_bands: Option<u32>,
+    /// Opaque background in `#RRGGBB` form. Omit for a transparent
+    /// sheet.
+    #[arg(long)]
+    background: Option<String>,
+    /// Ambient luminance floor, 0..=1.
+    #[arg(long, default_value_t = 0.35)]
+    ambient: f32,
+    /// Longitude segments per Sphere cell.
+    #[arg(long, default_value_t = mesher::DEFAULT_SPHERE_SEGMENTS)]
+    sphere_segments: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum PitchArg {
+    Side,
+    Dimetric,
+    Iso,
+    High,
+    Top,
+}
+
+impl From<PitchArg> for PitchPreset {
+    fn from(p: PitchArg) -> Self {
+        match p {
+            PitchArg::Side => PitchPreset::Side,
+            PitchArg::Dimetric => PitchPreset::Dimetric,
+            PitchArg::Iso => PitchPreset::Iso,
+            PitchArg::High => PitchPreset::High,
+            PitchArg::Top => PitchPreset::Top,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
 enum FormatArg {
     Glb,
     Gltf,
+    Obj,
+    Stl,
+    Vox,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum TexturesArg {
+    Flat,
+    Embed,
+    External,
 }
 
-impl From<FormatArg> for ExportFormat {
-    fn from(f: FormatArg) -> Self {
-        match f {
-            FormatArg::Glb => ExportFormat::Glb,
-            FormatArg::Gltf => ExportFormat::Gltf,
+impl From<TexturesArg> for TextureMode {
+    fn from(t: TexturesArg) -> Self {
+        match t {
+            TexturesArg::Flat => TextureMode::Flat,
+            TexturesArg::Embed => TextureMode::Embed,
+            TexturesArg::External => TextureMode::External,
         }
     }
 }
@@ -360,9 +490,11 @@ fn main() -> ExitCode {
 fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
     match cli.command {
         Command::Export(args) => cmd_export(args),
+        Command::Import(args) => cmd_import(args),
         Command::Info(args) => cmd_info(args),
         Command::Validate(args) => cmd_validate(args),
         Command::Render(args) => cmd_render(args),
+        Command::BakeSprites(args) => cmd_bake_sprites(args),
         Command::Palette(args) => match args.action {
             PaletteAction::Export(a) => cmd_palette_export(a),
             PaletteAction::Import(a) => cmd_palette_import(a),
@@ -455,7 +587,11 @@ fn cmd_texture_purge(args: TexturePurgeArgs) -> Result<(), Box<dyn std::error::E
 
 fn cmd_export(args: ExportArgs) -> Result<(), Box<dyn std::error::Error>> {
     let (grid, palette) = project::read_project(&args.input)?;
-    let format = resolve_format(&args)?;
+    let format = match resolve_format(&args)? {
+        FormatArg::Glb => ExportFormat::Glb,
+        FormatArg::Gltf => ExportFormat::Gltf,
+        other => return cmd_export_interchange(other, &args, &grid, &palette),
+    };
     let outline = OutlineConfig {
         enabled: !args.no_outline,
         width_pct: args.outline_width,
@@ -465,14 +601,48 @@ fn cmd_export(args: ExportArgs) -> Result<(), Box<dyn std::error::Error>> {
         path: args.out,
         format,
         outline,
+        sphere_segments: args.sphere_segments,
+        textures: TextureConfig {
+            mode: args.textures.into(),
+            cache_dir: args.texture_dir,
+        },
     };
     export::write(&grid, &palette, &opts)?;
     Ok(())
 }
 
-fn resolve_format(args: &ExportArgs) -> Result<ExportFormat, Box<dyn std::error::Error>> {
+/// OBJ / STL / `.vox`: same cells, none of the glTF extras. Anything
+/// the user asked for that the format drops is reported as a `note:`
+/// on stderr rather than failing the export.
+fn cmd_export_interchange(
+    format: FormatArg,
+    args: &ExportArgs,
+    grid: &Grid,
+    palette: &Palette,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if args.textures != TexturesArg::Flat {
+        eprintln!("note: {format:?} export ignores --textures; colors are written flat");
+    }
+    match format {
+        FormatArg::Obj => obj::write_obj(grid, palette, &args.out, args.sphere_segments)?,
+        FormatArg::Stl => stl::write_stl(grid, &args.out, args.sphere_segments)?,
+        FormatArg::Vox => {
+            let report = vox::write_vox(grid, palette, &args.out)?;
+            if report.sphere_cells > 0 {
+                eprintln!(
+                    "note: {} sphere cell(s) written as cubes — .vox has no shapes",
+                    report.sphere_cells
+                );
+            }
+        }
+        FormatArg::Glb | FormatArg::Gltf => unreachable!("handled by cmd_export"),
+    }
+    Ok(())
+}
+
+fn resolve_format(args: &ExportArgs) -> Result<FormatArg, Box<dyn std::error::Error>> {
     if let Some(f) = args.format {
-        return Ok(f.into());
+        return Ok(f);
     }
     // Infer from the output extension. If it's missing or unknown,
     // fall back to GLB — GLB is the single-file format most engines
@@ -485,9 +655,37 @@ fn resolve_format(args: &ExportArgs) -> Result<ExportFormat, Box<dyn std::error:
         .map(str::to_ascii_lowercase)
         .as_deref()
     {
-        Some("gltf") => Ok(ExportFormat::Gltf),
-        _ => Ok(ExportFormat::Glb),
+        Some("gltf") => Ok(FormatArg::Gltf),
+        Some("obj") => Ok(FormatArg::Obj),
+        Some("stl") => Ok(FormatArg::Stl),
+        Some("vox") => Ok(FormatArg::Vox),
+        _ => Ok(FormatArg::Glb),
+    }
+}
+
+fn cmd_import(args: ImportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let opts = VoxImportOptions {
+        max_colors: args.max_colors,
+    };
+    let (grid, palette, report) = vox::read_vox(&args.input, &opts)?;
+    project::write_project(&args.out, &grid, &palette)?;
+
+    let [sx, sy, sz] = report.source_size;
+    println!(
+        "imported {} voxels ({sx}×{sy}×{sz}) → {}×{} canvas, {} painted cells, {} colors",
+        report.voxels_read,
+        grid.w,
+        grid.h,
+        grid.painted_count(),
+        palette.live_count(),
+    );
+    if report.default_palette {
+        println!("no palette in file — used MagicaVoxel's default palette");
     }
+    for note in report.notes() {
+        eprintln!("note: {note}");
+    }
+    Ok(())
 }
 
 fn cmd_info(args: InfoArgs) -> Result<(), Box<dyn std::error::Error>> {
@@ -678,22 +876,71 @@ fn truncate(s: &str, max_chars: usize) -> String {
 
 fn cmd_render(args: RenderArgs) -> Result<(), Box<dyn std::error::Error>> {
     let (grid, palette) = project::read_project(&args.input)?;
-    let bg = parse_hex_color(&args.background)?.to_srgba();
     let opts = RenderOptions {
         width: args.width,
         height: args.height,
-        background: [
-            (bg.red * 255.0 + 0.5) as u8,
-            (bg.green * 255.0 + 0.5) as u8,
-            (bg.blue * 255.0 + 0.5) as u8,
-        ],
+        background: parse_hex_rgb(&args.background)?,
         ambient: args.ambient.clamp(0.0, 1.0),
+        sphere_segments: args.sphere_segments,
         ..RenderOptions::default()
     };
     render::write_png(&grid, &palette, &opts, &args.out)?;
     Ok(())
 }
 
+fn cmd_bake_sprites(args: BakeSpritesArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let (grid, palette) = project::read_project(&args.input)?;
+    let defaults = SpriteSheetOptions::default();
+    let mut frame = RenderOptions {
+        width: args.frame_size,
+        height: args.frame_size,
+        ambient: args.ambient.clamp(0.0, 1.0),
+        sphere_segments: args.sphere_segments,
+        toon_bands: args.toon_bands,
+        ..defaults.frame
+    };
+    if let Some(bg) = &args.background {
+        frame.background = parse_hex_rgb(bg)?;
+        frame.transparent = false;
+    }
+    if args.outline {
+        frame.outline = Some(RenderOutline {
+            width_px: args.outline_width,
+            color: parse_hex_rgb(&args.outline_color)?,
+        });
+    }
+    let opts = SpriteSheetOptions {
+        frame,
+        directions: args.directions,
+        start_yaw_deg: args.start_yaw,
+        pitches: args.pitch.iter().map(|&p| p.into()).collect(),
+        columns: args.columns,
+    };
+
+    let sheet = sprites::bake(&grid, &palette, &opts)?;
+    let index = args
+        .index
+        .unwrap_or_else(|| args.out.with_extension("json"));
+    sprites::write_sheet(&sheet, &args.out, &index)?;
+    println!(
+        "baked {} frames ({} directions × {} pitch) → {} ({}×{}), index {}",
+        sheet.frames.len(),
+        opts.directions,
+        opts.pitches.len(),
+        args.out.display(),
+        sheet.width,
+        sheet.height,
+        index.display(),
+    );
+    Ok(())
+}
+
+/// [`parse_hex_color`] as the rasterizer's sRGB byte triple.
+fn parse_hex_rgb(s: &str) -> Result<[u8; 3], Box<dyn std::error::Error>> {
+    let c = parse_hex_color(s)?.to_srgba();
+    Ok([c.red, c.green, c.blue].map(|v| (v * 255.0 + 0.5) as u8))
+}
+
 struct ProjectSummary {
     w: usize,
     h: usize,
@@ -832,14 +1079,39 @@ mod tests {
             no_outline: false,
             outline_width: 2.5,
             outline_color: "#000000".into(),
+            sphere_segments: mesher::DEFAULT_SPHERE_SEGMENTS,
+            textures: TexturesArg::Flat,
+            texture_dir: None,
         };
-        assert!(matches!(resolve_format(&args), Ok(ExportFormat::Gltf)));
+        assert!(matches!(resolve_format(&args), Ok(FormatArg::Gltf)));
 
         let args2 = ExportArgs {
             out: PathBuf::from("out.glb"),
             ..args
         };
-        assert!(matches!(resolve_format(&args2), Ok(ExportFormat::Glb)));
+        assert!(matches!(resolve_format(&args2), Ok(FormatArg::Glb)));
+    }
+
+    #[test]
+    fn format_infers_interchange_formats() {
+        for (out, want) in [
+            ("out.obj", FormatArg::Obj),
+            ("out.STL", FormatArg::Stl),
+            ("out.vox", FormatArg::Vox),
+        ] {
+            let args = ExportArgs {
+                input: PathBuf::from("in.maq"),
+                out: PathBuf::from(out),
+                format: None,
+                no_outline: false,
+                outline_width: 2.5,
+                outline_color: "#000000".into(),
+                sphere_segments: mesher::DEFAULT_SPHERE_SEGMENTS,
+                textures: TexturesArg::Flat,
+                texture_dir: None,
+            };
+            assert_eq!(resolve_format(&args).unwrap(), want);
+        }
     }
 
     #[test]
@@ -851,8 +1123,11 @@ mod tests {
             no_outline: false,
             outline_width: 2.5,
             outline_color: "#000000".into(),
+            sphere_segments: mesher::DEFAULT_SPHERE_SEGMENTS,
+            textures: TexturesArg::Flat,
+            texture_dir: None,
         };
-        assert!(matches!(resolve_format(&args), Ok(ExportFormat::Glb)));
+        assert!(matches!(resolve_format(&args), Ok(FormatArg::Glb)));
     }
 
     #[test]
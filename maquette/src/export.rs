@@ -8,7 +8,8 @@
 //! * **Body** — one glTF mesh with one primitive per palette color that
 //!   the user actually painted with. Each primitive has `POSITION` +
 //!   `NORMAL` attributes and references a dedicated unlit material with
-//!   that color as `baseColorFactor`.
+//!   that color as `baseColorFactor`. Cube and sphere cells of the same
+//!   color share a primitive (see `mesher::build_shape_buckets`).
 //! * **Outline** (optional, enabled by default) — one extra glTF mesh,
 //!   inverted-hull silhouette: vertex-extruded along normals by a
 //!   user-chosen percentage of the model's bounding diagonal, with
@@ -16,6 +17,14 @@
 //!   renders the back half as a black cage. No shader, no post-effect,
 //!   "just works" in Godot / Unity / Blender.
 //!
+//! * **Textures** (optional, off by default) — palette slots with a
+//!   generated texture (`PaletteSlotMeta::texture`, PNG in the texgen
+//!   cache) get a `TEXCOORD_0` attribute and a core-spec
+//!   `baseColorTexture` with a repeating sampler. The mesher's UVs tile
+//!   once per cell, so merged rectangles don't stretch. PNGs are either
+//!   embedded in the binary buffer or written next to the model. Slots
+//!   without a usable texture keep their flat color.
+//!
 //! The toon shader used in the preview is **never** referenced from the
 //! export. Preview ≠ export is an invariant; see `about` dialog.
 //!
@@ -36,7 +45,8 @@
 //!   inspect by hand, friendlier to version control.
 //!
 //! Both modes go through the same builder; only the final serializer
-//! differs.
+//! differs. The interchange writers (`crate::obj`, `crate::stl`) share
+//! the geometry via `centred_buckets` but not the outline or textures.
 
 use std::collections::BTreeMap;
 use std::fs;
@@ -50,7 +60,8 @@ use bevy::window::RequestRedraw;
 use serde::Serialize;
 
 use crate::grid::{Grid, Palette, CELL_SIZE};
-use crate::mesher::{build_color_buckets, MeshBuilder};
+use crate::mesher::{build_shape_buckets, MeshBuilder};
+use crate::texgen;
 
 /// Sent by the UI when the user confirms the Export dialog.
 #[derive(Message, Clone)]
@@ -61,6 +72,11 @@ pub struct ExportOptions {
     pub path: PathBuf,
     pub format: ExportFormat,
     pub outline: OutlineConfig,
+    /// Longitude segments per sphere cell. The GUI always passes
+    /// [`crate::mesher::DEFAULT_SPHERE_SEGMENTS`]; `maquette-cli export
+    /// --sphere-segments` overrides it.
+    pub sphere_segments: u32,
+    pub textures: TextureConfig,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -98,20 +114,34 @@ impl Default for OutlineConfig {
     }
 }
 
+/// What happens to palette-slot textures on export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextureMode {
+    /// Flat `baseColorFactor` per slot; textures are ignored. The
+    /// default — matches exports from before textures existed.
+    #[default]
+    Flat,
+    /// PNG bytes go into the model's binary buffer (the GLB BIN
+    /// chunk, or the sibling `.bin`). One self-contained artefact.
+    Embed,
+    /// PNGs are copied next to the model as `<stem>_tex<N>.png` and
+    /// referenced by relative URI. Easier to retouch in an editor.
+    External,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TextureConfig {
+    pub mode: TextureMode,
+    /// Directory holding `<cache_key>.png` files. `None` uses
+    /// [`texgen::default_cache_dir`], where the GUI stores them.
+    pub cache_dir: Option<PathBuf>,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ExportError {
-    /// Every painted cell was filtered out before mesh generation —
-    /// either the canvas is truly blank, or the user only painted
-    /// shapes the current exporter doesn't yet handle (Sphere cells
-    /// are skipped in v0.9; see `docs/handoff/USER-TODO.md` #1c).
-    /// Distinguishing the two cases in the message is important
-    /// because the second one looks like a bug to a user who's
-    /// clearly painted things.
-    #[error(
-        "no exportable geometry — paint at least one Cube cell before exporting \
-         (v0.9: Sphere cells are a placeholder and are not yet written to the \
-         exported model)"
-    )]
+    /// The canvas has no painted cells. Every shape is exported, so
+    /// this only fires on a truly blank project.
+    #[error("no exportable geometry — paint at least one cell before exporting")]
     Empty,
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
@@ -330,20 +360,7 @@ fn pump_export_redraws(
 // =====================================================================
 
 pub fn write(grid: &Grid, palette: &Palette, opts: &ExportOptions) -> Result<(), ExportError> {
-    let mut buckets = build_color_buckets(grid);
-    if buckets.is_empty() {
-        return Err(ExportError::Empty);
-    }
-
-    // Match the preview's centring: X/Z centred on origin, Y anchored
-    // at ground (the column grows upward from y=0). This is what every
-    // engine will assume when the user drops the asset at a node
-    // origin.
-    let ox = -(grid.w as f32) * CELL_SIZE * 0.5;
-    let oz = -(grid.h as f32) * CELL_SIZE * 0.5;
-    for (_, b) in &mut buckets {
-        b.translate(ox, 0.0, oz);
-    }
+    let buckets = centred_buckets(grid, opts.sphere_segments)?;
 
     let bounds = compute_bounds(&buckets);
     let bounds_diag = bounds.diagonal();
@@ -355,21 +372,140 @@ pub fn write(grid: &Grid, palette: &Palette, opts: &ExportOptions) -> Result<(),
         None
     };
 
+    let textures = collect_textures(&buckets, palette, &opts.textures, &opts.path);
+
     let (doc, blob, buffer_uri) = build_gltf(
         &buckets,
         outline.as_ref(),
         palette,
         &opts.outline,
+        &textures,
         opts.format,
         &opts.path,
     );
 
+    for image in &textures.images {
+        if let Some(uri) = &image.uri {
+            fs::write(sibling_path(&opts.path, uri), &image.bytes)?;
+        }
+    }
     match opts.format {
         ExportFormat::Glb => write_glb(&opts.path, &doc, &blob),
         ExportFormat::Gltf => write_gltf_pair(&opts.path, &doc, &blob, buffer_uri.as_deref()),
     }
 }
 
+/// Every painted cell meshed (see `mesher::build_shape_buckets`) and
+/// placed the way all mesh writers ship it — glTF here, plus
+/// `crate::obj` and `crate::stl`.
+///
+/// Matches the preview's centring: X/Z centred on origin, Y anchored
+/// at ground (the column grows upward from y=0). This is what every
+/// engine will assume when the user drops the asset at a node origin.
+pub(crate) fn centred_buckets(
+    grid: &Grid,
+    sphere_segments: u32,
+) -> Result<Vec<(u8, MeshBuilder)>, ExportError> {
+    let mut buckets = build_shape_buckets(grid, sphere_segments);
+    if buckets.is_empty() {
+        return Err(ExportError::Empty);
+    }
+    let ox = -(grid.w as f32) * CELL_SIZE * 0.5;
+    let oz = -(grid.h as f32) * CELL_SIZE * 0.5;
+    for (_, b) in &mut buckets {
+        b.translate(ox, 0.0, oz);
+    }
+    Ok(buckets)
+}
+
+// =====================================================================
+// Texture lookup
+// =====================================================================
+
+/// Palette-slot textures resolved for one export. `images` is
+/// deduplicated by cache key — slots sharing a generated texture share
+/// one glTF image.
+#[derive(Default)]
+struct TextureSet {
+    images: Vec<TextureImage>,
+    /// Palette index → index into `images`. Slots not listed export
+    /// flat.
+    slots: BTreeMap<u8, usize>,
+}
+
+struct TextureImage {
+    bytes: Vec<u8>,
+    /// Relative file name for [`TextureMode::External`]; `None` when
+    /// the bytes are embedded in the binary buffer.
+    uri: Option<String>,
+}
+
+/// Load the cached PNG of every painted slot that has one. Missing or
+/// unreadable files downgrade that slot to its flat color with a
+/// warning rather than failing the export — the cache is a cache, and
+/// a model with one flat slot beats no model.
+fn collect_textures(
+    buckets: &[(u8, MeshBuilder)],
+    palette: &Palette,
+    cfg: &TextureConfig,
+    out_path: &Path,
+) -> TextureSet {
+    let mut set = TextureSet::default();
+    if cfg.mode == TextureMode::Flat {
+        return set;
+    }
+    let Some(dir) = cfg.cache_dir.clone().or_else(texgen::default_cache_dir) else {
+        log::warn!("export: no texture cache directory (HOME unset?) — exporting flat colors");
+        return set;
+    };
+    let stem = out_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("model");
+    let mut by_key: BTreeMap<&str, usize> = BTreeMap::new();
+    for (ci, _) in buckets {
+        let Some(handle) = palette.meta(*ci).and_then(|m| m.texture.as_ref()) else {
+            continue;
+        };
+        if let Some(&image) = by_key.get(handle.cache_key.as_str()) {
+            set.slots.insert(*ci, image);
+            continue;
+        }
+        let path = dir.join(format!("{}.png", handle.cache_key));
+        let bytes = match fs::read(&path) {
+            Ok(bytes) if bytes.starts_with(b"\x89PNG\r\n\x1a\n") => bytes,
+            Ok(_) => {
+                log::warn!(
+                    "export: {} is not a PNG — slot {ci} exports flat",
+                    path.display()
+                );
+                continue;
+            }
+            Err(e) => {
+                log::warn!(
+                    "export: can't read {}: {e} — slot {ci} exports flat",
+                    path.display()
+                );
+                continue;
+            }
+        };
+        let uri = (cfg.mode == TextureMode::External)
+            .then(|| format!("{stem}_tex{}.png", set.images.len()));
+        by_key.insert(handle.cache_key.as_str(), set.images.len());
+        set.slots.insert(*ci, set.images.len());
+        set.images.push(TextureImage { bytes, uri });
+    }
+    set
+}
+
+/// `name` resolved next to the model file.
+fn sibling_path(model: &Path, name: &str) -> PathBuf {
+    model
+        .parent()
+        .map(|p| p.join(name))
+        .unwrap_or_else(|| PathBuf::from(name))
+}
+
 // =====================================================================
 // Geometry helpers
 // =====================================================================
@@ -469,6 +605,12 @@ struct GltfDoc {
     buffer_views: Vec<GltfBufferView>,
     buffers: Vec<GltfBuffer>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
+    images: Vec<GltfImage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    samplers: Vec<GltfSampler>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    textures: Vec<GltfTexture>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     extensions_used: Vec<String>,
 }
 
@@ -513,6 +655,8 @@ struct GltfPrimitiveAttrs {
     position: u32,
     #[serde(rename = "NORMAL")]
     normal: u32,
+    #[serde(rename = "TEXCOORD_0", skip_serializing_if = "Option::is_none")]
+    texcoord_0: Option<u32>,
 }
 
 #[derive(Serialize)]
@@ -530,6 +674,8 @@ struct GltfMaterial {
 #[serde(rename_all = "camelCase")]
 struct GltfPbr {
     base_color_factor: [f32; 4],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    base_color_texture: Option<GltfTextureInfo>,
     metallic_factor: f32,
     roughness_factor: f32,
 }
@@ -566,6 +712,37 @@ struct GltfBuffer {
     byte_length: u32,
 }
 
+#[derive(Serialize)]
+struct GltfTextureInfo {
+    index: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GltfImage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    buffer_view: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mime_type: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GltfSampler {
+    mag_filter: u32,
+    min_filter: u32,
+    wrap_s: u32,
+    wrap_t: u32,
+}
+
+#[derive(Serialize)]
+struct GltfTexture {
+    sampler: u32,
+    source: u32,
+}
+
 // glTF component types
 const COMP_UNSIGNED_INT: u32 = 5125;
 const COMP_FLOAT: u32 = 5126;
@@ -574,6 +751,13 @@ const COMP_FLOAT: u32 = 5126;
 const TARGET_ARRAY_BUFFER: u32 = 34962;
 const TARGET_ELEMENT_ARRAY_BUFFER: u32 = 34963;
 
+// glTF sampler enums. Nearest magnification keeps generated pixel-art
+// textures crisp up close; repeat wrapping is what makes per-cell UV
+// tiling work.
+const FILTER_NEAREST: u32 = 9728;
+const FILTER_LINEAR_MIPMAP_LINEAR: u32 = 9987;
+const WRAP_REPEAT: u32 = 10497;
+
 /// Build the glTF JSON document AND the packed binary blob that its
 /// buffer views index into.
 ///
@@ -586,6 +770,7 @@ fn build_gltf(
     outline: Option<&MeshBuilder>,
     palette: &Palette,
     outline_cfg: &OutlineConfig,
+    textures: &TextureSet,
     format: ExportFormat,
     out_path: &Path,
 ) -> (GltfDoc, Vec<u8>, Option<String>) {
@@ -594,6 +779,39 @@ fn build_gltf(
     let mut buffer_views = Vec::new();
     let mut materials = Vec::new();
 
+    // --- Texture images: one sampler shared by every texture ---
+    let mut images = Vec::with_capacity(textures.images.len());
+    for image in &textures.images {
+        images.push(match &image.uri {
+            Some(uri) => GltfImage {
+                uri: Some(uri.clone()),
+                buffer_view: None,
+                mime_type: None,
+            },
+            None => GltfImage {
+                uri: None,
+                buffer_view: Some(push_image(&mut blob, &mut buffer_views, &image.bytes)),
+                mime_type: Some("image/png"),
+            },
+        });
+    }
+    let samplers = if images.is_empty() {
+        Vec::new()
+    } else {
+        vec![GltfSampler {
+            mag_filter: FILTER_NEAREST,
+            min_filter: FILTER_LINEAR_MIPMAP_LINEAR,
+            wrap_s: WRAP_REPEAT,
+            wrap_t: WRAP_REPEAT,
+        }]
+    };
+    let gltf_textures: Vec<GltfTexture> = (0..images.len())
+        .map(|i| GltfTexture {
+            sampler: 0,
+            source: i as u32,
+        })
+        .collect();
+
     // --- Body primitives: one per color bucket ---
     let mut body_primitives = Vec::with_capacity(buckets.len());
     for (ci, b) in buckets {
@@ -605,15 +823,21 @@ fn build_gltf(
         // first), but we still degrade gracefully to white if it
         // somehow does — better than panicking during export.
         let color = palette.get(*ci).unwrap_or(Color::WHITE);
-        materials.push(unlit_material(
-            Some(format!("Color{ci}")),
-            srgba_array(color),
-            false,
-        ));
+        let texture = textures.slots.get(ci).map(|&i| i as u32);
+        let uv_a = texture.map(|_| push_uvs(&mut blob, &mut accessors, &mut buffer_views, &b.uvs));
+        let mut material = unlit_material(Some(format!("Color{ci}")), srgba_array(color), false);
+        if let Some(index) = texture {
+            // White factor so the texture shows its own colors; the
+            // slot color is only the fallback.
+            material.pbr_metallic_roughness.base_color_factor = [1.0; 4];
+            material.pbr_metallic_roughness.base_color_texture = Some(GltfTextureInfo { index });
+        }
+        materials.push(material);
         body_primitives.push(GltfPrimitive {
             attributes: GltfPrimitiveAttrs {
                 position: pos_a,
                 normal: nor_a,
+                texcoord_0: uv_a,
             },
             indices: idx_a,
             material: (materials.len() - 1) as u32,
@@ -653,6 +877,7 @@ fn build_gltf(
                 attributes: GltfPrimitiveAttrs {
                     position: pos_a,
                     normal: nor_a,
+                    texcoord_0: None,
                 },
                 indices: idx_a,
                 material: (materials.len() - 1) as u32,
@@ -695,6 +920,9 @@ fn build_gltf(
             uri: buffer_uri,
             byte_length: blob.len() as u32,
         }],
+        images,
+        samplers,
+        textures: gltf_textures,
         extensions_used: vec!["KHR_materials_unlit".into()],
     };
 
@@ -715,6 +943,7 @@ fn unlit_material(
         name,
         pbr_metallic_roughness: GltfPbr {
             base_color_factor: base_color,
+            base_color_texture: None,
             metallic_factor: 0.0,
             // Roughness=1 so engines that ignore the unlit extension
             // fall back to matte, not mirror.
@@ -809,6 +1038,53 @@ fn push_normals(
     (accessors.len() - 1) as u32
 }
 
+fn push_uvs(
+    blob: &mut Vec<u8>,
+    accessors: &mut Vec<GltfAccessor>,
+    buffer_views: &mut Vec<GltfBufferView>,
+    uvs: &[[f32; 2]],
+) -> u32 {
+    align4(blob);
+    let byte_offset = blob.len() as u32;
+    for uv in uvs {
+        for v in uv {
+            blob.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+    let byte_length = blob.len() as u32 - byte_offset;
+
+    buffer_views.push(GltfBufferView {
+        buffer: 0,
+        byte_offset,
+        byte_length,
+        target: Some(TARGET_ARRAY_BUFFER),
+    });
+    accessors.push(GltfAccessor {
+        buffer_view: (buffer_views.len() - 1) as u32,
+        component_type: COMP_FLOAT,
+        count: uvs.len() as u32,
+        ty: "VEC2",
+        min: None,
+        max: None,
+    });
+    (accessors.len() - 1) as u32
+}
+
+/// Embedded image bytes get a buffer view with no `target` — the spec
+/// reserves targets for vertex / index data.
+fn push_image(blob: &mut Vec<u8>, buffer_views: &mut Vec<GltfBufferView>, bytes: &[u8]) -> u32 {
+    align4(blob);
+    let byte_offset = blob.len() as u32;
+    blob.extend_from_slice(bytes);
+    buffer_views.push(GltfBufferView {
+        buffer: 0,
+        byte_offset,
+        byte_length: bytes.len() as u32,
+        target: None,
+    });
+    (buffer_views.len() - 1) as u32
+}
+
 fn push_indices(
     blob: &mut Vec<u8>,
     accessors: &mut Vec<GltfAccessor>,
@@ -858,10 +1134,7 @@ fn write_gltf_pair(
     let json = serde_json::to_string_pretty(doc)?;
     fs::write(path, json)?;
     let bin_path = match bin_uri {
-        Some(name) => path
-            .parent()
-            .map(|p| p.join(name))
-            .unwrap_or_else(|| PathBuf::from(name)),
+        Some(name) => sibling_path(path, name),
         None => path.with_extension("bin"),
     };
     fs::write(bin_path, blob)?;
@@ -923,6 +1196,7 @@ fn pad_to_4(data: &[u8], pad: u8) -> Vec<u8> {
 mod tests {
     use super::*;
     use crate::grid::Grid;
+    use crate::mesher::DEFAULT_SPHERE_SEGMENTS;
 
     fn tiny_project() -> (Grid, Palette) {
         let mut g = Grid::with_size(4, 4);
@@ -939,6 +1213,8 @@ mod tests {
             path: tmp.clone(),
             format: ExportFormat::Glb,
             outline: OutlineConfig::default(),
+            sphere_segments: DEFAULT_SPHERE_SEGMENTS,
+            textures: TextureConfig::default(),
         };
         write(&g, &p, &opts).unwrap();
         let bytes = fs::read(&tmp).unwrap();
@@ -963,6 +1239,8 @@ mod tests {
             path: path.clone(),
             format: ExportFormat::Gltf,
             outline: OutlineConfig::default(),
+            sphere_segments: DEFAULT_SPHERE_SEGMENTS,
+            textures: TextureConfig::default(),
         };
         write(&g, &p, &opts).unwrap();
         assert!(path.exists());
@@ -981,6 +1259,8 @@ mod tests {
             path: std::env::temp_dir().join("never.glb"),
             format: ExportFormat::Glb,
             outline: OutlineConfig::default(),
+            sphere_segments: DEFAULT_SPHERE_SEGMENTS,
+            textures: TextureConfig::default(),
         };
         let err = write(&g, &p, &opts).unwrap_err();
         assert!(matches!(err, ExportError::Empty));
@@ -997,6 +1277,8 @@ mod tests {
                 width_pct: 0.0,
                 color: Color::BLACK,
             },
+            sphere_segments: DEFAULT_SPHERE_SEGMENTS,
+            textures: TextureConfig::default(),
         };
         write(&g, &p, &opts).unwrap();
         // Shallow check: inspect JSON chunk to confirm a single mesh.
@@ -1020,6 +1302,8 @@ mod tests {
                 path: tmp.clone(),
                 format: ExportFormat::Glb,
                 outline: OutlineConfig::default(),
+                sphere_segments: DEFAULT_SPHERE_SEGMENTS,
+                textures: TextureConfig::default(),
             },
         )
         .unwrap();
@@ -1056,7 +1340,7 @@ mod tests {
     #[test]
     fn outline_hull_has_same_triangle_count_reversed() {
         let (g, _p) = tiny_project();
-        let mut buckets = build_color_buckets(&g);
+        let mut buckets = build_shape_buckets(&g, DEFAULT_SPHERE_SEGMENTS);
         for (_, b) in &mut buckets {
             b.translate(0.0, 0.0, 0.0);
         }
@@ -1068,4 +1352,148 @@ mod tests {
             buckets.iter().map(|(_, b)| b.positions.len()).sum::<usize>()
         );
     }
+
+    #[test]
+    fn sphere_only_project_exports_spheres_and_their_outline() {
+        // Regression: sphere cells used to be skipped, so a
+        // sphere-only canvas failed with `Empty`.
+        let mut g = Grid::with_size(4, 4);
+        g.paint(2, 1, 3, 2);
+        g.cycle_shape(2, 1);
+        let p = Palette::default();
+        let tmp = std::env::temp_dir().join("maquette_spheres.glb");
+        write(
+            &g,
+            &p,
+            &ExportOptions {
+                path: tmp.clone(),
+                format: ExportFormat::Glb,
+                outline: OutlineConfig::default(),
+                sphere_segments: 6,
+                textures: TextureConfig::default(),
+            },
+        )
+        .unwrap();
+
+        let (doc, buffers, _images) = ::gltf::import(&tmp).expect("canonical gltf parser");
+        let tris = |name: &str| -> usize {
+            let mesh = doc.meshes().find(|m| m.name() == Some(name)).unwrap();
+            mesh.primitives()
+                .map(|prim| {
+                    let reader = prim.reader(|buf| Some(&buffers[buf.index()]));
+                    reader.read_indices().unwrap().into_u32().count() / 3
+                })
+                .sum()
+        };
+        // Two stacked 6-segment balls, 3 rings each: 2 × (2·6·3 − 2·6).
+        assert_eq!(tris("MaquetteBody"), 2 * 24);
+        assert_eq!(tris("MaquetteOutline"), tris("MaquetteBody"));
+    }
+
+    /// Cache a mock texture for slot 0 of `tiny_project`, leaving
+    /// slot 2 untextured.
+    fn textured_project(cache_dir: &Path) -> (Grid, Palette) {
+        use crate::texgen::{MockProvider, TextureRequest};
+        use crate::texture_meta::TextureHandle;
+        let (g, mut p) = tiny_project();
+        let request = TextureRequest::new("grass", 1, 16, 16, MockProvider::MODEL_ID);
+        texgen::generate_cached(&MockProvider, &request, Some(cache_dir)).unwrap();
+        p.set_texture(
+            0,
+            Some(TextureHandle {
+                cache_key: request.cache_key(),
+                generated_at: 0,
+            }),
+        );
+        (g, p)
+    }
+
+    #[test]
+    fn embedded_textures_round_trip_with_flat_fallback() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (g, p) = textured_project(tmp.path());
+        let out = tmp.path().join("textured.glb");
+        write(
+            &g,
+            &p,
+            &ExportOptions {
+                path: out.clone(),
+                format: ExportFormat::Glb,
+                outline: OutlineConfig::default(),
+                sphere_segments: DEFAULT_SPHERE_SEGMENTS,
+                textures: TextureConfig {
+                    mode: TextureMode::Embed,
+                    cache_dir: Some(tmp.path().to_path_buf()),
+                },
+            },
+        )
+        .unwrap();
+
+        let (doc, buffers, images) = ::gltf::import(&out).expect("canonical gltf parser");
+        assert_eq!(images.len(), 1);
+        assert_eq!((images[0].width, images[0].height), (16, 16));
+        let sampler = doc.textures().next().unwrap().sampler();
+        assert_eq!(sampler.wrap_s(), ::gltf::texture::WrappingMode::Repeat);
+
+        let body = doc
+            .meshes()
+            .find(|m| m.name() == Some("MaquetteBody"))
+            .unwrap();
+        let mut textured = 0;
+        for prim in body.primitives() {
+            let pbr = prim.material().pbr_metallic_roughness();
+            let reader = prim.reader(|buf| Some(&buffers[buf.index()]));
+            match pbr.base_color_texture() {
+                Some(info) => {
+                    textured += 1;
+                    assert_eq!(info.texture().source().index(), 0);
+                    assert_eq!(pbr.base_color_factor(), [1.0; 4]);
+                    assert!(reader.read_tex_coords(0).is_some());
+                }
+                None => assert!(reader.read_tex_coords(0).is_none()),
+            }
+        }
+        assert_eq!(textured, 1, "only slot 0 has a texture");
+    }
+
+    #[test]
+    fn external_textures_sit_next_to_the_model() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (g, p) = textured_project(tmp.path());
+        let out_dir = tmp.path().join("out");
+        fs::create_dir_all(&out_dir).unwrap();
+        let out = out_dir.join("scene.gltf");
+        let opts = ExportOptions {
+            path: out.clone(),
+            format: ExportFormat::Gltf,
+            outline: OutlineConfig::default(),
+            sphere_segments: DEFAULT_SPHERE_SEGMENTS,
+            textures: TextureConfig {
+                mode: TextureMode::External,
+                cache_dir: Some(tmp.path().to_path_buf()),
+            },
+        };
+        write(&g, &p, &opts).unwrap();
+        assert!(out_dir.join("scene_tex0.png").exists());
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&out).unwrap()).unwrap();
+        assert_eq!(json["images"][0]["uri"], "scene_tex0.png");
+        let (_doc, _buffers, images) = ::gltf::import(&out).expect("canonical gltf parser");
+        assert_eq!(images.len(), 1);
+
+        // A cache miss downgrades to flat colors instead of failing.
+        let empty = tempfile::tempdir().unwrap();
+        let flat = ExportOptions {
+            path: out_dir.join("flat.gltf"),
+            textures: TextureConfig {
+                mode: TextureMode::External,
+                cache_dir: Some(empty.path().to_path_buf()),
+            },
+            ..opts
+        };
+        write(&g, &p, &flat).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&flat.path).unwrap()).unwrap();
+        assert!(json.get("images").is_none());
+    }
 }
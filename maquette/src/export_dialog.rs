@@ -23,8 +23,8 @@
 //! 2. `ui.rs` calls `PendingExportDialog::open(...)`; the Export
 //!    modal closes, so egui doesn't keep redrawing the now-empty
 //!    dialog box.
-//! 3. `open` stores the chosen `ExportFormat` + `OutlineConfig`
-//!    alongside an `AsyncComputeTaskPool` task that awaits
+//! 3. `open` stores the chosen `ExportFormat` + `OutlineConfig` +
+//!    `TextureConfig` alongside an `AsyncComputeTaskPool` task that awaits
 //!    `AsyncFileDialog::save_file()`.
 //! 4. `poll_pending_export_dialog` polls that task every frame with
 //!    `future::poll_once`, pumping a `RequestRedraw` so
@@ -44,7 +44,8 @@ use bevy::prelude::*;
 use bevy::tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task};
 use bevy::window::RequestRedraw;
 
-use maquette::export::{ExportFormat, ExportOptions, ExportRequest, OutlineConfig};
+use maquette::export::{ExportFormat, ExportOptions, ExportRequest, OutlineConfig, TextureConfig};
+use maquette::mesher::DEFAULT_SPHERE_SEGMENTS;
 
 pub struct ExportDialogPlugin;
 
@@ -71,6 +72,7 @@ struct Pending {
     task: Task<Option<PathBuf>>,
     format: ExportFormat,
     outline: OutlineConfig,
+    textures: TextureConfig,
 }
 
 impl PendingExportDialog {
@@ -92,6 +94,7 @@ impl PendingExportDialog {
         &mut self,
         format: ExportFormat,
         outline: OutlineConfig,
+        textures: TextureConfig,
         default_name: String,
         filter_name: String,
         filter_ext: String,
@@ -121,6 +124,7 @@ impl PendingExportDialog {
             task,
             format,
             outline,
+            textures,
         });
     }
 }
@@ -148,7 +152,12 @@ fn poll_pending_export_dialog(
         return;
     };
 
-    let Pending { format, outline, .. } = pending.inner.take().expect("just polled");
+    let Pending {
+        format,
+        outline,
+        textures,
+        ..
+    } = pending.inner.take().expect("just polled");
 
     match result {
         Some(path) => {
@@ -161,6 +170,8 @@ fn poll_pending_export_dialog(
                 path,
                 format,
                 outline,
+                sphere_segments: DEFAULT_SPHERE_SEGMENTS,
+                textures,
             }));
         }
         None => {
@@ -28,11 +28,11 @@ pub const CELL_SIZE: f32 = 1.0;
 pub const MAX_HEIGHT: u8 = 8;
 pub const MIN_HEIGHT: u8 = 1;
 
-/// Per-cell block shape. v0.9 introduces `Sphere` as a placeholder
-/// alternate shape for the right-click "cycle shape" gesture; more
-/// shapes (cone, cylinder, slab, etc.) will land post-v1.0. The
-/// default — and the only shape the exporter currently emits — is
-/// `Cube`.
+/// Per-cell block shape. v0.9 introduces `Sphere` as the alternate
+/// shape for the right-click "cycle shape" gesture; more shapes
+/// (cone, cylinder, slab, etc.) will land post-v1.0. The default is
+/// `Cube`. Both shapes are exported and rendered; see
+/// `mesher::build_shape_buckets`.
 ///
 /// Serde layout: `#[serde(rename_all = "snake_case")]` so the on-disk
 /// form is human-readable. Missing field in older .maq files falls
@@ -18,10 +18,18 @@
 //!   baker. This is the single source of truth for what Maquette
 //!   emits, shared by the GUI's `File → Export` flow and the CLI's
 //!   `maquette-cli export` verb.
+//! - [`obj`] / [`stl`] — Wavefront OBJ+MTL and binary STL writers
+//!   over the same geometry as [`export`], for legacy tools and 3D
+//!   printing.
+//! - [`vox`]     — MagicaVoxel `.vox` writer and importer; the only
+//!   path that brings outside geometry into a `Grid`.
 //! - [`render`]  — pure-CPU isometric rasterizer that turns a
 //!   grid+palette into an RGBA buffer / PNG file. Used by
 //!   `maquette-cli render` to produce preview thumbnails in CI
 //!   without a GPU.
+//! - [`sprites`] — multi-direction sprite-sheet baker on top of
+//!   [`render`]: one packed PNG plus a JSON frame index, driven by
+//!   `maquette-cli bake-sprites`.
 //! - [`palette_io`] — portable `colors.json` reader/writer for
 //!   sharing palettes across projects.
 //! - [`texture_meta`] — per-palette-slot texture metadata
@@ -38,8 +46,12 @@ pub mod block_meta;
 pub mod export;
 pub mod grid;
 pub mod mesher;
+pub mod obj;
 pub mod palette_io;
 pub mod project;
 pub mod render;
+pub mod sprites;
+pub mod stl;
 pub mod texgen;
 pub mod texture_meta;
+pub mod vox;
@@ -30,6 +30,15 @@
 //! glTF primitive on export and to a separate preview entity in the
 //! GUI.
 //!
+//! ## Non-cube shapes
+//!
+//! Both cube builders are strictly cube-only. [`build_shape_buckets`]
+//! layers the other shapes on top: it starts from the greedy cube
+//! buckets and appends one low-poly UV sphere per layer of every
+//! `Sphere` column into the bucket of the same color. The exporter and
+//! the CPU rasterizer consume that; the GUI preview keeps spawning
+//! instanced Bevy spheres from [`build_sphere_instances`].
+//!
 //! ## Why per-color means "no cross-color merging"
 //!
 //! The greedy merger only collapses same-color rectangles. Different
@@ -40,14 +49,24 @@
 //! its own quad instead of merging into alternating strips, but the
 //! render / export model stays dead simple.
 
+use std::f32::consts::{PI, TAU};
+
 use crate::grid::{Cell, Grid, ShapeKind, CELL_SIZE};
 
+/// Longitude segments per exported sphere. 12 (× 6 rings) reads as
+/// round at game-camera distances while costing 120 triangles a ball,
+/// about ten times a greedy-meshed cube.
+pub const DEFAULT_SPHERE_SEGMENTS: u32 = 12;
+
+/// Fewest longitude segments [`build_shape_buckets`] accepts. Smaller
+/// requests are clamped up — three segments is already a bipyramid.
+pub const MIN_SPHERE_SEGMENTS: u32 = 3;
+
 /// One sphere instance to render in the preview. v0.9 A introduced
 /// per-cell shapes as a right-click cycle gesture, with `Sphere` as
-/// the only non-cube placeholder. The exporter **does not** emit
-/// sphere geometry yet (see `build_color_buckets` for the cube
-/// path); the GUI-side `preview_mesh` consumes this list to spawn
-/// one scaled sphere entity per column.
+/// the only non-cube shape. The GUI-side `preview_mesh` consumes this
+/// list to spawn one sphere entity per layer; export and the CPU
+/// rasterizer go through [`build_shape_buckets`] instead.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct SphereInstance {
     /// World-space X centre (accounting for the grid-centre offset
@@ -61,9 +80,8 @@ pub struct SphereInstance {
     pub color_idx: u8,
 }
 
-/// Collect every painted cell whose shape is `Sphere`. Used only by
-/// the GUI preview; exporter / CPU rasterizer skip spheres until
-/// the toolchain supports non-cube geometry end-to-end.
+/// Collect every painted cell whose shape is `Sphere`. Feeds both
+/// the GUI preview and [`build_shape_buckets`].
 pub fn build_sphere_instances(grid: &Grid) -> Vec<SphereInstance> {
     let mut out = Vec::new();
     for z in 0..grid.h {
@@ -89,7 +107,7 @@ pub fn build_sphere_instances(grid: &Grid) -> Vec<SphereInstance> {
 /// A cell counts as a "cube voxel" for the mesher iff it's painted
 /// **and** its shape is `Cube`. All other shapes are intentionally
 /// invisible to the greedy / culled meshers — they get rendered by
-/// specialised paths (e.g. [`build_sphere_instances`]). Keeping
+/// specialised paths (e.g. [`build_shape_buckets`]). Keeping
 /// this predicate in one place makes it impossible for the two
 /// shape code paths to disagree about cell ownership.
 fn is_cube_voxel(cell: &Cell) -> bool {
@@ -113,6 +131,34 @@ pub fn build_color_buckets(grid: &Grid) -> Vec<(u8, MeshBuilder)> {
     collect(buckets)
 }
 
+/// Every shape, meshed. Cube cells go through the greedy mesher; each
+/// layer of a `Sphere` column becomes a UV sphere of diameter
+/// `CELL_SIZE` centred in its voxel — the same stacked-balls reading
+/// the preview shows — merged into the bucket of its palette color.
+///
+/// `sphere_segments` is the longitude count (rings are half that);
+/// it's clamped to at least [`MIN_SPHERE_SEGMENTS`]. Buckets stay
+/// sorted by color index, so export primitive order is stable.
+pub fn build_shape_buckets(grid: &Grid, sphere_segments: u32) -> Vec<(u8, MeshBuilder)> {
+    let spheres = build_sphere_instances(grid);
+    let mut buckets: Vec<Option<MeshBuilder>> = Vec::new();
+    for (ci, b) in build_color_buckets(grid) {
+        *ensure_bucket(&mut buckets, ci as usize) = b;
+    }
+    let segments = sphere_segments.max(MIN_SPHERE_SEGMENTS);
+    let radius = CELL_SIZE * 0.5;
+    for inst in spheres {
+        let b = ensure_bucket(&mut buckets, inst.color_idx as usize);
+        let cx = (inst.grid_x as f32 + 0.5) * CELL_SIZE;
+        let cz = (inst.grid_z as f32 + 0.5) * CELL_SIZE;
+        for layer in 0..inst.height.max(1) {
+            let cy = (layer as f32 + 0.5) * CELL_SIZE;
+            b.push_sphere([cx, cy, cz], radius, segments);
+        }
+    }
+    collect(buckets)
+}
+
 /// Regression oracle: one unit quad per visible voxel face, no
 /// rectangle merging. Same surface, many more quads. Retained for
 /// tests — `build_color_buckets` (greedy) is the shipping path.
@@ -607,14 +653,57 @@ impl MeshBuilder {
         for p in corners {
             self.positions.push(p);
             self.normals.push(normal);
-            // UV is unused by the toon shader; emit zeros so exporters
-            // don't have to special-case the absence of the attribute.
-            self.uvs.push([0.0, 0.0]);
+            self.uvs.push(planar_uv(p, normal));
         }
         self.indices
             .extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
     }
 
+    /// Push a UV sphere with smooth (radial) normals. `segments`
+    /// longitudes × `segments / 2` rings; the seam column is
+    /// duplicated so UVs wrap cleanly, and the degenerate pole
+    /// triangles are skipped. Winding is CCW seen from outside, like
+    /// [`Self::push_quad`].
+    fn push_sphere(&mut self, center: [f32; 3], radius: f32, segments: u32) {
+        let rings = (segments / 2).max(2);
+        let base = self.positions.len() as u32;
+        for r in 0..=rings {
+            let theta = PI * r as f32 / rings as f32;
+            for s in 0..=segments {
+                let phi = TAU * s as f32 / segments as f32;
+                let n = [
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                ];
+                self.positions.push([
+                    center[0] + n[0] * radius,
+                    center[1] + n[1] * radius,
+                    center[2] + n[2] * radius,
+                ]);
+                self.normals.push(n);
+                // `u` runs against `phi` so the texture isn't mirrored
+                // when seen from outside; one tile wraps the ball.
+                self.uvs
+                    .push([1.0 - s as f32 / segments as f32, r as f32 / rings as f32]);
+            }
+        }
+        let row = segments + 1;
+        for r in 0..rings {
+            for s in 0..segments {
+                let a = base + r * row + s;
+                let b = a + row;
+                let (c, d) = (b + 1, a + 1);
+                if r != 0 {
+                    self.indices.extend_from_slice(&[a, d, b]);
+                }
+                if r != rings - 1 {
+                    self.indices.extend_from_slice(&[d, c, b]);
+                }
+            }
+        }
+    }
+
     /// Apply the world-space centre offset (ox, oz) the preview uses
     /// and consume `self`. The GUI consumes this value and wraps it
     /// into a Bevy `Mesh`; the exporter calls [`Self::translate`]
@@ -628,6 +717,29 @@ impl MeshBuilder {
     }
 }
 
+/// Box-project a face vertex into UV space, one texture tile per
+/// cell. Coordinates are grid-local (before any centring offset), so
+/// tile seams land on cell boundaries and a merged 3×1 rectangle
+/// repeats its texture three times instead of stretching it. `u`
+/// runs to the right and `v` down (glTF's UV origin is top-left)
+/// as seen from outside the face, so textures aren't mirrored.
+fn planar_uv(p: [f32; 3], normal: [f32; 3]) -> [f32; 2] {
+    let [x, y, z] = p.map(|c| c / CELL_SIZE);
+    if normal[0] > 0.5 {
+        [-z, -y]
+    } else if normal[0] < -0.5 {
+        [z, -y]
+    } else if normal[2] > 0.5 {
+        [x, -y]
+    } else if normal[2] < -0.5 {
+        [-x, -y]
+    } else if normal[1] > 0.5 {
+        [x, z]
+    } else {
+        [x, -z]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -870,6 +982,92 @@ mod tests {
         );
     }
 
+    #[test]
+    fn shape_buckets_stack_spheres_into_color_buckets() {
+        // Cube of color 2 next to a height-2 sphere column of color 2
+        // and a height-1 sphere of color 0: spheres merge into the
+        // cube's bucket, one ball per layer, buckets sorted by color.
+        let mut grid = Grid::with_size(4, 4);
+        paint(&mut grid, 0, 0, 2, 1);
+        paint(&mut grid, 1, 0, 2, 2);
+        paint(&mut grid, 3, 3, 0, 1);
+        grid.cycle_shape(1, 0);
+        grid.cycle_shape(3, 3);
+
+        let segments = 8;
+        let rings = segments / 2;
+        let ball_tris = (2 * segments * rings - 2 * segments) as usize;
+        let ball_verts = ((segments + 1) * (rings + 1)) as usize;
+        let buckets = build_shape_buckets(&grid, segments);
+        let colors: Vec<u8> = buckets.iter().map(|(ci, _)| *ci).collect();
+        assert_eq!(colors, [0, 2]);
+        assert_eq!(buckets[0].1.indices.len(), ball_tris * 3);
+        assert_eq!(buckets[1].1.indices.len(), 12 * 3 + 2 * ball_tris * 3);
+        assert_eq!(buckets[1].1.positions.len(), 24 + 2 * ball_verts);
+
+        // Stacked layers: the upper ball tops out at y = 2, the column
+        // height, and every vertex lies on a radius-0.5 sphere whose
+        // outward normal is the radial direction.
+        let top = |b: &MeshBuilder| b.positions.iter().map(|p| p[1]).fold(f32::MIN, f32::max);
+        let lone = &buckets[0].1;
+        assert!((top(lone) - 1.0).abs() < 1e-5);
+        assert!((top(&buckets[1].1) - 2.0).abs() < 1e-5);
+        for (p, n) in lone.positions.iter().zip(&lone.normals) {
+            let d = [p[0] - 3.5, p[1] - 0.5, p[2] - 3.5];
+            for axis in 0..3 {
+                assert!((d[axis] - n[axis] * 0.5).abs() < 1e-5);
+            }
+        }
+
+        // Winding is CCW from outside: each triangle's geometric
+        // normal agrees with its vertex normals.
+        for tri in lone.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| lone.positions[tri[i] as usize]);
+            let ab = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+            let ac = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+            let cross = [
+                ab[1] * ac[2] - ab[2] * ac[1],
+                ab[2] * ac[0] - ab[0] * ac[2],
+                ab[0] * ac[1] - ab[1] * ac[0],
+            ];
+            let n = lone.normals[tri[0] as usize];
+            assert!(cross[0] * n[0] + cross[1] * n[1] + cross[2] * n[2] > 0.0);
+        }
+
+        // Below the minimum, segments are clamped rather than
+        // producing an empty or inside-out ball.
+        let clamped = build_shape_buckets(&grid, 0);
+        assert!(!clamped[0].1.indices.is_empty());
+    }
+
+    #[test]
+    fn greedy_uvs_tile_once_per_cell() {
+        // A merged 3×1 top rectangle spans three UV units, so a
+        // repeating texture tiles instead of stretching.
+        let mut grid = Grid::with_size(4, 4);
+        for x in 0..3 {
+            paint(&mut grid, x, 0, 0, 2);
+        }
+        let greedy = build_color_buckets(&grid);
+        let b = &greedy[0].1;
+        assert_eq!(b.uvs.len(), b.positions.len());
+        for q in 0..quads(b) {
+            let span = |axis: usize| {
+                let vals = (0..4).map(|i| b.uvs[q * 4 + i][axis]);
+                vals.clone().fold(f32::MIN, f32::max) - vals.fold(f32::MAX, f32::min)
+            };
+            let n = b.normals[q * 4];
+            let (du, dv) = (span(0), span(1));
+            if n[1].abs() > 0.5 {
+                assert_eq!((du, dv), (3.0, 1.0), "top/bottom");
+            } else if n[2].abs() > 0.5 {
+                assert_eq!((du, dv), (3.0, 2.0), "front/back");
+            } else {
+                assert_eq!((du, dv), (1.0, 2.0), "ends");
+            }
+        }
+    }
+
     #[test]
     fn cycle_shape_noop_on_empty_cell() {
         let mut grid = Grid::with_size(2, 2);
@@ -0,0 +1,138 @@
+//! Wavefront OBJ + MTL writer.
+//!
+//! For the legacy DCC tools and engines that still don't read glTF.
+//! The geometry is exactly what the glTF writer ships (same buckets,
+//! same centring, see `export::centred_buckets`); what OBJ can't carry
+//! is left out:
+//!
+//! * **No outline hull.** OBJ has no per-material cull mode, so an
+//!   inverted hull would render as a solid black shell.
+//! * **No textures.** Every material is a flat `Kd` color. UVs are
+//!   still written so a user can assign a texture by hand.
+//!
+//! The `.mtl` library is written next to the model with the same stem
+//! (`castle.obj` → `castle.mtl`), one `newmtl ColorN` per palette slot
+//! in use — the same names the glTF materials carry.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use bevy::prelude::Color;
+
+use crate::export::{centred_buckets, ExportError};
+use crate::grid::{Grid, Palette};
+
+/// Write `grid` as `path` (OBJ) plus a sibling `.mtl`.
+pub fn write_obj(
+    grid: &Grid,
+    palette: &Palette,
+    path: &Path,
+    sphere_segments: u32,
+) -> Result<(), ExportError> {
+    let buckets = centred_buckets(grid, sphere_segments)?;
+    let mtl_path = path.with_extension("mtl");
+    let mtl_name = mtl_path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "model.mtl".to_string());
+
+    let mut obj = String::new();
+    let mut mtl = String::new();
+    let _ = writeln!(obj, "# Maquette {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(obj, "mtllib {mtl_name}");
+    let _ = writeln!(obj, "o Maquette");
+    let _ = writeln!(mtl, "# Maquette {}", env!("CARGO_PKG_VERSION"));
+
+    // OBJ indices are 1-based and global across the whole file, so
+    // each bucket's local indices are offset by the vertices already
+    // emitted. Position / UV / normal counts always move together
+    // (one of each per `MeshBuilder` vertex), so a single `a/a/a`
+    // index addresses all three.
+    let mut base = 1u32;
+    for (ci, b) in &buckets {
+        for p in &b.positions {
+            let _ = writeln!(obj, "v {} {} {}", p[0], p[1], p[2]);
+        }
+        for uv in &b.uvs {
+            // OBJ's v axis points up; glTF's (and ours) points down.
+            let _ = writeln!(obj, "vt {} {}", uv[0], 1.0 - uv[1]);
+        }
+        for n in &b.normals {
+            let _ = writeln!(obj, "vn {} {} {}", n[0], n[1], n[2]);
+        }
+        let _ = writeln!(obj, "usemtl Color{ci}");
+        for tri in b.indices.chunks_exact(3) {
+            let [a, b, c] = [tri[0] + base, tri[1] + base, tri[2] + base];
+            let _ = writeln!(obj, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}");
+        }
+        base += b.positions.len() as u32;
+
+        // Same fallback as the glTF writer: a stale slot exports white
+        // rather than failing the whole file.
+        let s = palette.get(*ci).unwrap_or(Color::WHITE).to_srgba();
+        let _ = writeln!(mtl, "\nnewmtl Color{ci}");
+        let _ = writeln!(mtl, "Kd {:.4} {:.4} {:.4}", s.red, s.green, s.blue);
+        let _ = writeln!(mtl, "Ka 0 0 0");
+        let _ = writeln!(mtl, "d 1");
+        let _ = writeln!(mtl, "illum 1");
+    }
+
+    fs::write(path, obj)?;
+    fs::write(&mtl_path, mtl)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::ShapeKind;
+
+    fn palette() -> Palette {
+        Palette::from_colors(
+            vec![
+                Some(Color::srgb(1.0, 0.0, 0.0)),
+                Some(Color::srgb(0.0, 0.0, 1.0)),
+            ],
+            0,
+        )
+    }
+
+    #[test]
+    fn writes_obj_and_mtl_with_one_material_per_color() {
+        let mut grid = Grid::with_size(4, 4);
+        grid.paint(0, 0, 0, 2);
+        grid.paint_with_shape(2, 2, 1, 1, ShapeKind::Sphere);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("thing.obj");
+        write_obj(&grid, &palette(), &path, 8).unwrap();
+
+        let obj = fs::read_to_string(&path).unwrap();
+        let mtl = fs::read_to_string(dir.path().join("thing.mtl")).unwrap();
+        assert!(obj.contains("mtllib thing.mtl"));
+        assert!(obj.contains("usemtl Color0") && obj.contains("usemtl Color1"));
+        assert!(mtl.contains("newmtl Color0\nKd 1.0000 0.0000 0.0000"));
+        assert!(mtl.contains("newmtl Color1\nKd 0.0000 0.0000 1.0000"));
+
+        // Every face index must resolve to an emitted vertex.
+        let verts = obj.lines().filter(|l| l.starts_with("v ")).count() as u32;
+        let uvs = obj.lines().filter(|l| l.starts_with("vt ")).count() as u32;
+        let normals = obj.lines().filter(|l| l.starts_with("vn ")).count() as u32;
+        assert_eq!((verts, uvs), (normals, normals));
+        for line in obj.lines().filter(|l| l.starts_with("f ")) {
+            for corner in line[2..].split(' ') {
+                let i: u32 = corner.split('/').next().unwrap().parse().unwrap();
+                assert!((1..=verts).contains(&i), "{line}");
+            }
+        }
+    }
+
+    #[test]
+    fn empty_grid_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("empty.obj");
+        let err = write_obj(&Grid::with_size(4, 4), &palette(), &path, 8).unwrap_err();
+        assert!(matches!(err, ExportError::Empty));
+        assert!(!path.exists());
+    }
+}
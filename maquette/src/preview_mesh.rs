@@ -125,9 +125,9 @@ fn rebuild_cell_mesh(
     // "voxel-per-layer" semantics cube columns already have: height
     // encodes stack count, not per-block scale.
     //
-    // Placeholder path — the cube mesher is the only one the
-    // exporter currently consumes, so spheres are preview-only
-    // until the export pipeline grows shape support.
+    // The exporter meshes the same layout itself
+    // (`mesher::build_shape_buckets`); the preview keeps Bevy's
+    // smooth sphere because it can instance it.
     //
     // Sharing the sphere `Mesh` handle across every sphere entity
     // lets Bevy instance the draw call under the hood, which keeps
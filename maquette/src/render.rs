@@ -1,7 +1,8 @@
 //! Pure-CPU triangle rasterizer for headless preview PNGs.
 //!
-//! Used by `maquette-cli render` and available as a library function
-//! for anyone (CI, a docs generator, a thumbnail pipeline) who needs a
+//! Used by `maquette-cli render`, by the sprite-sheet baker
+//! ([`crate::sprites`]), and available as a library function for
+//! anyone (CI, a docs generator, a thumbnail pipeline) who needs a
 //! picture of a `.maq` project without booting Bevy or a GPU.
 //!
 //! Design constraints:
@@ -13,22 +14,30 @@
 //!   fuzz). We avoid HashMap iteration over geometry; the greedy
 //!   mesher already returns a deterministic order.
 //! * **Matches the export, not the preview**. We render the same
-//!   greedy mesh the exporter ships. The `ToonMaterial` cel shader
+//!   geometry the exporter ships — greedy-meshed cubes plus low-poly
+//!   sphere cells. The `ToonMaterial` cel shader
 //!   and `bevy_mod_outline` inverted-hull live only in the preview;
 //!   the CLI render reflects what game engines actually receive.
 //!
 //! ## What the picture shows
 //!
-//! Orthographic isometric projection: yaw = -45°, pitch =
-//! asin(1/√3) ≈ 35.264°. Model is centered and fit into the frame
-//! with a configurable margin. Each triangle is flat-shaded with a
-//! Lambert term against a fixed camera-space light direction plus an
-//! ambient floor so back-facing-toward-light surfaces stay readable.
+//! Orthographic projection, isometric by default: yaw = -45°, pitch =
+//! asin(1/√3) ≈ 35.264°. Both angles are options so the sprite baker
+//! can orbit the model. Model is centered and fit into the frame
+//! with a configurable margin (see [`Framing`]). Each triangle is
+//! flat-shaded with a Lambert term against a fixed camera-space light
+//! direction plus an ambient floor so back-facing-toward-light
+//! surfaces stay readable. Spheres keep their facets: the shading
+//! normal is the average of a triangle's three vertex normals.
 //!
-//! No outline is rasterized. Users who want a cartoonish silhouette
-//! can still see the result of the exported inverted-hull by loading
-//! the `.glb` into an engine; the CLI render is a shape sanity-check,
-//! not a marketing beauty shot.
+//! Two opt-in looks exist for sprites, both off by default so the
+//! `render` thumbnails stay a plain shape sanity-check:
+//!
+//! * `toon_bands` quantizes the Lambert term into flat bands, the
+//!   CPU cousin of the preview's `ToonMaterial`.
+//! * `outline` draws a screen-space silhouette around the covered
+//!   pixels — the 2D equivalent of the exported inverted hull.
+//!   Interior creases are not outlined.
 
 use std::fs::File;
 use std::io::BufWriter;
@@ -37,12 +46,40 @@ use std::path::Path;
 use bevy::prelude::Color;
 
 use crate::grid::{Grid, Palette};
-use crate::mesher::build_color_buckets;
+use crate::mesher::{build_shape_buckets, MeshBuilder, DEFAULT_SPHERE_SEGMENTS};
 
 /// Default output dimensions. Matches "small icon, still legible" for
 /// CI golden images.
 pub const DEFAULT_SIZE: u32 = 512;
 
+/// Default camera yaw: looking at the model's +X/+Z corner.
+pub const ISO_YAW_DEG: f32 = -45.0;
+/// asin(1/√3) — the canonical isometric tilt.
+pub const ISO_PITCH_DEG: f32 = 35.264_39;
+
+/// How the model is scaled and centred in the frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Framing {
+    /// Fit the projected bounds as tightly as the margin allows. Best
+    /// use of pixels for a single image, but the scale changes with
+    /// the view angle.
+    #[default]
+    Tight,
+    /// Fit the model's bounding sphere, centred on its bounding-box
+    /// centre. Same scale and anchor from every angle, which is what
+    /// frames of one sprite sheet need.
+    Stable,
+}
+
+/// Screen-space silhouette outline. See the module docs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderOutline {
+    /// Thickness in pixels, measured outward from the silhouette.
+    pub width_px: u32,
+    /// sRGB outline color (always opaque).
+    pub color: [u8; 3],
+}
+
 /// Knobs for [`render_to_rgba`] / [`write_png`]. Defaults reproduce
 /// the shipping `maquette-cli render` look.
 #[derive(Debug, Clone)]
@@ -50,9 +87,11 @@ pub struct RenderOptions {
     pub width: u32,
     pub height: u32,
     /// sRGB background color. Applied wherever no triangle wins the
-    /// depth test. Transparent output is deliberately not supported —
-    /// game-engine preview thumbs tend to be opaque.
+    /// depth test, unless `transparent` is set.
     pub background: [u8; 3],
+    /// Leave uncovered pixels at alpha 0. Preview thumbs are opaque;
+    /// sprites are not.
+    pub transparent: bool,
     /// Fraction of the shorter screen axis left empty around the
     /// model. `0.08` = 8% padding each side.
     pub margin: f32,
@@ -63,6 +102,20 @@ pub struct RenderOptions {
     /// rasterizer normalises it before use. "Upper-right-front" is
     /// the usual choice for iso voxel art.
     pub light_dir: [f32; 3],
+    /// Longitude segments per sphere cell; same knob as
+    /// `ExportOptions::sphere_segments`, so a render previews the
+    /// export at the same tessellation.
+    pub sphere_segments: u32,
+    /// Camera yaw about +Y, in degrees. 0 looks at the model's front
+    /// (+Z) face, -90 at its right (+X) face.
+    pub yaw_deg: f32,
+    /// Camera elevation in degrees: 0 is a side view, 90 top-down.
+    pub pitch_deg: f32,
+    pub framing: Framing,
+    /// Number of flat shading bands (toon look). `None` keeps smooth
+    /// Lambert shading; values below 2 are treated as 2.
+    pub toon_bands: Option<u32>,
+    pub outline: Option<RenderOutline>,
 }
 
 impl Default for RenderOptions {
@@ -71,9 +124,16 @@ impl Default for RenderOptions {
             width: DEFAULT_SIZE,
             height: DEFAULT_SIZE,
             background: [24, 26, 30],
+            transparent: false,
             margin: 0.08,
             ambient: 0.35,
             light_dir: [0.3, 0.55, 0.78],
+            sphere_segments: DEFAULT_SPHERE_SEGMENTS,
+            yaw_deg: ISO_YAW_DEG,
+            pitch_deg: ISO_PITCH_DEG,
+            framing: Framing::Tight,
+            toon_bands: None,
+            outline: None,
         }
     }
 }
@@ -95,6 +155,17 @@ pub fn render_to_rgba(
     grid: &Grid,
     palette: &Palette,
     opts: &RenderOptions,
+) -> Result<Vec<u8>, RenderError> {
+    let buckets = build_shape_buckets(grid, opts.sphere_segments);
+    render_buckets(&buckets, palette, opts)
+}
+
+/// [`render_to_rgba`] over already-meshed buckets, so callers that
+/// render one model many times (the sprite baker) mesh it once.
+pub(crate) fn render_buckets(
+    buckets: &[(u8, MeshBuilder)],
+    palette: &Palette,
+    opts: &RenderOptions,
 ) -> Result<Vec<u8>, RenderError> {
     if opts.width == 0 || opts.height == 0 {
         return Err(RenderError::InvalidSize {
@@ -109,29 +180,37 @@ pub fn render_to_rgba(
         opts.background[0],
         opts.background[1],
         opts.background[2],
-        255u8,
+        if opts.transparent { 0 } else { 255 },
     ];
     let mut fb = vec![bg; w * h];
 
-    let buckets = build_color_buckets(grid);
     if buckets.is_empty() {
         return Ok(flatten(fb));
     }
 
     // Pre-rotate every vertex / normal once per bucket; triangle
     // indexing then points into the rotated arrays.
+    let view = View::new(opts.yaw_deg, opts.pitch_deg);
     let mut tris: Vec<Tri> = Vec::new();
     let mut bounds = Bounds2::empty();
+    let mut world_min = [f32::INFINITY; 3];
+    let mut world_max = [f32::NEG_INFINITY; 3];
 
-    for (ci, builder) in &buckets {
+    for (ci, builder) in buckets {
         let color = palette.get(*ci).unwrap_or(Color::WHITE);
         let lin = color.to_linear();
         let rgb = [lin.red, lin.green, lin.blue];
 
+        for p in &builder.positions {
+            for k in 0..3 {
+                world_min[k] = world_min[k].min(p[k]);
+                world_max[k] = world_max[k].max(p[k]);
+            }
+        }
         let rotated_pos: Vec<[f32; 3]> =
-            builder.positions.iter().map(|p| rotate_iso(*p)).collect();
+            builder.positions.iter().map(|p| view.rotate(*p)).collect();
         let rotated_nrm: Vec<[f32; 3]> =
-            builder.normals.iter().map(|n| rotate_iso(*n)).collect();
+            builder.normals.iter().map(|n| view.rotate(*n)).collect();
 
         for p in &rotated_pos {
             bounds.include(p[0], p[1]);
@@ -143,10 +222,10 @@ pub fn render_to_rgba(
             let i2 = chunk[2] as usize;
             tris.push(Tri {
                 v: [rotated_pos[i0], rotated_pos[i1], rotated_pos[i2]],
-                // The mesher guarantees every vertex in a triangle shares
-                // a normal (one normal per quad). Using the first vertex's
-                // is safe and avoids an averaging step.
-                n: rotated_nrm[i0],
+                // Cube quads share one normal across their vertices, so
+                // the average is exact there; on spheres it's the facet
+                // normal, which keeps the low-poly look of the export.
+                n: [0, 1, 2].map(|k| rotated_nrm[i0][k] + rotated_nrm[i1][k] + rotated_nrm[i2][k]),
                 color: rgb,
             });
         }
@@ -156,15 +235,37 @@ pub fn render_to_rgba(
         return Ok(flatten(fb));
     }
 
-    let model_w = (bounds.max_x - bounds.min_x).max(1e-3);
-    let model_h = (bounds.max_y - bounds.min_y).max(1e-3);
-    let margin_x = opts.width as f32 * opts.margin;
-    let margin_y = opts.height as f32 * opts.margin;
+    // The outline grows outward from the silhouette, so it needs its
+    // own strip of the margin to stay inside the frame.
+    let outline_px = opts.outline.map_or(0.0, |o| o.width_px as f32);
+    let margin_x = opts.width as f32 * opts.margin + outline_px;
+    let margin_y = opts.height as f32 * opts.margin + outline_px;
     let avail_w = (opts.width as f32 - 2.0 * margin_x).max(1.0);
     let avail_h = (opts.height as f32 - 2.0 * margin_y).max(1.0);
-    let scale = (avail_w / model_w).min(avail_h / model_h);
-    let cx_model = (bounds.min_x + bounds.max_x) * 0.5;
-    let cy_model = (bounds.min_y + bounds.max_y) * 0.5;
+    let (scale, cx_model, cy_model) = match opts.framing {
+        Framing::Tight => {
+            let model_w = (bounds.max_x - bounds.min_x).max(1e-3);
+            let model_h = (bounds.max_y - bounds.min_y).max(1e-3);
+            (
+                (avail_w / model_w).min(avail_h / model_h),
+                (bounds.min_x + bounds.max_x) * 0.5,
+                (bounds.min_y + bounds.max_y) * 0.5,
+            )
+        }
+        Framing::Stable => {
+            // Rotation preserves distances, so the radius measured in
+            // camera space is the same from every angle.
+            let c = view.rotate([0, 1, 2].map(|k| (world_min[k] + world_max[k]) * 0.5));
+            let radius = tris
+                .iter()
+                .flat_map(|t| t.v)
+                .map(|v| {
+                    ((v[0] - c[0]).powi(2) + (v[1] - c[1]).powi(2) + (v[2] - c[2]).powi(2)).sqrt()
+                })
+                .fold(1e-3f32, f32::max);
+            (avail_w.min(avail_h) / (2.0 * radius), c[0], c[1])
+        }
+    };
     let cx_screen = opts.width as f32 * 0.5;
     let cy_screen = opts.height as f32 * 0.5;
 
@@ -177,7 +278,10 @@ pub fn render_to_rgba(
         let p2 = project(&t.v[2], cx_model, cy_model, scale, cx_screen, cy_screen);
 
         let n = normalize3(t.n);
-        let nl = (n[0] * light[0] + n[1] * light[1] + n[2] * light[2]).max(0.0);
+        let mut nl = (n[0] * light[0] + n[1] * light[1] + n[2] * light[2]).max(0.0);
+        if let Some(bands) = opts.toon_bands {
+            nl = toon_band(nl, bands.max(2));
+        }
         let shade = opts.ambient + (1.0 - opts.ambient) * nl;
 
         let lin = [
@@ -195,6 +299,10 @@ pub fn render_to_rgba(
         rasterize(&p0, &p1, &p2, pixel, w, h, &mut fb, &mut depth);
     }
 
+    if let Some(outline) = opts.outline {
+        draw_silhouette(&mut fb, &depth, w, h, outline);
+    }
+
     Ok(flatten(fb))
 }
 
@@ -207,14 +315,24 @@ pub fn write_png(
     out: &Path,
 ) -> Result<(), RenderError> {
     let rgba = render_to_rgba(grid, palette, opts)?;
+    encode_png(&rgba, opts.width, opts.height, out)
+}
+
+/// Write an RGBA8 buffer as an sRGB PNG.
+pub(crate) fn encode_png(
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+    out: &Path,
+) -> Result<(), RenderError> {
     let file = File::create(out)?;
     let buf = BufWriter::new(file);
-    let mut encoder = png::Encoder::new(buf, opts.width, opts.height);
+    let mut encoder = png::Encoder::new(buf, width, height);
     encoder.set_color(png::ColorType::Rgba);
     encoder.set_depth(png::BitDepth::Eight);
     encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
     let mut writer = encoder.write_header()?;
-    writer.write_image_data(&rgba)?;
+    writer.write_image_data(rgba)?;
     Ok(())
 }
 
@@ -272,7 +390,7 @@ fn project(
     let sx = (p[0] - cx_model) * scale + cx_screen;
     // Screen Y grows downward; model Y grows up.
     let sy = cy_screen - (p[1] - cy_model) * scale;
-    // Depth: after `rotate_iso`, the world's (+X,+Y,+Z) corner lands
+    // Depth: after `View::rotate`, the world's (+X,+Y,+Z) corner lands
     // at the largest +Z. We keep "greater z = closer" so the z-test
     // is `z > zbuf[idx]`.
     let sz = p[2];
@@ -346,37 +464,86 @@ fn edge(ax: f32, ay: f32, bx: f32, by: f32, cx: f32, cy: f32) -> f32 {
     (bx - ax) * (cy - ay) - (by - ay) * (cx - ax)
 }
 
-/// World-to-iso-camera rotation. Yaw first (−45° about Y), then pitch
-/// (≈ 35.264° about X). The constants are pre-baked so the hot loop
-/// never hits `asin`.
+/// World-to-camera rotation. Yaw first (about Y), then pitch (about
+/// X). Sines and cosines are computed once per render so the per-vertex
+/// path is plain multiply-adds.
 ///
-/// Sign convention (verified by trace):
+/// Sign convention (verified by trace, default iso angles):
 /// * world (+X, +Y, +Z) ↦ rotated (+0, +0, +√3). Biggest +Z =
 ///   closest to the viewer.
 /// * world (−X, −Y, −Z) ↦ rotated (0, 0, −√3). Furthest.
 ///
 /// The screen axes are then the rotated x and y; `rotated_z` is the
 /// depth value the z-buffer sorts on.
+struct View {
+    ycos: f32,
+    ysin: f32,
+    pcos: f32,
+    psin: f32,
+}
+
+impl View {
+    fn new(yaw_deg: f32, pitch_deg: f32) -> Self {
+        let (ysin, ycos) = yaw_deg.to_radians().sin_cos();
+        let (psin, pcos) = pitch_deg.to_radians().sin_cos();
+        Self {
+            ycos,
+            ysin,
+            pcos,
+            psin,
+        }
+    }
+
+    #[inline]
+    fn rotate(&self, p: [f32; 3]) -> [f32; 3] {
+        let x1 = self.ycos * p[0] + self.ysin * p[2];
+        let y1 = p[1];
+        let z1 = -self.ysin * p[0] + self.ycos * p[2];
+
+        let x2 = x1;
+        let y2 = self.pcos * y1 - self.psin * z1;
+        let z2 = self.psin * y1 + self.pcos * z1;
+
+        [x2, y2, z2]
+    }
+}
+
+/// Snap a Lambert term in `0..=1` to one of `bands` evenly spaced
+/// levels, darkest 0 and brightest 1.
 #[inline]
-fn rotate_iso(p: [f32; 3]) -> [f32; 3] {
-    const YAW: f32 = -std::f32::consts::FRAC_PI_4; // −45°
-    // asin(1/√3) — the canonical isometric tilt. Precomputed to avoid
-    // stdlib math in the inner loop.
-    const PITCH: f32 = 0.615_479_7;
-    let ycos = YAW.cos();
-    let ysin = YAW.sin();
-    let pcos = PITCH.cos();
-    let psin = PITCH.sin();
-
-    let x1 = ycos * p[0] + ysin * p[2];
-    let y1 = p[1];
-    let z1 = -ysin * p[0] + ycos * p[2];
-
-    let x2 = x1;
-    let y2 = pcos * y1 - psin * z1;
-    let z2 = psin * y1 + pcos * z1;
-
-    [x2, y2, z2]
+fn toon_band(nl: f32, bands: u32) -> f32 {
+    let n = bands as f32;
+    (nl * n).floor().min(n - 1.0) / (n - 1.0)
+}
+
+/// Paint every uncovered pixel within `width_px` (Euclidean) of a
+/// covered one. Coverage is read from the depth buffer, so the
+/// outline hugs exactly what was rasterized.
+fn draw_silhouette(fb: &mut [[u8; 4]], depth: &[f32], w: usize, h: usize, outline: RenderOutline) {
+    let r = outline.width_px as i64;
+    if r == 0 {
+        return;
+    }
+    let [cr, cg, cb] = outline.color;
+    let covered = |x: i64, y: i64| {
+        x >= 0
+            && y >= 0
+            && (x as usize) < w
+            && (y as usize) < h
+            && depth[y as usize * w + x as usize] > f32::NEG_INFINITY
+    };
+    for y in 0..h as i64 {
+        for x in 0..w as i64 {
+            if covered(x, y) {
+                continue;
+            }
+            let near = (-r..=r)
+                .any(|dy| (-r..=r).any(|dx| dx * dx + dy * dy <= r * r && covered(x + dx, y + dy)));
+            if near {
+                fb[y as usize * w + x as usize] = [cr, cg, cb, 255];
+            }
+        }
+    }
 }
 
 #[inline]
@@ -573,4 +740,134 @@ mod tests {
             "expected top face brighter than side: top={top:?} side={side:?}"
         );
     }
+
+    #[test]
+    fn sphere_cells_are_rasterized() {
+        // Sphere-only canvas: used to render as pure background
+        // because the rasterizer only saw cube buckets.
+        let mut g = Grid::with_size(4, 4);
+        g.paint(1, 1, 2, 1);
+        g.cycle_shape(1, 1);
+        let palette = Palette::default();
+        let opts = RenderOptions {
+            width: 64,
+            height: 64,
+            ..Default::default()
+        };
+        let rgba = render_to_rgba(&g, &palette, &opts).unwrap();
+        let bg = opts.background;
+        let centre = (32 * 64 + 32) * 4;
+        assert_ne!(
+            &rgba[centre..centre + 3],
+            &bg,
+            "sphere should cover the centre"
+        );
+        // A ball doesn't fill its bounding square: the frame corners
+        // stay background even with the model fit to the frame.
+        let corner = (6 * 64 + 6) * 4;
+        assert_eq!(&rgba[corner..corner + 3], &bg);
+    }
+
+    #[test]
+    fn toon_bands_limit_the_shades_per_color() {
+        // A sphere shows dozens of facet shades when smooth; banded,
+        // one color can only take as many values as there are bands.
+        let mut g = Grid::with_size(4, 4);
+        g.paint_with_shape(1, 1, 2, 1, crate::grid::ShapeKind::Sphere);
+        let palette = Palette::default();
+        let distinct = |toon_bands| {
+            let opts = RenderOptions {
+                width: 64,
+                height: 64,
+                sphere_segments: 24,
+                toon_bands,
+                ..Default::default()
+            };
+            let rgba = render_to_rgba(&g, &palette, &opts).unwrap();
+            let mut shades: Vec<&[u8]> = rgba
+                .chunks_exact(4)
+                .filter(|c| c[..3] != opts.background)
+                .collect();
+            shades.sort();
+            shades.dedup();
+            shades.len()
+        };
+        assert!(distinct(None) > 6);
+        assert!(distinct(Some(3)) <= 3);
+    }
+
+    #[test]
+    fn outline_rings_the_silhouette_on_a_transparent_frame() {
+        let grid = sample_grid();
+        let palette = Palette::default();
+        let plain = RenderOptions {
+            width: 64,
+            height: 64,
+            transparent: true,
+            ..Default::default()
+        };
+        let outlined = RenderOptions {
+            outline: Some(RenderOutline {
+                width_px: 2,
+                color: [255, 0, 255],
+            }),
+            ..plain.clone()
+        };
+        let a = render_to_rgba(&grid, &palette, &plain).unwrap();
+        assert_eq!(a[3], 0, "uncovered pixels are transparent");
+        assert!(!a.chunks_exact(4).any(|p| p == [255, 0, 255, 255]));
+
+        let b = render_to_rgba(&grid, &palette, &outlined).unwrap();
+        let px = |x: i32, y: i32| -> &[u8] {
+            let i = (y.clamp(0, 63) * 64 + x.clamp(0, 63)) as usize * 4;
+            &b[i..i + 4]
+        };
+        let mut ring = 0;
+        for y in 0..64 {
+            for x in 0..64 {
+                if px(x, y) != [255, 0, 255, 255] {
+                    continue;
+                }
+                ring += 1;
+                // Every outline pixel sits within 2 px of the model.
+                let hugs = (-2..=2).any(|dy| {
+                    (-2..=2).any(|dx| {
+                        let p = px(x + dx, y + dy);
+                        p[3] == 255 && p != [255, 0, 255, 255]
+                    })
+                });
+                assert!(hugs, "stray outline pixel at ({x}, {y})");
+            }
+        }
+        assert!(ring > 0, "outline pixels drawn");
+    }
+
+    #[test]
+    fn stable_framing_keeps_scale_across_yaw() {
+        // Seen from the side, a column's pixel height depends only on
+        // the scale, so it must not change as the camera orbits — even
+        // though its projected width swings between 1 and √2 cells.
+        let mut g = Grid::with_size(4, 4);
+        g.paint(1, 1, 0, 4);
+        let palette = Palette::default();
+        let covered_rows = |yaw_deg| {
+            let opts = RenderOptions {
+                width: 96,
+                height: 96,
+                transparent: true,
+                yaw_deg,
+                pitch_deg: 0.0,
+                framing: Framing::Stable,
+                ..Default::default()
+            };
+            let rgba = render_to_rgba(&g, &palette, &opts).unwrap();
+            (0..96)
+                .filter(|y| (0..96).any(|x| rgba[(y * 96 + x) * 4 + 3] == 255))
+                .count()
+        };
+        let front = covered_rows(0.0);
+        for yaw in [-45.0, 30.0, 90.0] {
+            assert!(front.abs_diff(covered_rows(yaw)) <= 1, "yaw {yaw}");
+        }
+    }
 }
@@ -0,0 +1,392 @@
+//! Multi-direction sprite-sheet baking.
+//!
+//! Orbits the CPU rasterizer ([`crate::render`]) around the model and
+//! packs the frames into one PNG plus a JSON frame index, so a 2D game
+//! can use a Maquette asset as 8 / 16 / 24-direction sprites.
+//!
+//! Every frame uses [`Framing::Stable`]: same scale, same anchor, so a
+//! sprite doesn't pulse or drift as it turns. The light is fixed in
+//! camera space, i.e. every direction is lit the same way on screen.
+//!
+//! ## Layout
+//!
+//! Frames run pitch-major: all directions of the first pitch preset,
+//! then all directions of the next. They fill the sheet row by row,
+//! `columns` frames wide (one row per pitch by default). Direction
+//! `d` of `n` is drawn with camera yaw `start_yaw + d × 360° / n`; see
+//! `RenderOptions::yaw_deg` for what 0° looks at.
+//!
+//! ## Frame index
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "image": "hero.png",
+//!   "width": 1024, "height": 128,
+//!   "frame_width": 128, "frame_height": 128,
+//!   "directions": 8, "columns": 8, "rows": 1,
+//!   "frames": [
+//!     { "index": 0, "direction": 0, "yaw_deg": 0.0,
+//!       "pitch": "iso", "pitch_deg": 35.26439,
+//!       "x": 0, "y": 0, "w": 128, "h": 128 },
+//!     ...
+//!   ]
+//! }
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::grid::{Grid, Palette};
+use crate::mesher::build_shape_buckets;
+use crate::render::{self, Framing, RenderError, RenderOptions, ISO_PITCH_DEG};
+
+pub const INDEX_VERSION: u32 = 1;
+
+/// Frame size used when the caller doesn't pick one.
+pub const DEFAULT_FRAME_SIZE: u32 = 128;
+
+/// Largest sheet side we'll allocate. Past this most engines refuse
+/// the texture anyway.
+pub const MAX_SHEET_SIDE: u32 = 16384;
+
+/// Camera elevations worth baking. Named rather than free-form so the
+/// frame index can say which one a frame is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PitchPreset {
+    /// Eye level, 0°.
+    Side,
+    /// 2:1 pixel-art "isometric", atan(½) ≈ 26.57°.
+    Dimetric,
+    /// True isometric, ≈ 35.26° — what `maquette-cli render` uses.
+    Iso,
+    /// Steep three-quarter view, 60°.
+    High,
+    /// Straight down, 90°.
+    Top,
+}
+
+impl PitchPreset {
+    pub fn degrees(self) -> f32 {
+        match self {
+            PitchPreset::Side => 0.0,
+            PitchPreset::Dimetric => 26.565_05,
+            PitchPreset::Iso => ISO_PITCH_DEG,
+            PitchPreset::High => 60.0,
+            PitchPreset::Top => 90.0,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PitchPreset::Side => "side",
+            PitchPreset::Dimetric => "dimetric",
+            PitchPreset::Iso => "iso",
+            PitchPreset::High => "high",
+            PitchPreset::Top => "top",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SpriteSheetOptions {
+    /// Per-frame render settings; `width` / `height` are the frame
+    /// size. `yaw_deg`, `pitch_deg` and `framing` are set per frame.
+    pub frame: RenderOptions,
+    pub directions: u32,
+    pub start_yaw_deg: f32,
+    pub pitches: Vec<PitchPreset>,
+    /// Frames per sheet row. `None` = `directions`.
+    pub columns: Option<u32>,
+}
+
+impl Default for SpriteSheetOptions {
+    fn default() -> Self {
+        Self {
+            frame: RenderOptions {
+                width: DEFAULT_FRAME_SIZE,
+                height: DEFAULT_FRAME_SIZE,
+                transparent: true,
+                ..RenderOptions::default()
+            },
+            directions: 8,
+            start_yaw_deg: 0.0,
+            pitches: vec![PitchPreset::Iso],
+            columns: None,
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SpriteError {
+    #[error("nothing to bake — paint at least one cell first")]
+    Empty,
+    #[error("sprite sheet needs at least one direction and one pitch")]
+    NoFrames,
+    #[error("sprite sheet would be {w}×{h} px, max {max} per side")]
+    SheetTooLarge { w: u64, h: u64, max: u32 },
+    #[error(transparent)]
+    Render(#[from] RenderError),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// One frame's place on the sheet.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpriteFrame {
+    pub index: u32,
+    pub direction: u32,
+    pub yaw_deg: f32,
+    pub pitch: &'static str,
+    pub pitch_deg: f32,
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+}
+
+/// A baked sheet, still in memory.
+#[derive(Debug, Clone)]
+pub struct SpriteSheet {
+    pub width: u32,
+    pub height: u32,
+    /// RGBA8, top-left origin, `width * height * 4` bytes.
+    pub rgba: Vec<u8>,
+    pub frame_width: u32,
+    pub frame_height: u32,
+    pub directions: u32,
+    pub columns: u32,
+    pub rows: u32,
+    pub frames: Vec<SpriteFrame>,
+}
+
+#[derive(Serialize)]
+struct IndexFile<'a> {
+    version: u32,
+    image: String,
+    width: u32,
+    height: u32,
+    frame_width: u32,
+    frame_height: u32,
+    directions: u32,
+    columns: u32,
+    rows: u32,
+    frames: &'a [SpriteFrame],
+}
+
+/// Render every direction × pitch of `grid` and pack them.
+pub fn bake(
+    grid: &Grid,
+    palette: &Palette,
+    opts: &SpriteSheetOptions,
+) -> Result<SpriteSheet, SpriteError> {
+    let count = opts.directions as u64 * opts.pitches.len() as u64;
+    if count == 0 {
+        return Err(SpriteError::NoFrames);
+    }
+    let (fw, fh) = (opts.frame.width, opts.frame.height);
+    if fw == 0 || fh == 0 {
+        return Err(RenderError::InvalidSize { w: fw, h: fh }.into());
+    }
+    let columns = opts
+        .columns
+        .unwrap_or(opts.directions)
+        .clamp(1, opts.directions);
+    let rows = count.div_ceil(columns as u64);
+    let (sheet_w, sheet_h) = (columns as u64 * fw as u64, rows * fh as u64);
+    if sheet_w > MAX_SHEET_SIDE as u64 || sheet_h > MAX_SHEET_SIDE as u64 {
+        return Err(SpriteError::SheetTooLarge {
+            w: sheet_w,
+            h: sheet_h,
+            max: MAX_SHEET_SIDE,
+        });
+    }
+    let (sheet_w, sheet_h) = (sheet_w as u32, sheet_h as u32);
+
+    let buckets = build_shape_buckets(grid, opts.frame.sphere_segments);
+    if buckets.is_empty() {
+        return Err(SpriteError::Empty);
+    }
+
+    let mut rgba = vec![0u8; sheet_w as usize * sheet_h as usize * 4];
+    let mut frames = Vec::with_capacity(count as usize);
+    let step = 360.0 / opts.directions as f32;
+    for pitch in &opts.pitches {
+        for direction in 0..opts.directions {
+            let index = frames.len() as u32;
+            let (x, y) = ((index % columns) * fw, (index / columns) * fh);
+            let yaw_deg = (opts.start_yaw_deg + direction as f32 * step).rem_euclid(360.0);
+            let frame_opts = RenderOptions {
+                yaw_deg,
+                pitch_deg: pitch.degrees(),
+                framing: Framing::Stable,
+                ..opts.frame.clone()
+            };
+            let frame = render::render_buckets(&buckets, palette, &frame_opts)?;
+            let row_bytes = fw as usize * 4;
+            for (r, src) in frame.chunks_exact(row_bytes).enumerate() {
+                let at = ((y as usize + r) * sheet_w as usize + x as usize) * 4;
+                rgba[at..at + row_bytes].copy_from_slice(src);
+            }
+            frames.push(SpriteFrame {
+                index,
+                direction,
+                yaw_deg,
+                pitch: pitch.label(),
+                pitch_deg: pitch.degrees(),
+                x,
+                y,
+                w: fw,
+                h: fh,
+            });
+        }
+    }
+
+    Ok(SpriteSheet {
+        width: sheet_w,
+        height: sheet_h,
+        rgba,
+        frame_width: fw,
+        frame_height: fh,
+        directions: opts.directions,
+        columns,
+        rows: rows as u32,
+        frames,
+    })
+}
+
+/// Write `sheet` as a PNG at `image` and its frame index as JSON at
+/// `index`. The index names the image by file name only, so the pair
+/// can move together.
+pub fn write_sheet(sheet: &SpriteSheet, image: &Path, index: &Path) -> Result<(), SpriteError> {
+    render::encode_png(&sheet.rgba, sheet.width, sheet.height, image)?;
+    let doc = IndexFile {
+        version: INDEX_VERSION,
+        image: image
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        width: sheet.width,
+        height: sheet.height,
+        frame_width: sheet.frame_width,
+        frame_height: sheet.frame_height,
+        directions: sheet.directions,
+        columns: sheet.columns,
+        rows: sheet.rows,
+        frames: &sheet.frames,
+    };
+    fs::write(index, serde_json::to_string_pretty(&doc)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column() -> Grid {
+        let mut g = Grid::with_size(4, 4);
+        g.paint(0, 0, 0, 3);
+        g.paint(3, 3, 2, 1);
+        g
+    }
+
+    fn small(directions: u32, pitches: Vec<PitchPreset>) -> SpriteSheetOptions {
+        let mut opts = SpriteSheetOptions {
+            directions,
+            pitches,
+            ..Default::default()
+        };
+        opts.frame.width = 24;
+        opts.frame.height = 16;
+        opts
+    }
+
+    fn frame_pixels(sheet: &SpriteSheet, f: &SpriteFrame) -> Vec<u8> {
+        let mut out = Vec::new();
+        for row in f.y..f.y + f.h {
+            let at = (row * sheet.width + f.x) as usize * 4;
+            out.extend_from_slice(&sheet.rgba[at..at + f.w as usize * 4]);
+        }
+        out
+    }
+
+    #[test]
+    fn frames_are_packed_pitch_major() {
+        let opts = small(4, vec![PitchPreset::Iso, PitchPreset::Top]);
+        let sheet = bake(&column(), &Palette::default(), &opts).unwrap();
+        assert_eq!((sheet.width, sheet.height), (4 * 24, 2 * 16));
+        assert_eq!(sheet.frames.len(), 8);
+        let f = &sheet.frames[5];
+        assert_eq!((f.direction, f.pitch, f.yaw_deg), (1, "top", 90.0));
+        assert_eq!((f.x, f.y), (24, 16));
+    }
+
+    #[test]
+    fn columns_wrap_frames_onto_more_rows() {
+        let mut opts = small(8, vec![PitchPreset::Iso]);
+        opts.columns = Some(3);
+        let sheet = bake(&column(), &Palette::default(), &opts).unwrap();
+        assert_eq!((sheet.columns, sheet.rows), (3, 3));
+        assert_eq!((sheet.width, sheet.height), (72, 48));
+        assert_eq!((sheet.frames[7].x, sheet.frames[7].y), (24, 32));
+    }
+
+    #[test]
+    fn directions_differ_and_stay_transparent_around_the_model() {
+        let opts = small(4, vec![PitchPreset::Iso]);
+        let sheet = bake(&column(), &Palette::default(), &opts).unwrap();
+        let a = frame_pixels(&sheet, &sheet.frames[0]);
+        let b = frame_pixels(&sheet, &sheet.frames[1]);
+        assert_ne!(a, b, "a quarter turn must change the picture");
+        assert_eq!(a[3], 0, "frame corner is transparent");
+        assert!(a.chunks_exact(4).any(|p| p[3] == 255));
+    }
+
+    #[test]
+    fn rejects_empty_models_and_layouts() {
+        let palette = Palette::default();
+        assert!(matches!(
+            bake(
+                &Grid::with_size(4, 4),
+                &palette,
+                &small(8, vec![PitchPreset::Iso])
+            ),
+            Err(SpriteError::Empty)
+        ));
+        assert!(matches!(
+            bake(&column(), &palette, &small(8, vec![])),
+            Err(SpriteError::NoFrames)
+        ));
+        let mut huge = small(360, vec![PitchPreset::Iso]);
+        huge.frame.width = 512;
+        assert!(matches!(
+            bake(&column(), &palette, &huge),
+            Err(SpriteError::SheetTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn index_names_the_image_and_every_rect() {
+        let opts = small(2, vec![PitchPreset::Side]);
+        let sheet = bake(&column(), &Palette::default(), &opts).unwrap();
+        let tmp = tempfile::tempdir().unwrap();
+        let (png, json) = (tmp.path().join("s.png"), tmp.path().join("s.json"));
+        write_sheet(&sheet, &png, &json).unwrap();
+
+        let v: serde_json::Value = serde_json::from_slice(&fs::read(&json).unwrap()).unwrap();
+        assert_eq!(v["version"], INDEX_VERSION);
+        assert_eq!(v["image"], "s.png");
+        assert_eq!(v["frames"][1]["x"], 24);
+        assert_eq!(v["frames"][1]["yaw_deg"], 180.0);
+        assert_eq!(v["frames"][1]["pitch"], "side");
+        let bytes = fs::read(&png).unwrap();
+        let info = png::Decoder::new(std::io::Cursor::new(&bytes))
+            .read_info()
+            .unwrap();
+        assert_eq!((info.info().width, info.info().height), (48, 16));
+    }
+}
@@ -0,0 +1,116 @@
+//! Binary STL writer for 3D printing.
+//!
+//! STL is geometry only: no colors, no UVs, no outline. Every bucket
+//! from `export::centred_buckets` is flattened into one triangle soup.
+//!
+//! Two conventions differ from the glTF output:
+//!
+//! * **Z-up.** Slicers treat +Z as the build direction, so Maquette's
+//!   Y-up coordinates are rotated `(x, y, z) → (x, -z, y)`. The model
+//!   still sits on the build plate (z = 0) and keeps its handedness.
+//! * **Millimetres by convention.** STL has no unit; slicers assume
+//!   mm, so one cell prints as a 1 mm cube. Scale in the slicer.
+
+use std::fs;
+use std::path::Path;
+
+use crate::export::{centred_buckets, ExportError};
+use crate::grid::Grid;
+
+/// Write `grid` as a binary STL at `path`.
+pub fn write_stl(grid: &Grid, path: &Path, sphere_segments: u32) -> Result<(), ExportError> {
+    let buckets = centred_buckets(grid, sphere_segments)?;
+    let tris: usize = buckets.iter().map(|(_, b)| b.indices.len() / 3).sum();
+
+    let mut out = Vec::with_capacity(84 + tris * 50);
+    // The header must not start with `solid`, or some readers take the
+    // file for ASCII STL.
+    let mut header = [b' '; 80];
+    let tag = format!("Maquette {} binary STL", env!("CARGO_PKG_VERSION"));
+    header[..tag.len()].copy_from_slice(tag.as_bytes());
+    out.extend_from_slice(&header);
+    out.extend_from_slice(&(tris as u32).to_le_bytes());
+
+    for (_, b) in &buckets {
+        for tri in b.indices.chunks_exact(3) {
+            let [p0, p1, p2] = [0, 1, 2].map(|k| z_up(b.positions[tri[k] as usize]));
+            // Per-facet normal from the winding; the shared vertex
+            // normals are smooth on spheres and would misreport facets.
+            let n = normalize(cross(sub(p1, p0), sub(p2, p0)));
+            for v in [n, p0, p1, p2] {
+                for c in v {
+                    out.extend_from_slice(&c.to_le_bytes());
+                }
+            }
+            out.extend_from_slice(&0u16.to_le_bytes());
+        }
+    }
+
+    fs::write(path, out)?;
+    Ok(())
+}
+
+fn z_up(p: [f32; 3]) -> [f32; 3] {
+    [p[0], -p[2], p[1]]
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let len = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    if len > 0.0 {
+        [v[0] / len, v[1] / len, v[2] / len]
+    } else {
+        [0.0; 3]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn f32_at(bytes: &[u8], at: usize) -> f32 {
+        f32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn single_column_is_a_closed_z_up_box() {
+        let mut grid = Grid::with_size(4, 4);
+        grid.paint(1, 1, 0, 3);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("col.stl");
+        write_stl(&grid, &path, 8).unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        assert!(!bytes.starts_with(b"solid"));
+        let count = u32::from_le_bytes(bytes[80..84].try_into().unwrap()) as usize;
+        assert_eq!(count, 12, "6 faces × 2 triangles");
+        assert_eq!(bytes.len(), 84 + count * 50);
+
+        let (mut zmin, mut zmax) = (f32::MAX, f32::MIN);
+        let mut up_facets = 0;
+        for t in 0..count {
+            let at = 84 + t * 50;
+            if f32_at(&bytes, at + 8) > 0.99 {
+                up_facets += 1;
+            }
+            for v in 1..4 {
+                let z = f32_at(&bytes, at + v * 12 + 8);
+                zmin = zmin.min(z);
+                zmax = zmax.max(z);
+            }
+        }
+        assert_eq!((zmin, zmax), (0.0, 3.0), "height maps to +Z");
+        assert_eq!(up_facets, 2, "only the top face points up");
+    }
+}
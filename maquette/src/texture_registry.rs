@@ -19,15 +19,14 @@
 //!    file every frame, and the failure is logged once.
 //!
 //! The whole module is bin-only — the headless lib has no
-//! `wgpu::Image` concept and the export pipeline (Export Golden
-//! Rule) deliberately ships flat colour, not toon-shaded textured
-//! geometry.
+//! `wgpu::Image` concept. Textured *export* reads the same cache
+//! files directly (`maquette::export::TextureMode`).
 
 use std::collections::HashMap;
 use std::path::PathBuf;
 
 use bevy::asset::RenderAssetUsages;
-use bevy::image::Image;
+use bevy::image::{Image, ImageAddressMode, ImageSampler, ImageSamplerDescriptor};
 use bevy::prelude::*;
 use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
 
@@ -165,6 +164,10 @@ fn resolve_and_load(
 /// Output is always RGBA8 sRGB. RGB sources are widened with
 /// alpha = 255 ; palette / 16-bit / grayscale images currently
 /// hard-fail rather than silently mis-render.
+///
+/// The sampler repeats: the mesher's UVs tile once per cell, so a
+/// merged rectangle samples well outside `0..1`. Matches the sampler
+/// textured export writes.
 pub fn decode_png_to_image(bytes: &[u8]) -> Result<Image, String> {
     let decoder = png::Decoder::new(std::io::Cursor::new(bytes));
     let mut reader = decoder
@@ -190,7 +193,7 @@ pub fn decode_png_to_image(bytes: &[u8]) -> Result<Image, String> {
         }
         other => return Err(format!("unsupported color_type={other:?}")),
     };
-    Ok(Image::new(
+    let mut image = Image::new(
         Extent3d {
             width: info.width,
             height: info.height,
@@ -200,7 +203,13 @@ pub fn decode_png_to_image(bytes: &[u8]) -> Result<Image, String> {
         rgba,
         TextureFormat::Rgba8UnormSrgb,
         RenderAssetUsages::RENDER_WORLD,
-    ))
+    );
+    image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: ImageAddressMode::Repeat,
+        address_mode_v: ImageAddressMode::Repeat,
+        ..ImageSamplerDescriptor::linear()
+    });
+    Ok(image)
 }
 
 #[cfg(test)]
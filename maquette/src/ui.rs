@@ -12,7 +12,7 @@ use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
 
-use maquette::export::{ExportFormat, ExportInProgress, OutlineConfig};
+use maquette::export::{ExportFormat, ExportInProgress, OutlineConfig, TextureConfig, TextureMode};
 use maquette::grid::{
     DeleteColorMode, Grid, Palette, ShapeKind, DEFAULT_GRID_H, DEFAULT_GRID_W, MAX_GRID, MAX_HEIGHT,
     MIN_GRID, MIN_HEIGHT,
@@ -188,6 +188,7 @@ struct ExportDraft {
     outline_enabled: bool,
     outline_width_pct: f32,
     outline_color: [f32; 3],
+    embed_textures: bool,
 }
 
 impl Default for ExportDraft {
@@ -199,6 +200,7 @@ impl Default for ExportDraft {
             outline_enabled: defaults.enabled,
             outline_width_pct: defaults.width_pct,
             outline_color: [c.red, c.green, c.blue],
+            embed_textures: false,
         }
     }
 }
@@ -612,6 +614,19 @@ fn ui_system(
                     .color(egui::Color32::from_gray(150)),
                 );
 
+                ui.add_space(10.0);
+                ui.separator();
+                ui.add_space(6.0);
+
+                ui.label(egui::RichText::new("Textures").strong());
+                ui.checkbox(&mut draft.embed_textures, "Embed generated palette textures");
+                ui.small(
+                    egui::RichText::new(
+                        "Slots without a generated texture keep their flat color.",
+                    )
+                    .color(egui::Color32::from_gray(150)),
+                );
+
                 ui.add_space(12.0);
                 ui.horizontal(|ui| {
                     if ui.button("Choose file & export").clicked() {
@@ -630,8 +645,9 @@ fn ui_system(
             };
             let default_name = format!("{}.{ext}", current.display_name());
             log::info!(
-                "ui: opening save dialog for export (.{ext}, outline={})",
+                "ui: opening save dialog for export (.{ext}, outline={}, textures={})",
                 draft.outline_enabled,
+                draft.embed_textures,
             );
             // Hand the native "Save As…" sheet off to the async
             // dispatch pipeline. The old synchronous path wedged
@@ -649,6 +665,14 @@ fn ui_system(
                     width_pct: draft.outline_width_pct,
                     color: Color::srgb(r, g, b),
                 },
+                TextureConfig {
+                    mode: if draft.embed_textures {
+                        TextureMode::Embed
+                    } else {
+                        TextureMode::Flat
+                    },
+                    cache_dir: None,
+                },
                 default_name,
                 filter_name.to_string(),
                 ext.to_string(),
@@ -0,0 +1,646 @@
+//! MagicaVoxel `.vox` import / export.
+//!
+//! `.vox` is a sparse voxel format; Maquette is a height map. Export
+//! is therefore exact up to shape (every layer of every column becomes
+//! one voxel, spheres included), while import has to squeeze arbitrary
+//! voxel art into columns and says exactly what it squeezed — see
+//! [`VoxImportReport`].
+//!
+//! ## Coordinates
+//!
+//! MagicaVoxel is Z-up. Grid cell `(x, z)` layer `y` maps to voxel
+//! `(x, h - 1 - z, y)` — the same Y-up → Z-up rotation the STL writer
+//! uses, shifted so voxel coordinates stay non-negative. Import is the
+//! exact inverse, so a round-trip leaves the canvas unchanged.
+//!
+//! ## Import rules
+//!
+//! * Only the first model of a multi-model scene is read.
+//! * The footprint is cropped to `MAX_GRID × MAX_GRID` and voxels at
+//!   z ≥ `MAX_HEIGHT` are dropped.
+//! * A column becomes solid from the ground to its highest remaining
+//!   voxel (gaps are filled) and takes that top voxel's color.
+//! * Colors used by more than `max_colors` columns' tops are
+//!   quantized: the most-used colors keep a slot, the rest merge into
+//!   their nearest kept neighbour (sRGB distance).
+//!
+//! ## Format
+//!
+//! `"VOX "` + version, then a `MAIN` chunk whose children are read as
+//! `SIZE` / `XYZI` pairs plus an optional `RGBA` palette. Every other
+//! chunk (scene graph, materials, layers, …) is skipped. A file with
+//! no `RGBA` chunk uses MagicaVoxel's built-in default palette.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use bevy::prelude::Color;
+
+use crate::grid::{Grid, Palette, ShapeKind, MAX_GRID, MAX_HEIGHT};
+
+/// Version number written into exported files — the one MagicaVoxel
+/// 0.99.x writes.
+pub const VOX_VERSION: u32 = 150;
+
+/// Colors a `.vox` palette can address (index 0 means "empty").
+pub const MAX_VOX_COLORS: usize = 255;
+
+#[derive(thiserror::Error, Debug)]
+pub enum VoxError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("not a MagicaVoxel file (missing `VOX ` header)")]
+    BadMagic,
+    #[error("malformed .vox file: {0}")]
+    Malformed(&'static str),
+    #[error(".vox file contains no model")]
+    NoModel,
+    #[error("nothing to convert — no voxels fit inside Maquette's limits")]
+    Empty,
+    #[error("too many colors for .vox: {got} in use, max {max}")]
+    TooManyColors { got: usize, max: usize },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct VoxImportOptions {
+    /// Upper bound on palette slots the import may create. Clamped to
+    /// `1..=MAX_VOX_COLORS`.
+    pub max_colors: usize,
+}
+
+impl Default for VoxImportOptions {
+    fn default() -> Self {
+        Self {
+            max_colors: MAX_VOX_COLORS,
+        }
+    }
+}
+
+/// Everything the import had to change to fit the model into a
+/// `Grid`. All counters zero means the import was lossless.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VoxImportReport {
+    /// `SIZE` of the imported model, in voxels (x, y, z).
+    pub source_size: [u32; 3],
+    pub voxels_read: usize,
+    /// Extra models in a multi-model scene that were skipped.
+    pub models_ignored: usize,
+    /// Voxels outside the `MAX_GRID × MAX_GRID` footprint.
+    pub voxels_cropped: usize,
+    /// Voxels at or above `MAX_HEIGHT`.
+    pub voxels_above_max_height: usize,
+    /// Columns with holes or overhangs that were filled solid.
+    pub columns_filled: usize,
+    /// Columns whose voxels had more than one color; they take the
+    /// color of their top voxel.
+    pub columns_recolored: usize,
+    /// Source colors merged into a neighbour by quantization.
+    pub colors_merged: usize,
+    /// The file had no `RGBA` chunk; MagicaVoxel's default palette
+    /// was used.
+    pub default_palette: bool,
+}
+
+impl VoxImportReport {
+    /// One human-readable line per lossy adjustment, in the order a
+    /// user would want to fix them. Empty when the import was exact.
+    pub fn notes(&self) -> Vec<String> {
+        let mut notes = Vec::new();
+        if self.models_ignored > 0 {
+            notes.push(format!(
+                "{} additional model(s) ignored — only the first model is imported",
+                self.models_ignored
+            ));
+        }
+        if self.voxels_cropped > 0 {
+            notes.push(format!(
+                "{} voxel(s) outside the {MAX_GRID}×{MAX_GRID} canvas were cropped",
+                self.voxels_cropped
+            ));
+        }
+        if self.voxels_above_max_height > 0 {
+            notes.push(format!(
+                "{} voxel(s) above the {MAX_HEIGHT}-layer height cap were dropped",
+                self.voxels_above_max_height
+            ));
+        }
+        if self.columns_filled > 0 {
+            notes.push(format!(
+                "{} column(s) had holes or overhangs and were filled solid",
+                self.columns_filled
+            ));
+        }
+        if self.columns_recolored > 0 {
+            notes.push(format!(
+                "{} column(s) mixed several colors and now use their top voxel's color",
+                self.columns_recolored
+            ));
+        }
+        if self.colors_merged > 0 {
+            notes.push(format!(
+                "{} color(s) merged into their nearest neighbour to fit the palette limit",
+                self.colors_merged
+            ));
+        }
+        notes
+    }
+
+    pub fn is_lossless(&self) -> bool {
+        self.notes().is_empty()
+    }
+}
+
+/// What `.vox` export couldn't carry.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VoxExportReport {
+    pub voxels_written: usize,
+    /// Sphere cells written as plain cubes — `.vox` has no shapes.
+    pub sphere_cells: usize,
+}
+
+pub fn read_vox(
+    path: &Path,
+    opts: &VoxImportOptions,
+) -> Result<(Grid, Palette, VoxImportReport), VoxError> {
+    let bytes = fs::read(path)?;
+    parse_vox(&bytes, opts)
+}
+
+pub fn write_vox(grid: &Grid, palette: &Palette, path: &Path) -> Result<VoxExportReport, VoxError> {
+    let (bytes, report) = encode_vox(grid, palette)?;
+    fs::write(path, bytes)?;
+    Ok(report)
+}
+
+// =====================================================================
+// Import
+// =====================================================================
+
+struct Model {
+    size: [u32; 3],
+    voxels: Vec<[u8; 4]>,
+}
+
+pub fn parse_vox(
+    bytes: &[u8],
+    opts: &VoxImportOptions,
+) -> Result<(Grid, Palette, VoxImportReport), VoxError> {
+    if bytes.len() < 8 || &bytes[..4] != b"VOX " {
+        return Err(VoxError::BadMagic);
+    }
+    let (id, main, _) = read_chunk(bytes, 8)?;
+    if id != b"MAIN" {
+        return Err(VoxError::Malformed("first chunk is not MAIN"));
+    }
+    let children = &main.1;
+
+    let mut models = Vec::new();
+    let mut pending_size = None;
+    let mut rgba: Option<[[u8; 4]; 256]> = None;
+    let mut at = 0;
+    while at < children.len() {
+        let (id, (content, _), next) = read_chunk(children, at)?;
+        match id {
+            b"SIZE" => {
+                if content.len() < 12 {
+                    return Err(VoxError::Malformed("SIZE chunk too short"));
+                }
+                pending_size = Some([0, 4, 8].map(|o| u32_at(content, o)));
+            }
+            b"XYZI" => {
+                let size = pending_size
+                    .take()
+                    .ok_or(VoxError::Malformed("XYZI chunk without a preceding SIZE"))?;
+                if content.len() < 4 {
+                    return Err(VoxError::Malformed("XYZI chunk too short"));
+                }
+                let n = u32_at(content, 0) as usize;
+                let data = &content[4..];
+                if data.len() < n.saturating_mul(4) {
+                    return Err(VoxError::Malformed("XYZI voxel count exceeds chunk size"));
+                }
+                let voxels = data[..n * 4]
+                    .chunks_exact(4)
+                    .map(|v| [v[0], v[1], v[2], v[3]])
+                    .collect();
+                models.push(Model { size, voxels });
+            }
+            b"RGBA" => {
+                if content.len() < 1024 {
+                    return Err(VoxError::Malformed("RGBA chunk too short"));
+                }
+                // Entry k holds color index k + 1; index 0 is "empty".
+                let mut table = [[0u8; 4]; 256];
+                for (k, c) in content[..1020].chunks_exact(4).enumerate() {
+                    table[k + 1] = [c[0], c[1], c[2], c[3]];
+                }
+                rgba = Some(table);
+            }
+            _ => {}
+        }
+        at = next;
+    }
+
+    let mut models = models.into_iter();
+    let model = models.next().ok_or(VoxError::NoModel)?;
+    let mut report = VoxImportReport {
+        source_size: model.size,
+        voxels_read: model.voxels.len(),
+        models_ignored: models.len(),
+        default_palette: rgba.is_none(),
+        ..Default::default()
+    };
+    let table = rgba.unwrap_or_else(default_palette);
+
+    // Bucket voxels into columns, one slot per layer we can keep.
+    let [sx, sy, _] = model.size;
+    let w = (sx as usize).min(MAX_GRID);
+    let h = (sy as usize).min(MAX_GRID);
+    let mut columns = vec![[None::<u8>; MAX_HEIGHT as usize]; w * h];
+    for [vx, vy, vz, ci] in model.voxels {
+        let gz = (sy as usize).checked_sub(vy as usize + 1);
+        let (x, z) = match gz {
+            Some(z) if (vx as usize) < w && z < h => (vx as usize, z),
+            _ => {
+                report.voxels_cropped += 1;
+                continue;
+            }
+        };
+        if vz >= MAX_HEIGHT {
+            report.voxels_above_max_height += 1;
+            continue;
+        }
+        columns[z * w + x][vz as usize] = Some(ci);
+    }
+
+    // Resolve each column to (top color, height).
+    let mut tops = vec![None::<(u8, u8)>; w * h];
+    let mut usage: BTreeMap<u8, usize> = BTreeMap::new();
+    for (col, top) in columns.iter().zip(&mut tops) {
+        let Some(top_layer) = col.iter().rposition(Option::is_some) else {
+            continue;
+        };
+        let ci = col[top_layer].expect("rposition found it");
+        let kept: Vec<u8> = col.iter().flatten().copied().collect();
+        if kept.len() < top_layer + 1 {
+            report.columns_filled += 1;
+        }
+        if kept.iter().any(|&c| c != ci) {
+            report.columns_recolored += 1;
+        }
+        *usage.entry(ci).or_default() += 1;
+        *top = Some((ci, top_layer as u8 + 1));
+    }
+    if usage.is_empty() {
+        return Err(VoxError::Empty);
+    }
+
+    // Keep the most-used colors (ties: lower vox index), in vox index
+    // order so the palette reads like the source file's.
+    let max_colors = opts.max_colors.clamp(1, MAX_VOX_COLORS);
+    let mut by_use: Vec<(u8, usize)> = usage.iter().map(|(&c, &n)| (c, n)).collect();
+    by_use.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let kept: BTreeSet<u8> = by_use.iter().take(max_colors).map(|&(c, _)| c).collect();
+    report.colors_merged = usage.len() - kept.len();
+
+    let slot_of: BTreeMap<u8, u8> = kept
+        .iter()
+        .enumerate()
+        .map(|(slot, &ci)| (ci, slot as u8))
+        .collect();
+    let remap = |ci: u8| -> u8 {
+        if let Some(&slot) = slot_of.get(&ci) {
+            return slot;
+        }
+        let nearest = kept
+            .iter()
+            .min_by_key(|&&k| rgb_distance(table[ci as usize], table[k as usize]))
+            .expect("at least one color kept");
+        slot_of[nearest]
+    };
+
+    let mut grid = Grid::with_size(w, h);
+    for z in 0..h {
+        for x in 0..w {
+            if let Some((ci, height)) = tops[z * w + x] {
+                grid.paint(x, z, remap(ci), height);
+            }
+        }
+    }
+    let colors = kept
+        .iter()
+        .map(|&ci| {
+            let [r, g, b, _] = table[ci as usize];
+            Some(Color::srgb_u8(r, g, b))
+        })
+        .collect();
+    Ok((grid, Palette::from_colors(colors, 0), report))
+}
+
+/// Chunk at `at`: `(id, (content, children), offset past the chunk)`.
+type Chunk<'a> = (&'a [u8], (&'a [u8], &'a [u8]), usize);
+
+fn read_chunk(bytes: &[u8], at: usize) -> Result<Chunk<'_>, VoxError> {
+    let header = bytes
+        .get(at..at + 12)
+        .ok_or(VoxError::Malformed("truncated chunk header"))?;
+    let content_len = u32_at(header, 4) as usize;
+    let children_len = u32_at(header, 8) as usize;
+    let content_end = at + 12 + content_len;
+    let end = content_end + children_len;
+    if end > bytes.len() {
+        return Err(VoxError::Malformed("chunk extends past end of file"));
+    }
+    Ok((
+        &header[..4],
+        (&bytes[at + 12..content_end], &bytes[content_end..end]),
+        end,
+    ))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().expect("4 bytes"))
+}
+
+fn rgb_distance(a: [u8; 4], b: [u8; 4]) -> u32 {
+    (0..3)
+        .map(|i| (a[i] as i32 - b[i] as i32).pow(2) as u32)
+        .sum()
+}
+
+// =====================================================================
+// Export
+// =====================================================================
+
+/// Serialize `grid` as a single-model `.vox`. Palette slots in use are
+/// compacted to vox indices `1..`, in slot order; the rest of the
+/// 255-entry table is MagicaVoxel's default palette so the file opens
+/// with a familiar swatch set for further editing.
+pub fn encode_vox(grid: &Grid, palette: &Palette) -> Result<(Vec<u8>, VoxExportReport), VoxError> {
+    let used: BTreeSet<u8> = grid.cells.iter().filter_map(|c| c.color_idx).collect();
+    if used.is_empty() {
+        return Err(VoxError::Empty);
+    }
+    if used.len() > MAX_VOX_COLORS {
+        return Err(VoxError::TooManyColors {
+            got: used.len(),
+            max: MAX_VOX_COLORS,
+        });
+    }
+    let vox_index: BTreeMap<u8, u8> = used
+        .iter()
+        .enumerate()
+        .map(|(i, &slot)| (slot, i as u8 + 1))
+        .collect();
+
+    let mut report = VoxExportReport::default();
+    let mut voxels = Vec::new();
+    let mut top = 1u8;
+    for z in 0..grid.h {
+        for x in 0..grid.w {
+            let cell = grid.get(x, z).expect("in bounds");
+            let Some(slot) = cell.color_idx else {
+                continue;
+            };
+            if cell.shape == ShapeKind::Sphere {
+                report.sphere_cells += 1;
+            }
+            // Height 0 is a legacy v1 cell; the mesher draws it as 1.
+            let height = cell.height.max(1);
+            top = top.max(height);
+            for layer in 0..height {
+                voxels.extend_from_slice(&[
+                    x as u8,
+                    (grid.h - 1 - z) as u8,
+                    layer,
+                    vox_index[&slot],
+                ]);
+            }
+        }
+    }
+    report.voxels_written = voxels.len() / 4;
+
+    let mut table = default_palette();
+    for (&slot, &vi) in &vox_index {
+        // Stale slot → white, like every other writer.
+        let s = palette.get(slot).unwrap_or(Color::WHITE).to_srgba();
+        table[vi as usize] =
+            [s.red, s.green, s.blue, 1.0].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    }
+
+    let mut size = Vec::with_capacity(12);
+    for d in [grid.w as u32, grid.h as u32, top as u32] {
+        size.extend_from_slice(&d.to_le_bytes());
+    }
+    let mut xyzi = Vec::with_capacity(4 + voxels.len());
+    xyzi.extend_from_slice(&(report.voxels_written as u32).to_le_bytes());
+    xyzi.extend_from_slice(&voxels);
+    let rgba: Vec<u8> = table[1..]
+        .iter()
+        .chain(std::iter::once(&[0u8; 4]))
+        .flatten()
+        .copied()
+        .collect();
+
+    let mut children = Vec::new();
+    write_chunk(&mut children, b"SIZE", &size, &[]);
+    write_chunk(&mut children, b"XYZI", &xyzi, &[]);
+    write_chunk(&mut children, b"RGBA", &rgba, &[]);
+
+    let mut out = Vec::with_capacity(8 + 12 + children.len());
+    out.extend_from_slice(b"VOX ");
+    out.extend_from_slice(&VOX_VERSION.to_le_bytes());
+    write_chunk(&mut out, b"MAIN", &[], &children);
+    Ok((out, report))
+}
+
+fn write_chunk(out: &mut Vec<u8>, id: &[u8; 4], content: &[u8], children: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(content.len() as u32).to_le_bytes());
+    out.extend_from_slice(&(children.len() as u32).to_le_bytes());
+    out.extend_from_slice(content);
+    out.extend_from_slice(children);
+}
+
+/// MagicaVoxel's built-in palette, indexed by vox color index (entry
+/// 0 unused). Indices 1..=215 are the 6-level web-safe cube minus
+/// black, R outermost; 216..=255 are ten-step red, green, blue and
+/// gray ramps.
+fn default_palette() -> [[u8; 4]; 256] {
+    const CUBE: [u8; 6] = [0xff, 0xcc, 0x99, 0x66, 0x33, 0x00];
+    const RAMP: [u8; 10] = [0xee, 0xdd, 0xbb, 0xaa, 0x88, 0x77, 0x55, 0x44, 0x22, 0x11];
+    let mut table = [[0u8; 4]; 256];
+    let mut i = 1;
+    for r in CUBE {
+        for g in CUBE {
+            for b in CUBE {
+                if (r, g, b) != (0, 0, 0) {
+                    table[i] = [r, g, b, 0xff];
+                    i += 1;
+                }
+            }
+        }
+    }
+    for channel in 0..4 {
+        for v in RAMP {
+            let mut c = [0, 0, 0, 0xff];
+            if channel == 3 {
+                c[..3].fill(v);
+            } else {
+                c[channel] = v;
+            }
+            table[i] = c;
+            i += 1;
+        }
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn palette() -> Palette {
+        Palette::from_colors(
+            vec![
+                Some(Color::srgb_u8(200, 40, 40)),
+                None,
+                Some(Color::srgb_u8(40, 200, 40)),
+            ],
+            0,
+        )
+    }
+
+    /// Hand-assemble a `.vox` with one model per `models` entry.
+    fn vox_file(models: &[([u32; 3], Vec<[u8; 4]>)], rgba: Option<&[[u8; 4]]>) -> Vec<u8> {
+        let mut children = Vec::new();
+        for (size, voxels) in models {
+            let size: Vec<u8> = size.iter().flat_map(|d| d.to_le_bytes()).collect();
+            let mut xyzi = (voxels.len() as u32).to_le_bytes().to_vec();
+            xyzi.extend(voxels.iter().flatten());
+            write_chunk(&mut children, b"SIZE", &size, &[]);
+            write_chunk(&mut children, b"XYZI", &xyzi, &[]);
+        }
+        if let Some(colors) = rgba {
+            let mut table = vec![0u8; 1024];
+            for (k, c) in colors.iter().enumerate() {
+                table[k * 4..k * 4 + 4].copy_from_slice(c);
+            }
+            write_chunk(&mut children, b"RGBA", &table, &[]);
+        }
+        let mut out = b"VOX ".to_vec();
+        out.extend_from_slice(&VOX_VERSION.to_le_bytes());
+        write_chunk(&mut out, b"MAIN", &[], &children);
+        out
+    }
+
+    #[test]
+    fn default_palette_matches_magicavoxel() {
+        let t = default_palette();
+        assert_eq!(t[1], [0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(t[2], [0xff, 0xff, 0xcc, 0xff]);
+        assert_eq!(t[215], [0x00, 0x00, 0x33, 0xff]);
+        assert_eq!(t[216], [0xee, 0x00, 0x00, 0xff]);
+        assert_eq!(t[255], [0x11, 0x11, 0x11, 0xff]);
+    }
+
+    #[test]
+    fn export_then_import_round_trips() {
+        let mut grid = Grid::with_size(6, 5);
+        grid.paint(0, 0, 0, 3);
+        grid.paint(5, 4, 2, 8);
+        grid.paint_with_shape(2, 1, 2, 1, ShapeKind::Sphere);
+
+        let (bytes, export) = encode_vox(&grid, &palette()).unwrap();
+        assert_eq!(export.voxels_written, 12);
+        assert_eq!(export.sphere_cells, 1);
+
+        let (back, pal, report) = parse_vox(&bytes, &VoxImportOptions::default()).unwrap();
+        assert!(report.is_lossless(), "{:?}", report.notes());
+        assert_eq!(report.source_size, [6, 5, 8]);
+        assert_eq!((back.w, back.h), (6, 5));
+        // Slots are compacted: 0 stays 0, 2 becomes 1.
+        assert_eq!(pal.get(0), palette().get(0));
+        assert_eq!(pal.get(1), palette().get(2));
+        let cell = |x, z| back.get(x, z).map(|c| (c.color_idx, c.height));
+        assert_eq!(cell(0, 0), Some((Some(0), 3)));
+        assert_eq!(cell(5, 4), Some((Some(1), 8)));
+        assert_eq!(cell(2, 1), Some((Some(1), 1)));
+        assert_eq!(back.painted_count(), 3);
+    }
+
+    #[test]
+    fn lossy_import_reports_every_adjustment() {
+        let main = (
+            [200, 4, 12],
+            vec![
+                [0, 3, 0, 1],   // cell (0, 0): hole at z=1 gets filled,
+                [0, 3, 2, 2],   //   column takes the top color
+                [1, 3, 10, 1],  // above the height cap
+                [150, 0, 0, 1], // outside the 128-wide canvas
+                [2, 3, 0, 3],
+            ],
+        );
+        let second = ([1, 1, 1], vec![[0, 0, 0, 1]]);
+        let rgba = [[255, 0, 0, 255], [0, 0, 250, 255], [0, 0, 255, 255]];
+        let bytes = vox_file(&[main, second], Some(&rgba));
+        let opts = VoxImportOptions { max_colors: 1 };
+        let (grid, pal, report) = parse_vox(&bytes, &opts).unwrap();
+
+        assert_eq!(report.voxels_read, 5);
+        assert_eq!(report.models_ignored, 1);
+        assert_eq!(report.voxels_cropped, 1);
+        assert_eq!(report.voxels_above_max_height, 1);
+        assert_eq!(report.columns_filled, 1);
+        assert_eq!(report.columns_recolored, 1);
+        assert_eq!(report.colors_merged, 1);
+        assert!(!report.default_palette);
+        assert_eq!(report.notes().len(), 6);
+
+        // Colors 2 and 3 tie on usage; the lower index wins and the
+        // near-identical blue merges into it.
+        assert_eq!(pal.live_count(), 1);
+        assert_eq!(pal.get(0), Some(Color::srgb_u8(0, 0, 250)));
+        assert_eq!(grid.w, MAX_GRID);
+        assert_eq!(
+            grid.get(0, 0).map(|c| (c.color_idx, c.height)),
+            Some((Some(0), 3))
+        );
+        assert_eq!(grid.get(2, 0).map(|c| c.color_idx), Some(Some(0)));
+        assert_eq!(grid.painted_count(), 2);
+    }
+
+    #[test]
+    fn missing_rgba_uses_default_palette() {
+        let bytes = vox_file(&[([4, 4, 1], vec![[1, 1, 0, 216]])], None);
+        let (_, pal, report) = parse_vox(&bytes, &VoxImportOptions::default()).unwrap();
+        assert!(report.default_palette);
+        assert_eq!(pal.get(0), Some(Color::srgb_u8(0xee, 0, 0)));
+    }
+
+    #[test]
+    fn rejects_garbage_and_empty_models() {
+        let opts = VoxImportOptions::default();
+        assert!(matches!(
+            parse_vox(b"PNG nope", &opts),
+            Err(VoxError::BadMagic)
+        ));
+        let mut truncated = vox_file(&[([4, 4, 4], vec![[0, 0, 0, 1]])], None);
+        truncated.truncate(truncated.len() - 2);
+        assert!(matches!(
+            parse_vox(&truncated, &opts),
+            Err(VoxError::Malformed(_))
+        ));
+        assert!(matches!(
+            parse_vox(&vox_file(&[], None), &opts),
+            Err(VoxError::NoModel)
+        ));
+        let tall = vox_file(&[([4, 4, 20], vec![[0, 0, 15, 1]])], None);
+        assert!(matches!(parse_vox(&tall, &opts), Err(VoxError::Empty)));
+        assert!(matches!(
+            encode_vox(&Grid::with_size(4, 4), &palette()),
+            Err(VoxError::Empty)
+        ));
+    }
+}
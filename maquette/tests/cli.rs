@@ -118,6 +118,158 @@ fn cli_export_no_outline_produces_fewer_primitives() {
     );
 }
 
+#[test]
+fn cli_export_sphere_segments_controls_tessellation() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut grid = Grid::with_size(4, 4);
+    grid.paint(1, 1, 0, 1);
+    grid.cycle_shape(1, 1);
+    let input = tmp.path().join("ball.maq");
+    project::write_project(&input, &grid, &Palette::default()).unwrap();
+
+    let vertex_count = |segments: &str| -> usize {
+        let out = tmp.path().join(format!("ball_{segments}.glb"));
+        run_ok([
+            "export".as_ref(),
+            input.as_os_str(),
+            "--out".as_ref(),
+            out.as_os_str(),
+            "--no-outline".as_ref(),
+            "--sphere-segments".as_ref(),
+            segments.as_ref(),
+        ]);
+        let g = gltf::Gltf::from_slice(&std::fs::read(&out).unwrap()).unwrap();
+        g.meshes()
+            .flat_map(|m| m.primitives().collect::<Vec<_>>())
+            .map(|p| p.get(&gltf::Semantic::Positions).unwrap().count())
+            .sum()
+    };
+    // (segments + 1) × (segments / 2 + 1) vertices, seam duplicated.
+    assert_eq!(vertex_count("4"), 5 * 3);
+    assert_eq!(vertex_count("16"), 17 * 9);
+}
+
+#[test]
+fn cli_export_textures_embed_uses_texture_dir() {
+    use maquette::texgen::{self, MockProvider, TextureRequest};
+    use maquette::texture_meta::TextureHandle;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let cache = tmp.path().join("cache");
+    let request = TextureRequest::new("stone", 3, 8, 8, MockProvider::MODEL_ID);
+    texgen::generate_cached(&MockProvider, &request, Some(&cache)).unwrap();
+
+    let mut grid = Grid::with_size(4, 4);
+    grid.paint(0, 0, 0, 1);
+    let mut palette = Palette::default();
+    palette.set_texture(
+        0,
+        Some(TextureHandle {
+            cache_key: request.cache_key(),
+            generated_at: 0,
+        }),
+    );
+    let input = tmp.path().join("stone.maq");
+    project::write_project(&input, &grid, &palette).unwrap();
+
+    let out = tmp.path().join("stone.glb");
+    run_ok([
+        "export".as_ref(),
+        input.as_os_str(),
+        "--out".as_ref(),
+        out.as_os_str(),
+        "--textures".as_ref(),
+        "embed".as_ref(),
+        "--texture-dir".as_ref(),
+        cache.as_os_str(),
+    ]);
+    let (doc, _buffers, images) = gltf::import(&out).unwrap();
+    assert_eq!(images.len(), 1);
+    assert_eq!(doc.textures().count(), 1);
+}
+
+#[test]
+fn cli_export_interchange_formats_by_extension() {
+    let tmp = tempfile::tempdir().unwrap();
+    let input = fixture_project(tmp.path(), "cross");
+    for ext in ["obj", "stl", "vox"] {
+        let out = tmp.path().join(format!("cross.{ext}"));
+        run_ok([
+            "export".as_ref(),
+            input.as_os_str(),
+            "--out".as_ref(),
+            out.as_os_str(),
+        ]);
+        assert!(out.exists(), "{ext} not written");
+    }
+    assert!(tmp.path().join("cross.mtl").exists(), "OBJ needs its MTL");
+    let stl = std::fs::read(tmp.path().join("cross.stl")).unwrap();
+    let tris = u32::from_le_bytes(stl[80..84].try_into().unwrap()) as usize;
+    assert_eq!(stl.len(), 84 + tris * 50);
+    let vox = std::fs::read(tmp.path().join("cross.vox")).unwrap();
+    assert_eq!(&vox[..4], b"VOX ");
+}
+
+#[test]
+fn cli_vox_export_import_round_trip() {
+    let tmp = tempfile::tempdir().unwrap();
+    let input = fixture_project(tmp.path(), "cross");
+    let vox = tmp.path().join("cross.vox");
+    let back = tmp.path().join("back.maq");
+    run_ok([
+        "export".as_ref(),
+        input.as_os_str(),
+        "--out".as_ref(),
+        vox.as_os_str(),
+    ]);
+    let out = Command::new(cli_bin())
+        .arg("import")
+        .arg(&vox)
+        .arg("--out")
+        .arg(&back)
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "import failed: {out:?}");
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("4 painted cells"), "{stdout}");
+    assert!(out.stderr.is_empty(), "lossless import printed notes");
+
+    let (original, _) = project::read_project(&input).unwrap();
+    let (grid, palette) = project::read_project(&back).unwrap();
+    assert_eq!(palette.live_count(), 2, "only used colors come back");
+    let shape = |g: &Grid| -> Vec<_> {
+        g.cells
+            .iter()
+            .map(|c| (c.color_idx.is_some(), c.height))
+            .collect()
+    };
+    assert_eq!(shape(&grid), shape(&original));
+}
+
+#[test]
+fn cli_import_reports_lossy_conversion() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut grid = Grid::with_size(4, 4);
+    for (i, x) in (0..4).enumerate() {
+        grid.paint(x, 0, i as u8, 1);
+    }
+    let vox = tmp.path().join("four.vox");
+    maquette::vox::write_vox(&grid, &Palette::default(), &vox).unwrap();
+
+    let out = Command::new(cli_bin())
+        .arg("import")
+        .arg(&vox)
+        .arg("--out")
+        .arg(tmp.path().join("two.maq"))
+        .arg("--max-colors")
+        .arg("2")
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("note: 2 color(s) merged"), "{stderr}");
+}
+
 #[test]
 fn cli_validate_ok_on_good_file() {
     let tmp = tempfile::tempdir().unwrap();
@@ -285,6 +437,45 @@ fn cli_render_writes_valid_png() {
     );
 }
 
+#[test]
+fn cli_bake_sprites_writes_sheet_and_index() {
+    let tmp = tempfile::tempdir().unwrap();
+    let input = fixture_project(tmp.path(), "hero");
+    let out = tmp.path().join("hero.png");
+
+    run_ok([
+        "bake-sprites".as_ref(),
+        input.as_os_str(),
+        "--out".as_ref(),
+        out.as_os_str(),
+        "--directions".as_ref(),
+        "16".as_ref(),
+        "--pitch".as_ref(),
+        "iso,top".as_ref(),
+        "--frame-size".as_ref(),
+        "32".as_ref(),
+        "--outline".as_ref(),
+        "--toon-bands".as_ref(),
+        "3".as_ref(),
+    ]);
+
+    let bytes = std::fs::read(&out).unwrap();
+    let decoder = png::Decoder::new(std::io::Cursor::new(&bytes));
+    let reader = decoder.read_info().unwrap();
+    let info = reader.info();
+    assert_eq!((info.width, info.height), (16 * 32, 2 * 32));
+
+    let index = std::fs::read(tmp.path().join("hero.json")).unwrap();
+    let index: serde_json::Value = serde_json::from_slice(&index).unwrap();
+    assert_eq!(index["image"], "hero.png");
+    let frames = index["frames"].as_array().unwrap();
+    assert_eq!(frames.len(), 32);
+    assert_eq!(frames[17]["pitch"], "top");
+    assert_eq!(frames[17]["yaw_deg"], 22.5);
+    assert_eq!(frames[17]["x"], 32);
+    assert_eq!(frames[17]["y"], 32);
+}
+
 #[test]
 fn cli_render_rejects_empty_output_path() {
     let tmp = tempfile::tempdir().unwrap();
@@ -0,0 +1,106 @@
+//! `mj_atlas compact` — defragment an incremental atlas under a move budget.
+//!
+//! Thin CLI wrapper around [`crate::pack::compact`]: runs the compaction,
+//! writes the touched pages and the manifest, deletes emptied trailing pages
+//! and reports the move set. The report is what a hot-patch pipeline needs
+//! to decide whether the new layout is worth shipping: which sprites'
+//! UVs moved, and how much contiguous free space that bought.
+
+use crate::error::Result;
+use crate::pack::compact::{self, CompactOptions, CompactReport, PageStats};
+use crate::pack::{self, PackOptions};
+
+pub fn run(opts: &PackOptions, copts: &CompactOptions, json: bool) -> Result<()> {
+    let opts = &PackOptions {
+        incremental: true,
+        force: false,
+        ..opts.clone()
+    };
+    let (results, report) = compact::compact(opts, copts)?;
+    for r in &results {
+        r.save_to_disk(opts, opts.format)?;
+    }
+    pack::persist_manifest(opts, &results)?;
+    compact::remove_dropped_pages(&report)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&to_json(&report))?);
+    } else {
+        print_human(&report);
+    }
+    Ok(())
+}
+
+fn totals(pages: &[PageStats]) -> (usize, u64) {
+    (
+        pages.len(),
+        pages.iter().map(|p| p.largest_free).max().unwrap_or(0),
+    )
+}
+
+fn print_human(report: &CompactReport) {
+    let (pages_before, free_before) = totals(&report.before);
+    let (pages_after, free_after) = totals(&report.after);
+    log::info!("Sync pack: {}", report.sync.as_str());
+    log::info!(
+        "Moved {} of {} candidate sprite(s) ({} UV change(s)){}",
+        report.moves.len(),
+        report.candidates,
+        report.uv_changes(),
+        if report.budget_exhausted {
+            " — move budget exhausted"
+        } else {
+            ""
+        }
+    );
+    for m in &report.moves {
+        log::info!(
+            "  ↳ {}  atlas={}({},{}) → atlas={}({},{}){}",
+            m.name,
+            m.from_atlas,
+            m.from[0],
+            m.from[1],
+            m.to_atlas,
+            m.to[0],
+            m.to[1],
+            if m.aliases.is_empty() {
+                String::new()
+            } else {
+                format!("  +aliases: {}", m.aliases.join(","))
+            }
+        );
+    }
+    log::info!(
+        "Pages: {} → {}, largest free rect: {} → {} px²",
+        pages_before, pages_after, free_before, free_after
+    );
+}
+
+fn page_json(p: &PageStats) -> serde_json::Value {
+    serde_json::json!({
+        "size": {"w": p.width, "h": p.height},
+        "used_area": p.used_area,
+        "largest_free": p.largest_free,
+        "free_rects": p.free_rects,
+    })
+}
+
+fn to_json(report: &CompactReport) -> serde_json::Value {
+    serde_json::json!({
+        "status": "ok",
+        "sync": report.sync.as_str(),
+        "candidates": report.candidates,
+        "budget_exhausted": report.budget_exhausted,
+        "uv_stable": report.moves.is_empty(),
+        "uv_changes": report.uv_changes(),
+        "moved": report.moves.iter().map(|m| serde_json::json!({
+            "name": m.name,
+            "aliases": m.aliases,
+            "from": {"atlas": m.from_atlas, "x": m.from[0], "y": m.from[1]},
+            "to":   {"atlas": m.to_atlas,   "x": m.to[0],   "y": m.to[1]},
+        })).collect::<Vec<_>>(),
+        "before": report.before.iter().map(page_json).collect::<Vec<_>>(),
+        "after": report.after.iter().map(page_json).collect::<Vec<_>>(),
+        "removed_files": report.dropped_files.iter().map(|p| p.display().to_string()).collect::<Vec<_>>(),
+    })
+}
@@ -12,12 +12,17 @@
 //!
 //! Atlas-level: PNG image_hash matches? layout/size changed? options_hash
 //! mismatch (which guarantees a full repack happened between A and B)?
+//!
+//! The move set is also summarized (canonicals vs aliases, page-to-page
+//! transfers, page count) so a hot-patch pipeline can judge a `compact` or
+//! repack at a glance; `--moves` prints only that part.
 
 use crate::error::Result;
 use crate::pack::manifest::{self, Manifest, SpriteEntry};
+use std::collections::BTreeMap;
 use std::path::Path;
 
-pub fn run(a: &Path, b: &Path, json: bool) -> Result<()> {
+pub fn run(a: &Path, b: &Path, json: bool, moves_only: bool) -> Result<()> {
     let path_a = manifest::resolve_manifest_path(a)?;
     let path_b = manifest::resolve_manifest_path(b)?;
     let manifest_a = Manifest::try_load(&path_a)?
@@ -27,10 +32,11 @@ pub fn run(a: &Path, b: &Path, json: bool) -> Result<()> {
 
     let report = compute(&manifest_a, &manifest_b);
 
-    if json {
-        println!("{}", serde_json::to_string_pretty(&report.to_json())?);
-    } else {
-        report.print_human(&path_a, &path_b);
+    match (json, moves_only) {
+        (true, false) => println!("{}", serde_json::to_string_pretty(&report.to_json())?),
+        (true, true) => println!("{}", serde_json::to_string_pretty(&report.moves_json())?),
+        (false, false) => report.print_human(&path_a, &path_b),
+        (false, true) => report.print_moves(&path_a, &path_b),
     }
     Ok(())
 }
@@ -47,6 +53,8 @@ pub struct DiffReport {
     pub unchanged: usize,
     pub uv_stable: bool,
     pub atlas_size_changes: Vec<AtlasSizeChange>,
+    /// Page count in A and B.
+    pub atlas_count: [usize; 2],
 }
 
 #[derive(Debug)]
@@ -65,6 +73,17 @@ pub struct MovedSprite {
     pub to_pos: [u32; 2],
     pub from_rotated: bool,
     pub to_rotated: bool,
+    /// The sprite is an alias in B — it moved because its canonical did.
+    pub alias: bool,
+}
+
+/// Aggregate view of the `moved` list.
+#[derive(Debug, PartialEq, Eq)]
+pub struct MoveSummary {
+    pub canonical: usize,
+    pub aliases: usize,
+    /// `(from_atlas, to_atlas) → sprites`, aliases included.
+    pub transfers: BTreeMap<(usize, usize), usize>,
 }
 
 #[derive(Debug)]
@@ -136,6 +155,7 @@ pub fn compute(a: &Manifest, b: &Manifest) -> DiffReport {
                 to_pos: [entry_b.content_x, entry_b.content_y],
                 from_rotated: entry_a.rotated,
                 to_rotated: entry_b.rotated,
+                alias: entry_b.alias_of.is_some(),
             });
             // Note: a `moved` sprite implicitly means UV broke for that sprite,
             // even though the pixels are identical.
@@ -184,6 +204,7 @@ pub fn compute(a: &Manifest, b: &Manifest) -> DiffReport {
         unchanged,
         uv_stable,
         atlas_size_changes,
+        atlas_count: [a.atlases.len(), b.atlases.len()],
     }
 }
 
@@ -219,6 +240,61 @@ fn tag_diff(name: &str, a: &SpriteEntry, b: &SpriteEntry) -> Option<TagDiff> {
 }
 
 impl DiffReport {
+    pub fn move_summary(&self) -> MoveSummary {
+        let mut transfers = BTreeMap::new();
+        for m in &self.moved {
+            *transfers.entry((m.from_atlas, m.to_atlas)).or_insert(0) += 1;
+        }
+        let aliases = self.moved.iter().filter(|m| m.alias).count();
+        MoveSummary {
+            canonical: self.moved.len() - aliases,
+            aliases,
+            transfers,
+        }
+    }
+
+    fn print_moves(&self, a: &Path, b: &Path) {
+        println!("Move set:");
+        println!("  A = {}", a.display());
+        println!("  B = {}", b.display());
+        println!();
+        self.print_move_summary();
+        for m in &self.moved {
+            self.print_moved(m);
+        }
+    }
+
+    fn print_move_summary(&self) {
+        let s = self.move_summary();
+        println!(
+            "  Move set:        {} sprite(s) ({} canonical, {} alias), pages {} → {}",
+            self.moved.len(),
+            s.canonical,
+            s.aliases,
+            self.atlas_count[0],
+            self.atlas_count[1]
+        );
+        for ((from, to), n) in &s.transfers {
+            println!("    atlas {} → {}: {}", from, to, n);
+        }
+    }
+
+    fn print_moved(&self, m: &MovedSprite) {
+        println!(
+            "    ↳ {}  atlas={}({},{}{}) → atlas={}({},{}{}){}",
+            m.name,
+            m.from_atlas,
+            m.from_pos[0],
+            m.from_pos[1],
+            if m.from_rotated { ",R" } else { "" },
+            m.to_atlas,
+            m.to_pos[0],
+            m.to_pos[1],
+            if m.to_rotated { ",R" } else { "" },
+            if m.alias { "  (alias)" } else { "" }
+        );
+    }
+
     fn print_human(&self, a: &Path, b: &Path) {
         println!("Diff:");
         println!("  A = {}", a.display());
@@ -262,18 +338,10 @@ impl DiffReport {
         }
         println!("  Moved (UV break): {}", self.moved.len());
         for m in &self.moved {
-            println!(
-                "    ↳ {}  atlas={}({},{}{}) → atlas={}({},{}{})",
-                m.name,
-                m.from_atlas,
-                m.from_pos[0],
-                m.from_pos[1],
-                if m.from_rotated { ",R" } else { "" },
-                m.to_atlas,
-                m.to_pos[0],
-                m.to_pos[1],
-                if m.to_rotated { ",R" } else { "" }
-            );
+            self.print_moved(m);
+        }
+        if !self.moved.is_empty() {
+            self.print_move_summary();
         }
         println!("  Tag changes:     {}", self.tags_changed.len());
         for td in &self.tags_changed {
@@ -305,6 +373,43 @@ impl DiffReport {
         }
     }
 
+    fn moved_json(&self) -> Vec<serde_json::Value> {
+        self.moved
+            .iter()
+            .map(|m| {
+                serde_json::json!({
+                    "name": m.name,
+                    "alias": m.alias,
+                    "from": {"atlas": m.from_atlas, "x": m.from_pos[0], "y": m.from_pos[1], "rotated": m.from_rotated},
+                    "to":   {"atlas": m.to_atlas,   "x": m.to_pos[0],   "y": m.to_pos[1],   "rotated": m.to_rotated},
+                })
+            })
+            .collect()
+    }
+
+    fn move_summary_json(&self) -> serde_json::Value {
+        let s = self.move_summary();
+        serde_json::json!({
+            "sprites": self.moved.len(),
+            "canonical": s.canonical,
+            "aliases": s.aliases,
+            "transfers": s.transfers.iter().map(|((from, to), n)| {
+                serde_json::json!({"from": from, "to": to, "sprites": n})
+            }).collect::<Vec<_>>(),
+            "atlases": {"before": self.atlas_count[0], "after": self.atlas_count[1]},
+        })
+    }
+
+    /// `--moves --json`: the move set alone.
+    fn moves_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "uv_stable": self.uv_stable,
+            "options_hash_changed": self.options_hash_changed,
+            "summary": self.move_summary_json(),
+            "moved": self.moved_json(),
+        })
+    }
+
     fn to_json(&self) -> serde_json::Value {
         let mut tag_changes: Vec<serde_json::Value> = Vec::new();
         for td in &self.tags_changed {
@@ -316,14 +421,6 @@ impl DiffReport {
                 "source_url_changed": td.source_url_changed,
             }));
         }
-        let mut moved: Vec<serde_json::Value> = Vec::new();
-        for m in &self.moved {
-            moved.push(serde_json::json!({
-                "name": m.name,
-                "from": {"atlas": m.from_atlas, "x": m.from_pos[0], "y": m.from_pos[1], "rotated": m.from_rotated},
-                "to":   {"atlas": m.to_atlas,   "x": m.to_pos[0],   "y": m.to_pos[1],   "rotated": m.to_rotated},
-            }));
-        }
         let mut resized: Vec<serde_json::Value> = Vec::new();
         for r in &self.resized {
             resized.push(serde_json::json!({
@@ -348,7 +445,8 @@ impl DiffReport {
             "removed": self.removed,
             "pixel_change": self.pixel_change,
             "resized": resized,
-            "moved": moved,
+            "moved": self.moved_json(),
+            "move_summary": self.move_summary_json(),
             "tags_changed": tag_changes,
             "unchanged": self.unchanged,
             "atlas_size_changes": atlas_size,
@@ -368,7 +466,7 @@ mod tests {
             file_size: 0, mtime: 0, content_hash: hash.into(),
             trim_offset: [0, 0], trimmed_size: dims, source_size: dims,
             polygon_hash: None, atlas_idx: atlas, content_x: x, content_y: y,
-            rotated: false, alias_of: None,
+            rotated: false, alias_of: None, group: None, channel: None,
             tags: vec![], attribution: None, source_url: None,
         }
     }
@@ -383,13 +481,16 @@ mod tests {
             tool: "test".into(),
             options_hash: "h".into(),
             input_root: "/".into(),
+            layout_grid: 1,
             sprites,
             atlases: vec![AtlasEntry {
                 image_filename: "atlas.png".into(),
                 data_filename: "atlas".into(),
                 width: 256, height: 256, image_hash: "".into(), format: "json".into(),
-                used_rects: vec![], free_rects: vec![],
+                used_rects: vec![], free_rects: vec![], variants: vec![], groups: vec![],
             }],
+            animations: Default::default(),
+            premultiplied_alpha: false,
         }
     }
 
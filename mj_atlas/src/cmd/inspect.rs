@@ -65,6 +65,8 @@ fn print_human(path: &Path, m: &Manifest) -> Result<()> {
     println!("Options:  options_hash = {}", short(&m.options_hash));
     println!();
 
+    let pages = group_pages(m);
+
     // Per-atlas breakdown.
     for (idx, a) in m.atlases.iter().enumerate() {
         let atlas_used: u64 = a.used_rects.iter().map(|r| r.w as u64 * r.h as u64).sum();
@@ -85,6 +87,10 @@ fn print_human(path: &Path, m: &Manifest) -> Result<()> {
             a.free_rects.len(),
             a.format
         );
+        if let Some(groups) = pages.get(&idx) {
+            let list: Vec<String> = groups.iter().map(|(g, n)| format!("{}({})", g, n)).collect();
+            println!("      groups: {}", list.join(" "));
+        }
     }
 
     // Tag aggregation — useful for "what's in this atlas semantically".
@@ -102,6 +108,26 @@ fn print_human(path: &Path, m: &Manifest) -> Result<()> {
         }
     }
 
+    if !m.animations.is_empty() {
+        println!();
+        println!("Animations:");
+        for (name, a) in &m.animations {
+            let events = if a.events.is_empty() {
+                String::new()
+            } else {
+                format!("  {} events", a.events.len())
+            };
+            println!(
+                "  {}  {} frames  {} fps  {}{}",
+                name,
+                a.frames.len(),
+                a.fps,
+                a.mode.as_str(),
+                events
+            );
+        }
+    }
+
     // Animation groups (recomputed-style summary).
     if total_sprites <= 64 {
         // Avoid spamming for huge atlases — they should pipe through --json.
@@ -165,6 +191,37 @@ fn print_json(path: &Path, m: &Manifest) -> Result<()> {
         }
     }
 
+    // Group name → sprite count and the pages it landed on.
+    let mut groups: BTreeMap<&str, serde_json::Value> = BTreeMap::new();
+    for (idx, page) in group_pages(m) {
+        for (g, n) in page {
+            let v = groups
+                .entry(g)
+                .or_insert_with(|| serde_json::json!({ "sprites": 0, "pages": [] }));
+            v["sprites"] = (v["sprites"].as_u64().unwrap_or(0) + n as u64).into();
+            v["pages"].as_array_mut().unwrap().push(idx.into());
+        }
+    }
+
+    let pages: Vec<serde_json::Value> = m
+        .atlases
+        .iter()
+        .enumerate()
+        .map(|(idx, a)| {
+            let used: u64 = a.used_rects.iter().map(|r| r.w as u64 * r.h as u64).sum();
+            let total = a.width as u64 * a.height as u64;
+            serde_json::json!({
+                "index": idx,
+                "image": a.image_filename,
+                "size": {"w": a.width, "h": a.height},
+                "sprites": a.used_rects.len(),
+                "used_pixels": used,
+                "occupancy": if total > 0 { used as f64 / total as f64 } else { 0.0 },
+                "free_rects": a.free_rects.len(),
+            })
+        })
+        .collect();
+
     let summary = serde_json::json!({
         "manifest_path": path.display().to_string(),
         "tool": m.tool,
@@ -179,7 +236,9 @@ fn print_json(path: &Path, m: &Manifest) -> Result<()> {
         "occupancy": if total_atlas_pixels > 0 {
             used_pixels as f64 / total_atlas_pixels as f64
         } else { 0.0 },
+        "pages": pages,
         "tags": tag_counts,
+        "groups": groups,
     });
 
     let combined = serde_json::json!({
@@ -190,6 +249,18 @@ fn print_json(path: &Path, m: &Manifest) -> Result<()> {
     Ok(())
 }
 
+/// Atlas index → (group name → sprite count). Aliases count toward the
+/// page their canonical lives on, same as `atlas_idx`.
+fn group_pages(m: &Manifest) -> BTreeMap<usize, BTreeMap<&str, usize>> {
+    let mut pages: BTreeMap<usize, BTreeMap<&str, usize>> = BTreeMap::new();
+    for e in m.sprites.values() {
+        if let Some(g) = &e.group {
+            *pages.entry(e.atlas_idx).or_default().entry(g.as_str()).or_default() += 1;
+        }
+    }
+    pages
+}
+
 fn short(hash: &str) -> String {
     if hash.len() > 12 {
         format!("{}…", &hash[..12])
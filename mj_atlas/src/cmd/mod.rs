@@ -5,8 +5,25 @@
 //! user-editable fields (`tag`). Together they form the v0.3 "manifest as
 //! first-class artifact" surface, on top of which v0.4's cross-project
 //! sprite library will be built.
+//!
+//! `unpack` goes the other way: it reads atlas metadata (mj_atlas's or
+//! another packer's) and writes the sprites back out, optionally with a
+//! manifest that adopts the existing layout.
+//!
+//! `report` renders the same data as pictures: a static HTML page plus PNG
+//! overlays (diff status, meshes, free rects, occupancy heatmap) for review.
+//!
+//! `pull` restores a published manifest and its atlas files from hfrog, so
+//! incremental packing can resume on a machine that never packed it.
+//!
+//! `compact` is the one that repacks: it relocates a bounded set of sprites
+//! to defragment an incremental layout and reports which UVs moved.
 
+pub mod compact;
 pub mod diff;
 pub mod inspect;
+pub mod pull;
+pub mod report;
 pub mod tag;
+pub mod unpack;
 pub mod verify;
@@ -0,0 +1,457 @@
+//! `mj_atlas pull` — restore a published atlas version from hfrog.
+//!
+//! Finds `<project>.manifest` on the `mj_atlas-atlas` runtime (the newest
+//! upload, or `--ver`), downloads it, then downloads every page PNG and
+//! metadata file it references. Nothing is written until all of them are in
+//! memory and verified: each file against the `md5` hfrog recorded at upload
+//! (the SHA-256 prefix from [`hfrog::content_md5`]), and each PNG against the
+//! manifest's own `image_hash`. The files then land in the output dir under
+//! their original names, so the next `pack --incremental` with the same
+//! options sees an intact cache and keeps every published UV.
+//!
+//! The manifest records the sprite directory as it was spelled on the
+//! publishing machine; `--input` rewrites it to the local spelling, since an
+//! `input_root` mismatch forces a full repack.
+
+use crate::config::{Config, HfrogConfig};
+use crate::error::{AppError, Result};
+use crate::hfrog::{self, ArtifactInfo, Client, RUNTIME_ATLAS};
+use crate::pack::manifest::{self, Manifest};
+use std::path::{Component, Path, PathBuf};
+
+pub struct PullOptions {
+    /// hfrog project name — the `-o` name the atlas was packed with.
+    pub project: String,
+    /// Version to restore. `None` = the most recently uploaded manifest.
+    pub ver: Option<String>,
+    pub output_dir: PathBuf,
+    /// Local sprite directory to record as the manifest's `input_root`.
+    pub input: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub struct PullReport {
+    pub ver: String,
+    pub manifest: PathBuf,
+    /// Every file written, manifest last.
+    pub files: Vec<PathBuf>,
+    pub input_root: String,
+}
+
+pub fn run(popts: &PullOptions, json: bool) -> Result<()> {
+    let cfg = Config::load()?;
+    let report = pull(&cfg.hfrog, popts)?;
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "status": "ok",
+                "project": popts.project,
+                "ver": report.ver,
+                "output_dir": popts.output_dir.display().to_string(),
+                "manifest": report.manifest.display().to_string(),
+                "input_root": report.input_root,
+                "files": report.files.iter().map(|p| p.display().to_string()).collect::<Vec<_>>(),
+            }))?
+        );
+    } else {
+        log::info!(
+            "Pulled {}@{}: {} file(s) into {}",
+            popts.project,
+            report.ver,
+            report.files.len(),
+            popts.output_dir.display()
+        );
+        log::info!(
+            "Resume with: mj_atlas pack {} -o {} --output-dir {} --incremental <same options as the publisher>",
+            report.input_root,
+            popts.project,
+            popts.output_dir.display()
+        );
+    }
+    Ok(())
+}
+
+pub fn pull(cfg: &HfrogConfig, popts: &PullOptions) -> Result<PullReport> {
+    let client = Client::from_config(cfg).ok_or_else(|| {
+        AppError::Custom("hfrog is not configured — set [hfrog] enabled + endpoint in config.toml".into())
+    })?;
+    let project = popts.project.as_str();
+    let prefix = format!("mj_atlas/{}/", project);
+    let artifacts: Vec<ArtifactInfo> = client
+        .list_all()?
+        .into_iter()
+        .filter(|a| a.runtime.trim() == RUNTIME_ATLAS && a.s3_key.starts_with(&prefix))
+        .collect();
+
+    let manifest_name = format!("{}.manifest", project);
+    let manifest_art = pick_version(&artifacts, &manifest_name, popts.ver.as_deref())
+        .ok_or_else(|| match &popts.ver {
+            Some(v) => AppError::Custom(format!("hfrog: no manifest for '{}' at version '{}'", project, v)),
+            None => AppError::Custom(format!("hfrog: no published manifest for '{}'", project)),
+        })?;
+    let ver = manifest_art.ver.clone();
+    log::info!("hfrog: pulling {}@{}", project, ver);
+
+    let manifest_bytes = download(&client, manifest_art)?;
+    let mut m: Manifest = serde_json::from_slice(&manifest_bytes)
+        .map_err(|e| AppError::Custom(format!("hfrog: manifest parse error: {}", e)))?;
+    if m.version != manifest::MANIFEST_VERSION {
+        return Err(AppError::Custom(format!(
+            "hfrog: manifest version {} is not supported (expected {})",
+            m.version,
+            manifest::MANIFEST_VERSION
+        )));
+    }
+
+    // (filename, expected image_hash for PNGs)
+    let mut wanted: Vec<(String, Option<&str>)> = Vec::new();
+    for atlas in &m.atlases {
+        let ext = data_extension(&atlas.format)?;
+        wanted.push((plain_filename(&atlas.image_filename)?, Some(atlas.image_hash.as_str())));
+        wanted.push((plain_filename(&format!("{}.{}", atlas.data_filename, ext))?, None));
+        for v in &atlas.variants {
+            wanted.push((plain_filename(&v.image_filename)?, Some(v.image_hash.as_str())));
+            wanted.push((plain_filename(&format!("{}.{}", v.data_filename, ext))?, None));
+        }
+    }
+
+    let mut payload: Vec<(String, Vec<u8>)> = Vec::with_capacity(wanted.len() + 1);
+    for (filename, image_hash) in &wanted {
+        let key = hfrog::s3_key_for(project, &ver, filename);
+        let art = artifacts
+            .iter()
+            .filter(|a| a.ver == ver && a.s3_key == key)
+            .max_by_key(|a| a.pid)
+            .ok_or_else(|| {
+                AppError::Custom(format!("hfrog: {} is missing from {}@{}", filename, project, ver))
+            })?;
+        let bytes = download(&client, art)?;
+        if let Some(expected) = image_hash {
+            if manifest::hash_bytes(&bytes) != *expected {
+                return Err(AppError::Custom(format!(
+                    "hfrog: {} does not match the manifest's image_hash",
+                    filename
+                )));
+            }
+        }
+        payload.push((filename.clone(), bytes));
+    }
+
+    let manifest_bytes = match &popts.input {
+        Some(input) => {
+            m.input_root = input.to_string_lossy().to_string();
+            serde_json::to_string_pretty(&m)?.into_bytes()
+        }
+        None => manifest_bytes,
+    };
+    payload.push((format!("{}.manifest.json", project), manifest_bytes));
+
+    std::fs::create_dir_all(&popts.output_dir)?;
+    let mut files = Vec::with_capacity(payload.len());
+    for (filename, bytes) in payload {
+        let path = popts.output_dir.join(&filename);
+        std::fs::write(&path, bytes)?;
+        log::info!("hfrog: restored {}", path.display());
+        files.push(path);
+    }
+    Ok(PullReport {
+        ver,
+        manifest: files.last().cloned().unwrap_or_default(),
+        files,
+        input_root: m.input_root,
+    })
+}
+
+/// `name`'s upload at `ver`, or its newest upload (highest `pid`; the later
+/// list entry on a tie).
+fn pick_version<'a>(artifacts: &'a [ArtifactInfo], name: &str, ver: Option<&str>) -> Option<&'a ArtifactInfo> {
+    artifacts
+        .iter()
+        .filter(|a| a.name == name && ver.is_none_or(|v| a.ver == v))
+        .max_by_key(|a| a.pid)
+}
+
+/// Download one artifact and check it against its recorded size and `md5`.
+fn download(client: &Client, art: &ArtifactInfo) -> Result<Vec<u8>> {
+    let bytes = client.fetch(&art.name, &art.ver, RUNTIME_ATLAS)?;
+    if bytes.len() as i64 != art.cont_size || hfrog::content_md5(&bytes) != art.md5.trim() {
+        return Err(AppError::Custom(format!(
+            "hfrog: {} ({}) failed its hash check",
+            art.name, art.ver
+        )));
+    }
+    Ok(bytes)
+}
+
+/// A file name taken from the downloaded manifest. It is joined onto the
+/// output dir, so anything but a single plain component is refused.
+fn plain_filename(name: &str) -> Result<String> {
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Ok(name.to_string()),
+        _ => Err(AppError::Custom(format!(
+            "hfrog: manifest file name '{}' is not a plain file name",
+            name
+        ))),
+    }
+}
+
+/// Metadata extension for a manifest `format`. `.tres` bundles aren't
+/// mirrored (see [`hfrog::pack_artifact_files`]), so they can't be pulled.
+fn data_extension(format: &str) -> Result<&'static str> {
+    match format {
+        "json" | "json-array" => Ok("json"),
+        "godot-tpsheet" => Ok("tpsheet"),
+        other => Err(AppError::Custom(format!(
+            "hfrog: '{}' atlases are not mirrored and can't be pulled",
+            other
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::Format;
+    use crate::pack::tests::{make_opts, write_png};
+    use crate::pack::{self, PackOptions, PackPath};
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// In-memory hfrog: `list`, `get_object_presigned_url`, and the
+    /// presigned `GET /s3/<key>` it points at.
+    struct MockHfrog {
+        endpoint: String,
+        rows: Arc<Mutex<Vec<serde_json::Value>>>,
+        blobs: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+    }
+
+    impl MockHfrog {
+        fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let endpoint = format!("http://{}", listener.local_addr().unwrap());
+            let rows: Arc<Mutex<Vec<serde_json::Value>>> = Arc::default();
+            let blobs: Arc<Mutex<HashMap<String, Vec<u8>>>> = Arc::default();
+            let (r, b, base) = (rows.clone(), blobs.clone(), endpoint.clone());
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(mut stream) = stream else { continue };
+                    let mut line = String::new();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    reader.read_line(&mut line).unwrap();
+                    loop {
+                        let mut header = String::new();
+                        if reader.read_line(&mut header).unwrap() == 0 || header == "\r\n" {
+                            break;
+                        }
+                    }
+                    let target = line.split_whitespace().nth(1).unwrap_or("").to_string();
+                    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+                    let q: HashMap<&str, &str> = query.split('&').filter_map(|kv| kv.split_once('=')).collect();
+                    let (status, body) = match path {
+                        "/api/artifactory/list" => {
+                            let index: usize = q["index"].parse().unwrap();
+                            let cnt: usize = q["cnt"].parse().unwrap();
+                            let rows = r.lock().unwrap();
+                            let page: Vec<_> = rows.iter().skip(index * cnt).take(cnt).cloned().collect();
+                            (200, serde_json::json!({"code": 0, "msg": "", "data": page}).to_string().into_bytes())
+                        }
+                        "/api/artifactory/get_object_presigned_url" => {
+                            let rows = r.lock().unwrap();
+                            let body = match rows.iter().rev().find(|row| {
+                                row["name"] == q["name"] && row["ver"] == q["ver"] && row["runtime"] == q["runtime"]
+                            }) {
+                                Some(row) => serde_json::json!({"code": 0, "msg": "",
+                                    "data": {"url": format!("{}/s3/{}", base, row["s3_key"].as_str().unwrap())}}),
+                                None => serde_json::json!({"code": 1004, "msg": "not found"}),
+                            };
+                            (200, body.to_string().into_bytes())
+                        }
+                        p => match b.lock().unwrap().get(p.trim_start_matches("/s3/")) {
+                            Some(bytes) => (200, bytes.clone()),
+                            None => (404, Vec::new()),
+                        },
+                    };
+                    let _ = write!(
+                        stream,
+                        "HTTP/1.1 {} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        status,
+                        body.len()
+                    );
+                    let _ = stream.write_all(&body);
+                }
+            });
+            MockHfrog { endpoint, rows, blobs }
+        }
+
+        fn config(&self) -> HfrogConfig {
+            HfrogConfig {
+                enabled: true,
+                endpoint: self.endpoint.clone(),
+                ..HfrogConfig::default()
+            }
+        }
+
+        /// What `mirror_pack_artifacts` would upload for this pack.
+        fn publish(&self, cfg: &HfrogConfig, opts: &PackOptions, pages: usize, ver: &str) {
+            for (filename, kind) in hfrog::pack_artifact_files(&opts.output_name, pages, &[], opts.format) {
+                let Ok(bytes) = std::fs::read(opts.output_dir.join(&filename)) else { continue };
+                let spec = hfrog::build_spec(cfg, &opts.output_name, &kind, &filename, &bytes, ver);
+                let mut rows = self.rows.lock().unwrap();
+                let pid = rows.len() as i64 + 1;
+                let mut row = serde_json::to_value(&spec).unwrap();
+                row["pid"] = pid.into();
+                rows.push(row);
+                self.blobs.lock().unwrap().insert(spec.s3_key, bytes);
+            }
+        }
+    }
+
+    /// Publish two versions, pull the newest onto a "fresh machine" and
+    /// check the next incremental pack there is a cache hit; then pull the
+    /// older one by `--ver`.
+    #[test]
+    fn pull_restores_latest_version_for_incremental_packing() {
+        let tmp = std::env::temp_dir().join(format!("mj_atlas_pull_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&tmp);
+        let input = tmp.join("in");
+        let published = tmp.join("published");
+        std::fs::create_dir_all(&input).unwrap();
+        write_png(&input.join("a.png"), 40, 40, [255, 0, 0, 255]);
+        write_png(&input.join("b.png"), 40, 40, [0, 255, 0, 255]);
+
+        let server = MockHfrog::start();
+        let cfg = server.config();
+        let mut opts = make_opts(&input, &published, None);
+        opts.incremental = true;
+        opts.max_size = 64; // one sprite per page
+        let pack_and_publish = |opts: &PackOptions, ver: &str| {
+            let results = pack::execute(opts).unwrap();
+            for r in &results {
+                r.save_to_disk(opts, opts.format).unwrap();
+            }
+            pack::persist_manifest(opts, &results).unwrap();
+            server.publish(&cfg, opts, results.len(), ver);
+        };
+        pack_and_publish(&opts, "v1");
+        write_png(&input.join("c.png"), 40, 40, [0, 0, 255, 255]);
+        pack_and_publish(&opts, "v2");
+
+        let fresh = tmp.join("fresh");
+        let popts = PullOptions {
+            project: "atlas".into(),
+            ver: None,
+            output_dir: fresh.clone(),
+            input: Some(input.clone()),
+        };
+        let report = pull(&cfg, &popts).unwrap();
+        assert_eq!(report.ver, "v2");
+        // 3 pages × (png + json) + manifest
+        assert_eq!(report.files.len(), 7);
+        for name in ["atlas.png", "atlas_2.json", "atlas.manifest.json"] {
+            assert!(fresh.join(name).is_file(), "{} not restored", name);
+        }
+
+        let mut local = make_opts(&input, &fresh, None);
+        local.incremental = true;
+        local.max_size = 64;
+        let (_, path) = pack::execute_with_path(&local).unwrap();
+        assert_eq!(path, PackPath::Skipped);
+
+        let old = pull(&cfg, &PullOptions { ver: Some("v1".into()), output_dir: tmp.join("old"), ..popts }).unwrap();
+        assert_eq!(old.ver, "v1");
+        assert_eq!(old.files.len(), 5);
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
+    /// A download whose bytes don't match the recorded md5 aborts the pull
+    /// before anything is written.
+    #[test]
+    fn pull_rejects_corrupted_download() {
+        let tmp = std::env::temp_dir().join(format!("mj_atlas_pull_corrupt_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&tmp);
+        let input = tmp.join("in");
+        let published = tmp.join("published");
+        std::fs::create_dir_all(&input).unwrap();
+        write_png(&input.join("a.png"), 8, 8, [255, 0, 0, 255]);
+
+        let server = MockHfrog::start();
+        let cfg = server.config();
+        let mut opts = make_opts(&input, &published, None);
+        opts.format = Format::GodotTpsheet;
+        opts.incremental = true;
+        let results = pack::execute(&opts).unwrap();
+        results[0].save_to_disk(&opts, opts.format).unwrap();
+        pack::persist_manifest(&opts, &results).unwrap();
+        server.publish(&cfg, &opts, 1, "v1");
+        server
+            .blobs
+            .lock()
+            .unwrap()
+            .insert(hfrog::s3_key_for("atlas", "v1", "atlas.png"), b"not a png".to_vec());
+
+        let out = tmp.join("fresh");
+        let err = pull(
+            &cfg,
+            &PullOptions {
+                project: "atlas".into(),
+                ver: None,
+                output_dir: out.clone(),
+                input: None,
+            },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("hash check"), "{}", err);
+        assert!(!out.exists());
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
+    /// A manifest naming a file outside the output dir is refused before
+    /// anything is downloaded or written.
+    #[test]
+    fn pull_rejects_manifest_paths_outside_output_dir() {
+        let tmp = std::env::temp_dir().join(format!("mj_atlas_pull_path_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&tmp);
+        let input = tmp.join("in");
+        let published = tmp.join("published");
+        std::fs::create_dir_all(&input).unwrap();
+        write_png(&input.join("a.png"), 8, 8, [255, 0, 0, 255]);
+
+        let server = MockHfrog::start();
+        let cfg = server.config();
+        let mut opts = make_opts(&input, &published, None);
+        opts.format = Format::GodotTpsheet;
+        opts.incremental = true;
+        let results = pack::execute(&opts).unwrap();
+        results[0].save_to_disk(&opts, opts.format).unwrap();
+        pack::persist_manifest(&opts, &results).unwrap();
+        let manifest_path = published.join("atlas.manifest.json");
+        let mut m: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&manifest_path).unwrap()).unwrap();
+        m["atlases"][0]["image_filename"] = "../evil.png".into();
+        std::fs::write(&manifest_path, serde_json::to_vec(&m).unwrap()).unwrap();
+        server.publish(&cfg, &opts, 1, "v1");
+
+        let out = tmp.join("fresh");
+        let err = pull(
+            &cfg,
+            &PullOptions {
+                project: "atlas".into(),
+                ver: None,
+                output_dir: out.clone(),
+                input: None,
+            },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("../evil.png"), "{}", err);
+        assert!(!out.exists());
+        assert!(!tmp.join("evil.png").exists());
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+}
@@ -0,0 +1,692 @@
+//! `mj_atlas report` — render a packed atlas as static review artifacts.
+//!
+//! Writes, per atlas page, `page<N>.png` — the atlas with free rects shaded,
+//! polygon meshes drawn as wireframes and every sprite outlined in its diff
+//! status color — and `page<N>_heatmap.png`, the share of opaque sprite
+//! pixels per `HEATMAP_CELL`² cell. `report.html` embeds those PNGs as data
+//! URIs so the single file can be attached to a merge request. With `--base`, statuses come from
+//! [`diff::compute`] against the older manifest: added / modified (pixels or
+//! resized) / moved / unchanged, and removed sprites are drawn as dashed
+//! outlines where they used to be. Pure `image` drawing — no GUI feature, so
+//! it runs headless in CI.
+
+use crate::cmd::diff::{self, DiffReport};
+use crate::error::{AppError, Result};
+use crate::pack::manifest::{self, AtlasEntry, Manifest};
+use image::{Rgba, RgbaImage};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Edge length of one heatmap cell, in atlas pixels.
+const HEATMAP_CELL: u32 = 16;
+
+const FREE_FILL: [u8; 4] = [0, 200, 220, 70];
+const FREE_EDGE: [u8; 4] = [0, 200, 220, 255];
+const MESH: [u8; 4] = [240, 18, 190, 170];
+
+/// Atlas-space mesh vertices and their triangle indices.
+type Mesh = (Vec<[f32; 2]>, Vec<[usize; 3]>);
+
+/// How a sprite changed relative to `--base`. Without a base every sprite is
+/// `Unchanged`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Status {
+    Added,
+    Modified,
+    Moved,
+    Removed,
+    Unchanged,
+}
+
+impl Status {
+    pub const ALL: [Status; 5] = [
+        Status::Added,
+        Status::Modified,
+        Status::Moved,
+        Status::Removed,
+        Status::Unchanged,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Status::Added => "added",
+            Status::Modified => "modified",
+            Status::Moved => "moved",
+            Status::Removed => "removed",
+            Status::Unchanged => "unchanged",
+        }
+    }
+
+    pub fn color(&self) -> [u8; 4] {
+        match self {
+            Status::Added => [46, 204, 64, 255],
+            Status::Modified => [255, 133, 27, 255],
+            Status::Moved => [0, 116, 217, 255],
+            Status::Removed => [255, 65, 54, 255],
+            Status::Unchanged => [170, 170, 170, 255],
+        }
+    }
+}
+
+/// One outlined rect on a page.
+#[derive(Debug, Clone)]
+pub struct Outline {
+    pub name: String,
+    pub status: Status,
+    pub rect: [u32; 4],
+    /// Previous top-left of a sprite that moved within this page.
+    pub moved_from: Option<[u32; 2]>,
+}
+
+#[derive(Debug)]
+pub struct ReportPage {
+    pub index: usize,
+    pub image: String,
+    pub width: u32,
+    pub height: u32,
+    pub sprites: usize,
+    pub used_pixels: u64,
+    pub occupancy: f64,
+    pub free_rects: usize,
+    /// Sprites drawn with a polygon mesh.
+    pub meshes: usize,
+    pub outlines: Vec<Outline>,
+    pub overlay: PathBuf,
+    pub heatmap: PathBuf,
+}
+
+#[derive(Debug)]
+pub struct Report {
+    pub manifest: PathBuf,
+    pub base: Option<PathBuf>,
+    pub diff: Option<DiffReport>,
+    pub pages: Vec<ReportPage>,
+    pub html: PathBuf,
+}
+
+impl Report {
+    /// Sprite count per status across all pages (aliases excluded — they
+    /// share their canonical's rect).
+    pub fn counts(&self) -> Vec<(Status, usize)> {
+        Status::ALL
+            .into_iter()
+            .map(|s| {
+                let n = self
+                    .pages
+                    .iter()
+                    .flat_map(|p| &p.outlines)
+                    .filter(|o| o.status == s)
+                    .count();
+                (s, n)
+            })
+            .collect()
+    }
+}
+
+fn load(input: &Path) -> Result<(PathBuf, Manifest)> {
+    let path = manifest::resolve_manifest_path(input)?;
+    let m = Manifest::try_load(&path)?
+        .ok_or_else(|| AppError::Custom(format!("{} not loadable", path.display())))?;
+    Ok((path, m))
+}
+
+pub fn run(input: &Path, base: Option<&Path>, out_dir: &Path, json: bool) -> Result<()> {
+    let report = render(input, base, out_dir)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&to_json(&report))?);
+    } else {
+        log::info!(
+            "Report: {} ({} page(s)) → {}",
+            report.manifest.display(),
+            report.pages.len(),
+            report.html.display()
+        );
+        if let Some(base) = &report.base {
+            let counts: Vec<String> = report
+                .counts()
+                .iter()
+                .map(|(s, n)| format!("{} {}", n, s.as_str()))
+                .collect();
+            log::info!("  vs {}: {}", base.display(), counts.join(", "));
+        }
+        for p in &report.pages {
+            log::info!(
+                "  [{}] {}  {}x{}  {} sprites  occupancy {:.1}%  {} free rects",
+                p.index,
+                p.image,
+                p.width,
+                p.height,
+                p.sprites,
+                100.0 * p.occupancy,
+                p.free_rects
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Render the report for `input` (compared against `base` when given) into
+/// `out_dir`.
+pub fn render(input: &Path, base: Option<&Path>, out_dir: &Path) -> Result<Report> {
+    let (manifest_path, m) = load(input)?;
+    let dir = manifest_path.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let (base_path, diff) = match base {
+        Some(b) => {
+            let (path, a) = load(b)?;
+            let d = diff::compute(&a, &m);
+            (Some(path), Some((a, d)))
+        }
+        None => (None, None),
+    };
+    std::fs::create_dir_all(out_dir)?;
+
+    let statuses = diff.as_ref().map(|(_, d)| status_map(d)).unwrap_or_default();
+    let mut pages = Vec::with_capacity(m.atlases.len());
+    for (idx, atlas) in m.atlases.iter().enumerate() {
+        let mut outlines: Vec<Outline> = m
+            .sprites
+            .iter()
+            .filter(|(_, e)| e.atlas_idx == idx && e.alias_of.is_none())
+            .map(|(name, e)| {
+                let status = statuses.get(name.as_str()).copied().unwrap_or(Status::Unchanged);
+                let moved_from = diff.as_ref().and_then(|(a, _)| {
+                    let prev = a.sprites.get(name)?;
+                    (status == Status::Moved && prev.atlas_idx == idx)
+                        .then_some([prev.content_x, prev.content_y])
+                });
+                Outline {
+                    name: name.clone(),
+                    status,
+                    rect: [e.content_x, e.content_y, e.trimmed_size[0], e.trimmed_size[1]],
+                    moved_from,
+                }
+            })
+            .collect();
+        if let Some((a, d)) = &diff {
+            for name in &d.removed {
+                let Some(e) = a.sprites.get(name) else { continue };
+                if e.atlas_idx == idx && e.alias_of.is_none() {
+                    outlines.push(Outline {
+                        name: name.clone(),
+                        status: Status::Removed,
+                        rect: [e.content_x, e.content_y, e.trimmed_size[0], e.trimmed_size[1]],
+                        moved_from: None,
+                    });
+                }
+            }
+        }
+
+        let image = load_page(&dir, atlas);
+        let meshes = if atlas.format.starts_with("json") {
+            read_meshes(&dir.join(&atlas.data_filename).with_extension("json"))
+        } else {
+            Vec::new()
+        };
+        let mask = coverage(atlas, image.as_ref());
+        let used_pixels = mask.iter().filter(|c| **c).count() as u64;
+        let total = atlas.width as u64 * atlas.height as u64;
+
+        let mut overlay = image.unwrap_or_else(|| checkerboard(atlas.width, atlas.height));
+        let mesh_count = draw_overlay(&mut overlay, atlas, &outlines, &meshes);
+        let heatmap = draw_heatmap(atlas.width, atlas.height, &mask);
+
+        let overlay_path = out_dir.join(format!("page{}.png", idx));
+        let heatmap_path = out_dir.join(format!("page{}_heatmap.png", idx));
+        overlay.save(&overlay_path)?;
+        heatmap.save(&heatmap_path)?;
+
+        pages.push(ReportPage {
+            index: idx,
+            image: atlas.image_filename.clone(),
+            width: atlas.width,
+            height: atlas.height,
+            sprites: outlines.iter().filter(|o| o.status != Status::Removed).count(),
+            used_pixels,
+            occupancy: if total > 0 { used_pixels as f64 / total as f64 } else { 0.0 },
+            free_rects: atlas.free_rects.len(),
+            meshes: mesh_count,
+            outlines,
+            overlay: overlay_path,
+            heatmap: heatmap_path,
+        });
+    }
+
+    let report = Report {
+        manifest: manifest_path,
+        base: base_path,
+        diff: diff.map(|(_, d)| d),
+        pages,
+        html: out_dir.join("report.html"),
+    };
+    let html = to_html(&report)?;
+    std::fs::write(&report.html, html)?;
+    Ok(report)
+}
+
+fn status_map(d: &DiffReport) -> HashMap<&str, Status> {
+    let mut out = HashMap::new();
+    for n in &d.added {
+        out.insert(n.as_str(), Status::Added);
+    }
+    for n in d.pixel_change.iter().chain(d.resized.iter().map(|r| &r.name)) {
+        out.insert(n.as_str(), Status::Modified);
+    }
+    for mv in &d.moved {
+        out.insert(mv.name.as_str(), Status::Moved);
+    }
+    out
+}
+
+fn load_page(dir: &Path, atlas: &AtlasEntry) -> Option<RgbaImage> {
+    let path = dir.join(&atlas.image_filename);
+    match image::open(&path) {
+        Ok(img) if img.width() == atlas.width && img.height() == atlas.height => {
+            Some(img.into_rgba8())
+        }
+        Ok(_) => {
+            log::warn!("{} does not match the manifest size; drawing outlines only", path.display());
+            None
+        }
+        Err(e) => {
+            log::warn!("{}: {}; drawing outlines only", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Atlas-space mesh wireframes (`verticesUV` + `triangles`) from a JSON
+/// data file — the only format that carries them.
+fn read_meshes(data_path: &Path) -> Vec<Mesh> {
+    let Ok(text) = std::fs::read_to_string(data_path) else {
+        return Vec::new();
+    };
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) else {
+        return Vec::new();
+    };
+    let frames: Vec<&serde_json::Value> = match &json["frames"] {
+        serde_json::Value::Object(map) => map.values().collect(),
+        serde_json::Value::Array(arr) => arr.iter().collect(),
+        _ => return Vec::new(),
+    };
+    frames
+        .into_iter()
+        .filter_map(|f| {
+            let verts = serde_json::from_value::<Vec<[f32; 2]>>(f.get("verticesUV")?.clone()).ok()?;
+            let tris = serde_json::from_value::<Vec<[usize; 3]>>(f.get("triangles")?.clone()).ok()?;
+            Some((verts, tris))
+        })
+        .collect()
+}
+
+/// Pixels covered by a packed sprite — and opaque, when the page image is
+/// available. Row-major, `width * height`.
+fn coverage(atlas: &AtlasEntry, image: Option<&RgbaImage>) -> Vec<bool> {
+    let (w, h) = (atlas.width, atlas.height);
+    let mut mask = vec![false; w as usize * h as usize];
+    for r in &atlas.used_rects {
+        for y in r.y..(r.y + r.h).min(h) {
+            for x in r.x..(r.x + r.w).min(w) {
+                if image.is_none_or(|img| img.get_pixel(x, y)[3] > 0) {
+                    mask[(y * w + x) as usize] = true;
+                }
+            }
+        }
+    }
+    mask
+}
+
+fn checkerboard(w: u32, h: u32) -> RgbaImage {
+    RgbaImage::from_fn(w, h, |x, y| {
+        if (x / 8 + y / 8) % 2 == 0 {
+            Rgba([60, 60, 60, 255])
+        } else {
+            Rgba([80, 80, 80, 255])
+        }
+    })
+}
+
+fn blend(img: &mut RgbaImage, x: i64, y: i64, c: [u8; 4]) {
+    if x < 0 || y < 0 || x >= img.width() as i64 || y >= img.height() as i64 {
+        return;
+    }
+    let px = img.get_pixel_mut(x as u32, y as u32);
+    let a = c[3] as u32;
+    for i in 0..3 {
+        px[i] = ((c[i] as u32 * a + px[i] as u32 * (255 - a)) / 255) as u8;
+    }
+    px[3] = px[3].max(c[3]);
+}
+
+fn line(img: &mut RgbaImage, from: [f32; 2], to: [f32; 2], c: [u8; 4], dash: u32) {
+    let (mut x0, mut y0) = (from[0].round() as i64, from[1].round() as i64);
+    let (x1, y1) = (to[0].round() as i64, to[1].round() as i64);
+    let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+    let (sx, sy) = (if x0 < x1 { 1 } else { -1 }, if y0 < y1 { 1 } else { -1 });
+    let mut err = dx + dy;
+    let mut step = 0u32;
+    loop {
+        if dash == 0 || (step / dash).is_multiple_of(2) {
+            blend(img, x0, y0, c);
+        }
+        step += 1;
+        if x0 == x1 && y0 == y1 {
+            break;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x0 += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y0 += sy;
+        }
+    }
+}
+
+fn rect_outline(img: &mut RgbaImage, [x, y, w, h]: [u32; 4], c: [u8; 4], dash: u32) {
+    if w == 0 || h == 0 {
+        return;
+    }
+    let (l, t) = (x as f32, y as f32);
+    let (r, b) = ((x + w - 1) as f32, (y + h - 1) as f32);
+    line(img, [l, t], [r, t], c, dash);
+    line(img, [r, t], [r, b], c, dash);
+    line(img, [r, b], [l, b], c, dash);
+    line(img, [l, b], [l, t], c, dash);
+}
+
+/// Free rects, then meshes, then where removed / moved sprites used to be,
+/// then the current outlines (so their status colors stay on top).
+/// Returns the number of meshes drawn.
+fn draw_overlay(
+    img: &mut RgbaImage,
+    atlas: &AtlasEntry,
+    outlines: &[Outline],
+    meshes: &[Mesh],
+) -> usize {
+    for f in &atlas.free_rects {
+        for y in f.y..f.y + f.h {
+            for x in f.x..f.x + f.w {
+                blend(img, x as i64, y as i64, FREE_FILL);
+            }
+        }
+        rect_outline(img, [f.x, f.y, f.w, f.h], FREE_EDGE, 2);
+    }
+    let mut drawn = 0;
+    for (verts, tris) in meshes {
+        let mut ok = false;
+        for t in tris {
+            let [Some(a), Some(b), Some(c)] = t.map(|i| verts.get(i).copied()) else {
+                continue;
+            };
+            line(img, a, b, MESH, 0);
+            line(img, b, c, MESH, 0);
+            line(img, c, a, MESH, 0);
+            ok = true;
+        }
+        drawn += ok as usize;
+    }
+    for o in outlines {
+        if o.status == Status::Removed {
+            rect_outline(img, o.rect, o.status.color(), 3);
+        }
+        if let Some([fx, fy]) = o.moved_from {
+            let [x, y, w, h] = o.rect;
+            rect_outline(img, [fx, fy, w, h], o.status.color(), 3);
+            let center = |x: u32, y: u32| [x as f32 + w as f32 / 2.0, y as f32 + h as f32 / 2.0];
+            line(img, center(fx, fy), center(x, y), o.status.color(), 0);
+        }
+    }
+    for o in outlines.iter().filter(|o| o.status != Status::Removed) {
+        rect_outline(img, o.rect, o.status.color(), 0);
+    }
+    drawn
+}
+
+/// Red (empty) → yellow → green (full), per `HEATMAP_CELL` cell.
+fn heat_color(t: f64) -> Rgba<u8> {
+    const STOPS: [[f64; 3]; 3] = [[215.0, 48.0, 39.0], [254.0, 224.0, 139.0], [26.0, 152.0, 80.0]];
+    let t = t.clamp(0.0, 1.0) * 2.0;
+    let (lo, hi, f) = if t < 1.0 {
+        (STOPS[0], STOPS[1], t)
+    } else {
+        (STOPS[1], STOPS[2], t - 1.0)
+    };
+    let mix = |i: usize| (lo[i] + (hi[i] - lo[i]) * f).round() as u8;
+    Rgba([mix(0), mix(1), mix(2), 255])
+}
+
+fn draw_heatmap(w: u32, h: u32, mask: &[bool]) -> RgbaImage {
+    let cols = w.div_ceil(HEATMAP_CELL);
+    let rows = h.div_ceil(HEATMAP_CELL);
+    let mut fill = vec![0u32; (cols * rows) as usize];
+    for y in 0..h {
+        for x in 0..w {
+            if mask[(y * w + x) as usize] {
+                fill[((y / HEATMAP_CELL) * cols + x / HEATMAP_CELL) as usize] += 1;
+            }
+        }
+    }
+    RgbaImage::from_fn(w, h, |x, y| {
+        let (cx, cy) = (x / HEATMAP_CELL, y / HEATMAP_CELL);
+        let cw = HEATMAP_CELL.min(w - cx * HEATMAP_CELL);
+        let ch = HEATMAP_CELL.min(h - cy * HEATMAP_CELL);
+        let n = fill[(cy * cols + cx) as usize];
+        heat_color(n as f64 / (cw * ch) as f64)
+    })
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn data_uri(path: &Path) -> Result<String> {
+    Ok(format!("data:image/png;base64,{}", base64(&std::fs::read(path)?)))
+}
+
+fn to_html(report: &Report) -> Result<String> {
+    let mut h = String::new();
+    h.push_str("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">\n");
+    h.push_str(&format!("<title>mj_atlas report — {}</title>\n", escape(&report.manifest.display().to_string())));
+    h.push_str(
+        "<style>body{font-family:sans-serif;background:#1e1e1e;color:#ddd;margin:2em}\
+         table{border-collapse:collapse}td,th{border:1px solid #555;padding:2px 8px;text-align:left}\
+         img{image-rendering:pixelated;max-width:100%;border:1px solid #555;background:#333}\
+         .pair{display:flex;gap:1em;flex-wrap:wrap}.sw{display:inline-block;width:1em;height:1em;\
+         vertical-align:middle;margin:0 4px}</style>\n</head><body>\n",
+    );
+    h.push_str(&format!("<h1>{}</h1>\n", escape(&report.manifest.display().to_string())));
+
+    h.push_str("<p>");
+    for s in Status::ALL {
+        let [r, g, b, _] = s.color();
+        h.push_str(&format!("<span class=\"sw\" style=\"background:rgb({},{},{})\"></span>{} ", r, g, b, s.as_str()));
+    }
+    let [r, g, b, _] = FREE_EDGE;
+    h.push_str(&format!("<span class=\"sw\" style=\"background:rgb({},{},{})\"></span>free rect ", r, g, b));
+    let [r, g, b, _] = MESH;
+    h.push_str(&format!("<span class=\"sw\" style=\"background:rgb({},{},{})\"></span>polygon mesh</p>\n", r, g, b));
+
+    if let (Some(base), Some(d)) = (&report.base, &report.diff) {
+        h.push_str(&format!("<h2>Changes vs {}</h2>\n<table>\n", escape(&base.display().to_string())));
+        for (s, n) in report.counts() {
+            h.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", s.as_str(), n));
+        }
+        h.push_str(&format!(
+            "<tr><th>UV stable</th><td>{}</td></tr>\n<tr><th>options hash changed</th><td>{}</td></tr>\n</table>\n",
+            d.uv_stable, d.options_hash_changed
+        ));
+    }
+
+    h.push_str("<h2>Pages</h2>\n<table>\n<tr><th>#</th><th>image</th><th>size</th><th>sprites</th>\
+                <th>occupancy</th><th>free rects</th><th>meshes</th></tr>\n");
+    for p in &report.pages {
+        h.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}×{}</td><td>{}</td><td>{:.1}%</td><td>{}</td><td>{}</td></tr>\n",
+            p.index,
+            escape(&p.image),
+            p.width,
+            p.height,
+            p.sprites,
+            100.0 * p.occupancy,
+            p.free_rects,
+            p.meshes
+        ));
+    }
+    h.push_str("</table>\n");
+
+    for p in &report.pages {
+        h.push_str(&format!("<h3>[{}] {}</h3>\n<div class=\"pair\">\n", p.index, escape(&p.image)));
+        h.push_str(&format!("<img alt=\"page {}\" src=\"{}\">\n", p.index, data_uri(&p.overlay)?));
+        h.push_str(&format!("<img alt=\"page {} heatmap\" src=\"{}\">\n</div>\n", p.index, data_uri(&p.heatmap)?));
+        let changed: Vec<&Outline> = p.outlines.iter().filter(|o| o.status != Status::Unchanged).collect();
+        if !changed.is_empty() {
+            h.push_str("<ul>\n");
+            for o in changed {
+                h.push_str(&format!(
+                    "<li>{} <code>{}</code> at ({}, {})</li>\n",
+                    o.status.as_str(),
+                    escape(&o.name),
+                    o.rect[0],
+                    o.rect[1]
+                ));
+            }
+            h.push_str("</ul>\n");
+        }
+    }
+    h.push_str("</body></html>\n");
+    Ok(h)
+}
+
+fn to_json(report: &Report) -> serde_json::Value {
+    let mut out = serde_json::json!({
+        "status": "ok",
+        "manifest": report.manifest.display().to_string(),
+        "html": report.html.display().to_string(),
+        "pages": report.pages.iter().map(|p| serde_json::json!({
+            "index": p.index,
+            "image": p.image,
+            "size": {"w": p.width, "h": p.height},
+            "sprites": p.sprites,
+            "used_pixels": p.used_pixels,
+            "occupancy": p.occupancy,
+            "free_rects": p.free_rects,
+            "meshes": p.meshes,
+            "overlay": p.overlay.display().to_string(),
+            "heatmap": p.heatmap.display().to_string(),
+        })).collect::<Vec<_>>(),
+    });
+    if let Some(base) = &report.base {
+        out["base"] = base.display().to_string().into();
+        out["changes"] = report
+            .counts()
+            .into_iter()
+            .map(|(s, n)| (s.as_str().to_string(), serde_json::Value::from(n)))
+            .collect::<serde_json::Map<_, _>>()
+            .into();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pack::tests::{make_opts, write_png};
+    use crate::pack::{self, PackOptions};
+
+    fn pack_and_save(opts: &PackOptions) {
+        let results = pack::execute(opts).unwrap();
+        for r in &results {
+            r.save_to_disk(opts, opts.format).unwrap();
+        }
+        pack::persist_manifest(opts, &results).unwrap();
+    }
+
+    #[test]
+    fn base64_matches_rfc4648_vectors() {
+        for (raw, enc) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(base64(raw.as_bytes()), enc);
+        }
+    }
+
+    /// Incremental add / modify / remove, then a report against the old
+    /// manifest: statuses, status colors on the overlay, self-contained HTML.
+    #[test]
+    fn report_colors_sprites_by_diff_status() {
+        let tmp = std::env::temp_dir().join(format!("mj_atlas_report_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&tmp);
+        let input = tmp.join("in");
+        let out = tmp.join("out");
+        let base_dir = tmp.join("base");
+        std::fs::create_dir_all(&input).unwrap();
+        std::fs::create_dir_all(&base_dir).unwrap();
+
+        write_png(&input.join("keep.png"), 20, 20, [200, 0, 0, 255]);
+        write_png(&input.join("edit.png"), 16, 16, [0, 200, 0, 255]);
+        write_png(&input.join("gone.png"), 12, 12, [0, 0, 200, 255]);
+        let mut opts = make_opts(&input, &out, None);
+        opts.incremental = true;
+        pack_and_save(&opts);
+        let manifest_path = manifest::Manifest::path_for(&opts);
+        std::fs::copy(&manifest_path, base_dir.join("atlas.manifest.json")).unwrap();
+
+        write_png(&input.join("edit.png"), 16, 16, [0, 90, 90, 255]);
+        std::fs::remove_file(input.join("gone.png")).unwrap();
+        write_png(&input.join("new.png"), 10, 10, [90, 90, 0, 255]);
+        pack_and_save(&opts);
+
+        let report_dir = tmp.join("report");
+        let report = render(&manifest_path, Some(&base_dir), &report_dir).unwrap();
+        let counts: HashMap<Status, usize> = report.counts().into_iter().collect();
+        assert_eq!(counts[&Status::Added], 1);
+        assert_eq!(counts[&Status::Modified], 1);
+        assert_eq!(counts[&Status::Removed], 1);
+        assert_eq!(counts[&Status::Unchanged], 1);
+        assert_eq!(counts[&Status::Moved], 0);
+
+        let page = &report.pages[0];
+        assert_eq!(page.sprites, 3);
+        assert!(page.occupancy > 0.0 && page.occupancy <= 1.0);
+        let overlay = image::open(&page.overlay).unwrap().into_rgba8();
+        for o in &page.outlines {
+            let [x, y, _, _] = o.rect;
+            if o.status != Status::Removed {
+                assert_eq!(overlay.get_pixel(x, y).0, o.status.color(), "{}", o.name);
+            }
+        }
+        assert!(page.heatmap.is_file());
+
+        let html = std::fs::read_to_string(&report.html).unwrap();
+        assert!(html.contains("data:image/png;base64,"));
+        assert!(html.contains("new.png") && html.contains("gone.png"));
+        assert!(!html.contains("page0.png\""), "images must be embedded");
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+}
@@ -272,7 +272,7 @@ mod tests {
             file_size: 0, mtime: 0, content_hash: "h".into(),
             trim_offset: [0, 0], trimmed_size: [10, 10], source_size: [10, 10],
             polygon_hash: None, atlas_idx: 0, content_x: 0, content_y: 0,
-            rotated: false, alias_of: None,
+            rotated: false, alias_of: None, group: None, channel: None,
             tags: vec![], attribution: None, source_url: None,
         }
     }
@@ -287,8 +287,11 @@ mod tests {
             tool: "test".into(),
             options_hash: "h".into(),
             input_root: "/".into(),
+            layout_grid: 1,
             sprites,
             atlases: vec![],
+            animations: Default::default(),
+            premultiplied_alpha: false,
         }
     }
 
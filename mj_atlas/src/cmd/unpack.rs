@@ -0,0 +1,758 @@
+//! `mj_atlas unpack` — reconstruct individual sprite PNGs from an atlas.
+//!
+//! Reads any metadata mj_atlas can write (TexturePacker JSON hash / array,
+//! Godot `.tpsheet`, a Godot `.tres` AtlasTexture bundle), whether it came
+//! from mj_atlas or another packer, and writes every frame back out as a PNG
+//! at its original `sourceSize`: rotation is undone and trimmed borders are
+//! restored as transparent pixels.
+//!
+//! With `--manifest`, a manifest describing the *existing* layout is written
+//! next to the atlas, keyed to the unpacked tree. Repacking that tree with
+//! the printed `pack --incremental` command is then a cache hit, and later
+//! edits go through the UV-stable partial repack instead of reshuffling an
+//! inherited atlas.
+//!
+//! Rotation conventions: mj_atlas records the rotated (atlas-space) rect in
+//! `frame`; TexturePacker records the unrotated size. `meta.app` tells them
+//! apart. Both rotate 90° clockwise when packing.
+
+use crate::error::{AppError, Result};
+use crate::output::Format;
+use crate::pack::manifest::{self, AtlasEntry, Manifest, SpriteEntry, UsedRect};
+use crate::pack::alpha::{self, Channel};
+use crate::pack::{PackOptions, PolygonShape, IMAGE_EXTENSIONS};
+use image::RgbaImage;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+/// One frame as described by the metadata, normalized across formats.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub name: String,
+    /// Index into `SourceAtlas::pages`.
+    pub page: usize,
+    /// Region inside the atlas page, atlas space (swapped when rotated).
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+    /// Stored rotated 90° clockwise.
+    pub rotated: bool,
+    /// Position of the trimmed content inside the source canvas.
+    pub offset_x: u32,
+    pub offset_y: u32,
+    pub source_w: u32,
+    pub source_h: u32,
+    /// Channel holding this frame's mask (mj_atlas `--channel-pack`).
+    pub channel: Option<Channel>,
+}
+
+impl Frame {
+    /// Trimmed content size, unrotated.
+    pub fn content_size(&self) -> (u32, u32) {
+        if self.rotated {
+            (self.h, self.w)
+        } else {
+            (self.w, self.h)
+        }
+    }
+
+    fn trimmed(&self) -> bool {
+        let (w, h) = self.content_size();
+        self.offset_x != 0 || self.offset_y != 0 || (w, h) != (self.source_w, self.source_h)
+    }
+}
+
+#[derive(Debug)]
+pub struct SourceAtlas {
+    /// Atlas base name (metadata file stem, or `x` for an `x.sprites/` bundle).
+    pub name: String,
+    /// Directory holding the metadata — where `--manifest` writes.
+    pub dir: PathBuf,
+    pub format: Format,
+    pub pages: Vec<PathBuf>,
+    pub frames: Vec<Frame>,
+}
+
+/// Read atlas metadata: a `.json` / `.tpsheet` file, a Godot `.tres`
+/// AtlasTexture (its whole bundle is read), or a `.sprites/` bundle dir.
+pub fn read_source(input: &Path) -> Result<SourceAtlas> {
+    if input.is_dir() {
+        return read_tres_bundle(input);
+    }
+    let ext = input
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    if ext == "tres" {
+        let dir = input.parent().unwrap_or(Path::new("."));
+        return read_tres_bundle(dir);
+    }
+
+    let content = std::fs::read_to_string(input)?;
+    let json: Value = serde_json::from_str(&content)
+        .map_err(|e| AppError::Custom(format!("{}: JSON parse: {}", input.display(), e)))?;
+    let dir = input.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let name = input
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "atlas".into());
+    let (format, pages, frames) = if json.get("textures").is_some() {
+        parse_tpsheet(&json, &dir)?
+    } else if json.get("frames").is_some() {
+        parse_texturepacker_json(&json, &dir)?
+    } else {
+        return Err(AppError::Custom(format!(
+            "{}: not TexturePacker JSON or .tpsheet (no `frames` / `textures`)",
+            input.display()
+        )));
+    };
+    Ok(SourceAtlas {
+        name,
+        dir,
+        format,
+        pages,
+        frames,
+    })
+}
+
+fn num(v: &Value, field: &str, ctx: &str) -> Result<u32> {
+    v.get(field)
+        .and_then(Value::as_f64)
+        .map(|n| n as u32)
+        .ok_or_else(|| AppError::Custom(format!("{}: missing numeric `{}`", ctx, field)))
+}
+
+fn parse_texturepacker_json(json: &Value, dir: &Path) -> Result<(Format, Vec<PathBuf>, Vec<Frame>)> {
+    let image = json["meta"]["image"]
+        .as_str()
+        .ok_or_else(|| AppError::Custom("missing meta.image".into()))?;
+    let from_mj_atlas = json["meta"]["app"].as_str() == Some("mj_atlas");
+
+    let (format, entries): (Format, Vec<(String, &Value)>) = match &json["frames"] {
+        Value::Object(map) => (
+            Format::JsonHash,
+            map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        ),
+        Value::Array(list) => (
+            Format::JsonArray,
+            list.iter()
+                .map(|v| {
+                    let name = v["filename"]
+                        .as_str()
+                        .ok_or_else(|| AppError::Custom("frame without `filename`".into()))?;
+                    Ok((name.to_string(), v))
+                })
+                .collect::<Result<_>>()?,
+        ),
+        _ => return Err(AppError::Custom("`frames` must be an object or array".into())),
+    };
+
+    let mut frames = Vec::with_capacity(entries.len());
+    for (name, v) in entries {
+        let ctx = format!("frame '{}'", name);
+        let rect = &v["frame"];
+        let rotated = v["rotated"].as_bool().unwrap_or(false);
+        let (mut w, mut h) = (num(rect, "w", &ctx)?, num(rect, "h", &ctx)?);
+        if rotated && !from_mj_atlas {
+            std::mem::swap(&mut w, &mut h);
+        }
+        let sss = &v["spriteSourceSize"];
+        let source = &v["sourceSize"];
+        let content = if rotated { (h, w) } else { (w, h) };
+        frames.push(Frame {
+            name,
+            page: 0,
+            x: num(rect, "x", &ctx)?,
+            y: num(rect, "y", &ctx)?,
+            w,
+            h,
+            rotated,
+            offset_x: num(sss, "x", &ctx).unwrap_or(0),
+            offset_y: num(sss, "y", &ctx).unwrap_or(0),
+            source_w: num(source, "w", &ctx).unwrap_or(content.0),
+            source_h: num(source, "h", &ctx).unwrap_or(content.1),
+            channel: v["channel"].as_str().and_then(Channel::parse),
+        });
+    }
+    Ok((format, vec![dir.join(image)], frames))
+}
+
+fn parse_tpsheet(json: &Value, dir: &Path) -> Result<(Format, Vec<PathBuf>, Vec<Frame>)> {
+    let textures = json["textures"]
+        .as_array()
+        .ok_or_else(|| AppError::Custom("`textures` must be an array".into()))?;
+    let mut pages = Vec::new();
+    let mut frames = Vec::new();
+    for (page, tex) in textures.iter().enumerate() {
+        let image = tex["image"]
+            .as_str()
+            .ok_or_else(|| AppError::Custom(format!("texture {} has no `image`", page)))?;
+        pages.push(dir.join(image));
+        for s in tex["sprites"].as_array().into_iter().flatten() {
+            let name = s["filename"]
+                .as_str()
+                .ok_or_else(|| AppError::Custom("sprite without `filename`".into()))?
+                .to_string();
+            let ctx = format!("sprite '{}'", name);
+            let (region, margin) = (&s["region"], &s["margin"]);
+            let (w, h) = (num(region, "w", &ctx)?, num(region, "h", &ctx)?);
+            // margin.w/h = sourceSize - region size (region as stored).
+            frames.push(Frame {
+                page,
+                x: num(region, "x", &ctx)?,
+                y: num(region, "y", &ctx)?,
+                w,
+                h,
+                rotated: s["rotated"].as_bool().unwrap_or(false),
+                offset_x: num(margin, "x", &ctx).unwrap_or(0),
+                offset_y: num(margin, "y", &ctx).unwrap_or(0),
+                source_w: w + num(margin, "w", &ctx).unwrap_or(0),
+                source_h: h + num(margin, "h", &ctx).unwrap_or(0),
+                channel: s["channel"].as_str().and_then(Channel::parse),
+                name,
+            });
+        }
+    }
+    Ok((Format::GodotTpsheet, pages, frames))
+}
+
+/// Parse a directory of Godot AtlasTexture `.tres` files (`atlas.sprites/`).
+/// SpriteFrames and other resources in the directory are skipped.
+fn read_tres_bundle(dir: &Path) -> Result<SourceAtlas> {
+    let mut pages: Vec<PathBuf> = Vec::new();
+    let mut frames = Vec::new();
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("tres")))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let content = std::fs::read_to_string(&path)?;
+        if !content.contains("type=\"AtlasTexture\"") {
+            continue;
+        }
+        let ctx = path.display().to_string();
+        let res = content
+            .lines()
+            .find(|l| l.starts_with("[ext_resource") && l.contains("path=\""))
+            .and_then(|l| l.split("path=\"").nth(1)?.split('"').next())
+            .ok_or_else(|| AppError::Custom(format!("{}: no atlas ext_resource", ctx)))?;
+        let image = resolve_res_path(dir, res)
+            .ok_or_else(|| AppError::Custom(format!("{}: cannot find atlas image '{}'", ctx, res)))?;
+        let page = match pages.iter().position(|p| *p == image) {
+            Some(i) => i,
+            None => {
+                pages.push(image);
+                pages.len() - 1
+            }
+        };
+        let region = rect2(&content, "region")
+            .ok_or_else(|| AppError::Custom(format!("{}: missing `region`", ctx)))?;
+        let margin = rect2(&content, "margin").unwrap_or([0; 4]);
+        // mj_atlas writes `hero/walk_01.png` as `hero__walk_01.tres`.
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        frames.push(Frame {
+            name: format!("{}.png", stem.replace("__", "/")),
+            page,
+            x: region[0],
+            y: region[1],
+            w: region[2],
+            h: region[3],
+            rotated: false,
+            offset_x: margin[0],
+            offset_y: margin[1],
+            source_w: region[2] + margin[2],
+            source_h: region[3] + margin[3],
+            channel: None,
+        });
+    }
+    if frames.is_empty() {
+        return Err(AppError::Custom(format!(
+            "{}: no AtlasTexture .tres resources found",
+            dir.display()
+        )));
+    }
+
+    let dir_name = dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let name = dir_name
+        .strip_suffix(".sprites")
+        .unwrap_or(&dir_name)
+        .to_string();
+    Ok(SourceAtlas {
+        name,
+        dir: dir.parent().unwrap_or(Path::new(".")).to_path_buf(),
+        format: Format::GodotTres,
+        pages,
+        frames,
+    })
+}
+
+/// `key = Rect2(a, b, c, d)` → `[a, b, c, d]`.
+fn rect2(content: &str, key: &str) -> Option<[u32; 4]> {
+    let line = content
+        .lines()
+        .find(|l| l.split('=').next().is_some_and(|k| k.trim() == key))?;
+    let inner = line.split("Rect2(").nth(1)?.split(')').next()?;
+    let nums: Vec<u32> = inner
+        .split(',')
+        .map(|n| n.trim().parse::<f64>().map(|f| f as u32))
+        .collect::<std::result::Result<_, _>>()
+        .ok()?;
+    nums.try_into().ok()
+}
+
+/// `res://` is the Godot project root, which we don't know — try the path
+/// relative to each ancestor of the bundle, then the bare file name.
+fn resolve_res_path(bundle: &Path, res: &str) -> Option<PathBuf> {
+    let rel = res.strip_prefix("res://").unwrap_or(res);
+    let file = Path::new(rel).file_name()?;
+    bundle
+        .ancestors()
+        .flat_map(|a| [a.join(rel), a.join(file)])
+        .find(|p| p.is_file())
+}
+
+/// Cut one frame out of its page and restore it to source size.
+pub fn extract(page: &RgbaImage, f: &Frame) -> Result<RgbaImage> {
+    if f.x + f.w > page.width() || f.y + f.h > page.height() {
+        return Err(AppError::Custom(format!(
+            "frame '{}' ({}x{} at {},{}) lies outside its {}x{} atlas",
+            f.name,
+            f.w,
+            f.h,
+            f.x,
+            f.y,
+            page.width(),
+            page.height()
+        )));
+    }
+    let mut region = image::imageops::crop_imm(page, f.x, f.y, f.w, f.h).to_image();
+    if let Some(channel) = f.channel {
+        region = alpha::extract_channel(&region, channel);
+    }
+    let content = if f.rotated {
+        image::imageops::rotate270(&region)
+    } else {
+        region
+    };
+    // Tolerate metadata whose sourceSize is smaller than content + offset.
+    let w = f.source_w.max(f.offset_x + content.width());
+    let h = f.source_h.max(f.offset_y + content.height());
+    let mut out = RgbaImage::new(w, h);
+    image::imageops::overlay(&mut out, &content, f.offset_x as i64, f.offset_y as i64);
+    Ok(out)
+}
+
+/// Relative output path for a frame: same tree, always `.png`. Rejects names
+/// that would escape the output directory.
+fn output_name(name: &str) -> Result<String> {
+    let path = Path::new(name);
+    if !path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+    {
+        return Err(AppError::InvalidParam(format!(
+            "frame name '{}' is not a relative path inside the output directory",
+            name
+        )));
+    }
+    let is_image = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()));
+    let out = if is_image {
+        path.with_extension("png")
+    } else {
+        PathBuf::from(format!("{}.png", name))
+    };
+    Ok(out.to_string_lossy().replace('\\', "/"))
+}
+
+pub struct UnpackReport {
+    pub atlas: SourceAtlas,
+    /// `(frame name, written relative path)`, sorted by path.
+    pub written: Vec<(String, String)>,
+    pub manifest: Option<(PathBuf, Vec<String>)>,
+}
+
+/// Unpack `input` into `out_dir`; with `write_manifest`, also adopt the
+/// existing layout (see module docs). Returns what was written.
+pub fn unpack(input: &Path, out_dir: &Path, write_manifest: bool) -> Result<UnpackReport> {
+    let atlas = read_source(input)?;
+    if write_manifest && atlas.frames.iter().any(|f| f.channel.is_some()) {
+        // Stacked slots can't be expressed as adopted single-sprite rects.
+        return Err(AppError::InvalidParam(
+            "--manifest does not support channel-packed atlases; repack with --channel-pack".into(),
+        ));
+    }
+    let pages: Vec<RgbaImage> = atlas
+        .pages
+        .iter()
+        .map(|p| {
+            image::open(p)
+                .map(|i| i.into_rgba8())
+                .map_err(|e| AppError::Custom(format!("load '{}': {}", p.display(), e)))
+        })
+        .collect::<Result<_>>()?;
+
+    let mut written: Vec<(String, String)> = Vec::new();
+    let mut images: HashMap<String, RgbaImage> = HashMap::new();
+    for f in &atlas.frames {
+        let rel = output_name(&f.name)?;
+        if images.contains_key(&rel) {
+            return Err(AppError::Custom(format!(
+                "two frames map to the same output file '{}'",
+                rel
+            )));
+        }
+        let img = extract(&pages[f.page], f)?;
+        let path = out_dir.join(&rel);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        img.save(&path)
+            .map_err(|e| AppError::Custom(format!("write '{}': {}", path.display(), e)))?;
+        images.insert(rel.clone(), img);
+        written.push((f.name.clone(), rel));
+    }
+    written.sort_by(|a, b| a.1.cmp(&b.1));
+
+    let manifest = if write_manifest {
+        Some(adopt_layout(&atlas, &pages, &written, &images, out_dir)?)
+    } else {
+        None
+    };
+    Ok(UnpackReport {
+        atlas,
+        written,
+        manifest,
+    })
+}
+
+/// Pack options matching the inherited atlas as closely as the metadata
+/// allows: no spacing / padding / extrude (unknowable, and the recorded rects
+/// are exact), trim and rotate only when some frame uses them.
+pub fn adoption_options(atlas: &SourceAtlas, pages: &[RgbaImage], out_dir: &Path) -> PackOptions {
+    let largest = pages
+        .iter()
+        .map(|p| p.width().max(p.height()) as usize)
+        .max()
+        .unwrap_or(0);
+    PackOptions {
+        input_dir: out_dir.to_path_buf(),
+        output_name: atlas.name.clone(),
+        output_dir: atlas.dir.clone(),
+        max_size: largest.max(4096),
+        spacing: 0,
+        padding: 0,
+        extrude: 0,
+        trim: atlas.frames.iter().any(Frame::trimmed),
+        trim_threshold: 0,
+        rotate: atlas.frames.iter().any(|f| f.rotated),
+        pot: false,
+        recursive: true,
+        explicit_sprites: None,
+        incremental: true,
+        force: false,
+        format: atlas.format,
+        quantize: false,
+        quantize_quality: 85,
+        polygon: false,
+        tolerance: 2.0,
+        polygon_shape: PolygonShape::Concave,
+        max_vertices: 0,
+        scale_variants: Vec::new(),
+        groups: Vec::new(),
+        animations: None,
+        algorithm: Default::default(),
+        alpha_bleed: false,
+        premultiply_alpha: false,
+        channel_pack: false,
+    }
+}
+
+/// The `pack` invocation whose options hash matches [`adoption_options`].
+pub fn repack_command(opts: &PackOptions) -> Vec<String> {
+    let mut cmd: Vec<String> = [
+        "mj_atlas",
+        "pack",
+        &opts.input_dir.to_string_lossy(),
+        "-o",
+        &opts.output_name,
+        "--output-dir",
+        &opts.output_dir.to_string_lossy(),
+        "--incremental",
+        "--format",
+        opts.format.as_str(),
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+    if opts.trim {
+        cmd.push("--trim".into());
+    }
+    if opts.rotate {
+        cmd.push("--rotate".into());
+    }
+    if opts.max_size != 4096 {
+        cmd.extend(["--max-size".to_string(), opts.max_size.to_string()]);
+    }
+    cmd
+}
+
+/// Write a manifest recording the existing layout against the unpacked
+/// sprites. Frames sharing one region become aliases of the first.
+fn adopt_layout(
+    atlas: &SourceAtlas,
+    pages: &[RgbaImage],
+    written: &[(String, String)],
+    images: &HashMap<String, RgbaImage>,
+    out_dir: &Path,
+) -> Result<(PathBuf, Vec<String>)> {
+    let opts = adoption_options(atlas, pages, out_dir);
+    if atlas.pages.iter().any(|p| p.parent() != Some(atlas.dir.as_path())) {
+        return Err(AppError::Custom(
+            "--manifest needs the atlas image next to its metadata".into(),
+        ));
+    }
+
+    let frame_of: HashMap<&str, &Frame> = atlas.frames.iter().map(|f| (f.name.as_str(), f)).collect();
+    let mut canonical: HashMap<(usize, u32, u32, u32, u32), String> = HashMap::new();
+    let mut used: Vec<Vec<UsedRect>> = vec![Vec::new(); pages.len()];
+    let mut sprites = BTreeMap::new();
+    for (name, rel) in written {
+        let f = frame_of[name.as_str()];
+        let key = (f.page, f.x, f.y, f.w, f.h);
+        let alias_of = canonical.get(&key).cloned();
+        if alias_of.is_none() {
+            canonical.insert(key, rel.clone());
+            used[f.page].push(UsedRect {
+                name: rel.clone(),
+                x: f.x,
+                y: f.y,
+                w: f.w,
+                h: f.h,
+                rotated: f.rotated,
+            });
+        }
+        let (file_size, mtime) = manifest::file_fingerprint(&out_dir.join(rel))?;
+        let img = &images[rel];
+        sprites.insert(
+            rel.clone(),
+            SpriteEntry {
+                rel_path: rel.clone(),
+                file_size,
+                mtime,
+                content_hash: manifest::hash_pixels(img),
+                trim_offset: [f.offset_x, f.offset_y],
+                trimmed_size: [f.w, f.h],
+                source_size: [img.width(), img.height()],
+                polygon_hash: None,
+                atlas_idx: f.page,
+                content_x: f.x,
+                content_y: f.y,
+                rotated: f.rotated,
+                alias_of,
+                group: None,
+                channel: None,
+                tags: Vec::new(),
+                attribution: None,
+                source_url: None,
+            },
+        );
+    }
+
+    let atlases = atlas
+        .pages
+        .iter()
+        .zip(pages)
+        .zip(used)
+        .enumerate()
+        .map(|(idx, ((path, img), used_rects))| {
+            let rects: Vec<(u32, u32, u32, u32)> =
+                used_rects.iter().map(|r| (r.x, r.y, r.w, r.h)).collect();
+            Ok(AtlasEntry {
+                image_filename: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+                data_filename: if idx == 0 {
+                    atlas.name.clone()
+                } else {
+                    format!("{}_{}", atlas.name, idx)
+                },
+                width: img.width(),
+                height: img.height(),
+                image_hash: manifest::hash_file(path)?,
+                format: atlas.format.as_str().to_string(),
+                free_rects: manifest::compute_free_rects(img.width(), img.height(), &rects),
+                used_rects,
+                variants: Vec::new(),
+                groups: Vec::new(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let m = Manifest {
+        version: manifest::MANIFEST_VERSION,
+        tool: format!("mj_atlas {} (unpack)", env!("CARGO_PKG_VERSION")),
+        options_hash: manifest::compute_options_hash(&opts),
+        input_root: opts.input_dir.to_string_lossy().to_string(),
+        layout_grid: 1,
+        sprites,
+        atlases,
+        animations: BTreeMap::new(),
+        premultiplied_alpha: false,
+    };
+    let path = Manifest::path_for(&opts);
+    m.save(&path)?;
+    Ok((path, repack_command(&opts)))
+}
+
+pub fn run(input: &Path, out_dir: &Path, write_manifest: bool, json: bool) -> Result<()> {
+    let report = unpack(input, out_dir, write_manifest)?;
+    if json {
+        let mut out = serde_json::json!({
+            "status": "ok",
+            "source": input.display().to_string(),
+            "format": report.atlas.format.as_str(),
+            "pages": report.atlas.pages.iter().map(|p| p.display().to_string()).collect::<Vec<_>>(),
+            "output_dir": out_dir.display().to_string(),
+            "sprites": report.written.len(),
+            "files": report.written.iter().map(|(_, rel)| rel).collect::<Vec<_>>(),
+        });
+        if let Some((path, cmd)) = &report.manifest {
+            out["manifest"] = path.display().to_string().into();
+            out["repack_command"] = cmd.clone().into();
+        }
+        println!("{}", serde_json::to_string_pretty(&out)?);
+    } else {
+        log::info!(
+            "Unpacked {} sprites from {} ({}) into {}",
+            report.written.len(),
+            input.display(),
+            report.atlas.format.as_str(),
+            out_dir.display()
+        );
+        if let Some((path, cmd)) = &report.manifest {
+            log::info!("Wrote manifest {}", path.display());
+            log::info!("Repack incrementally with: {}", cmd.join(" "));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pack::tests::{make_opts, write_png};
+
+    /// Pack a trimmed + rotated atlas, unpack it, and check every sprite comes
+    /// back pixel-identical — then adopt the layout and confirm the printed
+    /// repack is a cache hit.
+    #[test]
+    fn round_trips_trim_and_rotation_and_adopts_layout() {
+        let tmp = std::env::temp_dir().join(format!(
+            "mj_atlas_unpack_test_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&tmp);
+        let input = tmp.join("in");
+        let packed = tmp.join("packed");
+        let unpacked = tmp.join("unpacked");
+        std::fs::create_dir_all(input.join("ui")).unwrap();
+
+        // A full-width block leaves a 40x16 strip, so the 6x40 pole only fits
+        // lying down; the panel has a transparent border to trim.
+        write_png(&input.join("block.png"), 40, 24, [9, 9, 9, 255]);
+        let mut pole = RgbaImage::new(6, 40);
+        for (x, y, px) in pole.enumerate_pixels_mut() {
+            *px = image::Rgba([x as u8 * 40, y as u8 * 6, 200, 255]);
+        }
+        pole.save(input.join("pole.png")).unwrap();
+        let mut panel = RgbaImage::new(20, 12);
+        for (x, y, px) in panel.enumerate_pixels_mut() {
+            if (3..15).contains(&x) && (2..9).contains(&y) {
+                *px = image::Rgba([x as u8 * 10, y as u8 * 20, 7, 255]);
+            }
+        }
+        panel.save(input.join("ui/panel.png")).unwrap();
+        let names = ["block.png", "pole.png", "ui/panel.png"];
+
+        // The Godot formats have no lossless way to express rotation.
+        for (format, rotate) in [
+            (Format::JsonHash, true),
+            (Format::JsonArray, true),
+            (Format::GodotTpsheet, false),
+            (Format::GodotTres, false),
+        ] {
+            let _ = std::fs::remove_dir_all(&packed);
+            let _ = std::fs::remove_dir_all(&unpacked);
+            let mut opts = make_opts(&input, &packed, None);
+            opts.trim = true;
+            opts.rotate = rotate;
+            opts.max_size = if rotate { 40 } else { 64 };
+            opts.format = format;
+            let results = crate::pack::execute(&opts).unwrap();
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].sprites.iter().any(|s| s.rotated), rotate);
+            results[0].save_to_disk(&opts, format).unwrap();
+
+            let meta = match format {
+                Format::GodotTpsheet => packed.join("atlas.tpsheet"),
+                Format::GodotTres => packed.join("atlas.sprites/ui__panel.tres"),
+                _ => packed.join("atlas.json"),
+            };
+            let report = unpack(&meta, &unpacked, false).unwrap();
+            assert_eq!(report.atlas.format, format);
+            assert_eq!(report.written.len(), names.len());
+            for name in names {
+                let before = image::open(input.join(name)).unwrap().into_rgba8();
+                let after = image::open(unpacked.join(name)).unwrap().into_rgba8();
+                assert_eq!(before, after, "{} differs after {:?} round trip", name, format);
+            }
+        }
+
+        // Adopt a JSON atlas from a "foreign" packer and repack the tree.
+        let _ = std::fs::remove_dir_all(&packed);
+        let _ = std::fs::remove_dir_all(&unpacked);
+        let mut opts = make_opts(&input, &packed, None);
+        opts.trim = true;
+        opts.rotate = true;
+        opts.max_size = 40;
+        let results = crate::pack::execute(&opts).unwrap();
+        results[0].save_to_disk(&opts, opts.format).unwrap();
+
+        let report = unpack(&packed.join("atlas.json"), &unpacked, true).unwrap();
+        let (_, cmd) = report.manifest.unwrap();
+        assert!(cmd.contains(&"--trim".to_string()));
+        let pages = vec![image::open(packed.join("atlas.png")).unwrap().into_rgba8()];
+        let adopted = adoption_options(&report.atlas, &pages, &unpacked);
+        let (_, path) = crate::pack::execute_with_path(&adopted).unwrap();
+        assert_eq!(path, crate::pack::PackPath::Skipped);
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn reads_texturepacker_rotation_and_rejects_escaping_names() {
+        let json: Value = serde_json::from_str(
+            r#"{"frames": {"a.png": {"frame": {"x": 0, "y": 0, "w": 4, "h": 2},
+                 "rotated": true, "trimmed": false,
+                 "spriteSourceSize": {"x": 0, "y": 0, "w": 4, "h": 2},
+                 "sourceSize": {"w": 4, "h": 2}}},
+               "meta": {"app": "https://www.codeandweb.com/texturepacker", "image": "a.png"}}"#,
+        )
+        .unwrap();
+        let (_, _, frames) = parse_texturepacker_json(&json, Path::new(".")).unwrap();
+        // TexturePacker: `frame` is the unrotated size, the atlas holds 2x4.
+        assert_eq!((frames[0].w, frames[0].h), (2, 4));
+        assert_eq!(frames[0].content_size(), (4, 2));
+
+        assert!(output_name("../evil.png").is_err());
+        assert!(output_name("/abs.png").is_err());
+        assert_eq!(output_name("hero/walk.jpg").unwrap(), "hero/walk.png");
+        assert_eq!(output_name("noext").unwrap(), "noext.png");
+    }
+}
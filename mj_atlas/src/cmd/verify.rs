@@ -1,6 +1,6 @@
 //! `mj_atlas verify` — re-hash on-disk artifacts and report divergence from the manifest.
 //!
-//! Verifies, per atlas:
+//! Verifies, per atlas (and per scale variant of each atlas):
 //!   - PNG file exists and its SHA256 matches the manifest's `image_hash`
 //!
 //! Verifies, per sprite (when `--check-sources` is set and the source file
@@ -59,29 +59,17 @@ pub fn run(input: &Path, check_sources: bool, json: bool) -> Result<()> {
     let manifest_dir = manifest_path.parent().unwrap_or(Path::new("."));
     let mut report = VerifyReport::default();
 
-    // Atlas PNG checks.
+    // Atlas PNG checks — scale variants are reported under their master's index.
     for (idx, atlas) in m.atlases.iter().enumerate() {
-        let atlas_path = manifest_dir.join(&atlas.image_filename);
-        if !atlas_path.is_file() {
-            report.atlas_issues.push(AtlasIssue {
-                atlas_idx: idx,
-                image_filename: atlas.image_filename.clone(),
-                kind: AtlasIssueKind::Missing,
-            });
-            continue;
-        }
-        let on_disk = manifest::hash_file(&atlas_path)?;
-        if on_disk != atlas.image_hash {
-            report.atlas_issues.push(AtlasIssue {
-                atlas_idx: idx,
-                image_filename: atlas.image_filename.clone(),
-                kind: AtlasIssueKind::HashMismatch {
-                    expected: atlas.image_hash.clone(),
-                    got: on_disk,
-                },
-            });
-        } else {
-            report.atlases_ok += 1;
+        check_atlas_png(&mut report, manifest_dir, idx, &atlas.image_filename, &atlas.image_hash)?;
+        for variant in &atlas.variants {
+            check_atlas_png(
+                &mut report,
+                manifest_dir,
+                idx,
+                &variant.image_filename,
+                &variant.image_hash,
+            )?;
         }
     }
 
@@ -153,6 +141,38 @@ pub fn run(input: &Path, check_sources: bool, json: bool) -> Result<()> {
     Ok(())
 }
 
+fn check_atlas_png(
+    report: &mut VerifyReport,
+    dir: &Path,
+    idx: usize,
+    image_filename: &str,
+    expected_hash: &str,
+) -> Result<()> {
+    let atlas_path = dir.join(image_filename);
+    if !atlas_path.is_file() {
+        report.atlas_issues.push(AtlasIssue {
+            atlas_idx: idx,
+            image_filename: image_filename.to_string(),
+            kind: AtlasIssueKind::Missing,
+        });
+        return Ok(());
+    }
+    let on_disk = manifest::hash_file(&atlas_path)?;
+    if on_disk != expected_hash {
+        report.atlas_issues.push(AtlasIssue {
+            atlas_idx: idx,
+            image_filename: image_filename.to_string(),
+            kind: AtlasIssueKind::HashMismatch {
+                expected: expected_hash.to_string(),
+                got: on_disk,
+            },
+        });
+    } else {
+        report.atlases_ok += 1;
+    }
+    Ok(())
+}
+
 impl VerifyReport {
     fn print_human(&self, path: &Path, any_issues: bool) {
         println!("Verify: {}", path.display());
@@ -0,0 +1,70 @@
+//! Minimal path globs, shared by build projects and sprite grouping rules.
+//!
+//! Patterns match `/`-separated sprite paths (relative to the input root):
+//! `*` and `?` stay within one path segment, `**` crosses segments, `{a,b}`
+//! alternates, and a pattern without `/` matches the file name at any depth.
+
+use crate::error::{AppError, Result};
+use regex::Regex;
+
+/// Translate a path glob into an anchored regex over `/`-separated paths.
+pub fn to_regex(pattern: &str) -> Result<Regex> {
+    let mut re = String::from("^");
+    if !pattern.contains('/') {
+        re.push_str("(?:.*/)?");
+    }
+    let chars: Vec<char> = pattern.trim_start_matches('/').chars().collect();
+    let mut i = 0;
+    let mut in_alt = false;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    re.push_str("(?:.*/)?");
+                    i += 3;
+                } else {
+                    re.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            '{' if !in_alt => {
+                re.push_str("(?:");
+                in_alt = true;
+            }
+            '}' if in_alt => {
+                re.push(')');
+                in_alt = false;
+            }
+            ',' if in_alt => re.push('|'),
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    if in_alt {
+        return Err(AppError::InvalidParam(format!("unclosed '{{' in glob '{}'", pattern)));
+    }
+    re.push('$');
+    Regex::new(&re).map_err(|e| AppError::InvalidParam(format!("bad glob '{}': {}", pattern, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs_follow_path_segments() {
+        let g = |p: &str| to_regex(p).unwrap();
+        assert!(g("*.png").is_match("a/b/c.png"));
+        assert!(g("hero/*.png").is_match("hero/idle.png"));
+        assert!(!g("hero/*.png").is_match("hero/walk/01.png"));
+        assert!(g("hero/**").is_match("hero/walk/01.png"));
+        assert!(g("**/_wip/**").is_match("_wip/x.png"));
+        assert!(g("**/_wip/**").is_match("a/_wip/x.png"));
+        assert!(g("{ui,hud}/?.png").is_match("hud/a.png"));
+        assert!(!g("{ui,hud}/?.png").is_match("fx/a.png"));
+        assert!(to_regex("{a,b").is_err());
+    }
+}
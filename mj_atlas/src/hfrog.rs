@@ -1,9 +1,9 @@
 //! Minimal hfrog artifact-registry client.
 //!
 //! Wraps the `PUT /artifactory/add_form_file` endpoint that hfrog exposes for
-//! multipart uploads (server: `hfrog/src/services/api/artifactory/`). The
-//! client is intentionally narrow — we only push artifacts; reads / queries
-//! are out of scope for this milestone.
+//! multipart uploads (server: `hfrog/src/services/api/artifactory/`), plus the
+//! two read endpoints the GUI cloud drive and `mj_atlas pull` need: the
+//! artifact `list` and the presigned-URL download.
 //!
 //! Failure semantics: every method returns `Result<()>` but mj_atlas wraps
 //! these calls in best-effort flushes — a failed upload is logged via the
@@ -13,6 +13,7 @@
 
 use crate::config::HfrogConfig;
 use crate::error::{AppError, Result};
+use crate::output::Format;
 use sha2::{Digest, Sha256};
 use std::path::Path;
 
@@ -79,6 +80,114 @@ impl Client {
         })
     }
 
+    /// GET with the bearer token attached (when configured).
+    fn get(&self, url: &str) -> reqwest::blocking::RequestBuilder {
+        let req = self.inner.get(url);
+        if self.token.is_empty() {
+            req
+        } else {
+            req.bearer_auth(&self.token)
+        }
+    }
+
+    /// One page of `GET /api/artifactory/list`, every runtime.
+    pub fn list_page(&self, index: usize, cnt: usize) -> Result<Vec<ArtifactInfo>> {
+        let url = format!(
+            "{}/api/artifactory/list?index={}&cnt={}",
+            self.endpoint, index, cnt
+        );
+        let resp = self
+            .get(&url)
+            .send()
+            .map_err(|e| AppError::Custom(format!("hfrog: list request: {}", e)))?;
+        if !resp.status().is_success() {
+            return Err(AppError::Custom(format!(
+                "hfrog: list HTTP {}",
+                resp.status()
+            )));
+        }
+        let body = resp
+            .text()
+            .map_err(|e| AppError::Custom(format!("hfrog: list body: {}", e)))?;
+
+        // Server response: `{"code":0,"msg":"","data":[ArtifactoryModel,...]}`
+        // `ArtifactInfo` declares only the fields we read, so unknown fields
+        // don't trip serde and a future schema change doesn't break us.
+        #[derive(serde::Deserialize)]
+        struct ListResp {
+            code: i32,
+            #[serde(default)]
+            msg: String,
+            #[serde(default)]
+            data: Vec<ArtifactInfo>,
+        }
+        let parsed: ListResp = serde_json::from_str(&body)
+            .map_err(|e| AppError::Custom(format!("hfrog: list parse: {} (body={})", e, body)))?;
+        if parsed.code != 0 {
+            return Err(AppError::Custom(format!(
+                "hfrog: list code={} msg={}",
+                parsed.code, parsed.msg
+            )));
+        }
+        Ok(parsed.data)
+    }
+
+    /// Every artifact on the server, page by page until a short page.
+    pub fn list_all(&self) -> Result<Vec<ArtifactInfo>> {
+        const PAGE: usize = 100;
+        let mut out = Vec::new();
+        for index in 0.. {
+            let page = self.list_page(index, PAGE)?;
+            let last = page.len() < PAGE;
+            out.extend(page);
+            if last {
+                break;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Download one artifact's bytes. Two HTTP calls: hfrog returns a
+    /// presigned S3 URL for `(name, ver, runtime)`, we GET that URL.
+    ///
+    /// When the server's S3 backend is misconfigured this fails at the first
+    /// hop (`get_object_presigned_url` returns code 1003 / 1004).
+    pub fn fetch(&self, name: &str, ver: &str, runtime: &str) -> Result<Vec<u8>> {
+        let url = format!(
+            "{}/api/artifactory/get_object_presigned_url?name={}&ver={}&runtime={}",
+            self.endpoint,
+            urlencode(name),
+            urlencode(ver),
+            urlencode(runtime)
+        );
+        let resp = self
+            .get(&url)
+            .send()
+            .map_err(|e| AppError::Custom(format!("hfrog: presign request: {}", e)))?;
+        let body = resp
+            .text()
+            .map_err(|e| AppError::Custom(format!("hfrog: presign body: {}", e)))?;
+
+        let presigned_url = parse_presigned_url(&body)?;
+
+        // Hop two: GET the S3 URL. No auth header — the URL is signed.
+        let s3_resp = self
+            .inner
+            .get(&presigned_url)
+            .send()
+            .map_err(|e| AppError::Custom(format!("hfrog: S3 fetch: {}", e)))?;
+        if !s3_resp.status().is_success() {
+            return Err(AppError::Custom(format!(
+                "hfrog: S3 fetch HTTP {}",
+                s3_resp.status()
+            )));
+        }
+        let bytes = s3_resp
+            .bytes()
+            .map_err(|e| AppError::Custom(format!("hfrog: S3 body: {}", e)))?;
+        Ok(bytes.to_vec())
+    }
+
     /// Push a single file's bytes plus its metadata to hfrog.
     ///
     /// `bytes` is consumed (moved into the multipart form). Returns Ok(()) on
@@ -173,6 +282,27 @@ struct RespRet {
     msg: String,
 }
 
+/// One row of the artifact list (a subset of hfrog's `ArtifactoryModel`).
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct ArtifactInfo {
+    /// Server-assigned id; increases with upload order.
+    #[serde(default)]
+    pub pid: i64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub ver: String,
+    #[serde(default)]
+    pub md5: String,
+    #[serde(default)]
+    pub cont_size: i64,
+    /// CHAR(20) on the server, so it comes back padded — compare trimmed.
+    #[serde(default)]
+    pub runtime: String,
+    #[serde(default)]
+    pub s3_key: String,
+}
+
 /// Two-runtime taxonomy on the hfrog side. mj_atlas v0.5+ registers both
 /// runtimes in the registry so cloud listings can be filtered cleanly:
 ///   - `mj_atlas-project`: the `.tpproj` project bundle (one per project)
@@ -193,6 +323,24 @@ fn runtime_for_kind(file_kind: &str) -> &'static str {
     }
 }
 
+/// The `md5` column value for `bytes`. Hfrog's column is exactly 32 chars —
+/// we hand it the first 32 hex chars of SHA-256 instead of pulling in an MD5
+/// dep. The collision space (16^32) is more than enough for asset dedup.
+pub fn content_md5(bytes: &[u8]) -> String {
+    let mut h = Sha256::new();
+    h.update(bytes);
+    h.finalize()
+        .iter()
+        .take(16)
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// The `s3_key` an mj_atlas upload of `filename` uses.
+pub fn s3_key_for(project_name: &str, ver: &str, filename: &str) -> String {
+    format!("mj_atlas/{}/{}/{}", project_name, ver, filename)
+}
+
 /// Build a fresh `ArtifactSpec` for a file destined for hfrog.
 ///
 /// `project_name` typically maps to the `.tpproj` basename. `file_kind` is a
@@ -209,27 +357,14 @@ pub fn build_spec(
     bytes: &[u8],
     ver: &str,
 ) -> ArtifactSpec {
-    let cont_size = bytes.len() as i64;
-    // Hfrog's `md5` column is exactly 32 chars — we hand it the first 32 hex
-    // chars of SHA-256 instead of pulling in an MD5 dep. The collision space
-    // (16^32) is more than enough for asset dedup.
-    let mut h = Sha256::new();
-    h.update(bytes);
-    let digest = h.finalize();
-    let md5 = digest
-        .iter()
-        .take(16)
-        .map(|b| format!("{:02x}", b))
-        .collect::<String>();
-
     ArtifactSpec {
         name: format!("{}.{}", project_name, file_kind),
         ver: ver.to_string(),
-        md5,
+        md5: content_md5(bytes),
         descript: format!("mj_atlas {} of '{}'", file_kind, project_name),
-        cont_size,
+        cont_size: bytes.len() as i64,
         runtime: runtime_for_kind(file_kind).to_string(),
-        s3_key: format!("mj_atlas/{}/{}/{}", project_name, ver, filename),
+        s3_key: s3_key_for(project_name, ver, filename),
         s3_inc_id: cfg.s3_inc_id as i32,
         is_artifactory_ready: false,
         is_raw: Some(false),
@@ -269,66 +404,11 @@ pub struct CloudProject {
 pub fn list_cloud_projects(cfg: &HfrogConfig) -> Result<Vec<CloudProject>> {
     let client = Client::from_config(cfg)
         .ok_or_else(|| AppError::Custom("hfrog: client inactive".into()))?;
-    let url = format!(
-        "{}/api/artifactory/list?index=0&cnt=50",
-        client.endpoint
-    );
-    let mut req = client.inner.get(&url);
-    if !client.token.is_empty() {
-        req = req.bearer_auth(&client.token);
-    }
-
-    let resp = req
-        .send()
-        .map_err(|e| AppError::Custom(format!("hfrog: list request: {}", e)))?;
-    if !resp.status().is_success() {
-        return Err(AppError::Custom(format!(
-            "hfrog: list HTTP {}",
-            resp.status()
-        )));
-    }
-    let body = resp
-        .text()
-        .map_err(|e| AppError::Custom(format!("hfrog: list body: {}", e)))?;
-
-    // Server response: `{"code":0,"msg":"","data":[ArtifactoryModel,...]}`
-    // We only need a few fields — declare a thin shape so unknown fields
-    // don't trip serde and a future schema change doesn't break us.
-    #[derive(serde::Deserialize)]
-    struct ListResp {
-        code: i32,
-        #[serde(default)]
-        msg: String,
-        #[serde(default)]
-        data: Vec<ListItem>,
-    }
-    #[derive(serde::Deserialize)]
-    struct ListItem {
-        #[serde(default)]
-        name: String,
-        #[serde(default)]
-        ver: String,
-        #[serde(default)]
-        md5: String,
-        #[serde(default)]
-        cont_size: i64,
-        #[serde(default)]
-        runtime: String,
-    }
-
-    let parsed: ListResp = serde_json::from_str(&body)
-        .map_err(|e| AppError::Custom(format!("hfrog: list parse: {} (body={})", e, body)))?;
-    if parsed.code != 0 {
-        return Err(AppError::Custom(format!(
-            "hfrog: list code={} msg={}",
-            parsed.code, parsed.msg
-        )));
-    }
+    let data = client.list_page(0, 50)?;
 
     // Filter to project-runtime entries. The server's `runtime` column is a
     // CHAR(20) so it pads with trailing spaces — we trim before comparing.
-    let mut projects: Vec<CloudProject> = parsed
-        .data
+    let mut projects: Vec<CloudProject> = data
         .into_iter()
         .filter(|it| it.runtime.trim() == RUNTIME_PROJECT)
         .filter(|it| it.name.ends_with(".tpproj"))
@@ -360,42 +440,7 @@ pub fn list_cloud_projects(cfg: &HfrogConfig) -> Result<Vec<CloudProject>> {
 pub fn download_project(cfg: &HfrogConfig, project: &CloudProject) -> Result<Vec<u8>> {
     let client = Client::from_config(cfg)
         .ok_or_else(|| AppError::Custom("hfrog: client inactive".into()))?;
-    let url = format!(
-        "{}/api/artifactory/get_object_presigned_url?name={}&ver={}&runtime={}",
-        client.endpoint,
-        urlencode(&project.name),
-        urlencode(&project.ver),
-        urlencode(RUNTIME_PROJECT)
-    );
-    let mut req = client.inner.get(&url);
-    if !client.token.is_empty() {
-        req = req.bearer_auth(&client.token);
-    }
-    let resp = req
-        .send()
-        .map_err(|e| AppError::Custom(format!("hfrog: presign request: {}", e)))?;
-    let body = resp
-        .text()
-        .map_err(|e| AppError::Custom(format!("hfrog: presign body: {}", e)))?;
-
-    let presigned_url = parse_presigned_url(&body)?;
-
-    // Hop two: GET the S3 URL. No auth header — the URL is signed.
-    let s3_resp = client
-        .inner
-        .get(&presigned_url)
-        .send()
-        .map_err(|e| AppError::Custom(format!("hfrog: S3 fetch: {}", e)))?;
-    if !s3_resp.status().is_success() {
-        return Err(AppError::Custom(format!(
-            "hfrog: S3 fetch HTTP {}",
-            s3_resp.status()
-        )));
-    }
-    let bytes = s3_resp
-        .bytes()
-        .map_err(|e| AppError::Custom(format!("hfrog: S3 body: {}", e)))?;
-    Ok(bytes.to_vec())
+    client.fetch(&project.name, &project.ver, RUNTIME_PROJECT)
 }
 
 fn parse_presigned_url(body: &str) -> Result<String> {
@@ -455,56 +500,77 @@ fn urlencode(s: &str) -> String {
 // individual-file level — one failed file shouldn't abort the rest of the
 // batch — and emit a single summary log line at the end.
 
-/// Push every artifact produced by a `pack::execute` + `save_to_disk` cycle
-/// to hfrog. No-op if the config is not active. Errors are logged but never
-/// returned to the caller — the local pack is the source of truth.
-pub fn mirror_pack_artifacts(
-    cfg: &HfrogConfig,
-    project_name: &str,
-    ver: &str,
-    output_dir: &Path,
+/// `(filename, file_kind)` of every artifact a pack run leaves in its output
+/// dir: each page's PNG and metadata (per scale variant), then the manifest
+/// and log. Page 0 of the master uses the plain `atlas-png` / `atlas-<ext>`
+/// kinds; other pages and variants append their filename suffix
+/// (`atlas-png_1`, `atlas-png@1x`) so every file gets its own hfrog `name` —
+/// `mj_atlas pull` fetches them one by one. Godot `.tres` bundles are a
+/// directory tree and aren't mirrored.
+pub fn pack_artifact_files(
     output_name: &str,
-    is_multi_bin: bool,
     bin_count: usize,
-) {
-    let client = match Client::from_config(cfg) {
-        Some(c) => c,
-        None => return,
+    variant_suffixes: &[String],
+    format: Format,
+) -> Vec<(String, String)> {
+    let data_ext = match format {
+        Format::JsonHash | Format::JsonArray => Some("json"),
+        Format::GodotTpsheet => Some("tpsheet"),
+        Format::GodotTres => None,
     };
-
-    // Each pack run may produce multiple atlas variants when sprites overflow
-    // a single bin. Mirror all of them — the s3_key includes the suffix so
-    // hfrog stores them as distinct artifacts under the same project/version.
-    let mut to_push: Vec<(String, &str)> = Vec::new();
-    if is_multi_bin && bin_count > 1 {
-        for i in 0..bin_count {
+    // Each pack run may produce multiple atlas pages when sprites overflow a
+    // single bin; scale variants (`atlas@1x.png`, …) follow the same naming.
+    let mut files = Vec::new();
+    for variant in std::iter::once("").chain(variant_suffixes.iter().map(|s| s.as_str())) {
+        for i in 0..bin_count.max(1) {
             let suffix = if i == 0 {
                 String::new()
             } else {
                 format!("_{}", i)
             };
-            to_push.push((format!("{}{}.png", output_name, suffix), "atlas-png"));
-            to_push.push((format!("{}{}.json", output_name, suffix), "atlas-json"));
+            let stem = format!("{}{}{}", output_name, variant, suffix);
+            files.push((format!("{}.png", stem), format!("atlas-png{}{}", variant, suffix)));
+            if let Some(ext) = data_ext {
+                files.push((
+                    format!("{}.{}", stem, ext),
+                    format!("atlas-{}{}{}", ext, variant, suffix),
+                ));
+            }
         }
-    } else {
-        to_push.push((format!("{}.png", output_name), "atlas-png"));
-        to_push.push((format!("{}.json", output_name), "atlas-json"));
     }
     // Manifest + log are per-pack, never per-bin.
-    to_push.push((format!("{}.manifest.json", output_name), "manifest"));
-    to_push.push((format!("{}.log", output_name), "log"));
+    files.push((format!("{}.manifest.json", output_name), "manifest".to_string()));
+    files.push((format!("{}.log", output_name), "log".to_string()));
+    files
+}
+
+/// Push every artifact produced by a `pack::execute` + `save_to_disk` cycle
+/// (`files` from [`pack_artifact_files`]) to hfrog. No-op if the config is
+/// not active. Errors are logged but never returned to the caller — the
+/// local pack is the source of truth.
+pub fn mirror_pack_artifacts(
+    cfg: &HfrogConfig,
+    project_name: &str,
+    ver: &str,
+    output_dir: &Path,
+    files: &[(String, String)],
+) {
+    let client = match Client::from_config(cfg) {
+        Some(c) => c,
+        None => return,
+    };
 
     let mut ok = 0usize;
     let mut failed = 0usize;
-    for (filename, kind) in to_push {
-        let path = output_dir.join(&filename);
+    for (filename, kind) in files {
+        let path = output_dir.join(filename);
         if !path.is_file() {
             // Non-fatal: incremental cache hits skip the manifest write, the
             // log might land elsewhere on early failure, etc.
             log::debug!("hfrog: skip {} (not found)", path.display());
             continue;
         }
-        match push_one(&client, cfg, project_name, kind, &filename, &path, ver) {
+        match push_one(&client, cfg, project_name, kind, filename, &path, ver) {
             Ok(()) => ok += 1,
             Err(e) => {
                 failed += 1;
@@ -657,6 +723,29 @@ mod tests {
         assert!(Client::from_config(&cfg).is_some());
     }
 
+    #[test]
+    fn pack_artifacts_get_one_name_per_file() {
+        let files = pack_artifact_files("ui", 2, &["@1x".to_string()], Format::GodotTpsheet);
+        let names: Vec<(&str, &str)> = files.iter().map(|(f, k)| (f.as_str(), k.as_str())).collect();
+        assert_eq!(
+            names,
+            [
+                ("ui.png", "atlas-png"),
+                ("ui.tpsheet", "atlas-tpsheet"),
+                ("ui_1.png", "atlas-png_1"),
+                ("ui_1.tpsheet", "atlas-tpsheet_1"),
+                ("ui@1x.png", "atlas-png@1x"),
+                ("ui@1x.tpsheet", "atlas-tpsheet@1x"),
+                ("ui@1x_1.png", "atlas-png@1x_1"),
+                ("ui@1x_1.tpsheet", "atlas-tpsheet@1x_1"),
+                ("ui.manifest.json", "manifest"),
+                ("ui.log", "log"),
+            ]
+        );
+        let tres = pack_artifact_files("ui", 1, &[], Format::GodotTres);
+        assert_eq!(tres.len(), 3, "no metadata for .tres bundles");
+    }
+
     #[test]
     fn mime_table_covers_atlas_outputs() {
         assert_eq!(mime_for("a.png"), "image/png");
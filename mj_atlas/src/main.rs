@@ -3,14 +3,17 @@ mod config;
 #[cfg(feature = "gui")]
 mod connection;
 mod error;
+mod glob;
 mod hfrog;
 mod output;
 mod pack;
 #[cfg(feature = "gui")]
 mod preview;
+mod project;
 mod runlog;
+mod watch;
 
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use pack::PolygonShape;
 use error::Result;
 use std::path::PathBuf;
@@ -80,141 +83,56 @@ enum Commands {
             Files matching pattern `<name>_<number>.<ext>` (e.g. walk_01.png, walk_02.png)\n  \
             are automatically grouped into animation sequences in the metadata."
     )]
-    Pack {
-        /// Directory containing sprite images to pack.
-        /// All PNG/JPG/BMP/GIF/TGA/WebP files are included.
-        /// Subdirectories are scanned recursively by default.
-        #[arg(value_name = "INPUT_DIR")]
-        input: PathBuf,
-
-        /// Base filename for output atlas (without extension).
-        /// The atlas image will be <name>.png, metadata will be <name>.json (or .tpsheet/.tres).
-        /// For multi-atlas output, files are suffixed: atlas.png, atlas_1.png, atlas_2.png, ...
-        #[arg(short, long, value_name = "NAME", default_value = "atlas")]
-        output: String,
-
-        /// Directory where output files are written.
-        /// Defaults to the same directory as INPUT_DIR.
-        #[arg(short = 'd', long, value_name = "DIR")]
-        output_dir: Option<PathBuf>,
-
-        /// Maximum atlas width/height in pixels.
-        /// If all sprites don't fit, the packer auto-splits into multiple atlases.
-        /// Common values: 1024 (mobile), 2048 (standard), 4096 (high-end).
-        #[arg(long, default_value = "4096", value_name = "PIXELS")]
-        max_size: usize,
-
-        /// Gap between sprites in the atlas, in pixels.
-        /// Use 1-2 to prevent visual artifacts from bilinear filtering.
-        #[arg(long, default_value = "0", value_name = "PIXELS")]
-        spacing: u32,
-
-        /// Inner padding added around each sprite, in pixels.
-        /// Extends the sprite's allocated rectangle without affecting the content.
-        #[arg(long, default_value = "0", value_name = "PIXELS")]
-        padding: u32,
-
-        /// Repeat edge pixels outward by N pixels to prevent texture bleeding.
-        /// Essential for tiled/seamless textures. Recommended: 1-2.
-        /// The extruded pixels are NOT included in the sprite's frame rectangle in metadata.
-        #[arg(long, default_value = "0", value_name = "PIXELS")]
-        extrude: u32,
-
-        /// Remove transparent border pixels from each sprite before packing.
-        /// Significantly reduces atlas size for sprites with large transparent areas.
-        /// Original sprite dimensions are preserved in metadata (sourceSize field).
-        /// The trim offset is recorded in metadata (spriteSourceSize field).
-        #[arg(long)]
-        trim: bool,
+    Pack(PackArgs),
 
-        /// Allow 90-degree clockwise rotation for tighter packing.
-        /// When a sprite is rotated, metadata includes "rotated": true.
-        /// The game engine must handle rotation when rendering.
-        #[arg(long)]
-        rotate: bool,
+    /// Watch INPUT_DIR and re-run an incremental pack whenever sprites change.
+    /// Takes every `pack` option; --incremental is always on.
+    #[command(
+        long_about = "Watch INPUT_DIR and re-run an incremental pack on every change.\n\n\
+            Change bursts (an editor saving several files, a git checkout) are\n\
+            debounced into a single run. Each run goes through the same incremental\n\
+            pipeline as `pack --incremental` — cache hit, UV-stable partial repack, or\n\
+            full repack — and reports what happened.\n\n\
+            With --json every run prints one JSON object per line (NDJSON) on stdout:\n  \
+            {\"event\": \"pack\", \"run\": 1, \"path\": \"partial\", \"added\": [...],\n   \
+            \"modified\": [...], \"removed\": [...], \"uv_stable\": true, ...}\n\n\
+            A failed run prints {\"event\": \"error\", ...} and watching continues.\n\
+            Stop with Ctrl-C."
+    )]
+    Watch {
+        #[command(flatten)]
+        pack: PackArgs,
 
-        /// Force atlas dimensions to be power-of-2 (e.g., 256, 512, 1024, 2048, 4096).
-        /// Required by some older GPU hardware and certain game engines.
-        #[arg(long)]
-        pot: bool,
-
-        /// Output metadata format. Determines the file extension and data structure.
-        /// json = TexturePacker JSON Hash (.json) — universal, default.
-        /// json-array = TexturePacker JSON Array (.json) — frame list instead of map.
-        /// godot-tpsheet = Godot .tpsheet — import with TexturePacker Godot plugin.
-        /// godot-tres = Godot native .tres — generates AtlasTexture + SpriteFrames, zero plugin.
-        #[arg(long, value_enum, default_value = "json", value_name = "FORMAT")]
-        format: OutputFormat,
-
-        /// Scan subdirectories recursively for images.
-        #[arg(long, default_value = "true")]
-        recursive: bool,
-
-        /// Enable incremental packing. Reads `<output>.manifest.json` next to
-        /// the atlas and skips work for unchanged inputs:
-        ///   - All inputs unchanged + matching options ⇒ skip everything (fast cache hit)
-        ///   - Pure additions / in-place pixel edits ⇒ partial repack with UV stability
-        ///   - Removed sprites or resized sprites ⇒ partial repack (UV-stable, no compaction)
-        ///   - Anything that breaks the layout (atlas would need to grow) ⇒ full repack
-        /// Sprites that did NOT change keep their exact `(x, y, rotated)` across runs,
-        /// so already-deployed game code can drop in a new atlas without rebaking UVs.
-        #[arg(long)]
-        incremental: bool,
+        /// Quiet period before a run starts, in milliseconds. Every new change
+        /// inside the window restarts it, so a burst of saves packs once.
+        #[arg(long, default_value = "300", value_name = "MS")]
+        debounce: u64,
+    },
 
-        /// Force a full repack even when the incremental cache would hit.
-        /// Use this when you suspect the manifest is corrupt or want to verify
-        /// determinism. Has no effect without --incremental.
+    /// Build every atlas declared in a project file (TOML or JSON).
+    /// Atlases are packed in parallel, each incrementally, and reported together.
+    #[command(
+        long_about = "Build every atlas declared in a project file.\n\n\
+            The project (`.toml`, or JSON for any other extension) lists atlases, each\n\
+            with an input root, include / exclude globs and its own pack options, plus\n\
+            shared [defaults]. See llms.txt for the schema.\n\n\
+            Atlases build in parallel and incrementally (manifest sidecar per atlas).\n\
+            With --json one aggregated report is printed; the command exits non-zero\n\
+            if any atlas failed, after building all the others."
+    )]
+    Build {
+        /// Path to the project file.
+        #[arg(value_name = "PROJECT")]
+        project: PathBuf,
+        /// Build only these atlases (comma-separated names).
+        #[arg(long, value_name = "NAMES", value_delimiter = ',')]
+        only: Vec<String>,
+        /// Ignore incremental caches and repack every atlas from scratch.
         #[arg(long)]
         force: bool,
-
-        /// Alpha threshold for trim. Pixels with alpha <= this value are considered transparent.
-        /// 0 = only fully transparent pixels are trimmed (default).
-        /// Higher values trim semi-transparent edges.
-        #[arg(long, default_value = "0", value_name = "0-255")]
-        trim_threshold: u8,
-
-        /// Enable lossy PNG quantization (imagequant).
-        /// Reduces atlas PNG file size by ~60-70% with minimal visual quality loss.
-        /// Uses palette-based encoding (256 colors max with dithering).
-        /// Note: imagequant is licensed under GPL-3.0.
-        #[arg(long)]
-        quantize: bool,
-
-        /// Quality level for PNG quantization. Lower = smaller file, more artifacts.
-        /// 100 = best quality. 60-85 = good balance. Below 40 = noticeable artifacts.
-        #[arg(long, default_value = "85", value_name = "1-100")]
-        quantize_quality: u8,
-
-        /// Enable polygon mesh mode. For each sprite, outputs:
-        /// - vertices: polygon contour in sprite-local coordinates
-        /// - verticesUV: corresponding atlas UV coordinates
-        /// - triangles: earcut triangulation indices
-        /// Game engines can render only the non-transparent polygon instead of the full
-        /// rectangle, reducing GPU overdraw by 30%+ for irregularly shaped sprites.
-        #[arg(long)]
-        polygon: bool,
-
-        /// Polygon contour simplification tolerance (Douglas-Peucker algorithm).
-        /// Lower = tighter fit to sprite outline, more vertices, less overdraw.
-        /// Higher = coarser outline, fewer vertices, more overdraw.
-        /// Recommended: 1.0 (tight) to 4.0 (coarse). Default: 2.0.
-        #[arg(long, default_value = "2.0", value_name = "TOLERANCE")]
-        tolerance: f32,
-
-        /// Polygon shape model. Controls how each connected component is meshed.
-        /// concave (default) — keep the simplified outline; tightest fit, most vertices.
-        /// convex — replace each component with its convex hull; few vertices, may overdraw.
-        /// auto — pick convex when concave-area / hull-area ≥ 0.85, else concave.
-        #[arg(long, value_enum, default_value = "concave", value_name = "MODE")]
-        polygon_shape: PolygonShapeArg,
-
-        /// Maximum total vertex count per sprite (across all components).
-        /// 0 (default) disables the budget — uses --tolerance as-is.
-        /// >0 enables iterative tolerance escalation (×1.5 per round, max 8 rounds)
-        /// until the total vertex count fits the budget. Useful for hard
-        /// per-frame draw call budgets on mobile/web.
-        #[arg(long, default_value = "0", value_name = "N")]
-        max_vertices: u32,
+        /// Maximum number of atlases built at once. Default: one per CPU.
+        #[arg(long, value_name = "N")]
+        jobs: Option<usize>,
     },
 
     /// Launch the interactive GUI application.
@@ -278,6 +196,51 @@ enum Commands {
         /// Second (newer) manifest.
         #[arg(value_name = "B")]
         b: PathBuf,
+        /// Print only the move set: relocated sprites plus a summary
+        /// (canonical vs alias counts, page-to-page transfers, page count).
+        #[arg(long)]
+        moves: bool,
+    },
+
+    /// Render a packed atlas as a static HTML report plus PNGs: sprite
+    /// outlines (colored by diff status with --base), polygon meshes, free
+    /// rects and an occupancy heatmap. Headless — no GUI feature needed.
+    Report {
+        /// Manifest to render. Same path-resolution rules as `inspect`.
+        #[arg(value_name = "ATLAS_OR_MANIFEST")]
+        input: PathBuf,
+        /// Older manifest to diff against: outlines become added / modified /
+        /// moved / unchanged, and removed sprites are drawn dashed.
+        #[arg(long, value_name = "ATLAS_OR_MANIFEST")]
+        base: Option<PathBuf>,
+        /// Directory for report.html and the page PNGs.
+        #[arg(short, long, value_name = "DIR")]
+        output: PathBuf,
+    },
+
+    /// Restore a published atlas from hfrog (PNGs, metadata, manifest) so
+    /// `pack --incremental` can resume from it with the published UVs.
+    #[command(long_about = "Download an atlas that `pack` mirrored to hfrog — the \
+            newest version, or --ver — and place its page PNGs, metadata and manifest \
+            in --output-dir. Every file is checked against the hash recorded at upload \
+            and every PNG against the manifest before anything is written.\n\n\
+            Then run `pack <input> -o <project> --output-dir <dir> --incremental` with \
+            the publisher's options: unchanged sprites keep their UVs. Uses the hfrog \
+            settings from config.toml.")]
+    Pull {
+        /// hfrog project name: the `-o` name the atlas was packed with.
+        #[arg(value_name = "PROJECT")]
+        project: String,
+        /// Version to restore (default: the newest upload).
+        #[arg(long, value_name = "VER")]
+        ver: Option<String>,
+        /// Directory to write the atlas files into.
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        output_dir: PathBuf,
+        /// Local sprite directory; recorded in the manifest so the next pack
+        /// with this input is recognized as the same tree.
+        #[arg(long, value_name = "DIR")]
+        input: Option<PathBuf>,
     },
 
     /// Verify that on-disk artifacts match the manifest's hashes.
@@ -329,6 +292,392 @@ enum Commands {
         #[arg(long)]
         list: bool,
     },
+
+    /// Defragment an incremental atlas by moving a bounded set of sprites.
+    /// Takes the same pack options as `pack`; reports which UVs moved.
+    #[command(
+        long_about = "Compact the layout recorded in <output>.manifest.json.\n\n\
+            Incremental packs never move a sprite, so removals and resizes leave holes \
+            that eventually force a full repack (every UV moves). `compact` first syncs \
+            the atlas with INPUT_DIR (an ordinary incremental pack), then slides sprites \
+            towards the top-left of the earliest page they may use, so free space merges \
+            and emptied trailing pages are deleted.\n\n\
+            Every moved sprite is a UV break, so the move set is bounded:\n  \
+            --max-moves N    move at most N sprites (aliases follow their canonical)\n  \
+            --only-tag TAG   only consider sprites carrying TAG\n  \
+            --pinned-tag TAG never move sprites carrying TAG (default: pinned)\n\n\
+            Pass the same pack options as the original pack; a mismatch is a full \
+            repack, not a compaction. Compare snapshots with `mj_atlas diff --moves`."
+    )]
+    Compact {
+        #[command(flatten)]
+        pack: PackArgs,
+
+        /// Maximum number of sprites to relocate. Unlimited when omitted.
+        #[arg(long, value_name = "N")]
+        max_moves: Option<usize>,
+
+        /// Only relocate sprites carrying this tag (see `mj_atlas tag`).
+        #[arg(long, value_name = "TAG")]
+        only_tag: Option<String>,
+
+        /// Sprites carrying this tag never move.
+        #[arg(long, value_name = "TAG", default_value = pack::compact::DEFAULT_PINNED_TAG)]
+        pinned_tag: String,
+    },
+
+    /// Reconstruct individual sprite PNGs from an existing atlas.
+    /// Undoes rotation and trim (sprites get their original sourceSize back).
+    #[command(
+        long_about = "Read atlas metadata and write every frame back out as a PNG, \
+            restoring the original size (rotation undone, trimmed borders as transparent \
+            pixels). Works on atlases from mj_atlas or other packers.\n\n\
+            Accepted inputs:\n  \
+            - TexturePacker JSON hash / array (.json)\n  \
+            - Godot TexturePacker plugin sheet (.tpsheet)\n  \
+            - Godot AtlasTexture bundle (any .tres in it, or the .sprites/ dir)\n\n\
+            With --manifest, also writes <atlas>.manifest.json next to the atlas, recording \
+            its current layout against the unpacked sprites, and prints the `pack \
+            --incremental` command that picks it up: unchanged sprites keep their UVs."
+    )]
+    Unpack {
+        /// Atlas metadata (.json / .tpsheet / .tres) or a .sprites/ directory.
+        #[arg(value_name = "ATLAS_METADATA")]
+        input: PathBuf,
+        /// Directory to write the sprite tree into.
+        #[arg(short, long, value_name = "DIR")]
+        output: PathBuf,
+        /// Also write a manifest adopting the existing layout, so the
+        /// unpacked tree can be repacked incrementally right away.
+        #[arg(long)]
+        manifest: bool,
+    },
+}
+
+/// Options shared by `pack`, `watch` and `compact`.
+#[derive(Args)]
+struct PackArgs {
+    /// Directory containing sprite images to pack.
+    /// All PNG/JPG/BMP/GIF/TGA/WebP files are included.
+    /// Subdirectories are scanned recursively by default.
+    #[arg(value_name = "INPUT_DIR")]
+    input: PathBuf,
+
+    /// Base filename for output atlas (without extension).
+    /// The atlas image will be <name>.png, metadata will be <name>.json (or .tpsheet/.tres).
+    /// For multi-atlas output, files are suffixed: atlas.png, atlas_1.png, atlas_2.png, ...
+    #[arg(short, long, value_name = "NAME", default_value = "atlas")]
+    output: String,
+
+    /// Directory where output files are written.
+    /// Defaults to the same directory as INPUT_DIR.
+    #[arg(short = 'd', long, value_name = "DIR")]
+    output_dir: Option<PathBuf>,
+
+    /// Maximum atlas width/height in pixels.
+    /// If all sprites don't fit, the packer auto-splits into multiple atlases.
+    /// Common values: 1024 (mobile), 2048 (standard), 4096 (high-end).
+    #[arg(long, default_value = "4096", value_name = "PIXELS")]
+    max_size: usize,
+
+    /// Gap between sprites in the atlas, in pixels.
+    /// Use 1-2 to prevent visual artifacts from bilinear filtering.
+    #[arg(long, default_value = "0", value_name = "PIXELS")]
+    spacing: u32,
+
+    /// Inner padding added around each sprite, in pixels.
+    /// Extends the sprite's allocated rectangle without affecting the content.
+    #[arg(long, default_value = "0", value_name = "PIXELS")]
+    padding: u32,
+
+    /// Repeat edge pixels outward by N pixels to prevent texture bleeding.
+    /// Essential for tiled/seamless textures. Recommended: 1-2.
+    /// The extruded pixels are NOT included in the sprite's frame rectangle in metadata.
+    #[arg(long, default_value = "0", value_name = "PIXELS")]
+    extrude: u32,
+
+    /// Remove transparent border pixels from each sprite before packing.
+    /// Significantly reduces atlas size for sprites with large transparent areas.
+    /// Original sprite dimensions are preserved in metadata (sourceSize field).
+    /// The trim offset is recorded in metadata (spriteSourceSize field).
+    #[arg(long)]
+    trim: bool,
+
+    /// Allow 90-degree clockwise rotation for tighter packing.
+    /// When a sprite is rotated, metadata includes "rotated": true.
+    /// The game engine must handle rotation when rendering.
+    #[arg(long)]
+    rotate: bool,
+
+    /// Force atlas dimensions to be power-of-2 (e.g., 256, 512, 1024, 2048, 4096).
+    /// Required by some older GPU hardware and certain game engines.
+    #[arg(long)]
+    pot: bool,
+
+    /// Output metadata format. Determines the file extension and data structure.
+    /// json = TexturePacker JSON Hash (.json) — universal, default.
+    /// json-array = TexturePacker JSON Array (.json) — frame list instead of map.
+    /// godot-tpsheet = Godot .tpsheet — import with TexturePacker Godot plugin.
+    /// godot-tres = Godot native .tres — generates AtlasTexture + SpriteFrames, zero plugin.
+    #[arg(long, value_enum, default_value = "json", value_name = "FORMAT")]
+    format: OutputFormat,
+
+    /// Scan subdirectories recursively for images.
+    #[arg(long, default_value = "true")]
+    recursive: bool,
+
+    /// Enable incremental packing. Reads `<output>.manifest.json` next to
+    /// the atlas and skips work for unchanged inputs:
+    ///   - All inputs unchanged + matching options ⇒ skip everything (fast cache hit)
+    ///   - Pure additions / in-place pixel edits ⇒ partial repack with UV stability
+    ///   - Removed sprites or resized sprites ⇒ partial repack (UV-stable, no compaction)
+    ///   - Anything that breaks the layout (atlas would need to grow) ⇒ full repack
+    /// Sprites that did NOT change keep their exact `(x, y, rotated)` across runs,
+    /// so already-deployed game code can drop in a new atlas without rebaking UVs.
+    #[arg(long)]
+    incremental: bool,
+
+    /// Force a full repack even when the incremental cache would hit.
+    /// Use this when you suspect the manifest is corrupt or want to verify
+    /// determinism. Has no effect without --incremental.
+    #[arg(long)]
+    force: bool,
+
+    /// Alpha threshold for trim. Pixels with alpha <= this value are considered transparent.
+    /// 0 = only fully transparent pixels are trimmed (default).
+    /// Higher values trim semi-transparent edges.
+    #[arg(long, default_value = "0", value_name = "0-255")]
+    trim_threshold: u8,
+
+    /// Enable lossy PNG quantization (imagequant).
+    /// Reduces atlas PNG file size by ~60-70% with minimal visual quality loss.
+    /// Uses palette-based encoding (256 colors max with dithering).
+    /// Note: imagequant is licensed under GPL-3.0.
+    #[arg(long)]
+    quantize: bool,
+
+    /// Quality level for PNG quantization. Lower = smaller file, more artifacts.
+    /// 100 = best quality. 60-85 = good balance. Below 40 = noticeable artifacts.
+    #[arg(long, default_value = "85", value_name = "1-100")]
+    quantize_quality: u8,
+
+    /// Enable polygon mesh mode. For each sprite, outputs:
+    /// - vertices: polygon contour in sprite-local coordinates
+    /// - verticesUV: corresponding atlas UV coordinates
+    /// - triangles: earcut triangulation indices
+    /// Game engines can render only the non-transparent polygon instead of the full
+    /// rectangle, reducing GPU overdraw by 30%+ for irregularly shaped sprites.
+    #[arg(long)]
+    polygon: bool,
+
+    /// Polygon contour simplification tolerance (Douglas-Peucker algorithm).
+    /// Lower = tighter fit to sprite outline, more vertices, less overdraw.
+    /// Higher = coarser outline, fewer vertices, more overdraw.
+    /// Recommended: 1.0 (tight) to 4.0 (coarse). Default: 2.0.
+    #[arg(long, default_value = "2.0", value_name = "TOLERANCE")]
+    tolerance: f32,
+
+    /// Polygon shape model. Controls how each connected component is meshed.
+    /// concave (default) — keep the simplified outline; tightest fit, most vertices.
+    /// convex — replace each component with its convex hull; few vertices, may overdraw.
+    /// auto — pick convex when concave-area / hull-area ≥ 0.85, else concave.
+    #[arg(long, value_enum, default_value = "concave", value_name = "MODE")]
+    polygon_shape: PolygonShapeArg,
+
+    /// Maximum total vertex count per sprite (across all components).
+    /// 0 (default) disables the budget — uses --tolerance as-is.
+    /// >0 enables iterative tolerance escalation (×1.5 per round, max 8 rounds)
+    /// until the total vertex count fits the budget. Useful for hard
+    /// per-frame draw call budgets on mobile/web.
+    #[arg(long, default_value = "0", value_name = "N")]
+    max_vertices: u32,
+
+    /// Also emit a scaled copy of every atlas, sharing the master layout.
+    /// Repeatable. Format: SUFFIX=SCALE, e.g. `--scale-variant @1x=0.5`
+    /// writes atlas@1x.png + atlas@1x.json next to atlas.png. SCALE is a
+    /// decimal or fraction (0.5, 0.25, 1/3). Sprite rects in each variant
+    /// are exactly master × SCALE: the master layout is snapped to a grid
+    /// (padding / spacing / trim rects rounded up) to make that possible.
+    /// All variants share one manifest; --incremental keeps them UV-stable.
+    #[arg(long = "scale-variant", value_name = "SUFFIX=SCALE", value_parser = parse_scale_variant)]
+    scale_variants: Vec<pack::variants::ScaleVariant>,
+
+    /// Keep related sprites on the same atlas page. Repeatable, ordered:
+    /// a sprite joins the first rule it matches and earlier groups get
+    /// earlier pages. Forms: `hud=dir:ui/hud`, `menu=glob:menu_*.png`,
+    /// `boss=tag:boss` (tags from the existing manifest, see `tag`).
+    /// A group larger than one page is split with a warning.
+    #[arg(long = "group", value_name = "NAME=KIND:PATTERN", value_parser = parse_group_rule)]
+    groups: Vec<pack::groups::GroupRule>,
+
+    /// Animation definition file (TOML or JSON): per-animation fps,
+    /// loop / pingpong / once, per-frame durations and frame events, on top
+    /// of `name_NN` detection. Written to the manifest, the JSON
+    /// `animationInfo` block and Godot SpriteFrames. Frames that aren't
+    /// packed sprites fail the pack.
+    #[arg(long, value_name = "FILE")]
+    animations: Option<PathBuf>,
+
+    /// Packing heuristic for full packs.
+    /// maxrects (default) — crunch MaxRects, the layout of every earlier release.
+    /// maxrects-bssf / maxrects-baf / maxrects-contact — MaxRects with best
+    /// short side fit, best area fit, or contact-point scoring.
+    /// skyline — bottom-left skyline. guillotine — best area fit, guillotine cuts.
+    /// optimize — try all of them with several sort orders in parallel and keep
+    /// the fewest pages, then smallest total area (ties: fixed candidate order).
+    #[arg(long, value_enum, default_value = "maxrects", value_name = "ALGORITHM")]
+    algorithm: AlgorithmArg,
+
+    /// Recolor fully transparent texels with the nearest visible color so
+    /// bilinear filtering doesn't pull dark fringes into sprite edges.
+    /// Alpha is unchanged. Not combinable with --premultiply-alpha.
+    #[arg(long)]
+    alpha_bleed: bool,
+
+    /// Store color premultiplied by alpha. Recorded as
+    /// `meta.premultipliedAlpha` in JSON / tpsheet and in the manifest.
+    #[arg(long)]
+    premultiply_alpha: bool,
+
+    /// Treat sprites as grayscale masks (luma × alpha) and stack up to four
+    /// on the same rect, one per RGBA channel; each frame gets a `channel`
+    /// field (r/g/b/a). Any input change re-lays out the atlas. Not
+    /// combinable with alpha options, --quantize, --scale-variant or godot-tres.
+    #[arg(long)]
+    channel_pack: bool,
+}
+
+impl PackArgs {
+    fn options(&self) -> pack::PackOptions {
+        let format = match self.format {
+            OutputFormat::Json => output::Format::JsonHash,
+            OutputFormat::JsonArray => output::Format::JsonArray,
+            OutputFormat::GodotTpsheet => output::Format::GodotTpsheet,
+            OutputFormat::GodotTres => output::Format::GodotTres,
+        };
+        pack::PackOptions {
+            input_dir: self.input.clone(),
+            output_name: self.output.clone(),
+            output_dir: self.output_dir(),
+            max_size: self.max_size,
+            spacing: self.spacing,
+            padding: self.padding,
+            extrude: self.extrude,
+            trim: self.trim,
+            trim_threshold: self.trim_threshold,
+            rotate: self.rotate,
+            pot: self.pot,
+            recursive: self.recursive,
+            explicit_sprites: None,
+            incremental: self.incremental,
+            force: self.force,
+            format,
+            quantize: self.quantize,
+            quantize_quality: self.quantize_quality,
+            polygon: self.polygon,
+            tolerance: self.tolerance,
+            polygon_shape: PolygonShape::from(&self.polygon_shape),
+            max_vertices: self.max_vertices,
+            scale_variants: self.scale_variants.clone(),
+            groups: self.groups.clone(),
+            animations: self.animations.clone(),
+            algorithm: pack::heuristics::Algorithm::from(&self.algorithm),
+            alpha_bleed: self.alpha_bleed,
+            premultiply_alpha: self.premultiply_alpha,
+            channel_pack: self.channel_pack,
+        }
+    }
+
+    fn output_dir(&self) -> PathBuf {
+        self.output_dir.clone().unwrap_or_else(|| self.input.clone())
+    }
+
+    fn log_path(&self) -> PathBuf {
+        self.output_dir().join(format!("{}.log", self.output))
+    }
+
+    /// Log header lines describing the pack configuration.
+    fn header_lines(&self) -> Vec<String> {
+        let format_name = match self.format {
+            OutputFormat::Json => "json",
+            OutputFormat::JsonArray => "json-array",
+            OutputFormat::GodotTpsheet => "godot-tpsheet",
+            OutputFormat::GodotTres => "godot-tres",
+        };
+        let shape = match self.polygon_shape {
+            PolygonShapeArg::Concave => "concave",
+            PolygonShapeArg::Convex => "convex",
+            PolygonShapeArg::Auto => "auto",
+        };
+        vec![
+            format!("input:      {}", self.input.display()),
+            format!(
+                "output:     {}/{}.{}",
+                self.output_dir().display(),
+                self.output,
+                match self.format {
+                    OutputFormat::GodotTres => "tres",
+                    OutputFormat::GodotTpsheet => "tpsheet",
+                    _ => "json",
+                }
+            ),
+            format!(
+                "layout:     max_size={} spacing={} padding={} extrude={} trim={} rotate={} pot={}",
+                self.max_size, self.spacing, self.padding, self.extrude, self.trim, self.rotate, self.pot
+            ),
+            format!(
+                "polygon:    {} (shape={} tolerance={} max_vertices={})",
+                if self.polygon { "on" } else { "off" },
+                shape,
+                self.tolerance,
+                self.max_vertices
+            ),
+            format!(
+                "incremental: {}{}  format: {}  quantize: {}",
+                self.incremental,
+                if self.force { " (force)" } else { "" },
+                format_name,
+                self.quantize
+            ),
+            format!(
+                "variants:   {}",
+                if self.scale_variants.is_empty() {
+                    "none".to_string()
+                } else {
+                    self.scale_variants
+                        .iter()
+                        .map(|v| v.canonical())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                }
+            ),
+            format!("algorithm:  {}", pack::heuristics::Algorithm::from(&self.algorithm).as_str()),
+            format!(
+                "alpha:      bleed={} premultiply={} channel_pack={}",
+                self.alpha_bleed, self.premultiply_alpha, self.channel_pack
+            ),
+            format!(
+                "groups:     {}",
+                if self.groups.is_empty() {
+                    "none".to_string()
+                } else {
+                    self.groups
+                        .iter()
+                        .map(|g| g.canonical())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                }
+            ),
+        ]
+    }
+}
+
+fn parse_group_rule(s: &str) -> std::result::Result<pack::groups::GroupRule, String> {
+    pack::groups::GroupRule::parse(s).map_err(|e| e.to_string())
+}
+
+fn parse_scale_variant(s: &str) -> std::result::Result<pack::variants::ScaleVariant, String> {
+    pack::variants::ScaleVariant::parse(s).map_err(|e| e.to_string())
 }
 
 #[derive(Clone, ValueEnum)]
@@ -348,6 +697,32 @@ impl From<&PolygonShapeArg> for PolygonShape {
     }
 }
 
+#[derive(Clone, ValueEnum)]
+enum AlgorithmArg {
+    Maxrects,
+    MaxrectsBssf,
+    MaxrectsBaf,
+    MaxrectsContact,
+    Skyline,
+    Guillotine,
+    Optimize,
+}
+
+impl From<&AlgorithmArg> for pack::heuristics::Algorithm {
+    fn from(a: &AlgorithmArg) -> Self {
+        use pack::heuristics::Algorithm;
+        match a {
+            AlgorithmArg::Maxrects => Algorithm::MaxRects,
+            AlgorithmArg::MaxrectsBssf => Algorithm::MaxRectsBssf,
+            AlgorithmArg::MaxrectsBaf => Algorithm::MaxRectsBaf,
+            AlgorithmArg::MaxrectsContact => Algorithm::MaxRectsContact,
+            AlgorithmArg::Skyline => Algorithm::Skyline,
+            AlgorithmArg::Guillotine => Algorithm::Guillotine,
+            AlgorithmArg::Optimize => Algorithm::Optimize,
+        }
+    }
+}
+
 #[derive(Clone, ValueEnum)]
 enum OutputFormat {
     /// TexturePacker JSON Hash — frames as key-value map. Universal format, widest engine support.
@@ -401,19 +776,24 @@ async fn main() {
 /// the CLI is operating on so a debug session is "look next to the atlas".
 fn compute_log_path(cli: &Cli) -> Option<PathBuf> {
     match &cli.command {
-        Commands::Pack {
-            input,
-            output,
-            output_dir,
-            ..
-        } => {
-            let dir = output_dir.clone().unwrap_or_else(|| input.clone());
-            Some(dir.join(format!("{}.log", output)))
-        }
+        Commands::Pack(args)
+        | Commands::Watch { pack: args, .. }
+        | Commands::Compact { pack: args, .. } => Some(args.log_path()),
         Commands::Inspect { input }
         | Commands::Verify { input, .. }
-        | Commands::Tag { input, .. } => log_path_from_anchor(input),
+        | Commands::Tag { input, .. }
+        | Commands::Report { input, .. }
+        | Commands::Unpack { input, .. } => log_path_from_anchor(input),
         Commands::Diff { a, .. } => log_path_from_anchor(a),
+        Commands::Pull {
+            project,
+            output_dir,
+            ..
+        } => Some(output_dir.join(format!("{}.log", project))),
+        Commands::Build { project, .. } => {
+            let stem = project.file_stem()?.to_string_lossy().to_string();
+            Some(project.with_file_name(format!("{}.log", stem)))
+        }
         Commands::Formats => None,
         #[cfg(feature = "gui")]
         Commands::Gui | Commands::Preview { .. } => None,
@@ -459,85 +839,54 @@ fn log_path_from_anchor(input: &PathBuf) -> Option<PathBuf> {
 /// for quick context when reviewing.
 fn subcommand_header_lines(cli: &Cli) -> Vec<String> {
     match &cli.command {
-        Commands::Pack {
-            input,
-            output,
-            output_dir,
-            max_size,
-            spacing,
-            padding,
-            extrude,
-            trim,
-            rotate,
-            pot,
-            format,
-            incremental,
-            force,
-            polygon,
-            tolerance,
-            polygon_shape,
-            max_vertices,
-            quantize,
-            ..
-        } => {
-            let format_name = match format {
-                OutputFormat::Json => "json",
-                OutputFormat::JsonArray => "json-array",
-                OutputFormat::GodotTpsheet => "godot-tpsheet",
-                OutputFormat::GodotTres => "godot-tres",
-            };
-            let shape = match polygon_shape {
-                PolygonShapeArg::Concave => "concave",
-                PolygonShapeArg::Convex => "convex",
-                PolygonShapeArg::Auto => "auto",
-            };
-            vec![
-                "subcommand: pack".to_string(),
-                format!("input:      {}", input.display()),
-                format!(
-                    "output:     {}/{}.{}",
-                    output_dir
-                        .as_ref()
-                        .map(|p| p.display().to_string())
-                        .unwrap_or_else(|| input.display().to_string()),
-                    output,
-                    if matches!(format, OutputFormat::GodotTres) {
-                        "tres"
-                    } else if matches!(format, OutputFormat::GodotTpsheet) {
-                        "tpsheet"
-                    } else {
-                        "json"
-                    }
-                ),
-                format!(
-                    "layout:     max_size={} spacing={} padding={} extrude={} trim={} rotate={} pot={}",
-                    max_size, spacing, padding, extrude, trim, rotate, pot
-                ),
-                format!(
-                    "polygon:    {} (shape={} tolerance={} max_vertices={})",
-                    if *polygon { "on" } else { "off" },
-                    shape,
-                    tolerance,
-                    max_vertices
-                ),
-                format!(
-                    "incremental: {}{}  format: {}  quantize: {}",
-                    incremental,
-                    if *force { " (force)" } else { "" },
-                    format_name,
-                    quantize
-                ),
-            ]
+        Commands::Pack(args) => {
+            let mut lines = vec!["subcommand: pack".to_string()];
+            lines.extend(args.header_lines());
+            lines
+        }
+        Commands::Watch { pack, debounce } => {
+            let mut lines = vec!["subcommand: watch".to_string()];
+            lines.extend(pack.header_lines());
+            lines.push(format!("debounce:   {}ms", debounce));
+            lines
         }
         Commands::Inspect { input } => vec![
             "subcommand: inspect".to_string(),
             format!("input:      {}", input.display()),
         ],
-        Commands::Diff { a, b } => vec![
+        Commands::Diff { a, b, .. } => vec![
             "subcommand: diff".to_string(),
             format!("a:          {}", a.display()),
             format!("b:          {}", b.display()),
         ],
+        Commands::Pull {
+            project,
+            ver,
+            output_dir,
+            input,
+        } => vec![
+            "subcommand: pull".to_string(),
+            format!("project:    {}", project),
+            format!("ver:        {}", ver.as_deref().unwrap_or("latest")),
+            format!("output_dir: {}", output_dir.display()),
+            format!(
+                "input:      {}",
+                input.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "-".into())
+            ),
+        ],
+        Commands::Report {
+            input,
+            base,
+            output,
+        } => vec![
+            "subcommand: report".to_string(),
+            format!("input:      {}", input.display()),
+            format!(
+                "base:       {}",
+                base.as_ref().map(|b| b.display().to_string()).unwrap_or_else(|| "-".into())
+            ),
+            format!("output:     {}", output.display()),
+        ],
         Commands::Verify {
             input,
             check_sources,
@@ -576,6 +925,50 @@ fn subcommand_header_lines(cli: &Cli) -> Vec<String> {
                 list
             ),
         ],
+        Commands::Build {
+            project,
+            only,
+            force,
+            jobs,
+        } => vec![
+            "subcommand: build".to_string(),
+            format!("project:    {}", project.display()),
+            format!(
+                "only:       {}",
+                if only.is_empty() { "(all atlases)".to_string() } else { only.join(",") }
+            ),
+            format!(
+                "force: {}  jobs: {}",
+                force,
+                jobs.map(|j| j.to_string()).unwrap_or_else(|| "auto".into())
+            ),
+        ],
+        Commands::Compact {
+            pack,
+            max_moves,
+            only_tag,
+            pinned_tag,
+        } => {
+            let mut lines = vec!["subcommand: compact".to_string()];
+            lines.extend(pack.header_lines());
+            lines.push(format!(
+                "budget:     max_moves={} only_tag={} pinned_tag={}",
+                max_moves.map(|n| n.to_string()).unwrap_or_else(|| "unlimited".into()),
+                only_tag.as_deref().unwrap_or("-"),
+                pinned_tag
+            ));
+            lines
+        }
+        Commands::Unpack {
+            input,
+            output,
+            manifest,
+        } => vec![
+            "subcommand: unpack".to_string(),
+            format!("input:      {}", input.display()),
+            format!("output:     {}", output.display()),
+            format!("manifest:   {}", manifest),
+        ],
         Commands::Formats => vec!["subcommand: formats".to_string()],
         #[cfg(feature = "gui")]
         Commands::Gui => vec!["subcommand: gui".to_string()],
@@ -587,134 +980,99 @@ fn subcommand_header_lines(cli: &Cli) -> Vec<String> {
     }
 }
 
-fn run(cli: &Cli) -> Result<()> {
-    match &cli.command {
-        Commands::Pack {
-            input,
-            output,
-            output_dir,
-            max_size,
-            spacing,
-            padding,
-            extrude,
-            trim,
-            rotate,
-            pot,
-            format,
-            recursive,
-            incremental,
-            trim_threshold,
-            quantize,
-            quantize_quality,
-            polygon,
-            tolerance,
-            polygon_shape,
-            max_vertices,
-            force,
-        } => {
-            let out_dir = output_dir.clone().unwrap_or_else(|| input.clone());
+fn run_pack(args: &PackArgs, json: bool) -> Result<()> {
+    let opts = args.options();
 
-            let fmt = match format {
-                OutputFormat::Json => output::Format::JsonHash,
-                OutputFormat::JsonArray => output::Format::JsonArray,
-                OutputFormat::GodotTpsheet => output::Format::GodotTpsheet,
-                OutputFormat::GodotTres => output::Format::GodotTres,
-            };
+    let results = pack::execute(&opts)?;
 
-            let opts = pack::PackOptions {
-                input_dir: input.clone(),
-                output_name: output.clone(),
-                output_dir: out_dir,
-                max_size: *max_size,
-                spacing: *spacing,
-                padding: *padding,
-                extrude: *extrude,
-                trim: *trim,
-                trim_threshold: *trim_threshold,
-                rotate: *rotate,
-                pot: *pot,
-                recursive: *recursive,
-                explicit_sprites: None,
-                incremental: *incremental,
-                force: *force,
-                format: fmt,
-                quantize: *quantize,
-                quantize_quality: *quantize_quality,
-                polygon: *polygon,
-                tolerance: *tolerance,
-                polygon_shape: PolygonShape::from(polygon_shape),
-                max_vertices: *max_vertices,
-            };
-
-            let results = pack::execute(&opts)?;
+    for atlas_result in &results {
+        atlas_result.save_to_disk(&opts, opts.format)?;
+    }
 
-            for atlas_result in &results {
-                atlas_result.save_to_disk(&opts, fmt)?;
-            }
+    pack::persist_manifest(&opts, &results)?;
 
-            pack::persist_manifest(&opts, &results)?;
-
-            // Optional hfrog mirror — best-effort, never blocks the local
-            // pipeline. Reads the user config (~/.config/mj_atlas/config.toml);
-            // skips silently when the mirror is disabled or unconfigured.
-            // Loaded lazily so a malformed config can't kill an otherwise
-            // healthy pack — we just log and move on.
-            match config::Config::load() {
-                Ok(cfg) if cfg.hfrog.is_active() => {
-                    let ver = mirror_version_for(&results);
-                    hfrog::mirror_pack_artifacts(
-                        &cfg.hfrog,
-                        output, // project name = output_name
-                        &ver,
-                        &opts.output_dir,
-                        output,
-                        results.len() > 1,
-                        results.len(),
-                    );
-                }
-                Ok(_) => {}
-                Err(e) => log::warn!("hfrog: skipping mirror — config load failed: {}", e),
-            }
+    // Optional hfrog mirror — best-effort, never blocks the local
+    // pipeline. Reads the user config (~/.config/mj_atlas/config.toml);
+    // skips silently when the mirror is disabled or unconfigured.
+    // Loaded lazily so a malformed config can't kill an otherwise
+    // healthy pack — we just log and move on.
+    match config::Config::load() {
+        Ok(cfg) if cfg.hfrog.is_active() => {
+            let ver = mirror_version_for(&results);
+            let files = hfrog::pack_artifact_files(
+                &args.output,
+                results.len(),
+                &opts
+                    .scale_variants
+                    .iter()
+                    .map(|v| v.suffix.clone())
+                    .collect::<Vec<_>>(),
+                opts.format,
+            );
+            // project name = output_name
+            hfrog::mirror_pack_artifacts(&cfg.hfrog, &args.output, &ver, &opts.output_dir, &files);
+        }
+        Ok(_) => {}
+        Err(e) => log::warn!("hfrog: skipping mirror — config load failed: {}", e),
+    }
 
-            if cli.json {
-                let total_dups: usize = results.iter().map(|r| r.duplicates_removed).sum();
-                let cached_count = results.iter().filter(|r| r.from_cache).count();
-                let summary = serde_json::json!({
-                    "status": "ok",
-                    "atlases": results.len(),
-                    "total_sprites": results.iter().map(|r| r.sprites.len()).sum::<usize>(),
-                    "duplicates_removed": total_dups,
-                    "cached_atlases": cached_count,
-                    "skipped": cached_count == results.len() && !results.is_empty(),
-                    "files": results.iter().map(|r| {
-                        serde_json::json!({
-                            "image": r.image_path.display().to_string(),
-                            "data": r.data_path.display().to_string(),
-                            "size": {"w": r.width, "h": r.height},
-                            "sprites": r.sprites.len(),
-                            "from_cache": r.from_cache,
-                        })
-                    }).collect::<Vec<_>>(),
-                });
-                println!("{}", serde_json::to_string_pretty(&summary)?);
-            } else {
-                for r in &results {
-                    log::info!(
-                        "Atlas: {} ({}x{}, {} sprites)",
-                        r.image_path.display(),
-                        r.width,
-                        r.height,
-                        r.sprites.len()
-                    );
-                }
+    if json {
+        let total_dups: usize = results.iter().map(|r| r.duplicates_removed).sum();
+        let cached_count = results.iter().filter(|r| r.from_cache).count();
+        let summary = serde_json::json!({
+            "status": "ok",
+            "atlases": results.len(),
+            "total_sprites": results.iter().map(|r| r.sprites.len()).sum::<usize>(),
+            "duplicates_removed": total_dups,
+            "cached_atlases": cached_count,
+            "skipped": cached_count == results.len() && !results.is_empty(),
+            "files": output::files_summary(&results),
+        });
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else {
+        for r in &results {
+            log::info!(
+                "Atlas: {} ({}x{}, {} sprites, {:.1}% occupancy)",
+                r.image_path.display(),
+                r.width,
+                r.height,
+                r.sprites.len(),
+                100.0 * r.occupancy()
+            );
+            for v in &r.variants {
                 log::info!(
-                    "Done! {} atlas(es), {} sprites total.",
-                    results.len(),
-                    results.iter().map(|r| r.sprites.len()).sum::<usize>()
+                    "  variant x{}: {} ({}x{})",
+                    v.scale,
+                    v.image_path.display(),
+                    v.width,
+                    v.height
                 );
             }
+        }
+        log::info!(
+            "Done! {} atlas(es), {} sprites total.",
+            results.len(),
+            results.iter().map(|r| r.sprites.len()).sum::<usize>()
+        );
+    }
 
-            Ok(())
+    Ok(())
+}
+
+fn run(cli: &Cli) -> Result<()> {
+    match &cli.command {
+        Commands::Pack(args) => run_pack(args, cli.json),
+
+        Commands::Watch { pack, debounce } => {
+            let mut header = runlog::standard_header();
+            header.extend(subcommand_header_lines(cli));
+            watch::run(
+                &pack.options(),
+                std::time::Duration::from_millis(*debounce),
+                cli.json,
+                &pack.log_path(),
+                &header,
+            )
         }
 
         #[cfg(feature = "gui")]
@@ -778,9 +1136,37 @@ fn run(cli: &Cli) -> Result<()> {
             Ok(())
         }
 
+        Commands::Build {
+            project,
+            only,
+            force,
+            jobs,
+        } => project::build(project, only, *force, *jobs, cli.json),
+
         Commands::Inspect { input } => cmd::inspect::run(input, cli.json),
 
-        Commands::Diff { a, b } => cmd::diff::run(a, b, cli.json),
+        Commands::Diff { a, b, moves } => cmd::diff::run(a, b, cli.json, *moves),
+
+        Commands::Pull {
+            project,
+            ver,
+            output_dir,
+            input,
+        } => cmd::pull::run(
+            &cmd::pull::PullOptions {
+                project: project.clone(),
+                ver: ver.clone(),
+                output_dir: output_dir.clone(),
+                input: input.clone(),
+            },
+            cli.json,
+        ),
+
+        Commands::Report {
+            input,
+            base,
+            output,
+        } => cmd::report::run(input, base.as_deref(), output, cli.json),
 
         Commands::Verify {
             input,
@@ -811,5 +1197,25 @@ fn run(cli: &Cli) -> Result<()> {
             };
             cmd::tag::run(input, sprite.as_deref(), ops, cli.json)
         }
+
+        Commands::Compact {
+            pack,
+            max_moves,
+            only_tag,
+            pinned_tag,
+        } => {
+            let copts = pack::compact::CompactOptions {
+                max_moves: *max_moves,
+                only_tag: only_tag.clone(),
+                pinned_tag: pinned_tag.clone(),
+            };
+            cmd::compact::run(&pack.options(), &copts, cli.json)
+        }
+
+        Commands::Unpack {
+            input,
+            output,
+            manifest,
+        } => cmd::unpack::run(input, output, *manifest, cli.json),
     }
 }
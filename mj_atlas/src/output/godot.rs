@@ -1,4 +1,5 @@
 use crate::error::Result;
+use crate::pack::animations::{LoopMode, DEFAULT_FPS};
 use crate::pack::{AtlasResult, PackOptions};
 use serde_json::json;
 use std::path::Path;
@@ -6,7 +7,7 @@ use std::path::Path;
 /// Generate `.tpsheet` format — Godot TexturePacker plugin compatible.
 /// This is JSON that the official texturepacker-godot-plugin can directly import,
 /// auto-generating `.tres` AtlasTexture resources for every sprite.
-pub fn to_tpsheet(atlas: &AtlasResult, _opts: &PackOptions) -> Result<String> {
+pub fn to_tpsheet(atlas: &AtlasResult, opts: &PackOptions) -> Result<String> {
     let image_name = file_name(&atlas.image_path);
 
     let sprites: Vec<serde_json::Value> = atlas
@@ -21,7 +22,7 @@ pub fn to_tpsheet(atlas: &AtlasResult, _opts: &PackOptions) -> Result<String> {
             let margin_w = s.source_w.saturating_sub(s.w);
             let margin_h = s.source_h.saturating_sub(s.h);
 
-            json!({
+            let mut entry = json!({
                 "filename": s.name,
                 "region": {
                     "x": s.x,
@@ -36,11 +37,15 @@ pub fn to_tpsheet(atlas: &AtlasResult, _opts: &PackOptions) -> Result<String> {
                     "h": margin_h
                 },
                 "rotated": s.rotated
-            })
+            });
+            if let Some(channel) = s.channel {
+                entry["channel"] = json!(channel.as_str());
+            }
+            entry
         })
         .collect();
 
-    let root = json!({
+    let mut root = json!({
         "textures": [{
             "image": image_name,
             "size": {
@@ -54,6 +59,9 @@ pub fn to_tpsheet(atlas: &AtlasResult, _opts: &PackOptions) -> Result<String> {
             "version": env!("CARGO_PKG_VERSION")
         }
     });
+    // The Godot plugin ignores unknown keys; runtime code can read it.
+    super::json::insert_animation_info(&mut root, atlas);
+    super::json::insert_alpha_mode(&mut root, opts);
 
     Ok(serde_json::to_string_pretty(&root)?)
 }
@@ -120,7 +128,9 @@ margin = Rect2({mx}, {my}, {mw}, {mh})
 
     // Generate SpriteFrames .tres for each animation group
     for (anim_name, frames) in &atlas.animations {
-        let sf_path = sprites_dir.join(format!("{}_frames.tres", anim_name));
+        // Detected groups keep the sprite's folder prefix; flatten it like sprite names
+        let safe_anim = anim_name.replace('/', "__").replace('\\', "__");
+        let sf_path = sprites_dir.join(format!("{}_frames.tres", safe_anim));
 
         let mut sf = String::new();
 
@@ -159,21 +169,46 @@ margin = Rect2({mx}, {my}, {mw}, {mh})
             }
         }
 
-        // Main resource: SpriteFrames with one animation
+        // Main resource: SpriteFrames with one animation. Without a
+        // definition: 10 fps, looping, uniform durations. Ping-pong is
+        // unrolled since SpriteFrames only knows plain looping.
+        let meta = atlas.animation_meta.get(anim_name);
+        let (speed, looping, order) = match meta {
+            Some(m) => (m.fps, m.mode != LoopMode::Once, m.playback_order()),
+            None => (DEFAULT_FPS, true, (0..frames.len()).collect()),
+        };
         sf.push_str("[resource]\n");
+        if let Some(m) = meta.filter(|m| !m.events.is_empty()) {
+            // Frame events as resource metadata: [{frame, name}], frame
+            // indices into the declared (not unrolled) frame list.
+            let events: Vec<String> = m
+                .events
+                .iter()
+                .map(|e| {
+                    format!(
+                        "{{\"frame\": {}, \"name\": {}}}",
+                        e.frame,
+                        tres_string(&e.name)
+                    )
+                })
+                .collect();
+            sf.push_str(&format!("metadata/events = [{}]\n", events.join(", ")));
+        }
         sf.push_str("animations = [{\n");
-        sf.push_str(&format!("\"name\": &\"{}\",\n", anim_name));
-        sf.push_str("\"speed\": 10.0,\n");
-        sf.push_str("\"loop\": true,\n");
+        sf.push_str(&format!("\"name\": &{},\n", tres_string(anim_name)));
+        sf.push_str(&format!("\"speed\": {:?},\n", speed));
+        sf.push_str(&format!("\"loop\": {},\n", looping));
         sf.push_str("\"frames\": [");
 
-        for (i, _) in frames.iter().enumerate() {
-            if i > 0 {
+        for (n, i) in order.into_iter().enumerate() {
+            if n > 0 {
                 sf.push_str(", ");
             }
+            let duration = meta.map_or(1.0, |m| m.durations[i]);
             sf.push_str(&format!(
-                "{{\n\"texture\": SubResource(\"{}\"),\n\"duration\": 1.0\n}}",
-                i + 2
+                "{{\n\"texture\": SubResource(\"{}\"),\n\"duration\": {:?}\n}}",
+                i + 2,
+                duration
             ));
         }
 
@@ -196,3 +231,20 @@ fn file_name(path: &Path) -> String {
         .map(|f| f.to_string_lossy().to_string())
         .unwrap_or_default()
 }
+
+/// Quote `s` as a Godot resource string literal. Animation and event names
+/// come from user definition files and may contain quotes or backslashes.
+fn tres_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -3,7 +3,7 @@ use crate::pack::{AtlasResult, PackOptions};
 use serde_json::{json, Map, Value};
 
 /// TexturePacker-compatible JSON Hash format.
-pub fn to_json_hash(atlas: &AtlasResult, _opts: &PackOptions) -> Result<String> {
+pub fn to_json_hash(atlas: &AtlasResult, opts: &PackOptions) -> Result<String> {
     let mut frames = Map::new();
 
     for sprite in &atlas.sprites {
@@ -31,6 +31,9 @@ pub fn to_json_hash(atlas: &AtlasResult, _opts: &PackOptions) -> Result<String>
         if let Some(alias) = &sprite.alias_of {
             entry.as_object_mut().unwrap().insert("alias".to_string(), json!(alias));
         }
+        if let Some(channel) = sprite.channel {
+            entry.as_object_mut().unwrap().insert("channel".to_string(), json!(channel.as_str()));
+        }
 
         // Polygon mesh data
         if let (Some(verts), Some(uvs), Some(tris)) =
@@ -62,7 +65,7 @@ pub fn to_json_hash(atlas: &AtlasResult, _opts: &PackOptions) -> Result<String>
                 "w": atlas.width,
                 "h": atlas.height
             },
-            "scale": 1
+            "scale": super::scale_value(atlas.scale)
         }
     });
 
@@ -72,13 +75,15 @@ pub fn to_json_hash(atlas: &AtlasResult, _opts: &PackOptions) -> Result<String>
             .expect("root is object")
             .insert("animations".to_string(), json!(atlas.animations));
     }
+    insert_animation_info(&mut root, atlas);
+    insert_alpha_mode(&mut root, opts);
 
     let output = serde_json::to_string_pretty(&root)?;
     Ok(output)
 }
 
 /// TexturePacker-compatible JSON Array format.
-pub fn to_json_array(atlas: &AtlasResult, _opts: &PackOptions) -> Result<String> {
+pub fn to_json_array(atlas: &AtlasResult, opts: &PackOptions) -> Result<String> {
     let frames: Vec<Value> = atlas
         .sprites
         .iter()
@@ -112,6 +117,9 @@ pub fn to_json_array(atlas: &AtlasResult, _opts: &PackOptions) -> Result<String>
                 obj.insert("verticesUV".to_string(), json!(uvs));
                 obj.insert("triangles".to_string(), json!(tris));
             }
+            if let Some(channel) = sprite.channel {
+                entry.as_object_mut().unwrap().insert("channel".to_string(), json!(channel.as_str()));
+            }
             entry
         })
         .collect();
@@ -133,7 +141,7 @@ pub fn to_json_array(atlas: &AtlasResult, _opts: &PackOptions) -> Result<String>
                 "w": atlas.width,
                 "h": atlas.height
             },
-            "scale": 1
+            "scale": super::scale_value(atlas.scale)
         }
     });
 
@@ -142,7 +150,30 @@ pub fn to_json_array(atlas: &AtlasResult, _opts: &PackOptions) -> Result<String>
             .expect("root is object")
             .insert("animations".to_string(), json!(atlas.animations));
     }
+    insert_animation_info(&mut root, atlas);
+    insert_alpha_mode(&mut root, opts);
 
     let output = serde_json::to_string_pretty(&root)?;
     Ok(output)
 }
+
+/// `animationInfo`: playback metadata (fps, mode, durations, events) for
+/// animations declared in a definition file. `animations` keeps the plain
+/// TexturePacker `name → [frames]` shape so existing loaders are unaffected.
+pub(crate) fn insert_animation_info(root: &mut Value, atlas: &AtlasResult) {
+    if !atlas.animation_meta.is_empty() {
+        root.as_object_mut()
+            .expect("root is object")
+            .insert("animationInfo".to_string(), json!(atlas.animation_meta));
+    }
+}
+
+/// `meta.premultipliedAlpha` when colors are stored premultiplied. Omitted
+/// for straight alpha, which is what every loader assumes by default.
+pub(crate) fn insert_alpha_mode(root: &mut Value, opts: &PackOptions) {
+    if opts.premultiply_alpha {
+        if let Some(meta) = root.get_mut("meta").and_then(Value::as_object_mut) {
+            meta.insert("premultipliedAlpha".to_string(), json!(true));
+        }
+    }
+}
@@ -25,6 +25,53 @@ impl Format {
             Format::GodotTres => "godot-tres",
         }
     }
+
+    /// Inverse of [`Format::as_str`] — accepts the CLI `--format` values.
+    pub fn parse(name: &str) -> Option<Self> {
+        [
+            Format::JsonHash,
+            Format::JsonArray,
+            Format::GodotTpsheet,
+            Format::GodotTres,
+        ]
+        .into_iter()
+        .find(|f| f.as_str() == name)
+    }
+}
+
+/// `meta.scale` value: integers stay integers (`1`, matching what every
+/// single-resolution pack has always written), fractions are emitted as-is.
+pub(crate) fn scale_value(scale: f64) -> serde_json::Value {
+    if scale.fract() == 0.0 {
+        serde_json::json!(scale as u64)
+    } else {
+        serde_json::json!(scale)
+    }
+}
+
+/// Per-atlas file listing used by the `--json` summaries of `pack` and `watch`.
+pub(crate) fn files_summary(results: &[AtlasResult]) -> serde_json::Value {
+    results
+        .iter()
+        .map(|r| {
+            serde_json::json!({
+                "image": r.image_path.display().to_string(),
+                "data": r.data_path.display().to_string(),
+                "size": {"w": r.width, "h": r.height},
+                "sprites": r.sprites.len(),
+                "occupancy": r.occupancy(),
+                "from_cache": r.from_cache,
+                "variants": r.variants.iter().map(|v| {
+                    serde_json::json!({
+                        "image": v.image_path.display().to_string(),
+                        "data": v.data_path.display().to_string(),
+                        "size": {"w": v.width, "h": v.height},
+                        "scale": v.scale,
+                    })
+                }).collect::<Vec<_>>(),
+            })
+        })
+        .collect()
 }
 
 pub fn write_output(atlas: &AtlasResult, format: Format, opts: &PackOptions) -> Result<()> {
@@ -0,0 +1,285 @@
+//! Alpha post-processing: alpha bleeding, premultiplied alpha and channel
+//! packing.
+//!
+//! - **Alpha bleeding** (`--alpha-bleed`) recolors every fully transparent
+//!   texel with the color of the nearest visible texels, so bilinear
+//!   filtering at a sprite's edge blends towards the sprite's own color
+//!   instead of black. The result depends only on texels with alpha > 0, so
+//!   it runs on the composed page (after full *and* partial repacks) and is
+//!   idempotent: re-bleeding an atlas loaded from disk reproduces it.
+//!   Scale-variant pages are resampled from the master's content rects only,
+//!   so they are bled again after rendering.
+//! - **Premultiplied alpha** (`--premultiply-alpha`) stores `rgb * a`. It is
+//!   applied per sprite before composition — premultiplying a page twice is
+//!   not a no-op, and partial repacks compose into the page already on disk.
+//!   Metadata records it (`meta.premultipliedAlpha`, manifest
+//!   `premultiplied_alpha`).
+//! - **Channel packing** (`--channel-pack`) treats every sprite as a
+//!   grayscale mask and stacks up to four masks on the same rect, one per
+//!   RGBA channel. Each frame's metadata names its `channel`.
+//!
+//! All three are part of the options hash.
+
+use crate::error::{AppError, Result};
+use crate::output::Format;
+use crate::pack::{AtlasResult, PackOptions};
+use image::{Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+/// RGBA channel a channel-packed mask lives in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    R,
+    G,
+    B,
+    A,
+}
+
+impl Channel {
+    /// Fill order of a channel-packed slot.
+    pub const ALL: [Channel; 4] = [Channel::R, Channel::G, Channel::B, Channel::A];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Channel::R => "r",
+            Channel::G => "g",
+            Channel::B => "b",
+            Channel::A => "a",
+        }
+    }
+
+    /// Inverse of [`Channel::as_str`].
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == name)
+    }
+}
+
+/// Reject option combinations that contradict each other. Called before any
+/// packing so nothing half-processed reaches the disk.
+pub fn validate(opts: &PackOptions) -> Result<()> {
+    if opts.alpha_bleed && opts.premultiply_alpha {
+        // Bled color under alpha 0 is additive glow once premultiplied.
+        return Err(AppError::InvalidParam(
+            "--alpha-bleed and --premultiply-alpha are mutually exclusive \
+             (premultiplied transparent texels are already black)"
+                .into(),
+        ));
+    }
+    if opts.channel_pack {
+        let conflict = if opts.alpha_bleed {
+            Some("--alpha-bleed")
+        } else if opts.premultiply_alpha {
+            Some("--premultiply-alpha")
+        } else if opts.quantize {
+            Some("--quantize")
+        } else if !opts.scale_variants.is_empty() {
+            Some("--scale-variant")
+        } else if opts.format == Format::GodotTres {
+            Some("--format godot-tres")
+        } else {
+            None
+        };
+        if let Some(flag) = conflict {
+            return Err(AppError::InvalidParam(format!(
+                "--channel-pack cannot be combined with {} (every channel holds mask data)",
+                flag
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Bleed every freshly composed master page. Cached pages are already bled
+/// on disk; variant pages go through [`finish_variant_page`].
+pub fn finish_pages(opts: &PackOptions, results: &mut [AtlasResult]) {
+    if !opts.alpha_bleed {
+        return;
+    }
+    for r in results.iter_mut().filter(|r| !r.from_cache) {
+        bleed(&mut r.atlas_image);
+    }
+}
+
+/// Post-process a freshly rendered scale-variant page. Its gutters start
+/// blank, so bleeding runs again. Premultiplied color already comes from the
+/// master pixels and must not be applied twice; only resampling overshoot
+/// (`rgb > a`) is clamped. Channel packing never reaches here (see
+/// [`validate`]).
+pub fn finish_variant_page(opts: &PackOptions, img: &mut RgbaImage) {
+    if opts.alpha_bleed {
+        bleed(img);
+    }
+    if opts.premultiply_alpha {
+        for px in img.pixels_mut() {
+            let a = px[3];
+            for c in 0..3 {
+                px[c] = px[c].min(a);
+            }
+        }
+    }
+}
+
+/// Recolor fully transparent texels with the average color of their nearest
+/// visible neighbours, growing outwards one ring at a time (8-connected).
+/// Alpha is left untouched. A page with no visible texel ends up all zero.
+pub fn bleed(img: &mut RgbaImage) {
+    let (w, h) = (img.width() as usize, img.height() as usize);
+    let mut filled = vec![false; w * h];
+    for (i, px) in img.pixels_mut().enumerate() {
+        if px[3] > 0 {
+            filled[i] = true;
+        } else {
+            *px = Rgba([0, 0, 0, 0]);
+        }
+    }
+
+    let neighbours = |i: usize| {
+        let (x, y) = ((i % w) as isize, (i / w) as isize);
+        (-1..=1)
+            .flat_map(move |dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+            .filter(move |&(nx, ny)| {
+                (nx, ny) != (x, y) && nx >= 0 && ny >= 0 && (nx as usize) < w && (ny as usize) < h
+            })
+            .map(move |(nx, ny)| ny as usize * w + nx as usize)
+    };
+
+    let mut ring: Vec<usize> = (0..w * h)
+        .filter(|&i| !filled[i] && neighbours(i).any(|n| filled[n]))
+        .collect();
+    let mut queued = filled.clone();
+    for &i in &ring {
+        queued[i] = true;
+    }
+
+    while !ring.is_empty() {
+        // Colors of one ring only read earlier rings, so the result doesn't
+        // depend on visiting order.
+        let colors: Vec<[u8; 3]> = ring
+            .iter()
+            .map(|&i| {
+                let (mut sum, mut n) = ([0u32; 3], 0u32);
+                for j in neighbours(i).filter(|&j| filled[j]) {
+                    let p = img.as_raw();
+                    for c in 0..3 {
+                        sum[c] += p[j * 4 + c] as u32;
+                    }
+                    n += 1;
+                }
+                [0, 1, 2].map(|c| ((sum[c] + n / 2) / n) as u8)
+            })
+            .collect();
+        let raw: &mut [u8] = img;
+        for (&i, rgb) in ring.iter().zip(&colors) {
+            raw[i * 4..i * 4 + 3].copy_from_slice(rgb);
+            filled[i] = true;
+        }
+        let mut next = Vec::new();
+        for &i in &ring {
+            for j in neighbours(i) {
+                if !queued[j] {
+                    queued[j] = true;
+                    next.push(j);
+                }
+            }
+        }
+        ring = next;
+    }
+}
+
+/// Multiply color by alpha in place (rounded).
+pub fn premultiply(img: &mut RgbaImage) {
+    for px in img.pixels_mut() {
+        let a = px[3] as u32;
+        for c in 0..3 {
+            px[c] = ((px[c] as u32 * a + 127) / 255) as u8;
+        }
+    }
+}
+
+/// Grayscale mask of a sprite: Rec. 601 luma weighted by alpha, stored
+/// opaque in every channel so extrusion and rotation treat it like any
+/// other image.
+pub fn to_mask(img: &RgbaImage) -> RgbaImage {
+    RgbaImage::from_fn(img.width(), img.height(), |x, y| {
+        let p = img.get_pixel(x, y);
+        let luma = (299 * p[0] as u32 + 587 * p[1] as u32 + 114 * p[2] as u32 + 500) / 1000;
+        let v = ((luma * p[3] as u32 + 127) / 255) as u8;
+        Rgba([v, v, v, 255])
+    })
+}
+
+/// Write `mask` (read from its red channel) into one channel of `dst` at
+/// `(x, y)`, leaving the other channels alone. Clipped to `dst`.
+pub fn blit_channel(dst: &mut RgbaImage, mask: &RgbaImage, x: u32, y: u32, channel: Channel) {
+    for (mx, my, p) in mask.enumerate_pixels() {
+        let (dx, dy) = (x + mx, y + my);
+        if dx < dst.width() && dy < dst.height() {
+            dst.get_pixel_mut(dx, dy)[channel.index()] = p[0];
+        }
+    }
+}
+
+/// Pull one channel back out as white with the mask as alpha (used by
+/// unpack). [`to_mask`] maps that straight back to the same mask.
+pub fn extract_channel(img: &RgbaImage, channel: Channel) -> RgbaImage {
+    RgbaImage::from_fn(img.width(), img.height(), |x, y| {
+        Rgba([255, 255, 255, img.get_pixel(x, y)[channel.index()]])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bleed_spreads_nearest_color_and_is_idempotent() {
+        let mut img = RgbaImage::from_pixel(6, 3, Rgba([9, 9, 9, 0]));
+        img.put_pixel(0, 1, Rgba([200, 40, 10, 255]));
+        img.put_pixel(5, 1, Rgba([0, 100, 250, 128]));
+        bleed(&mut img);
+
+        assert_eq!(*img.get_pixel(1, 0), Rgba([200, 40, 10, 0]));
+        assert_eq!(*img.get_pixel(4, 2), Rgba([0, 100, 250, 0]));
+        // Each ring takes the color of the ring before it.
+        assert_eq!(*img.get_pixel(2, 1), Rgba([200, 40, 10, 0]));
+        assert_eq!(*img.get_pixel(3, 1), Rgba([0, 100, 250, 0]));
+        assert_eq!(*img.get_pixel(0, 1), Rgba([200, 40, 10, 255]));
+
+        let once = img.clone();
+        bleed(&mut img);
+        assert_eq!(img, once);
+
+        let mut empty = RgbaImage::from_pixel(2, 2, Rgba([5, 5, 5, 0]));
+        bleed(&mut empty);
+        assert!(empty.pixels().all(|p| p.0 == [0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn premultiply_mask_and_channels() {
+        let mut img = RgbaImage::from_pixel(1, 1, Rgba([255, 128, 0, 128]));
+        premultiply(&mut img);
+        assert_eq!(img.get_pixel(0, 0).0, [128, 64, 0, 128]);
+
+        let src = RgbaImage::from_fn(2, 1, |x, _| {
+            if x == 0 { Rgba([255, 255, 255, 255]) } else { Rgba([255, 255, 255, 0]) }
+        });
+        let mask = to_mask(&src);
+        assert_eq!(mask.get_pixel(0, 0).0, [255, 255, 255, 255]);
+        assert_eq!(mask.get_pixel(1, 0).0, [0, 0, 0, 255]);
+
+        let mut page = RgbaImage::new(3, 1);
+        blit_channel(&mut page, &mask, 1, 0, Channel::B);
+        blit_channel(&mut page, &mask, 0, 0, Channel::A);
+        assert_eq!(page.get_pixel(1, 0).0, [0, 0, 255, 0]);
+        assert_eq!(page.get_pixel(0, 0).0, [0, 0, 0, 255]);
+        let unpacked = extract_channel(&page, Channel::B);
+        assert_eq!(unpacked.get_pixel(0, 0).0, [255, 255, 255, 0]);
+        assert_eq!(to_mask(&unpacked).get_pixel(1, 0).0, [255, 255, 255, 255]);
+        assert_eq!(Channel::parse("g"), Some(Channel::G));
+    }
+}
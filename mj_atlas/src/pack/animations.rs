@@ -0,0 +1,332 @@
+//! Animation definitions — playback metadata on top of `name_NN` detection.
+//!
+//! Name detection only yields ordered frame lists. A definition file
+//! (`--animations anims.toml`, TOML or JSON) adds what a runtime needs to
+//! play them: fps, loop mode, per-frame durations and named frame events.
+//!
+//! ```toml
+//! version = 1
+//!
+//! [[animation]]
+//! name = "walk"          # overrides the detected `walk_NN` group
+//! fps = 12
+//! mode = "pingpong"      # loop (default) | pingpong | once
+//!
+//! [[animation]]
+//! name = "attack"        # explicit frame list (may repeat sprites)
+//! fps = 15
+//! mode = "once"
+//! frames = [
+//!   "hero/attack_01.png",
+//!   { sprite = "hero/attack_02.png", duration = 2.0 },
+//!   { sprite = "hero/attack_03.png", events = ["hit"] },
+//! ]
+//! ```
+//!
+//! `duration` is a multiplier of the base frame time `1 / fps` (Godot's
+//! `SpriteFrames` semantics). Without `frames`, the animation reuses the
+//! detected group of the same name. Every referenced sprite must be part of
+//! the pack — a typo fails the pack instead of shipping a broken animation.
+
+use crate::error::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+/// Current animation-definition schema version.
+pub const ANIMATIONS_VERSION: u32 = 1;
+
+/// Frame rate used for animations without a definition (what the Godot
+/// `SpriteFrames` output has always written).
+pub const DEFAULT_FPS: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoopMode {
+    #[default]
+    Loop,
+    /// Forward then backward, endpoints not repeated (a b c b a b c …).
+    PingPong,
+    Once,
+}
+
+impl LoopMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoopMode::Loop => "loop",
+            LoopMode::PingPong => "pingpong",
+            LoopMode::Once => "once",
+        }
+    }
+}
+
+/// A resolved animation as carried in the manifest and written to outputs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Animation {
+    pub frames: Vec<String>,
+    pub fps: f32,
+    pub mode: LoopMode,
+    /// Per-frame duration multipliers, parallel to `frames`.
+    pub durations: Vec<f32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<FrameEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrameEvent {
+    /// Index into `Animation::frames`.
+    pub frame: usize,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnimationFile {
+    pub version: u32,
+    /// `[[animation]]` in TOML, `"animation": [...]` in JSON.
+    #[serde(rename = "animation", default)]
+    pub animations: Vec<AnimationSpec>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnimationSpec {
+    pub name: String,
+    pub fps: Option<f32>,
+    pub mode: Option<LoopMode>,
+    /// Explicit frame list. `None` = the detected `name_NN` group.
+    pub frames: Option<Vec<FrameSpec>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum FrameSpec {
+    Sprite(String),
+    Detailed(FrameDetail),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FrameDetail {
+    pub sprite: String,
+    pub duration: Option<f32>,
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+impl AnimationFile {
+    /// Load from `.toml` (TOML) or any other extension (JSON).
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            AppError::Custom(format!("cannot read animations file {}: {}", path.display(), e))
+        })?;
+        let is_toml = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("toml"));
+        let file: AnimationFile = if is_toml {
+            toml::from_str(&content).map_err(|e| {
+                AppError::Custom(format!("animations parse error ({}): {}", path.display(), e))
+            })?
+        } else {
+            serde_json::from_str(&content).map_err(|e| {
+                AppError::Custom(format!("animations parse error ({}): {}", path.display(), e))
+            })?
+        };
+        file.validate()?;
+        Ok(file)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.version != ANIMATIONS_VERSION {
+            return Err(AppError::InvalidParam(format!(
+                "unsupported animations version {} (expected {})",
+                self.version, ANIMATIONS_VERSION
+            )));
+        }
+        let mut seen = HashSet::new();
+        for spec in &self.animations {
+            let bad = |msg: &str| {
+                AppError::InvalidParam(format!("animation '{}': {}", spec.name, msg))
+            };
+            if spec.name.is_empty() {
+                return Err(AppError::InvalidParam("animation name must not be empty".into()));
+            }
+            // The name becomes part of output file names (e.g. Godot's `<name>_frames.tres`)
+            if spec.name.contains(['/', '\\']) || spec.name.contains("..") {
+                return Err(bad("name must not contain '/', '\\' or '..'"));
+            }
+            if !seen.insert(spec.name.as_str()) {
+                return Err(bad("defined more than once"));
+            }
+            if spec.fps.is_some_and(|f| !(f.is_finite() && f > 0.0)) {
+                return Err(bad("fps must be a positive number"));
+            }
+            if let Some(frames) = &spec.frames {
+                if frames.is_empty() {
+                    return Err(bad("frames must not be empty"));
+                }
+                for f in frames {
+                    if let FrameSpec::Detailed(d) = f {
+                        if d.duration.is_some_and(|x| !(x.is_finite() && x > 0.0)) {
+                            return Err(bad("frame duration must be a positive number"));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve definitions against the packed sprite set. `detected` holds the
+    /// `name_NN` groups; each definition replaces (or adds) its entry there so
+    /// every output's plain frame lists agree with the metadata. Returns the
+    /// defined animations keyed by name.
+    pub fn resolve(
+        &self,
+        detected: &mut HashMap<String, Vec<String>>,
+        sprites: &HashSet<&str>,
+    ) -> Result<BTreeMap<String, Animation>> {
+        let mut out = BTreeMap::new();
+        for spec in &self.animations {
+            let mut frames = Vec::new();
+            let mut durations = Vec::new();
+            let mut events = Vec::new();
+            match &spec.frames {
+                Some(list) => {
+                    for (idx, f) in list.iter().enumerate() {
+                        let (sprite, duration, names) = match f {
+                            FrameSpec::Sprite(s) => (s, None, &[][..]),
+                            FrameSpec::Detailed(d) => (&d.sprite, d.duration, d.events.as_slice()),
+                        };
+                        frames.push(sprite.clone());
+                        durations.push(duration.unwrap_or(1.0));
+                        events.extend(names.iter().map(|n| FrameEvent {
+                            frame: idx,
+                            name: n.clone(),
+                        }));
+                    }
+                }
+                None => {
+                    frames = detected.get(&spec.name).cloned().ok_or_else(|| {
+                        AppError::InvalidParam(format!(
+                            "animation '{}': no `{}_NN` sprites detected; list `frames` explicitly",
+                            spec.name, spec.name
+                        ))
+                    })?;
+                    durations = vec![1.0; frames.len()];
+                }
+            }
+            if let Some(unknown) = frames.iter().find(|f| !sprites.contains(f.as_str())) {
+                return Err(AppError::InvalidParam(format!(
+                    "animation '{}': frame '{}' is not a packed sprite",
+                    spec.name, unknown
+                )));
+            }
+            detected.insert(spec.name.clone(), frames.clone());
+            out.insert(
+                spec.name.clone(),
+                Animation {
+                    frames,
+                    fps: spec.fps.unwrap_or(DEFAULT_FPS),
+                    mode: spec.mode.unwrap_or_default(),
+                    durations,
+                    events,
+                },
+            );
+        }
+        Ok(out)
+    }
+}
+
+impl Animation {
+    /// Frame indices in playback order for one cycle. Ping-pong is unrolled
+    /// (`0 1 2 1`) for runtimes that only know plain looping.
+    pub fn playback_order(&self) -> Vec<usize> {
+        let n = self.frames.len();
+        let mut order: Vec<usize> = (0..n).collect();
+        if self.mode == LoopMode::PingPong && n > 2 {
+            order.extend((1..n - 1).rev());
+        }
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(toml_src: &str) -> Result<AnimationFile> {
+        let file: AnimationFile =
+            toml::from_str(toml_src).map_err(|e| AppError::Custom(e.to_string()))?;
+        file.validate()?;
+        Ok(file)
+    }
+
+    #[test]
+    fn resolves_detected_and_explicit_frames() {
+        let file = parse(
+            r#"
+            version = 1
+            [[animation]]
+            name = "walk"
+            fps = 12
+            mode = "pingpong"
+            [[animation]]
+            name = "attack"
+            mode = "once"
+            frames = ["a_1.png", { sprite = "a_2.png", duration = 2.0, events = ["hit", "shake"] }, "a_1.png"]
+            "#,
+        )
+        .unwrap();
+        let mut detected = HashMap::new();
+        detected.insert(
+            "walk".to_string(),
+            vec!["walk_1.png".to_string(), "walk_2.png".to_string(), "walk_3.png".to_string()],
+        );
+        let sprites: HashSet<&str> = ["walk_1.png", "walk_2.png", "walk_3.png", "a_1.png", "a_2.png"]
+            .into_iter()
+            .collect();
+
+        let anims = file.resolve(&mut detected, &sprites).unwrap();
+        let walk = &anims["walk"];
+        assert_eq!(walk.fps, 12.0);
+        assert_eq!(walk.durations, vec![1.0; 3]);
+        assert_eq!(walk.playback_order(), vec![0, 1, 2, 1]);
+
+        let attack = &anims["attack"];
+        assert_eq!(attack.fps, DEFAULT_FPS);
+        assert_eq!(attack.mode, LoopMode::Once);
+        assert_eq!(attack.durations, vec![1.0, 2.0, 1.0]);
+        assert_eq!(attack.events.len(), 2);
+        assert!(attack.events.iter().all(|e| e.frame == 1));
+        assert_eq!(detected["attack"], attack.frames);
+    }
+
+    #[test]
+    fn unknown_frames_and_bad_values_fail() {
+        let file = parse(
+            "version = 1\n[[animation]]\nname = \"run\"\nframes = [\"run_1.png\", \"typo.png\"]\n",
+        )
+        .unwrap();
+        let sprites: HashSet<&str> = ["run_1.png"].into_iter().collect();
+        let err = file.resolve(&mut HashMap::new(), &sprites).unwrap_err();
+        assert!(err.to_string().contains("typo.png"), "{}", err);
+
+        let missing = parse("version = 1\n[[animation]]\nname = \"idle\"\n").unwrap();
+        assert!(missing.resolve(&mut HashMap::new(), &sprites).is_err());
+
+        for bad in [
+            "version = 2\n",
+            "version = 1\n[[animation]]\nname = \"a\"\nfps = 0\n",
+            "version = 1\n[[animation]]\nname = \"a\"\nmode = \"bounce\"\n",
+            "version = 1\n[[animation]]\nname = \"a\"\nframes = []\n",
+            "version = 1\n[[animation]]\nname = \"a\"\n[[animation]]\nname = \"a\"\n",
+            "version = 1\n[[animation]]\nname = \"hero/attack\"\n",
+            "version = 1\n[[animation]]\nname = 'hero\\attack'\n",
+            "version = 1\n[[animation]]\nname = \"../x\"\n",
+        ] {
+            assert!(parse(bad).is_err(), "{}", bad);
+        }
+    }
+}
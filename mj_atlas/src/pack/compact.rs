@@ -0,0 +1,503 @@
+//! Layout compaction — trade a bounded number of UV moves for free space.
+//!
+//! Incremental packing never relocates a sprite, so removals and resizes
+//! leave holes that fragment `free_rects` until an addition no longer fits
+//! and the next pack falls back to a full repack (every UV moves). `compact`
+//! is the middle ground: it slides a limited set of sprites towards the
+//! top-left of the earliest page they may live on, so the free space merges
+//! into one region at the bottom/right and trailing pages can empty out.
+//!
+//! Every move is a UV break for that sprite (and its aliases), so the caller
+//! bounds them:
+//!   - `max_moves` caps how many sprites may move (aliases ride along free);
+//!   - `only_tag` restricts candidates to sprites carrying that tag;
+//!   - sprites tagged `pinned_tag` never move.
+//!
+//! Candidates are visited furthest-out first (last page, then largest
+//! bottom-right extent, then name), so a small budget is spent where it
+//! frees the most space. A sprite only moves when that strictly lowers its
+//! (page, extent) — a move that merely shuffles pixels is never worth a UV
+//! break. The whole pass is deterministic for a given manifest.
+
+use super::{
+    alpha, clear_atlas_region, execute_master, manifest, sprite_groups, variants, AtlasResult,
+    PackOptions, PackPath,
+};
+use crate::error::{AppError, Result};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// Tag that keeps a sprite in place unless overridden.
+pub const DEFAULT_PINNED_TAG: &str = "pinned";
+
+/// Limits on which sprites a compaction may relocate.
+#[derive(Debug, Clone)]
+pub struct CompactOptions {
+    /// Maximum number of sprites to move. `None` = unlimited.
+    pub max_moves: Option<usize>,
+    /// Only sprites carrying this tag are candidates.
+    pub only_tag: Option<String>,
+    /// Sprites carrying this tag are never moved.
+    pub pinned_tag: String,
+}
+
+impl Default for CompactOptions {
+    fn default() -> Self {
+        Self {
+            max_moves: None,
+            only_tag: None,
+            pinned_tag: DEFAULT_PINNED_TAG.to_string(),
+        }
+    }
+}
+
+/// One relocated sprite. Positions are content (UV) coordinates, as stored
+/// in the manifest's `content_x` / `content_y`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpriteMove {
+    pub name: String,
+    /// Aliases sharing the canonical's pixels — their UVs moved too.
+    pub aliases: Vec<String>,
+    pub from_atlas: usize,
+    pub from: [u32; 2],
+    pub to_atlas: usize,
+    pub to: [u32; 2],
+}
+
+/// Space accounting for one atlas page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageStats {
+    pub width: u32,
+    pub height: u32,
+    pub used_area: u64,
+    /// Area of the largest maximal free rectangle — the biggest sprite
+    /// (by area) an incremental pack could still place here.
+    pub largest_free: u64,
+    pub free_rects: usize,
+}
+
+#[derive(Debug)]
+pub struct CompactReport {
+    /// Pipeline branch of the incremental pack that synced the manifest with
+    /// the inputs before compacting.
+    pub sync: PackPath,
+    pub moves: Vec<SpriteMove>,
+    /// Sprites eligible to move under the tag filters.
+    pub candidates: usize,
+    /// True when the move budget stopped the pass early.
+    pub budget_exhausted: bool,
+    pub before: Vec<PageStats>,
+    pub after: Vec<PageStats>,
+    /// Files of trailing pages that compaction emptied. Still on disk until
+    /// [`remove_dropped_pages`] runs (after the new atlases are saved).
+    pub dropped_files: Vec<PathBuf>,
+}
+
+impl CompactReport {
+    /// Number of sprite names whose UVs changed (canonicals + aliases).
+    pub fn uv_changes(&self) -> usize {
+        self.moves.iter().map(|m| 1 + m.aliases.len()).sum()
+    }
+}
+
+/// Sync the manifest with the inputs (incremental pack), then compact its
+/// layout within `copts`. Results are in memory, like [`super::execute`]:
+/// the caller saves them, persists the manifest and then calls
+/// [`remove_dropped_pages`].
+pub fn compact(opts: &PackOptions, copts: &CompactOptions) -> Result<(Vec<AtlasResult>, CompactReport)> {
+    let opts = &PackOptions {
+        incremental: true,
+        force: false,
+        ..variants::snapped_options(opts)
+    };
+    if opts.polygon {
+        // Cached pages carry no mesh in memory; rewriting their metadata
+        // would drop it.
+        return Err(AppError::InvalidParam(
+            "compact does not support polygon mode yet".into(),
+        ));
+    }
+    if opts.channel_pack {
+        // Slots hold up to four sprites; moving one would split the stack.
+        return Err(AppError::InvalidParam(
+            "compact does not support channel-packed atlases".into(),
+        ));
+    }
+
+    let (mut results, sync) = execute_master(opts)?;
+    let before = page_stats(&results);
+
+    let tags: HashMap<String, Vec<String>> =
+        match manifest::Manifest::try_load(&manifest::Manifest::path_for(opts))? {
+            Some(m) => m.sprites.into_iter().map(|(n, e)| (n, e.tags)).collect(),
+            None => HashMap::new(),
+        };
+    let mut aliases: HashMap<String, Vec<String>> = HashMap::new();
+    for s in results.iter().flat_map(|r| &r.sprites) {
+        if let Some(canonical) = &s.alias_of {
+            aliases.entry(canonical.clone()).or_default().push(s.name.clone());
+        }
+    }
+    for list in aliases.values_mut() {
+        list.sort();
+    }
+    let group_of = sprite_groups(
+        opts,
+        results.iter().flat_map(|r| r.sprites.iter().map(|s| s.name.as_str())),
+    )?;
+
+    // A canonical carries the tags of every name that shares its pixels:
+    // moving it moves them all.
+    let has_tag = |name: &str, tag: &str| {
+        std::iter::once(name)
+            .chain(aliases.get(name).into_iter().flatten().map(|s| s.as_str()))
+            .any(|n| tags.get(n).is_some_and(|t| t.iter().any(|x| x == tag)))
+    };
+    let mut candidates: Vec<(usize, u32, String)> = results
+        .iter()
+        .enumerate()
+        .flat_map(|(page, r)| r.outer_rects.iter().map(move |u| (page, extent(u), u.name.clone())))
+        .filter(|(_, _, name)| {
+            !has_tag(name, &copts.pinned_tag)
+                && copts.only_tag.as_deref().is_none_or(|t| has_tag(name, t))
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)).then(a.2.cmp(&b.2)));
+
+    let grid = variants::layout_grid(&opts.scale_variants);
+    let mut moves = Vec::new();
+    let mut budget_exhausted = false;
+    for (page, _, name) in &candidates {
+        if copts.max_moves.is_some_and(|max| moves.len() >= max) {
+            budget_exhausted = true;
+            break;
+        }
+        let rect = match results[*page].outer_rects.iter().find(|u| u.name == *name) {
+            Some(r) => r.clone(),
+            None => continue,
+        };
+        let group = group_of.get(name);
+        let mut best: Option<(usize, u32, u32, u32)> = None;
+        for (p, r) in results.iter().enumerate().take(page + 1) {
+            // Grouped sprites only move between pages their group already uses.
+            if p != *page
+                && group.is_some_and(|g| !r.sprites.iter().any(|s| group_of.get(&s.name) == Some(g)))
+            {
+                continue;
+            }
+            let used: Vec<(u32, u32, u32, u32)> = r
+                .outer_rects
+                .iter()
+                .filter(|u| !(p == *page && u.name == *name))
+                .map(|u| (u.x, u.y, u.w, u.h))
+                .collect();
+            for f in manifest::compute_free_rects(r.width, r.height, &used) {
+                // Keep the offset to the layout grid so scaled variants stay exact.
+                let x = f.x + (grid - f.x % grid + rect.x % grid) % grid;
+                let y = f.y + (grid - f.y % grid + rect.y % grid) % grid;
+                if x + rect.w > f.x + f.w || y + rect.h > f.y + f.h {
+                    continue;
+                }
+                let key = (p, (x + rect.w).max(y + rect.h), y, x);
+                if best.is_none_or(|b| key < b) {
+                    best = Some(key);
+                }
+            }
+        }
+        let (to_page, to_extent, y, x) = match best {
+            Some(b) if (b.0, b.1) < (*page, extent(&rect)) => b,
+            _ => continue,
+        };
+        log::debug!(
+            "compact: '{}' page {} ({}, {}) -> page {} ({}, {}), extent {} -> {}",
+            name, page, rect.x, rect.y, to_page, x, y, extent(&rect), to_extent
+        );
+        moves.push(relocate(&mut results, *page, &rect, to_page, x, y, &aliases));
+    }
+
+    for r in results.iter_mut().filter(|r| !r.from_cache) {
+        let used: Vec<(u32, u32, u32, u32)> =
+            r.outer_rects.iter().map(|u| (u.x, u.y, u.w, u.h)).collect();
+        r.free_rects = manifest::compute_free_rects(r.width, r.height, &used);
+    }
+
+    // Only trailing pages go: dropping one in the middle would rename the
+    // pages after it, which is a UV break for sprites that never moved.
+    let mut dropped_files = Vec::new();
+    while results.len() > 1 && results.last().is_some_and(|r| r.outer_rects.is_empty()) {
+        let r = results.pop().expect("non-empty");
+        dropped_files.extend(page_files(opts, &r));
+    }
+    if !dropped_files.is_empty() {
+        // The manifest lists every page; make sure it gets rewritten.
+        results[0].from_cache = false;
+    }
+
+    alpha::finish_pages(opts, &mut results);
+    variants::attach(opts, &mut results)?;
+    let after = page_stats(&results);
+    Ok((
+        results,
+        CompactReport {
+            sync,
+            moves,
+            candidates: candidates.len(),
+            budget_exhausted,
+            before,
+            after,
+            dropped_files,
+        },
+    ))
+}
+
+/// Delete the files of pages emptied by [`compact`]. Call after the new
+/// atlases and manifest are on disk.
+pub fn remove_dropped_pages(report: &CompactReport) -> Result<()> {
+    for path in &report.dropped_files {
+        if path.exists() {
+            std::fs::remove_file(path)?;
+            log::info!("Removed emptied page file: {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Bottom-right reach of an outer rect — what compaction minimizes.
+fn extent(u: &manifest::UsedRect) -> u32 {
+    (u.x + u.w).max(u.y + u.h)
+}
+
+/// Move one canonical sprite's outer block (and its aliases' UVs) to
+/// `(x, y)` on `to_page`.
+fn relocate(
+    results: &mut [AtlasResult],
+    from_page: usize,
+    rect: &manifest::UsedRect,
+    to_page: usize,
+    x: u32,
+    y: u32,
+    aliases: &HashMap<String, Vec<String>>,
+) -> SpriteMove {
+    let block =
+        image::imageops::crop_imm(&results[from_page].atlas_image, rect.x, rect.y, rect.w, rect.h)
+            .to_image();
+    clear_atlas_region(&mut results[from_page].atlas_image, rect);
+    image::imageops::replace(&mut results[to_page].atlas_image, &block, x as i64, y as i64);
+
+    let src = &mut results[from_page];
+    src.outer_rects.retain(|u| u.name != rect.name);
+    let alias_names = aliases.get(&rect.name).cloned().unwrap_or_default();
+    let riders: HashSet<&str> = std::iter::once(rect.name.as_str())
+        .chain(alias_names.iter().map(|s| s.as_str()))
+        .collect();
+    let (mut moving, staying): (Vec<_>, Vec<_>) = std::mem::take(&mut src.sprites)
+        .into_iter()
+        .partition(|s| riders.contains(s.name.as_str()));
+    src.sprites = staying;
+    src.from_cache = false;
+
+    let canonical = moving
+        .iter()
+        .find(|s| s.name == rect.name)
+        .map(|s| [s.x, s.y])
+        .unwrap_or([rect.x, rect.y]);
+    for s in &mut moving {
+        s.x = s.x + x - rect.x;
+        s.y = s.y + y - rect.y;
+    }
+    let dst = &mut results[to_page];
+    dst.outer_rects.push(manifest::UsedRect {
+        x,
+        y,
+        ..rect.clone()
+    });
+    dst.sprites.extend(moving);
+    dst.sprites.sort_by(|a, b| a.name.cmp(&b.name));
+    dst.from_cache = false;
+
+    SpriteMove {
+        name: rect.name.clone(),
+        aliases: alias_names,
+        from_atlas: from_page,
+        from: canonical,
+        to_atlas: to_page,
+        to: [canonical[0] + x - rect.x, canonical[1] + y - rect.y],
+    }
+}
+
+fn page_stats(results: &[AtlasResult]) -> Vec<PageStats> {
+    results
+        .iter()
+        .map(|r| {
+            let used: Vec<(u32, u32, u32, u32)> =
+                r.outer_rects.iter().map(|u| (u.x, u.y, u.w, u.h)).collect();
+            let free = manifest::compute_free_rects(r.width, r.height, &used);
+            PageStats {
+                width: r.width,
+                height: r.height,
+                used_area: used.iter().map(|&(_, _, w, h)| w as u64 * h as u64).sum(),
+                largest_free: free.iter().map(|f| f.w as u64 * f.h as u64).max().unwrap_or(0),
+                free_rects: free.len(),
+            }
+        })
+        .collect()
+}
+
+/// Master + variant image and metadata files written for one page.
+fn page_files(opts: &PackOptions, r: &AtlasResult) -> Vec<PathBuf> {
+    let mut files = vec![r.image_path.clone()];
+    let data_ext = match opts.format {
+        crate::output::Format::JsonHash | crate::output::Format::JsonArray => Some("json"),
+        crate::output::Format::GodotTpsheet => Some("tpsheet"),
+        // Per-sprite .tres files live in the shared bundle dir and are
+        // rewritten with the page each sprite moved to.
+        crate::output::Format::GodotTres => None,
+    };
+    if let Some(ext) = data_ext {
+        files.push(r.data_path.with_extension(ext));
+    }
+    let bin_suffix = variants::bin_suffix_of(&r.image_path, &opts.output_name);
+    for v in &opts.scale_variants {
+        let base = variants::variant_base_name(&opts.output_name, v, &bin_suffix);
+        files.push(opts.output_dir.join(format!("{}.png", base)));
+        if let Some(ext) = data_ext {
+            files.push(opts.output_dir.join(format!("{}.{}", base, ext)));
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::diff;
+    use crate::pack::manifest::Manifest;
+    use crate::pack::tests::{make_opts, write_png};
+    use crate::pack::{execute, execute_with_path, persist_manifest};
+    use std::path::Path;
+
+    const COLORS: [[u8; 4]; 5] = [
+        [255, 0, 0, 255],
+        [0, 255, 0, 255],
+        [0, 0, 255, 255],
+        [255, 255, 0, 255],
+        [0, 255, 255, 255],
+    ];
+
+    /// Five 16x16 sprites in 32x32 pages (four on page 0, one on page 1),
+    /// then two page-0 sprites deleted and repacked incrementally: page 0
+    /// has holes the page-1 sprite fits into. Returns (opts, page-1 sprite).
+    fn fragmented(tmp: &Path) -> (PackOptions, String) {
+        let _ = std::fs::remove_dir_all(tmp);
+        let input = tmp.join("in");
+        std::fs::create_dir_all(&input).unwrap();
+        for (i, color) in COLORS.iter().enumerate() {
+            write_png(&input.join(format!("s{}.png", i)), 16, 16, *color);
+        }
+        let mut opts = make_opts(&input, &tmp.join("out"), None);
+        opts.max_size = 32;
+        opts.incremental = true;
+
+        let results = save(&opts, execute(&opts).unwrap());
+        assert_eq!(results.len(), 2);
+        let mut page0: Vec<String> = results[0].sprites.iter().map(|s| s.name.clone()).collect();
+        page0.sort();
+        let last = results[1].sprites[0].name.clone();
+        for name in &page0[..2] {
+            std::fs::remove_file(input.join(name)).unwrap();
+        }
+        let (results, path) = execute_with_path(&opts).unwrap();
+        assert_eq!(path, PackPath::Partial);
+        save(&opts, results);
+        (opts, last)
+    }
+
+    fn save(opts: &PackOptions, results: Vec<AtlasResult>) -> Vec<AtlasResult> {
+        for r in &results {
+            r.save_to_disk(opts, opts.format).unwrap();
+        }
+        persist_manifest(opts, &results).unwrap();
+        results
+    }
+
+    fn color_of(name: &str) -> [u8; 4] {
+        let i: usize = name[1..2].parse().unwrap();
+        COLORS[i]
+    }
+
+    #[test]
+    fn moves_within_budget_and_drops_emptied_pages() {
+        let tmp = std::env::temp_dir().join(format!("mj_atlas_compact_test_{}", std::process::id()));
+        let (opts, last) = fragmented(&tmp);
+        let manifest_path = Manifest::path_for(&opts);
+        let before = Manifest::try_load(&manifest_path).unwrap().unwrap();
+
+        let copts = CompactOptions {
+            max_moves: Some(1),
+            ..Default::default()
+        };
+        let (results, report) = compact(&opts, &copts).unwrap();
+        assert_eq!(report.sync, PackPath::Skipped);
+        assert_eq!(report.moves.len(), 1);
+        assert!(report.budget_exhausted);
+        let m = &report.moves[0];
+        assert_eq!((m.name.as_str(), m.from_atlas, m.to_atlas), (last.as_str(), 1, 0));
+        assert_eq!(results.len(), 1);
+        assert_eq!(report.before.len(), 2);
+        assert!(report.after[0].used_area > report.before[0].used_area);
+
+        let px = results[0].atlas_image.get_pixel(m.to[0] + 8, m.to[1] + 8).0;
+        assert_eq!(px, color_of(&last));
+
+        save(&opts, results);
+        remove_dropped_pages(&report).unwrap();
+        assert!(!opts.output_dir.join("atlas_1.png").exists());
+        assert!(!opts.output_dir.join("atlas_1.json").exists());
+
+        // The manifest matches disk again, and diff sees exactly the move set.
+        let after = Manifest::try_load(&manifest_path).unwrap().unwrap();
+        let d = diff::compute(&before, &after);
+        assert_eq!(d.moved.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(), vec![last.as_str()]);
+        let summary = d.move_summary();
+        assert_eq!((summary.canonical, summary.aliases), (1, 0));
+        assert_eq!(summary.transfers.get(&(1, 0)), Some(&1));
+        assert_eq!(d.atlas_count, [2, 1]);
+        let (_, path) = execute_with_path(&opts).unwrap();
+        assert_eq!(path, PackPath::Skipped);
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn pinned_and_tag_filters_limit_candidates() {
+        let tmp = std::env::temp_dir().join(format!("mj_atlas_compact_pin_test_{}", std::process::id()));
+        let (opts, last) = fragmented(&tmp);
+        let manifest_path = Manifest::path_for(&opts);
+        let mut m = Manifest::try_load(&manifest_path).unwrap().unwrap();
+        m.sprites.get_mut(&last).unwrap().tags = vec!["pinned".into()];
+        m.save(&manifest_path).unwrap();
+
+        let (results, report) = compact(&opts, &CompactOptions::default()).unwrap();
+        assert!(report.moves.iter().all(|mv| mv.name != last));
+        assert_eq!(results.len(), 2);
+        assert_eq!(report.candidates, 2);
+        assert!(report.dropped_files.is_empty());
+
+        let only = CompactOptions {
+            only_tag: Some("hot".into()),
+            ..Default::default()
+        };
+        let (_, report) = compact(&opts, &only).unwrap();
+        assert_eq!((report.candidates, report.moves.len()), (0, 0));
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn polygon_mode_is_rejected() {
+        let tmp = std::env::temp_dir();
+        let mut opts = make_opts(&tmp, &tmp, None);
+        opts.polygon = true;
+        assert!(compact(&opts, &CompactOptions::default()).is_err());
+    }
+}
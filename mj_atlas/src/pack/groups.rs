@@ -0,0 +1,155 @@
+//! Sprite grouping rules — keep sprites used together on the same atlas page.
+//!
+//! `pack_multi_bin` splits purely by fit, so two sprites drawn on the same
+//! screen can end up on different pages and cost an extra draw call. A group
+//! rule names a set of sprites that must share a page:
+//!
+//!   - `hud=dir:ui/hud`        every sprite under `ui/hud/`
+//!   - `menu=glob:menu_*.png`  sprite paths matching a glob (see [`crate::glob`])
+//!   - `boss=tag:boss`         sprites carrying a manifest tag (`mj_atlas tag`)
+//!
+//! Rules are ordered: a sprite joins the first rule it matches, and earlier
+//! groups are assigned pages first (group 1 starts on page 0). Ungrouped
+//! sprites fill in after all groups. A group too large for one `max_size`
+//! page is split across consecutive pages with a warning.
+//!
+//! Tag rules read tags from the existing manifest, so they only match once a
+//! sprite has been packed and tagged. Because tags are not part of the
+//! options hash, the incremental path re-checks group membership and falls
+//! back to a full repack when a tag edit moved a sprite between groups.
+
+use crate::error::{AppError, Result};
+use regex::Regex;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct GroupRule {
+    pub name: String,
+    pub matcher: GroupMatch,
+}
+
+#[derive(Debug, Clone)]
+pub enum GroupMatch {
+    /// Directory prefix, without trailing `/`.
+    Dir(String),
+    /// Original pattern + compiled form.
+    Glob(String, Regex),
+    Tag(String),
+}
+
+impl GroupRule {
+    /// Parse `NAME=dir:PATH`, `NAME=glob:PATTERN` or `NAME=tag:TAG`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let bad = || {
+            AppError::InvalidParam(format!(
+                "group rule '{}': expected NAME=dir:PATH, NAME=glob:PATTERN or NAME=tag:TAG",
+                spec
+            ))
+        };
+        let (name, rule) = spec.split_once('=').ok_or_else(bad)?;
+        let (kind, arg) = rule.split_once(':').ok_or_else(bad)?;
+        let name = name.trim();
+        if name.is_empty() || arg.is_empty() {
+            return Err(bad());
+        }
+        let matcher = match kind {
+            "dir" => GroupMatch::Dir(arg.replace('\\', "/").trim_matches('/').to_string()),
+            "glob" => GroupMatch::Glob(arg.to_string(), crate::glob::to_regex(arg)?),
+            "tag" => GroupMatch::Tag(arg.to_string()),
+            _ => return Err(bad()),
+        };
+        Ok(Self {
+            name: name.to_string(),
+            matcher,
+        })
+    }
+
+    /// Stable spelling for the options hash and log headers.
+    pub fn canonical(&self) -> String {
+        match &self.matcher {
+            GroupMatch::Dir(d) => format!("{}=dir:{}", self.name, d),
+            GroupMatch::Glob(g, _) => format!("{}=glob:{}", self.name, g),
+            GroupMatch::Tag(t) => format!("{}=tag:{}", self.name, t),
+        }
+    }
+
+    pub fn uses_tags(&self) -> bool {
+        matches!(self.matcher, GroupMatch::Tag(_))
+    }
+
+    fn matches(&self, sprite: &str, tags: &[String]) -> bool {
+        match &self.matcher {
+            GroupMatch::Dir(d) => d.is_empty() || sprite.starts_with(&format!("{}/", d)),
+            GroupMatch::Glob(_, re) => re.is_match(sprite),
+            GroupMatch::Tag(t) => tags.iter().any(|x| x == t),
+        }
+    }
+}
+
+/// Map each sprite name to its group (first matching rule). Ungrouped
+/// sprites are absent from the result.
+pub fn assign<'a>(
+    rules: &[GroupRule],
+    sprites: impl IntoIterator<Item = &'a str>,
+    tags: &HashMap<String, Vec<String>>,
+) -> HashMap<String, String> {
+    let mut out = HashMap::new();
+    if rules.is_empty() {
+        return out;
+    }
+    for sprite in sprites {
+        let sprite_tags = tags.get(sprite).map(Vec::as_slice).unwrap_or(&[]);
+        if let Some(rule) = rules.iter().find(|r| r.matches(sprite, sprite_tags)) {
+            out.insert(sprite.to_string(), rule.name.clone());
+        }
+    }
+    out
+}
+
+/// Page-assignment order: group names by rule priority, deduplicated (two
+/// rules may feed the same group).
+pub fn priority_order(rules: &[GroupRule]) -> Vec<String> {
+    let mut order: Vec<String> = Vec::new();
+    for r in rules {
+        if !order.contains(&r.name) {
+            order.push(r.name.clone());
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rules: Vec<GroupRule> = ["hud=dir:ui/hud/", "menu=glob:menu_*", "boss=tag:boss", "ui=dir:ui"]
+            .iter()
+            .map(|s| GroupRule::parse(s).unwrap())
+            .collect();
+        let mut tags = HashMap::new();
+        tags.insert("enemies/dragon.png".to_string(), vec!["boss".to_string()]);
+        let names = [
+            "ui/hud/hp.png",
+            "ui/menu_play.png",
+            "ui/icon.png",
+            "enemies/dragon.png",
+            "enemies/rat.png",
+        ];
+        let groups = assign(&rules, names, &tags);
+        assert_eq!(groups["ui/hud/hp.png"], "hud");
+        assert_eq!(groups["ui/menu_play.png"], "menu");
+        assert_eq!(groups["ui/icon.png"], "ui");
+        assert_eq!(groups["enemies/dragon.png"], "boss");
+        assert!(!groups.contains_key("enemies/rat.png"));
+        assert_eq!(rules[0].canonical(), "hud=dir:ui/hud");
+    }
+
+    #[test]
+    fn rejects_malformed_rules() {
+        for bad in ["hud", "hud=ui/hud", "=dir:x", "hud=dir:", "hud=regex:x"] {
+            assert!(GroupRule::parse(bad).is_err(), "{}", bad);
+        }
+    }
+}
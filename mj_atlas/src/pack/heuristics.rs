@@ -0,0 +1,597 @@
+//! Bin-packing heuristics — how items are placed inside one page.
+//!
+//! `maxrects` (the default) is the crunch engine every earlier release used;
+//! its layouts are unchanged so existing manifests stay valid. The others are
+//! implemented here:
+//!
+//!   - `maxrects-bssf`     MaxRects, best short side fit
+//!   - `maxrects-baf`      MaxRects, best area fit
+//!   - `maxrects-contact`  MaxRects, contact point (hug placed sprites/edges)
+//!   - `skyline`           Skyline, bottom-left
+//!   - `guillotine`        Guillotine, best area fit + shorter-leftover split
+//!
+//! `optimize` is not a heuristic of its own: [`search`] runs every
+//! [`Strategy`] in [`candidates`] in parallel and keeps the layout with the
+//! fewest pages, then the smallest total page area. Ties go to the earlier
+//! candidate, so the winner only depends on the inputs — a full repack with
+//! the same sprites reproduces it byte for byte.
+//!
+//! Every packer here is deterministic: items are sorted by [`SortOrder`] with
+//! the sprite name as the final tie-break, and candidate positions are
+//! compared with strict `<`, so the first best position wins.
+
+use crate::error::{AppError, Result};
+use crunch::{Item, PackedItem, Packer, Rect, Rotation};
+
+/// Packing algorithm selected with `--algorithm`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Algorithm {
+    /// crunch's MaxRects (mixed area / short-side scoring). Default.
+    #[default]
+    MaxRects,
+    MaxRectsBssf,
+    MaxRectsBaf,
+    MaxRectsContact,
+    Skyline,
+    Guillotine,
+    /// Try every heuristic × sort order, keep the smallest result.
+    Optimize,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 7] = [
+        Algorithm::MaxRects,
+        Algorithm::MaxRectsBssf,
+        Algorithm::MaxRectsBaf,
+        Algorithm::MaxRectsContact,
+        Algorithm::Skyline,
+        Algorithm::Guillotine,
+        Algorithm::Optimize,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Algorithm::MaxRects => "maxrects",
+            Algorithm::MaxRectsBssf => "maxrects-bssf",
+            Algorithm::MaxRectsBaf => "maxrects-baf",
+            Algorithm::MaxRectsContact => "maxrects-contact",
+            Algorithm::Skyline => "skyline",
+            Algorithm::Guillotine => "guillotine",
+            Algorithm::Optimize => "optimize",
+        }
+    }
+
+    /// Inverse of [`Algorithm::as_str`].
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.as_str() == name)
+    }
+}
+
+/// Order in which items are fed to a packer (largest first).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Area,
+    MaxSide,
+    Perimeter,
+    Width,
+    Height,
+}
+
+impl SortOrder {
+    pub const ALL: [SortOrder; 5] = [
+        SortOrder::Area,
+        SortOrder::MaxSide,
+        SortOrder::Perimeter,
+        SortOrder::Width,
+        SortOrder::Height,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Area => "area",
+            SortOrder::MaxSide => "max-side",
+            SortOrder::Perimeter => "perimeter",
+            SortOrder::Width => "width",
+            SortOrder::Height => "height",
+        }
+    }
+
+    fn key(&self, w: usize, h: usize) -> (usize, usize) {
+        match self {
+            SortOrder::Area => (w * h, w.max(h)),
+            SortOrder::MaxSide => (w.max(h), w.min(h)),
+            SortOrder::Perimeter => (w + h, w.max(h)),
+            SortOrder::Width => (w, h),
+            SortOrder::Height => (h, w),
+        }
+    }
+}
+
+/// One concrete way to pack a page: a heuristic plus the item order it
+/// consumes. `algorithm` is never [`Algorithm::Optimize`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Strategy {
+    pub algorithm: Algorithm,
+    pub sort: SortOrder,
+}
+
+impl Strategy {
+    /// The strategy a plain (non-optimize) `--algorithm` runs.
+    pub fn of(algorithm: Algorithm) -> Self {
+        Strategy {
+            algorithm,
+            sort: SortOrder::Area,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self.algorithm {
+            // crunch orders items itself.
+            Algorithm::MaxRects => self.algorithm.as_str().to_string(),
+            _ => format!("{}/{}", self.algorithm.as_str(), self.sort.as_str()),
+        }
+    }
+
+    /// Pack `items` into a `w`×`h` page. `Ok` when everything fit; otherwise
+    /// `Err` with the items that did (crunch stops at the first miss, the
+    /// heuristics here keep trying the smaller items).
+    pub fn pack(
+        &self,
+        items: &[Item<String>],
+        w: usize,
+        h: usize,
+    ) -> std::result::Result<Vec<PackedItem<String>>, Vec<PackedItem<String>>> {
+        if self.algorithm == Algorithm::MaxRects || self.algorithm == Algorithm::Optimize {
+            return Packer::with_items(items.iter().cloned()).pack(Rect::of_size(w, h));
+        }
+        let mut order: Vec<&Item<String>> = items.iter().collect();
+        order.sort_by(|a, b| {
+            self.sort
+                .key(b.w, b.h)
+                .cmp(&self.sort.key(a.w, a.h))
+                .then_with(|| a.data.cmp(&b.data))
+        });
+        let mut page: Box<dyn Page> = match self.algorithm {
+            Algorithm::Skyline => Box::new(Skyline::new(w, h)),
+            Algorithm::Guillotine => Box::new(Guillotine::new(w, h)),
+            _ => Box::new(MaxRects::new(w, h, self.algorithm)),
+        };
+        let mut packed = Vec::with_capacity(items.len());
+        let mut complete = true;
+        for item in order {
+            let mut sizes = vec![(item.w, item.h)];
+            if item.rot == Rotation::Allowed && item.w != item.h {
+                sizes.push((item.h, item.w));
+            }
+            match page.place(&sizes) {
+                Some(rect) => packed.push(PackedItem {
+                    data: item.data.clone(),
+                    rect,
+                }),
+                None => complete = false,
+            }
+        }
+        if complete {
+            Ok(packed)
+        } else {
+            Err(packed)
+        }
+    }
+}
+
+/// The search space of `--algorithm optimize`, in tie-break order. The
+/// default engine comes first so it wins any tie.
+pub fn candidates() -> Vec<Strategy> {
+    let mut out = vec![Strategy::of(Algorithm::MaxRects)];
+    for algorithm in [
+        Algorithm::MaxRectsBssf,
+        Algorithm::MaxRectsBaf,
+        Algorithm::MaxRectsContact,
+        Algorithm::Skyline,
+        Algorithm::Guillotine,
+    ] {
+        for sort in SortOrder::ALL {
+            out.push(Strategy { algorithm, sort });
+        }
+    }
+    out
+}
+
+/// Packed pages as `(page_w, page_h, contents)`.
+pub type Pages<T> = Vec<(usize, usize, T)>;
+
+/// Run `pack` with the strategy `algorithm` selects — or, for
+/// [`Algorithm::Optimize`], with every candidate in parallel, keeping the
+/// fewest pages, then the smallest total area, then the earliest candidate.
+pub fn search<T, F>(algorithm: Algorithm, pack: F) -> Result<Pages<T>>
+where
+    T: Send,
+    F: Fn(Strategy) -> Result<Pages<T>> + Sync,
+{
+    use rayon::prelude::*;
+    if algorithm != Algorithm::Optimize {
+        return pack(Strategy::of(algorithm));
+    }
+    let all = candidates();
+    let results: Vec<Result<Pages<T>>> = all.par_iter().map(|s| pack(*s)).collect();
+    let mut best: Option<(usize, u64, usize, Pages<T>)> = None;
+    let mut first_err: Option<AppError> = None;
+    for (idx, result) in results.into_iter().enumerate() {
+        match result {
+            Ok(bins) => {
+                let area: u64 = bins.iter().map(|(w, h, _)| (*w as u64) * (*h as u64)).sum();
+                if best.as_ref().is_none_or(|b| (bins.len(), area) < (b.0, b.1)) {
+                    best = Some((bins.len(), area, idx, bins));
+                }
+            }
+            Err(e) => {
+                first_err.get_or_insert(e);
+            }
+        }
+    }
+    match best {
+        Some((pages, area, idx, bins)) => {
+            log::info!(
+                "optimize: {} wins ({} page(s), {} px total) of {} candidates",
+                all[idx].describe(),
+                pages,
+                area,
+                all.len()
+            );
+            Ok(bins)
+        }
+        None => Err(first_err.unwrap_or(AppError::PackingFailed(0))),
+    }
+}
+
+/// One page being filled by a heuristic. `sizes` are the orientations the
+/// item may take (unrotated first); returns the placed rect.
+trait Page {
+    fn place(&mut self, sizes: &[(usize, usize)]) -> Option<Rect>;
+}
+
+// ─── MaxRects ───────────────────────────────────────────────────────────────
+
+struct MaxRects {
+    w: usize,
+    h: usize,
+    rule: Algorithm,
+    free: Vec<Rect>,
+    used: Vec<Rect>,
+}
+
+impl MaxRects {
+    fn new(w: usize, h: usize, rule: Algorithm) -> Self {
+        MaxRects {
+            w,
+            h,
+            rule,
+            free: vec![Rect::of_size(w, h)],
+            used: Vec::new(),
+        }
+    }
+
+    /// Smaller is better.
+    fn score(&self, f: &Rect, w: usize, h: usize) -> (i64, i64) {
+        let short = (f.w - w).min(f.h - h) as i64;
+        let long = (f.w - w).max(f.h - h) as i64;
+        match self.rule {
+            Algorithm::MaxRectsBaf => ((f.w * f.h - w * h) as i64, short),
+            Algorithm::MaxRectsContact => (-(self.contact(f.x, f.y, w, h) as i64), short),
+            _ => (short, long),
+        }
+    }
+
+    /// Perimeter length a rect at `(x, y)` would share with the page edges
+    /// and already placed rects.
+    fn contact(&self, x: usize, y: usize, w: usize, h: usize) -> usize {
+        let mut total = 0;
+        if x == 0 || x + w == self.w {
+            total += h;
+        }
+        if y == 0 || y + h == self.h {
+            total += w;
+        }
+        for u in &self.used {
+            if u.x == x + w || u.x + u.w == x {
+                total += overlap(u.y, u.y + u.h, y, y + h);
+            }
+            if u.y == y + h || u.y + u.h == y {
+                total += overlap(u.x, u.x + u.w, x, x + w);
+            }
+        }
+        total
+    }
+}
+
+fn overlap(a0: usize, a1: usize, b0: usize, b1: usize) -> usize {
+    a1.min(b1).saturating_sub(a0.max(b0))
+}
+
+impl Page for MaxRects {
+    fn place(&mut self, sizes: &[(usize, usize)]) -> Option<Rect> {
+        let mut best: Option<((i64, i64), Rect)> = None;
+        for f in &self.free {
+            for &(w, h) in sizes {
+                if w <= f.w && h <= f.h {
+                    let score = self.score(f, w, h);
+                    if best.as_ref().is_none_or(|(s, _)| score < *s) {
+                        best = Some((score, Rect::new(f.x, f.y, w, h)));
+                    }
+                }
+            }
+        }
+        let (_, rect) = best?;
+
+        let mut next = Vec::with_capacity(self.free.len() + 4);
+        for f in self.free.drain(..) {
+            if !intersects(&f, &rect) {
+                next.push(f);
+                continue;
+            }
+            if rect.x > f.x {
+                next.push(Rect::new(f.x, f.y, rect.x - f.x, f.h));
+            }
+            if rect.x + rect.w < f.x + f.w {
+                next.push(Rect::new(rect.x + rect.w, f.y, f.x + f.w - rect.x - rect.w, f.h));
+            }
+            if rect.y > f.y {
+                next.push(Rect::new(f.x, f.y, f.w, rect.y - f.y));
+            }
+            if rect.y + rect.h < f.y + f.h {
+                next.push(Rect::new(f.x, rect.y + rect.h, f.w, f.y + f.h - rect.y - rect.h));
+            }
+        }
+        // Drop rects contained in another (keep the first of equal pairs).
+        let mut keep = vec![true; next.len()];
+        for i in 0..next.len() {
+            for j in 0..next.len() {
+                if i != j && keep[j] && contains(&next[j], &next[i]) && (next[i] != next[j] || j < i) {
+                    keep[i] = false;
+                    break;
+                }
+            }
+        }
+        self.free = next
+            .into_iter()
+            .zip(keep)
+            .filter_map(|(r, k)| k.then_some(r))
+            .collect();
+        self.used.push(rect);
+        Some(rect)
+    }
+}
+
+fn intersects(a: &Rect, b: &Rect) -> bool {
+    a.x < b.x + b.w && b.x < a.x + a.w && a.y < b.y + b.h && b.y < a.y + a.h
+}
+
+fn contains(outer: &Rect, inner: &Rect) -> bool {
+    inner.x >= outer.x
+        && inner.y >= outer.y
+        && inner.x + inner.w <= outer.x + outer.w
+        && inner.y + inner.h <= outer.y + outer.h
+}
+
+// ─── Skyline ────────────────────────────────────────────────────────────────
+
+/// Bottom-left skyline: the page's filled outline as `(x, y, w)` segments,
+/// left to right. Space under an overhang is lost, which makes it fast and
+/// good with similar-height sprites.
+struct Skyline {
+    w: usize,
+    h: usize,
+    segments: Vec<(usize, usize, usize)>,
+}
+
+impl Skyline {
+    fn new(w: usize, h: usize) -> Self {
+        Skyline {
+            w,
+            h,
+            segments: vec![(0, 0, w)],
+        }
+    }
+
+    /// Lowest `y` a `w`-wide item can rest at, starting at segment `i`.
+    fn fit(&self, i: usize, w: usize, h: usize) -> Option<usize> {
+        let x = self.segments[i].0;
+        if x + w > self.w {
+            return None;
+        }
+        let mut y = 0;
+        let mut covered = 0;
+        for &(_, sy, sw) in &self.segments[i..] {
+            y = y.max(sy);
+            if y + h > self.h {
+                return None;
+            }
+            covered += sw;
+            if covered >= w {
+                return Some(y);
+            }
+        }
+        None
+    }
+}
+
+impl Page for Skyline {
+    fn place(&mut self, sizes: &[(usize, usize)]) -> Option<Rect> {
+        let mut best: Option<((usize, usize), Rect)> = None;
+        for i in 0..self.segments.len() {
+            for &(w, h) in sizes {
+                if let Some(y) = self.fit(i, w, h) {
+                    let x = self.segments[i].0;
+                    let score = (y + h, x);
+                    if best.as_ref().is_none_or(|(s, _)| score < *s) {
+                        best = Some((score, Rect::new(x, y, w, h)));
+                    }
+                }
+            }
+        }
+        let (_, rect) = best?;
+
+        let (x0, x1) = (rect.x, rect.x + rect.w);
+        let mut next = Vec::with_capacity(self.segments.len() + 2);
+        for &(sx, sy, sw) in &self.segments {
+            let end = sx + sw;
+            if end <= x0 || sx >= x1 {
+                next.push((sx, sy, sw));
+                continue;
+            }
+            if sx < x0 {
+                next.push((sx, sy, x0 - sx));
+            }
+            if sx < x1 && next.last().is_none_or(|&(lx, _, lw)| lx + lw <= x0) {
+                next.push((x0, rect.y + rect.h, rect.w));
+            }
+            if end > x1 {
+                next.push((x1, sy, end - x1));
+            }
+        }
+        // Merge neighbours at the same height.
+        let mut merged: Vec<(usize, usize, usize)> = Vec::with_capacity(next.len());
+        for seg in next {
+            match merged.last_mut() {
+                Some(last) if last.1 == seg.1 => last.2 += seg.2,
+                _ => merged.push(seg),
+            }
+        }
+        self.segments = merged;
+        Some(rect)
+    }
+}
+
+// ─── Guillotine ─────────────────────────────────────────────────────────────
+
+/// Guillotine: each placement cuts its free rect in two along the axis that
+/// leaves the larger remainder whole.
+struct Guillotine {
+    free: Vec<Rect>,
+}
+
+impl Guillotine {
+    fn new(w: usize, h: usize) -> Self {
+        Guillotine {
+            free: vec![Rect::of_size(w, h)],
+        }
+    }
+}
+
+impl Page for Guillotine {
+    fn place(&mut self, sizes: &[(usize, usize)]) -> Option<Rect> {
+        let mut best: Option<((usize, usize), usize, usize, usize)> = None;
+        for (i, f) in self.free.iter().enumerate() {
+            for &(w, h) in sizes {
+                if w <= f.w && h <= f.h {
+                    let score = (f.w * f.h - w * h, (f.w - w).min(f.h - h));
+                    if best.as_ref().is_none_or(|b| score < b.0) {
+                        best = Some((score, i, w, h));
+                    }
+                }
+            }
+        }
+        let (_, i, w, h) = best?;
+        let f = self.free.swap_remove(i);
+        let rect = Rect::new(f.x, f.y, w, h);
+
+        let (right, bottom) = if f.w - w < f.h - h {
+            // Shorter leftover is horizontal: cut across, the bottom keeps full width.
+            (
+                Rect::new(f.x + w, f.y, f.w - w, h),
+                Rect::new(f.x, f.y + h, f.w, f.h - h),
+            )
+        } else {
+            (
+                Rect::new(f.x + w, f.y, f.w - w, f.h),
+                Rect::new(f.x, f.y + h, w, f.h - h),
+            )
+        };
+        for r in [right, bottom] {
+            if r.w > 0 && r.h > 0 {
+                self.free.push(r);
+            }
+        }
+        // swap_remove reorders; keep the list in a position order so ties
+        // resolve the same way regardless of history.
+        self.free.sort_by_key(|r| (r.y, r.x, r.w, r.h));
+        Some(rect)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(sizes: &[(usize, usize)], rot: Rotation) -> Vec<Item<String>> {
+        sizes
+            .iter()
+            .enumerate()
+            .map(|(i, &(w, h))| Item::new(format!("s{:02}", i), w, h, rot))
+            .collect()
+    }
+
+    fn assert_valid(packed: &[PackedItem<String>], w: usize, h: usize) {
+        for (i, a) in packed.iter().enumerate() {
+            assert!(a.rect.x + a.rect.w <= w && a.rect.y + a.rect.h <= h, "{:?} out of page", a.rect);
+            for b in &packed[i + 1..] {
+                assert!(!intersects(&a.rect, &b.rect), "{:?} overlaps {:?}", a.rect, b.rect);
+            }
+        }
+    }
+
+    #[test]
+    fn every_heuristic_packs_without_overlap() {
+        let sizes: Vec<(usize, usize)> = (0..40).map(|i| (4 + (i * 7) % 23, 3 + (i * 11) % 19)).collect();
+        for rot in [Rotation::None, Rotation::Allowed] {
+            let items = items(&sizes, rot);
+            for s in candidates() {
+                let packed = s.pack(&items, 128, 128).unwrap_or_else(|_| panic!("{} failed", s.describe()));
+                assert_eq!(packed.len(), items.len());
+                assert_valid(&packed, 128, 128);
+                // Deterministic: same inputs, same layout.
+                let again = s.pack(&items, 128, 128).unwrap_or_default();
+                assert_eq!(again.len(), packed.len());
+                assert!(packed.iter().zip(&again).all(|(a, b)| a.rect == b.rect && a.data == b.data));
+            }
+        }
+    }
+
+    #[test]
+    fn overflow_returns_what_fit() {
+        let items = items(&[(20, 20), (20, 20), (12, 12)], Rotation::None);
+        for algorithm in [Algorithm::MaxRectsBssf, Algorithm::Skyline, Algorithm::Guillotine] {
+            let partial = match Strategy::of(algorithm).pack(&items, 32, 32) {
+                Ok(_) => panic!("{} fit 3 sprites in 32x32", algorithm.as_str()),
+                Err(partial) => partial,
+            };
+            assert_eq!(partial.len(), 2, "{}", algorithm.as_str());
+            assert_valid(&partial, 32, 32);
+        }
+    }
+
+    #[test]
+    fn optimize_prefers_fewer_pages_then_area_then_order() {
+        let pick = search(Algorithm::Optimize, |s| {
+            let area = match s.algorithm {
+                Algorithm::Skyline => 10,
+                Algorithm::Guillotine => 10,
+                _ => 20,
+            };
+            Ok(vec![(area, 1, s.describe())])
+        })
+        .unwrap();
+        assert_eq!(pick[0].2, "skyline/area");
+
+        let pages = search(Algorithm::Optimize, |s| {
+            Ok(if s.algorithm == Algorithm::MaxRectsBaf {
+                vec![(64, 64, ()), (64, 64, ())]
+            } else {
+                vec![(128, 128, ())]
+            })
+        })
+        .unwrap();
+        assert_eq!(pages.len(), 1);
+        assert!(Algorithm::ALL.iter().all(|a| Algorithm::parse(a.as_str()) == Some(*a)));
+    }
+}
@@ -32,9 +32,21 @@ pub struct Manifest {
     /// Hash of all PackOptions that affect the output. Mismatch ⇒ full repack.
     pub options_hash: String,
     pub input_root: String,
+    /// Layout grid the pack was snapped to (1 = none). Greater than 1 when
+    /// scale variants are enabled. Only `used_rects` carry the grid padding;
+    /// sprite source sizes and content rects stay the true ones.
+    #[serde(default = "default_layout_grid", skip_serializing_if = "is_unit_grid")]
+    pub layout_grid: u32,
     /// Sprites keyed by their relative path (matches `PackedSprite.name`).
     pub sprites: BTreeMap<String, SpriteEntry>,
     pub atlases: Vec<AtlasEntry>,
+    /// Animations declared in the definition file, fully resolved (frames,
+    /// fps, mode, durations, events). Detected-only groups aren't recorded.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub animations: BTreeMap<String, crate::pack::animations::Animation>,
+    /// Atlas colors are premultiplied by alpha (`--premultiply-alpha`).
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub premultiplied_alpha: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -63,6 +75,12 @@ pub struct SpriteEntry {
     pub rotated: bool,
     /// If this sprite is a duplicate, name of the canonical it aliases.
     pub alias_of: Option<String>,
+    /// Grouping rule (`--group`) this sprite matched when packed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Channel holding this mask (`--channel-pack`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<crate::pack::alpha::Channel>,
 
     // ─── User-editable metadata (v0.3+) ──────────────────────────────────────
     // These are NOT part of the cache key — they describe the sprite to humans
@@ -98,6 +116,39 @@ pub struct AtlasEntry {
     /// Free rectangles available for additive incremental packing.
     /// Maintained as the maximal-rectangles set after every layout change.
     pub free_rects: Vec<FreeRect>,
+    /// Scaled copies of this atlas (`--scale-variant`). They share this
+    /// atlas's layout, so they carry no rects of their own.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<VariantEntry>,
+    /// Sprite groups with members on this page, sorted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct VariantEntry {
+    /// Name suffix, e.g. `@1x`.
+    pub suffix: String,
+    /// Exact scale relative to the master as `num/den`, e.g. `1/2`.
+    pub scale: String,
+    pub image_filename: String,
+    pub data_filename: String,
+    pub width: u32,
+    pub height: u32,
+    /// SHA256 of the variant PNG on disk.
+    pub image_hash: String,
+}
+
+fn default_layout_grid() -> u32 {
+    1
+}
+
+fn is_unit_grid(g: &u32) -> bool {
+    *g <= 1
+}
+
+fn is_false(b: &bool) -> bool {
+    !*b
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -203,6 +254,39 @@ pub fn compute_options_hash(opts: &PackOptions) -> String {
         }
     );
     field!("max_vertices", opts.max_vertices);
+    // Variants change the layout grid (and the set of files written).
+    // Hashed only when present so single-resolution caches stay valid.
+    if !opts.scale_variants.is_empty() {
+        let specs: Vec<String> = opts.scale_variants.iter().map(|v| v.canonical()).collect();
+        field!("scale_variants", specs.join(","));
+    }
+    // Group rules change page assignment. Tag-based membership is checked
+    // separately by the incremental path (tags aren't part of the key).
+    if !opts.groups.is_empty() {
+        let rules: Vec<String> = opts.groups.iter().map(|g| g.canonical()).collect();
+        field!("groups", rules.join(","));
+    }
+    // Animation definitions don't move sprites but change every metadata
+    // file, so a cache hit must not skip them. Keyed by file content.
+    if let Some(path) = &opts.animations {
+        let content = hash_file(path).unwrap_or_else(|_| "unreadable".into());
+        field!("animations", content);
+    }
+    // Hashed only when non-default so pre-existing caches (all packed with
+    // the crunch MaxRects engine) stay valid.
+    if opts.algorithm != crate::pack::heuristics::Algorithm::MaxRects {
+        field!("algorithm", opts.algorithm.as_str());
+    }
+    // Pixel post-processing; hashed only when on for the same reason.
+    if opts.alpha_bleed {
+        field!("alpha_bleed", true);
+    }
+    if opts.premultiply_alpha {
+        field!("premultiply_alpha", true);
+    }
+    if opts.channel_pack {
+        field!("channel_pack", true);
+    }
     // tolerance is f32 — format with full precision for stable hashing.
     h.update(b"tolerance=");
     h.update(format!("{:.6}", opts.tolerance).as_bytes());
@@ -223,10 +307,14 @@ pub fn hash_pixels(img: &image::RgbaImage) -> String {
 
 /// SHA256 over a file's raw bytes — used for atlas PNG integrity checks.
 pub fn hash_file(path: &Path) -> Result<String> {
-    let bytes = std::fs::read(path)?;
+    Ok(hash_bytes(&std::fs::read(path)?))
+}
+
+/// SHA256 of an in-memory file, same encoding as [`hash_file`].
+pub fn hash_bytes(bytes: &[u8]) -> String {
     let mut h = Sha256::new();
-    h.update(&bytes);
-    Ok(hex_encode(&h.finalize()))
+    h.update(bytes);
+    hex_encode(&h.finalize())
 }
 
 /// SHA256 over the simplified polygon contour + triangulation.
@@ -467,7 +555,8 @@ pub struct FitResult {
 ///   - `<name>.png` / `.json` / `.tpsheet` / `.tres` → strip extension,
 ///     append `.manifest.json` next to it. If the stem ends in `_<digits>`
 ///     (multi-bin atlas suffix) and the sibling lookup fails, also try after
-///     stripping that suffix.
+///     stripping that suffix. Scale-variant files (`atlas@1x.png`,
+///     `atlas@1x_1.png`) resolve to the master's manifest.
 ///   - directory → find a single `*.manifest.json` inside; error otherwise.
 pub fn resolve_manifest_path(input: &Path) -> Result<PathBuf> {
     // 1. Direct hit on a `.manifest.json` file.
@@ -542,6 +631,15 @@ pub fn resolve_manifest_path(input: &Path) -> Result<PathBuf> {
         }
     }
 
+    // 6. Scale variant: everything from the first `@` on is the variant
+    //    suffix (plus any bin suffix); variants share the master's manifest.
+    if let Some((base, _)) = stem.split_once('@') {
+        let master = parent.join(format!("{}.manifest.json", base));
+        if !base.is_empty() && master.is_file() {
+            return Ok(master);
+        }
+    }
+
     Err(AppError::Custom(format!(
         "no manifest sidecar for {} (looked for {}.manifest.json next to it)",
         input.display(),
@@ -649,6 +747,18 @@ mod tests {
         assert_ne!(h1, h2);
     }
 
+    #[test]
+    fn options_hash_tracks_scale_variants() {
+        let mut opts = mock_opts();
+        let h1 = compute_options_hash(&opts);
+        opts.scale_variants = vec![crate::pack::variants::ScaleVariant::parse("@1x=0.5").unwrap()];
+        let h2 = compute_options_hash(&opts);
+        assert_ne!(h1, h2);
+        // `0.5` and `1/2` are the same variant.
+        opts.scale_variants = vec![crate::pack::variants::ScaleVariant::parse("@1x=1/2").unwrap()];
+        assert_eq!(compute_options_hash(&opts), h2);
+    }
+
     #[test]
     fn options_hash_unaffected_by_output_paths() {
         let mut opts = mock_opts();
@@ -701,7 +811,7 @@ mod tests {
             file_size: 0, mtime: 0, content_hash: "".into(),
             trim_offset: [0, 0], trimmed_size: [1, 1], source_size: [1, 1],
             polygon_hash: None, atlas_idx: 0, content_x: 0, content_y: 0,
-            rotated: false, alias_of: None,
+            rotated: false, alias_of: None, group: None, channel: None,
             tags: vec![], attribution: None, source_url: None,
         };
         let s = serde_json::to_string(&entry).unwrap();
@@ -753,7 +863,7 @@ mod tests {
                 file_size: 100, mtime: 0, content_hash: "h".into(),
                 trim_offset: [0, 0], trimmed_size: [10, 10], source_size: [10, 10],
                 polygon_hash: None, atlas_idx: 0, content_x: 0, content_y: 0,
-                rotated: false, alias_of: None,
+                rotated: false, alias_of: None, group: None, channel: None,
                 tags: vec![], attribution: None, source_url: None,
             },
         );
@@ -762,8 +872,11 @@ mod tests {
             tool: "mj_atlas test".into(),
             options_hash: "abc".into(),
             input_root: "/in".into(),
+            layout_grid: 1,
             sprites,
             atlases: vec![],
+            animations: Default::default(),
+            premultiplied_alpha: false,
         }
     }
 
@@ -791,6 +904,13 @@ mod tests {
             tolerance: 2.0,
             polygon_shape: crate::pack::PolygonShape::Concave,
             max_vertices: 0,
+            scale_variants: Vec::new(),
+            groups: Vec::new(),
+            animations: None,
+            algorithm: Default::default(),
+            alpha_bleed: false,
+            premultiply_alpha: false,
+            channel_pack: false,
         }
     }
 }
@@ -1,18 +1,27 @@
+pub mod alpha;
+pub mod animations;
+pub mod compact;
 pub mod contour;
 pub mod dedup;
 pub mod extrude;
+pub mod groups;
+pub mod heuristics;
 pub mod manifest;
 pub mod multi_bin;
 pub mod simplify;
 pub mod triangulate;
 pub mod trim;
+pub mod variants;
 
 use crate::error::{AppError, Result};
 use image::RgbaImage;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+/// Lower-case file extensions the packer treats as sprite sources.
+pub const IMAGE_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "bmp", "gif", "tga", "webp"];
+
 /// Options for the packing operation.
 #[derive(Debug, Clone)]
 pub struct PackOptions {
@@ -61,6 +70,28 @@ pub struct PackOptions {
     /// 0 disables the budget — uses `tolerance` as-is.
     /// >0 enables a binary search on tolerance to land within this budget.
     pub max_vertices: u32,
+    /// Extra resolutions emitted next to the master atlas (`atlas@1x.png`,
+    /// …), all derived from the single master layout. Empty = master only.
+    /// Non-empty snaps the layout to a grid so scaled rects stay exact — see
+    /// [`variants`].
+    pub scale_variants: Vec<variants::ScaleVariant>,
+    /// Grouping rules, highest priority first. Sprites in one group share an
+    /// atlas page when it fits — see [`groups`].
+    pub groups: Vec<groups::GroupRule>,
+    /// Animation definition file (fps, loop mode, durations, events) layered
+    /// over `name_NN` detection — see [`animations`]. Its content is part of
+    /// the cache key.
+    pub animations: Option<PathBuf>,
+    /// Placement heuristic for full packs — see [`heuristics`]. Partial
+    /// repacks always fill holes best-short-side-fit.
+    pub algorithm: heuristics::Algorithm,
+    /// Recolor transparent texels from their nearest visible neighbours —
+    /// see [`alpha`].
+    pub alpha_bleed: bool,
+    /// Write color premultiplied by alpha (recorded in the metadata).
+    pub premultiply_alpha: bool,
+    /// Pack grayscale masks four to a rect, one per RGBA channel.
+    pub channel_pack: bool,
 }
 
 /// Polygon shape mode — controls how each connected component is converted to a mesh.
@@ -74,6 +105,18 @@ pub enum PolygonShape {
     Auto,
 }
 
+impl PolygonShape {
+    /// Parse the CLI spelling (`concave` / `convex` / `auto`).
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "concave" => Some(PolygonShape::Concave),
+            "convex" => Some(PolygonShape::Convex),
+            "auto" => Some(PolygonShape::Auto),
+            _ => None,
+        }
+    }
+}
+
 /// Information about a packed sprite in the final atlas.
 #[derive(Debug, Clone)]
 pub struct PackedSprite {
@@ -103,6 +146,8 @@ pub struct PackedSprite {
     pub vertices_uv: Option<Vec<[f32; 2]>>,
     /// Triangle indices into vertices array (polygon mode)
     pub triangles: Option<Vec<[usize; 3]>>,
+    /// Channel holding this mask (channel-pack mode only)
+    pub channel: Option<alpha::Channel>,
 }
 
 /// Result of packing a single atlas.
@@ -114,6 +159,8 @@ pub struct AtlasResult {
     pub height: u32,
     pub sprites: Vec<PackedSprite>,
     pub animations: HashMap<String, Vec<String>>,
+    /// Playback metadata for animations declared in `PackOptions::animations`.
+    pub animation_meta: BTreeMap<String, animations::Animation>,
     pub duplicates_removed: usize,
     /// In-memory atlas image (not written to disk until save_to_disk is called)
     pub atlas_image: RgbaImage,
@@ -124,9 +171,30 @@ pub struct AtlasResult {
     pub free_rects: Vec<manifest::FreeRect>,
     /// True when this atlas was reused from cache and disk writes should be skipped.
     pub from_cache: bool,
+    /// Resolution relative to the master layout — 1.0 for master atlases,
+    /// the variant's factor for entries in `variants`.
+    pub scale: f64,
+    /// Scaled copies of this atlas, one per `PackOptions::scale_variants`
+    /// entry. Always empty on the variants themselves.
+    pub variants: Vec<AtlasResult>,
 }
 
 impl AtlasResult {
+    /// Fraction of the page covered by sprite reservations (outer rects,
+    /// including extrude / padding / spacing), 0.0–1.0.
+    pub fn occupancy(&self) -> f64 {
+        let area = self.width as u64 * self.height as u64;
+        if area == 0 {
+            return 0.0;
+        }
+        let used: u64 = self
+            .outer_rects
+            .iter()
+            .map(|r| r.w as u64 * r.h as u64)
+            .sum();
+        used as f64 / area as f64
+    }
+
     /// Write atlas image and metadata to disk. When `from_cache` is true, both
     /// the atlas PNG and the metadata sidecar are already valid on disk and we
     /// skip writes (idempotent re-runs become near-zero cost).
@@ -150,6 +218,16 @@ impl AtlasResult {
         }
         log::info!("Saved atlas image: {}", self.image_path.display());
         crate::output::write_output(self, fmt, opts)?;
+
+        // Variant sidecars are named after the variant (`atlas@1x.json`,
+        // `atlas@1x.sprites/`), so hand the writers a renamed copy of opts.
+        for (variant, spec) in self.variants.iter().zip(&opts.scale_variants) {
+            let variant_opts = PackOptions {
+                output_name: format!("{}{}", opts.output_name, spec.suffix),
+                ..opts.clone()
+            };
+            variant.save_to_disk(&variant_opts, fmt)?;
+        }
         Ok(())
     }
 }
@@ -162,6 +240,49 @@ impl AtlasResult {
 /// [`AtlasResult::save_to_disk`] (a no-op for cached entries) and, in CLI
 /// flows, [`persist_manifest`] afterwards to refresh the manifest sidecar.
 pub fn execute(opts: &PackOptions) -> Result<Vec<AtlasResult>> {
+    execute_with_path(opts).map(|(results, _)| results)
+}
+
+/// Which branch of the pipeline produced a set of results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackPath {
+    /// Incremental cache hit — nothing was packed.
+    Skipped,
+    /// Incremental partial repack — unchanged sprites kept their placement.
+    Partial,
+    /// Full repack (no incremental, cache miss, or partial repack rejected).
+    Full,
+}
+
+impl PackPath {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PackPath::Skipped => "skipped",
+            PackPath::Partial => "partial",
+            PackPath::Full => "full",
+        }
+    }
+}
+
+/// [`execute`], also reporting which pipeline branch ran. Used by `watch` to
+/// tell listeners whether a hot-reload can keep its UVs.
+pub fn execute_with_path(opts: &PackOptions) -> Result<(Vec<AtlasResult>, PackPath)> {
+    // Scale variants need a grid-aligned layout; everything below (including
+    // the options hash) works on the snapped options so it stays consistent.
+    let opts = &variants::snapped_options(opts);
+    let (mut results, path) = execute_master(opts)?;
+    variants::attach(opts, &mut results)?;
+    Ok((results, path))
+}
+
+fn execute_master(opts: &PackOptions) -> Result<(Vec<AtlasResult>, PackPath)> {
+    alpha::validate(opts)?;
+    let (mut results, path) = compose_master(opts)?;
+    alpha::finish_pages(opts, &mut results);
+    Ok((results, path))
+}
+
+fn compose_master(opts: &PackOptions) -> Result<(Vec<AtlasResult>, PackPath)> {
     let entries = collect_images_for(opts)?;
     if entries.is_empty() {
         return Err(AppError::NoImages(opts.input_dir.display().to_string()));
@@ -178,13 +299,13 @@ pub fn execute(opts: &PackOptions) -> Result<Vec<AtlasResult>> {
 
     if opts.incremental && !opts.force {
         match try_incremental(opts, &entries)? {
-            Some(results) => return Ok(results),
+            Some(traced) => return Ok(traced),
             None => {} // fall through to full repack
         }
     }
 
     log::info!("Running full repack");
-    full_pack(opts, &entries)
+    Ok((full_pack(opts, &entries)?, PackPath::Full))
 }
 
 /// Persist (or refresh) the manifest sidecar after atlases have been written.
@@ -195,6 +316,7 @@ pub fn execute(opts: &PackOptions) -> Result<Vec<AtlasResult>> {
 /// performed so a stale sidecar from a previous incremental run can't poison
 /// future runs.
 pub fn persist_manifest(opts: &PackOptions, results: &[AtlasResult]) -> Result<()> {
+    let opts = &variants::snapped_options(opts);
     if !opts.incremental {
         let mpath = manifest::Manifest::path_for(opts);
         if mpath.exists() {
@@ -236,72 +358,63 @@ fn full_pack(opts: &PackOptions, entries: &[(String, PathBuf)]) -> Result<Vec<At
         .par_iter()
         .map(|&idx| {
             let (name, img) = &loaded[idx];
-            let (trimmed_img, trim_info) = if opts.trim {
-                let tr = trim::trim_transparent(img, opts.trim_threshold);
-                (tr.image.clone(), tr)
-            } else {
-                let (w, h) = img.dimensions();
-                (
-                    img.clone(),
-                    trim::TrimResult {
-                        image: img.clone(),
-                        offset_x: 0,
-                        offset_y: 0,
-                        source_w: w,
-                        source_h: h,
-                        trimmed: false,
-                    },
-                )
-            };
-
-            let extruded = extrude::extrude_edges(&trimmed_img, opts.extrude);
-
-            let extra = opts.extrude * 2 + opts.padding * 2;
-            let pack_w = trimmed_img.width() + extra;
-            let pack_h = trimmed_img.height() + extra;
-
-            let polygon_data = if opts.polygon {
-                Some(build_polygon_data(&trimmed_img, opts))
-            } else {
-                None
-            };
-
-            SpriteData {
-                name: name.clone(),
-                original_image: trimmed_img,
-                extruded_image: extruded,
-                trim_info,
-                pack_w,
-                pack_h,
-                polygon_data,
-            }
+            preprocess_sprite(name.clone(), img.clone(), opts)
         })
         .collect();
 
-    // 5. Build packing items (add spacing)
-    let pack_items: Vec<(String, usize, usize)> = sprites
+    // 5. Build packing items (add spacing) — one per slot
+    let group_of = sprite_groups(opts, sprites.iter().map(|s| s.name.as_str()))?;
+    let slots = build_slots(opts, &sprites, &group_of);
+    let pack_items: Vec<(String, usize, usize)> = slots
         .iter()
         .map(|s| {
             (
                 s.name.clone(),
-                s.pack_w as usize + opts.spacing as usize,
-                s.pack_h as usize + opts.spacing as usize,
+                s.w as usize + opts.spacing as usize,
+                s.h as usize + opts.spacing as usize,
             )
         })
         .collect();
 
-    // 6. Pack into bins
-    let bins = multi_bin::pack_multi_bin(pack_items, opts.max_size, opts.pot, opts.rotate)?;
+    // 6. Pack into bins (grouped sprites share a page when they fit). With
+    //    `--algorithm optimize` this runs once per candidate strategy.
+    let bins = heuristics::search(opts.algorithm, |strategy| {
+        if opts.groups.is_empty() {
+            return multi_bin::pack_multi_bin(
+                pack_items.clone(),
+                opts.max_size,
+                opts.pot,
+                opts.rotate,
+                strategy,
+            );
+        }
+        let mut by_group: HashMap<&str, Vec<(String, usize, usize)>> = HashMap::new();
+        let mut ungrouped = Vec::new();
+        for item in &pack_items {
+            match group_of.get(&item.0) {
+                Some(g) => by_group.entry(g.as_str()).or_default().push(item.clone()),
+                None => ungrouped.push(item.clone()),
+            }
+        }
+        let ordered = groups::priority_order(&opts.groups)
+            .into_iter()
+            .map(|g| {
+                let items = by_group.remove(g.as_str()).unwrap_or_default();
+                (g, items)
+            })
+            .collect();
+        multi_bin::pack_grouped(ordered, ungrouped, opts.max_size, opts.pot, opts.rotate, strategy)
+    })?;
 
-    // 7. Build sprite lookup
-    let sprite_map: HashMap<&str, &SpriteData> =
-        sprites.iter().map(|s| (s.name.as_str(), s)).collect();
+    // 7. Build slot lookup
+    let slot_map: HashMap<&str, &Slot> = slots.iter().map(|s| (s.name.as_str(), s)).collect();
 
     // 8. Detect animation groups (on ALL sprites including aliases)
     let all_names: Vec<String> = loaded.iter().map(|(n, _)| n.clone()).collect();
-    let animations = detect_animations_from_names(&all_names);
+    let (animations, animation_meta) = resolve_animations(opts, &all_names)?;
 
     // 9. Compose each atlas
+    let grid = variants::layout_grid(&opts.scale_variants);
     let mut results = Vec::with_capacity(bins.len());
     for (bin_idx, (bin_w, bin_h, packed_items)) in bins.into_iter().enumerate() {
         let suffix = if bin_idx == 0 && results.is_empty() {
@@ -324,34 +437,21 @@ fn full_pack(opts: &PackOptions, entries: &[(String, PathBuf)]) -> Result<Vec<At
         let mut canonical_packed: HashMap<String, PackedSprite> = HashMap::new();
 
         for packed in &packed_items {
-            let sprite = sprite_map
+            let slot = slot_map
                 .get(packed.data.as_str())
                 .ok_or_else(|| AppError::Custom(format!("Sprite '{}' not found", packed.data)))?;
 
             let place_x = packed.rect.x as u32;
             let place_y = packed.rect.y as u32;
 
-            let was_rotated = packed.rect.w as u32 != sprite.pack_w + opts.spacing
-                && packed.rect.h as u32 == sprite.pack_w + opts.spacing;
-
-            let img_to_place = if was_rotated {
-                rotate_90cw(&sprite.extruded_image)
-            } else {
-                sprite.extruded_image.clone()
-            };
-
-            image::imageops::overlay(
-                &mut atlas_img,
-                &img_to_place,
-                place_x as i64,
-                place_y as i64,
-            );
+            let was_rotated = packed.rect.w as u32 != slot.w + opts.spacing
+                && packed.rect.h as u32 == slot.w + opts.spacing;
 
             // The bin packer reserves `pack_w + spacing` × `pack_h + spacing` per
             // item — record that full reservation so additive incremental packing
             // stays clear of the spacing margin.
             outer_rects.push(manifest::UsedRect {
-                name: sprite.name.clone(),
+                name: slot.name.clone(),
                 x: place_x,
                 y: place_y,
                 w: packed.rect.w as u32,
@@ -359,46 +459,69 @@ fn full_pack(opts: &PackOptions, entries: &[(String, PathBuf)]) -> Result<Vec<At
                 rotated: was_rotated,
             });
 
-            let content_x = place_x + opts.extrude + opts.padding;
-            let content_y = place_y + opts.extrude + opts.padding;
-            let content_w = sprite.original_image.width();
-            let content_h = sprite.original_image.height();
-
-            // Build polygon mesh data if in polygon mode
-            let (vertices, vertices_uv, triangles) =
-                if let Some(poly) = &sprite.polygon_data {
-                    let verts: Vec<[f32; 2]> =
-                        poly.contour.iter().map(|&(x, y)| [x, y]).collect();
-                    let uvs: Vec<[f32; 2]> = poly
-                        .contour
-                        .iter()
-                        .map(|&(x, y)| [content_x as f32 + x, content_y as f32 + y])
-                        .collect();
-                    (Some(verts), Some(uvs), Some(poly.triangles.clone()))
+            for (member, sprite) in slot.members.iter().enumerate() {
+                let img_to_place = if was_rotated {
+                    rotate_90cw(&sprite.extruded_image)
                 } else {
-                    (None, None, None)
+                    sprite.extruded_image.clone()
                 };
 
-            let packed_sprite = PackedSprite {
-                name: sprite.name.clone(),
-                x: content_x,
-                y: content_y,
-                w: if was_rotated { content_h } else { content_w },
-                h: if was_rotated { content_w } else { content_h },
-                rotated: was_rotated,
-                trimmed: sprite.trim_info.trimmed,
-                trim_offset_x: sprite.trim_info.offset_x,
-                trim_offset_y: sprite.trim_info.offset_y,
-                source_w: sprite.trim_info.source_w,
-                source_h: sprite.trim_info.source_h,
-                alias_of: None,
-                vertices,
-                vertices_uv,
-                triangles,
-            };
+                let channel = opts.channel_pack.then(|| alpha::Channel::ALL[member]);
+                match channel {
+                    Some(c) => alpha::blit_channel(&mut atlas_img, &img_to_place, place_x, place_y, c),
+                    None => image::imageops::overlay(
+                        &mut atlas_img,
+                        &img_to_place,
+                        place_x as i64,
+                        place_y as i64,
+                    ),
+                }
+
+                let content_x = place_x + opts.extrude + opts.padding;
+                let content_y = place_y + opts.extrude + opts.padding;
+                let content_w = sprite.original_image.width();
+                let content_h = sprite.original_image.height();
+
+                // Build polygon mesh data if in polygon mode
+                let (vertices, vertices_uv, triangles) =
+                    if let Some(poly) = &sprite.polygon_data {
+                        let verts: Vec<[f32; 2]> =
+                            poly.contour.iter().map(|&(x, y)| [x, y]).collect();
+                        let uvs: Vec<[f32; 2]> = poly
+                            .contour
+                            .iter()
+                            .map(|&(x, y)| [content_x as f32 + x, content_y as f32 + y])
+                            .collect();
+                        (Some(verts), Some(uvs), Some(poly.triangles.clone()))
+                    } else {
+                        (None, None, None)
+                    };
+
+                let mut packed_sprite = PackedSprite {
+                    name: sprite.name.clone(),
+                    x: content_x,
+                    y: content_y,
+                    w: if was_rotated { content_h } else { content_w },
+                    h: if was_rotated { content_w } else { content_h },
+                    rotated: was_rotated,
+                    trimmed: sprite.trim_info.trimmed,
+                    trim_offset_x: sprite.trim_info.offset_x,
+                    trim_offset_y: sprite.trim_info.offset_y,
+                    source_w: sprite.trim_info.source_w,
+                    source_h: sprite.trim_info.source_h,
+                    alias_of: None,
+                    vertices,
+                    vertices_uv,
+                    triangles,
+                    channel,
+                };
+                if grid > 1 {
+                    variants::clip_to_source(&mut packed_sprite);
+                }
 
-            canonical_packed.insert(sprite.name.clone(), packed_sprite.clone());
-            atlas_sprites.push(packed_sprite);
+                canonical_packed.insert(sprite.name.clone(), packed_sprite.clone());
+                atlas_sprites.push(packed_sprite);
+            }
         }
 
         // Add alias entries — they share the same atlas position as the canonical
@@ -429,11 +552,14 @@ fn full_pack(opts: &PackOptions, entries: &[(String, PathBuf)]) -> Result<Vec<At
             height: bin_h as u32,
             sprites: atlas_sprites,
             animations: animations.clone(),
+            animation_meta: animation_meta.clone(),
             duplicates_removed: dup_count,
             atlas_image: atlas_img,
             outer_rects,
             free_rects,
             from_cache: false,
+            scale: 1.0,
+            variants: Vec::new(),
         });
     }
 
@@ -646,6 +772,57 @@ struct SpriteData {
     polygon_data: Option<PolygonData>,
 }
 
+/// One bin-packer item: a single sprite, or in channel-pack mode up to four
+/// masks stacked on the same rect (member `i` goes to `Channel::ALL[i]`).
+struct Slot<'a> {
+    /// Key in the packer — the first member's name.
+    name: String,
+    w: u32,
+    h: u32,
+    members: Vec<&'a SpriteData>,
+}
+
+/// Slots for a full pack. Channel packing sorts masks by size so slot-mates
+/// waste little space, and never stacks sprites from different groups.
+fn build_slots<'a>(
+    opts: &PackOptions,
+    sprites: &'a [SpriteData],
+    group_of: &HashMap<String, String>,
+) -> Vec<Slot<'a>> {
+    let single = |s: &'a SpriteData| Slot {
+        name: s.name.clone(),
+        w: s.pack_w,
+        h: s.pack_h,
+        members: vec![s],
+    };
+    if !opts.channel_pack {
+        return sprites.iter().map(single).collect();
+    }
+    let mut order: Vec<&SpriteData> = sprites.iter().collect();
+    order.sort_by(|a, b| {
+        group_of
+            .get(&a.name)
+            .cmp(&group_of.get(&b.name))
+            .then((b.pack_h, b.pack_w).cmp(&(a.pack_h, a.pack_w)))
+            .then(a.name.cmp(&b.name))
+    });
+    let mut slots: Vec<Slot> = Vec::new();
+    for s in order {
+        match slots.last_mut() {
+            Some(slot)
+                if slot.members.len() < alpha::Channel::ALL.len()
+                    && group_of.get(&slot.name) == group_of.get(&s.name) =>
+            {
+                slot.w = slot.w.max(s.pack_w);
+                slot.h = slot.h.max(s.pack_h);
+                slot.members.push(s);
+            }
+            _ => slots.push(single(s)),
+        }
+    }
+    slots
+}
+
 /// Collect input sprites for packing.
 ///
 /// When `opts.explicit_sprites` is `Some(list)`, pack exactly that list — this
@@ -653,7 +830,7 @@ struct SpriteData {
 /// unrelated images that happen to live in the same directory. Otherwise we
 /// fall back to scanning `opts.input_dir` (recursively when `opts.recursive`).
 fn collect_images_for(opts: &PackOptions) -> Result<Vec<(String, PathBuf)>> {
-    let image_exts = ["png", "jpg", "jpeg", "bmp", "gif", "tga", "webp"];
+    let image_exts = IMAGE_EXTENSIONS;
     let mut entries: Vec<(String, PathBuf)> = Vec::new();
 
     if let Some(list) = &opts.explicit_sprites {
@@ -768,16 +945,33 @@ fn detect_animations_from_names(names: &[String]) -> HashMap<String, Vec<String>
     groups
 }
 
+/// Frame lists by animation name, plus metadata for the defined ones.
+type ResolvedAnimations = (HashMap<String, Vec<String>>, BTreeMap<String, animations::Animation>);
+
+/// Detected animation groups, overridden / extended by the definition file
+/// when one is configured, plus the playback metadata of defined animations.
+fn resolve_animations(opts: &PackOptions, names: &[String]) -> Result<ResolvedAnimations> {
+    let mut detected = detect_animations_from_names(names);
+    let meta = match &opts.animations {
+        Some(path) => {
+            let sprites: HashSet<&str> = names.iter().map(String::as_str).collect();
+            animations::AnimationFile::load(path)?.resolve(&mut detected, &sprites)?
+        }
+        None => BTreeMap::new(),
+    };
+    Ok((detected, meta))
+}
+
 // ─── Incremental pack ────────────────────────────────────────────────────────
 
 /// Try the incremental fast path. Returns:
-///   - `Ok(Some(results))`  → cache is valid, use these results (some may be `from_cache`)
+///   - `Ok(Some((results, path)))` → cache is valid, use these results (some may be `from_cache`)
 ///   - `Ok(None)`           → cache miss / partial repack rejected; caller falls back to full
 ///   - `Err(_)`             → I/O error reading manifest or input metadata
 fn try_incremental(
     opts: &PackOptions,
     entries: &[(String, PathBuf)],
-) -> Result<Option<Vec<AtlasResult>>> {
+) -> Result<Option<(Vec<AtlasResult>, PackPath)>> {
     let manifest_path = manifest::Manifest::path_for(opts);
     let cached = match manifest::Manifest::try_load(&manifest_path)? {
         Some(m) => m,
@@ -821,6 +1015,18 @@ fn try_incremental(
             );
             return Ok(None);
         }
+        // Variants are derived from the master layout — a missing or edited
+        // variant PNG is rebuilt by a full pack, same as a master atlas.
+        for variant in &atlas.variants {
+            let path = opts.output_dir.join(&variant.image_filename);
+            if !path.exists() || manifest::hash_file(&path)? != variant.image_hash {
+                log::info!(
+                    "incremental: variant atlas missing or changed on disk: {}",
+                    path.display()
+                );
+                return Ok(None);
+            }
+        }
     }
 
     // Stage 4: diff inputs against the manifest (no pixel decode yet — file
@@ -834,10 +1040,35 @@ fn try_incremental(
         diff.unchanged.len()
     );
 
+    // Stage 5: group membership. Rules are in the options hash, but tag
+    // rules depend on manifest tags, which aren't — a `tag` edit that moves
+    // a sprite to another group needs a fresh page assignment.
+    if !opts.groups.is_empty() {
+        let current = sprite_groups(opts, entries.iter().map(|(n, _)| n.as_str()))?;
+        let moved = cached
+            .sprites
+            .iter()
+            .find(|(name, e)| !diff.removed.contains(name) && e.group.as_ref() != current.get(*name));
+        if let Some((name, _)) = moved {
+            log::info!("incremental: group membership of '{}' changed, full repack", name);
+            return Ok(None);
+        }
+    }
+
     // Branch A: nothing changed → full skip, synthesize cached results.
     if diff.is_unchanged() {
         log::info!("incremental: full cache hit, skipping pack entirely");
-        return Ok(Some(synthesize_cached_results(opts, &cached)?));
+        return Ok(Some((
+            synthesize_cached_results(opts, &cached)?,
+            PackPath::Skipped,
+        )));
+    }
+
+    // Channel-packed slots are built from the whole sorted sprite set, so
+    // there is no hole-filling equivalent.
+    if opts.channel_pack {
+        log::info!("incremental: channel-packed layout changed, full repack");
+        return Ok(None);
     }
 
     // Branch B: try partial repack — keep unchanged sprites at their exact
@@ -846,7 +1077,7 @@ fn try_incremental(
     match try_partial_repack(opts, entries, &cached, &diff)? {
         Some(results) => {
             log::info!("incremental: partial repack succeeded (UV-stable)");
-            Ok(Some(results))
+            Ok(Some((results, PackPath::Partial)))
         }
         None => {
             log::info!("incremental: partial repack rejected, falling back to full repack");
@@ -875,7 +1106,7 @@ fn synthesize_cached_results(
     }
 
     let all_names: Vec<String> = manifest.sprites.keys().cloned().collect();
-    let animations = detect_animations_from_names(&all_names);
+    let (animations, animation_meta) = resolve_animations(opts, &all_names)?;
 
     for (atlas_idx, atlas) in manifest.atlases.iter().enumerate() {
         let image_path = opts.output_dir.join(&atlas.image_filename);
@@ -909,6 +1140,7 @@ fn synthesize_cached_results(
                         vertices: None,
                         vertices_uv: None,
                         triangles: None,
+                        channel: e.channel,
                     })
                     .collect()
             })
@@ -922,11 +1154,14 @@ fn synthesize_cached_results(
             height: atlas.height,
             sprites,
             animations: animations.clone(),
+            animation_meta: animation_meta.clone(),
             duplicates_removed: 0,
             atlas_image,
             outer_rects: atlas.used_rects.clone(),
             free_rects: atlas.free_rects.clone(),
             from_cache: true,
+            scale: 1.0,
+            variants: Vec::new(),
         });
     }
 
@@ -1085,6 +1320,7 @@ fn try_partial_repack(
     // 2. Build a mutable view of the manifest's per-sprite entries.
     let mut sprites: HashMap<String, manifest::SpriteEntry> =
         cached.sprites.clone().into_iter().collect();
+    let group_of = sprite_groups(opts, entries.iter().map(|(n, _)| n.as_str()))?;
 
     // 3. Apply removals: drop entry, free outer rect, clear pixels.
     for name in &diff.removed {
@@ -1179,10 +1415,16 @@ fn try_partial_repack(
             continue;
         }
         // Verify trimmed dims match (else bail and let full-repack handle).
-        let new_trim = [
-            sprite_data.original_image.width(),
-            sprite_data.original_image.height(),
-        ];
+        // Entries record the clipped size in atlas orientation.
+        let (w, h) = sprite_data.original_image.dimensions();
+        let (_, w, h) = variants::clip_rect(
+            entry.content_x,
+            if entry.rotated { (h, w) } else { (w, h) },
+            entry.rotated,
+            (sprite_data.trim_info.offset_x, sprite_data.trim_info.offset_y),
+            (sprite_data.trim_info.source_w, sprite_data.trim_info.source_h),
+        );
+        let new_trim = [w, h];
         if new_trim != entry.trimmed_size {
             log::info!(
                 "partial: sprite '{}' trimmed size changed {:?} -> {:?}; bail to full",
@@ -1242,9 +1484,23 @@ fn try_partial_repack(
         let outer_w = sd.original_image.width() + extra + opts.spacing;
         let outer_h = sd.original_image.height() + extra + opts.spacing;
 
+        // A grouped sprite may only join a page its group already lives on
+        // (any page when the group is new).
+        let group_pages: Vec<usize> = match group_of.get(&sd.name) {
+            Some(g) => sprites
+                .values()
+                .filter(|e| e.group.as_ref() == Some(g))
+                .map(|e| e.atlas_idx)
+                .collect(),
+            None => Vec::new(),
+        };
+
         // Find the best-fit atlas + free rect.
         let mut best: Option<(usize, manifest::FitResult)> = None;
         for (idx, atlas) in atlases.iter().enumerate() {
+            if !group_pages.is_empty() && !group_pages.contains(&idx) {
+                continue;
+            }
             if let Some(fit) = manifest::try_fit(&atlas.free_rects, outer_w, outer_h, opts.rotate) {
                 if best.as_ref().map_or(true, |(_, f)| fit.score < f.score) {
                     best = Some((idx, fit));
@@ -1288,10 +1544,17 @@ fn try_partial_repack(
         );
         atlas.dirty = true;
 
-        let content_x = fit.x + opts.extrude + opts.padding;
+        // Record the clipped content rect in atlas orientation, as a full
+        // pack does.
+        let (w, h) = sd.original_image.dimensions();
+        let (content_x, content_w, content_h) = variants::clip_rect(
+            fit.x + opts.extrude + opts.padding,
+            if fit.rotated { (h, w) } else { (w, h) },
+            fit.rotated,
+            (sd.trim_info.offset_x, sd.trim_info.offset_y),
+            (sd.trim_info.source_w, sd.trim_info.source_h),
+        );
         let content_y = fit.y + opts.extrude + opts.padding;
-        let content_w = sd.original_image.width();
-        let content_h = sd.original_image.height();
 
         let path = entries_by_name
             .get(sd.name.as_str())
@@ -1319,6 +1582,8 @@ fn try_partial_repack(
                 content_y,
                 rotated: fit.rotated,
                 alias_of: None,
+                group: group_of.get(&sd.name).cloned(),
+                channel: None,
                 // Newly-added sprites have no tags by default. The user can
                 // attach metadata afterwards with `mj_atlas tag`.
                 tags: Vec::new(),
@@ -1330,7 +1595,7 @@ fn try_partial_repack(
 
     // 8. Build AtlasResult set. Reuse cached atlases when not dirty.
     let all_names: Vec<String> = sprites.keys().cloned().collect();
-    let animations = detect_animations_from_names(&all_names);
+    let (animations, animation_meta) = resolve_animations(opts, &all_names)?;
 
     let mut sprites_by_atlas: HashMap<usize, Vec<&manifest::SpriteEntry>> = HashMap::new();
     for entry in sprites.values() {
@@ -1360,6 +1625,7 @@ fn try_partial_repack(
                         vertices: None,
                         vertices_uv: None,
                         triangles: None,
+                        channel: e.channel,
                     })
                     .collect()
             })
@@ -1382,11 +1648,14 @@ fn try_partial_repack(
             height: atlas.height,
             sprites: atlas_sprites,
             animations: animations.clone(),
+            animation_meta: animation_meta.clone(),
             duplicates_removed: 0,
             atlas_image: atlas.image,
             outer_rects: atlas.used_rects,
             free_rects: atlas.free_rects,
             from_cache,
+            scale: 1.0,
+            variants: Vec::new(),
         });
     }
 
@@ -1428,8 +1697,16 @@ fn clear_atlas_region(img: &mut RgbaImage, rect: &manifest::UsedRect) {
 
 /// Preprocess a single sprite — trim, extrude, polygon mesh.
 fn preprocess_sprite(name: String, img: RgbaImage, opts: &PackOptions) -> SpriteData {
-    let (trimmed_img, trim_info) = if opts.trim {
-        let tr = trim::trim_transparent(&img, opts.trim_threshold);
+    // With scale variants every content rect must sit on the layout grid:
+    // grow the canvas first, then widen the trim rect to grid boundaries.
+    let grid = variants::layout_grid(&opts.scale_variants);
+    let source = img.dimensions();
+    let img = if grid > 1 { variants::pad_to_grid(img, grid) } else { img };
+    let (trimmed_img, mut trim_info) = if opts.trim {
+        let mut tr = trim::trim_transparent(&img, opts.trim_threshold);
+        if grid > 1 {
+            tr = variants::snap_trim(&img, tr, grid);
+        }
         (tr.image.clone(), tr)
     } else {
         let (w, h) = img.dimensions();
@@ -1446,7 +1723,18 @@ fn preprocess_sprite(name: String, img: RgbaImage, opts: &PackOptions) -> Sprite
         )
     };
 
-    let extruded = extrude::extrude_edges(&trimmed_img, opts.extrude);
+    // The padding is layout-only — the published source size stays the true
+    // one (see `variants::clip_to_source`).
+    (trim_info.source_w, trim_info.source_h) = source;
+
+    let mut extruded = if opts.channel_pack {
+        extrude::extrude_edges(&alpha::to_mask(&trimmed_img), opts.extrude)
+    } else {
+        extrude::extrude_edges(&trimmed_img, opts.extrude)
+    };
+    if opts.premultiply_alpha {
+        alpha::premultiply(&mut extruded);
+    }
     let extra = opts.extrude * 2 + opts.padding * 2;
     let pack_w = trimmed_img.width() + extra;
     let pack_h = trimmed_img.height() + extra;
@@ -1481,6 +1769,35 @@ fn preprocess_one(
     Ok(Some(preprocess_sprite(name.to_string(), img, opts)))
 }
 
+/// Group membership for `names` under `opts.groups`. Tag rules read tags
+/// from the existing manifest sidecar (if any).
+fn sprite_groups<'a>(
+    opts: &PackOptions,
+    names: impl IntoIterator<Item = &'a str>,
+) -> Result<HashMap<String, String>> {
+    if opts.groups.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    if opts.groups.iter().any(|r| r.uses_tags()) {
+        if let Some(m) = manifest::Manifest::try_load(&manifest::Manifest::path_for(opts))? {
+            tags = m
+                .sprites
+                .into_iter()
+                .filter(|(_, e)| !e.tags.is_empty())
+                .map(|(name, e)| (name, e.tags))
+                .collect();
+        }
+    }
+    Ok(groups::assign(&opts.groups, names, &tags))
+}
+
+fn file_name_or(path: &Path, fallback: &str) -> String {
+    path.file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| fallback.to_string())
+}
+
 /// Persist the manifest after a successful (full or partial) pack.
 fn write_manifest(
     opts: &PackOptions,
@@ -1510,6 +1827,11 @@ fn write_manifest(
         by_name.insert(h.0.clone(), h);
     }
 
+    let group_of = sprite_groups(
+        opts,
+        results.iter().flat_map(|r| r.sprites.iter().map(|s| s.name.as_str())),
+    )?;
+
     // Build atlas entries first (and remember where each sprite landed).
     let mut atlases: Vec<manifest::AtlasEntry> = Vec::with_capacity(results.len());
     let mut sprite_atlas_idx: HashMap<String, usize> = HashMap::new();
@@ -1530,10 +1852,29 @@ fn write_manifest(
         // whatever variant we wrote (quantized / not).
         let image_hash = manifest::hash_file(&r.image_path)?;
 
-        for ur in &r.outer_rects {
-            sprite_atlas_idx.insert(ur.name.clone(), atlas_idx);
+        // Canonical sprites, not outer rects: a channel-packed rect is named
+        // after only the first of its masks.
+        for s in r.sprites.iter().filter(|s| s.alias_of.is_none()) {
+            sprite_atlas_idx.insert(s.name.clone(), atlas_idx);
         }
 
+        let variants = r
+            .variants
+            .iter()
+            .zip(&opts.scale_variants)
+            .map(|(v, spec)| {
+                Ok(manifest::VariantEntry {
+                    suffix: spec.suffix.clone(),
+                    scale: format!("{}/{}", spec.num, spec.den),
+                    image_filename: file_name_or(&v.image_path, ""),
+                    data_filename: file_name_or(&v.data_path, ""),
+                    width: v.width,
+                    height: v.height,
+                    image_hash: manifest::hash_file(&v.image_path)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
         atlases.push(manifest::AtlasEntry {
             image_filename,
             data_filename,
@@ -1543,6 +1884,14 @@ fn write_manifest(
             format: opts.format.as_str().to_string(),
             used_rects: r.outer_rects.clone(),
             free_rects: r.free_rects.clone(),
+            variants,
+            groups: r
+                .sprites
+                .iter()
+                .filter_map(|s| group_of.get(&s.name).cloned())
+                .collect::<std::collections::BTreeSet<_>>()
+                .into_iter()
+                .collect(),
         });
     }
 
@@ -1555,7 +1904,7 @@ fn write_manifest(
             let lookup_name = ps.alias_of.as_deref().unwrap_or(&ps.name);
             let atlas_idx = match sprite_atlas_idx.get(lookup_name).copied() {
                 Some(i) => i,
-                None => continue, // alias to a sprite not in outer_rects (shouldn't happen)
+                None => continue, // alias to a sprite not on any page (shouldn't happen)
             };
 
             let (file_size, mtime, content_hash, source_size) =
@@ -1589,6 +1938,8 @@ fn write_manifest(
                     content_y: ps.y,
                     rotated: ps.rotated,
                     alias_of: ps.alias_of.clone(),
+                    group: group_of.get(&ps.name).cloned(),
+                    channel: ps.channel,
                     // Tags / attribution / source_url are user-editable metadata
                     // and survive across packs via merge_user_metadata below.
                     tags: Vec::new(),
@@ -1604,8 +1955,14 @@ fn write_manifest(
         tool: format!("mj_atlas {}", env!("CARGO_PKG_VERSION")),
         options_hash: manifest::compute_options_hash(opts),
         input_root: opts.input_dir.to_string_lossy().to_string(),
+        layout_grid: variants::layout_grid(&opts.scale_variants),
         sprites,
         atlases,
+        animations: results
+            .first()
+            .map(|r| r.animation_meta.clone())
+            .unwrap_or_default(),
+        premultiplied_alpha: opts.premultiply_alpha,
     };
 
     let path = manifest::Manifest::path_for(opts);
@@ -1620,12 +1977,25 @@ fn write_manifest(
     Ok(())
 }
 
+/// Rotate an image 90° clockwise.
+fn rotate_90cw(img: &RgbaImage) -> RgbaImage {
+    let (w, h) = img.dimensions();
+    let mut rotated = RgbaImage::new(h, w);
+    for y in 0..h {
+        for x in 0..w {
+            let pixel = *img.get_pixel(x, y);
+            rotated.put_pixel(h - 1 - y, x, pixel);
+        }
+    }
+    rotated
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use std::io::Cursor;
 
-    fn write_png(path: &Path, w: u32, h: u32, color: [u8; 4]) {
+    pub(crate) fn write_png(path: &Path, w: u32, h: u32, color: [u8; 4]) {
         let mut img = RgbaImage::new(w, h);
         for px in img.pixels_mut() {
             *px = image::Rgba(color);
@@ -1641,7 +2011,7 @@ mod tests {
         std::fs::write(path, buf).unwrap();
     }
 
-    fn make_opts(input_dir: &Path, output_dir: &Path, explicit: Option<Vec<PathBuf>>) -> PackOptions {
+    pub(crate) fn make_opts(input_dir: &Path, output_dir: &Path, explicit: Option<Vec<PathBuf>>) -> PackOptions {
         PackOptions {
             input_dir: input_dir.to_path_buf(),
             output_name: "atlas".into(),
@@ -1665,6 +2035,13 @@ mod tests {
             tolerance: 2.0,
             polygon_shape: PolygonShape::Concave,
             max_vertices: 0,
+            scale_variants: Vec::new(),
+            groups: Vec::new(),
+            animations: None,
+            algorithm: heuristics::Algorithm::MaxRects,
+            alpha_bleed: false,
+            premultiply_alpha: false,
+            channel_pack: false,
         }
     }
 
@@ -1769,17 +2146,458 @@ mod tests {
 
         let _ = std::fs::remove_dir_all(&root);
     }
-}
 
-/// Rotate an image 90° clockwise.
-fn rotate_90cw(img: &RgbaImage) -> RgbaImage {
-    let (w, h) = img.dimensions();
-    let mut rotated = RgbaImage::new(h, w);
-    for y in 0..h {
-        for x in 0..w {
-            let pixel = *img.get_pixel(x, y);
-            rotated.put_pixel(h - 1 - y, x, pixel);
+    fn pack_and_save(opts: &PackOptions) -> Vec<AtlasResult> {
+        let results = execute(opts).unwrap();
+        for r in &results {
+            r.save_to_disk(opts, opts.format).unwrap();
         }
+        persist_manifest(opts, &results).unwrap();
+        results
+    }
+
+    fn assert_variant_proportional(master: &AtlasResult, num: u32, den: u32) {
+        assert_eq!(master.variants.len(), 1);
+        let v = &master.variants[0];
+        for (m, s) in master.sprites.iter().zip(&v.sprites) {
+            assert_eq!(m.name, s.name);
+            // The grid-aligned layout rect behind each frame scales exactly.
+            let (x, y, w, h) = variants::layout_rect(m, den);
+            for (a, b) in [(x, s.x), (y, s.y), (m.trim_offset_x, s.trim_offset_x)] {
+                assert_eq!(a * num % den, 0, "{} not grid aligned", m.name);
+                assert_eq!(a * num / den, b, "{} not proportional", m.name);
+            }
+            assert_eq!((w % den, h % den), (0, 0), "{} not grid aligned", m.name);
+            // Frames are clipped to the source size, which rounds up.
+            assert_eq!(s.source_w, (m.source_w * num).div_ceil(den), "{}", m.name);
+            assert!(s.w <= w * num / den && s.w * den >= m.w * num, "{}", m.name);
+            assert!(s.h <= h * num / den && s.h * den >= m.h * num, "{}", m.name);
+        }
+    }
+
+    /// Scale variants share the master layout exactly, and an incremental
+    /// add keeps every existing sprite in place in master AND variant.
+    #[test]
+    fn scale_variants_are_proportional_and_uv_stable() {
+        let tmp = std::env::temp_dir().join(format!(
+            "mj_atlas_variants_test_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&tmp);
+        let input = tmp.join("in");
+        let out = tmp.join("out");
+        std::fs::create_dir_all(&input).unwrap();
+
+        // Odd sizes on purpose — the grid has to pad them.
+        write_png(&input.join("a.png"), 13, 7, [255, 0, 0, 255]);
+        write_png(&input.join("b.png"), 9, 21, [0, 255, 0, 255]);
+        write_png(&input.join("c.png"), 30, 30, [0, 0, 255, 255]);
+
+        let mut opts = make_opts(&input, &out, None);
+        opts.incremental = true;
+        opts.extrude = 1;
+        opts.spacing = 1;
+        opts.scale_variants = vec![variants::ScaleVariant::parse("@sd=0.25").unwrap()];
+
+        let first = pack_and_save(&opts);
+        assert_eq!(first.len(), 1);
+        assert_variant_proportional(&first[0], 1, 4);
+        assert!(out.join("atlas@sd.png").is_file());
+        assert!(out.join("atlas@sd.json").is_file());
+
+        write_png(&input.join("d.png"), 5, 5, [9, 9, 9, 255]);
+        let second = pack_and_save(&opts);
+        assert_variant_proportional(&second[0], 1, 4);
+        for before in &first[0].variants[0].sprites {
+            let after = second[0].variants[0]
+                .sprites
+                .iter()
+                .find(|s| s.name == before.name)
+                .unwrap();
+            assert_eq!((before.x, before.y), (after.x, after.y), "{} moved", before.name);
+        }
+
+        let m = manifest::Manifest::try_load(&manifest::Manifest::path_for(&opts))
+            .unwrap()
+            .unwrap();
+        assert_eq!(m.layout_grid, 4);
+        assert_eq!(m.atlases[0].variants.len(), 1);
+        assert_eq!(
+            manifest::resolve_manifest_path(&out.join("atlas@sd.png")).unwrap(),
+            manifest::Manifest::path_for(&opts)
+        );
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
+    /// The grid padding is layout-only: master frames keep their true size.
+    #[test]
+    fn scale_variants_keep_master_source_size() {
+        let tmp = std::env::temp_dir().join(format!(
+            "mj_atlas_variants_source_test_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&tmp);
+        let input = tmp.join("in");
+        let out = tmp.join("out");
+        std::fs::create_dir_all(&input).unwrap();
+        write_png(&input.join("a.png"), 13, 7, [255, 0, 0, 255]);
+
+        let mut opts = make_opts(&input, &out, None);
+        opts.scale_variants = vec![variants::ScaleVariant::parse("@1x=0.5").unwrap()];
+        let results = pack_and_save(&opts);
+        let a = &results[0].sprites[0];
+        assert_eq!((a.w, a.h, a.source_w, a.source_h), (13, 7, 13, 7));
+        assert!(!a.trimmed);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(out.join("atlas.json")).unwrap()).unwrap();
+        let frame = &json["frames"]["a.png"];
+        assert_eq!(frame["sourceSize"], serde_json::json!({ "w": 13, "h": 7 }));
+        assert_eq!(frame["frame"]["w"], 13);
+        assert_eq!(frame["frame"]["h"], 7);
+
+        let half = &results[0].variants[0].sprites[0];
+        assert_eq!((half.w, half.h, half.source_w, half.source_h), (7, 4, 7, 4));
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
+    /// `optimize` never does worse than the default engine, and a forced
+    /// full repack reproduces its layout exactly.
+    #[test]
+    fn optimize_is_deterministic_and_no_larger_than_maxrects() {
+        let tmp = std::env::temp_dir().join(format!(
+            "mj_atlas_optimize_test_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&tmp);
+        let input = tmp.join("in");
+        let out = tmp.join("out");
+        std::fs::create_dir_all(&input).unwrap();
+        for (i, (w, h)) in [(40, 12), (12, 40), (25, 25), (60, 8), (8, 33), (17, 17), (30, 5)]
+            .into_iter()
+            .enumerate()
+        {
+            write_png(&input.join(format!("s{}.png", i)), w, h, [i as u8 * 30, 0, 0, 255]);
+        }
+
+        let total_area = |rs: &[AtlasResult]| -> u64 {
+            rs.iter().map(|r| r.width as u64 * r.height as u64).sum()
+        };
+        let baseline = execute(&make_opts(&input, &out, None)).unwrap();
+
+        let mut opts = make_opts(&input, &out, None);
+        opts.algorithm = heuristics::Algorithm::Optimize;
+        opts.incremental = true;
+        let first = pack_and_save(&opts);
+        assert!(first.len() <= baseline.len());
+        assert!(total_area(&first) <= total_area(&baseline));
+        assert!(first.iter().all(|r| r.occupancy() > 0.0 && r.occupancy() <= 1.0));
+
+        opts.force = true;
+        let second = pack_and_save(&opts);
+        assert_eq!(first.len(), second.len());
+        for (a, b) in first.iter().zip(&second) {
+            assert_eq!((a.width, a.height), (b.width, b.height));
+            for (sa, sb) in a.sprites.iter().zip(&b.sprites) {
+                assert_eq!(
+                    (&sa.name, sa.x, sa.y, sa.rotated),
+                    (&sb.name, sb.x, sb.y, sb.rotated)
+                );
+            }
+        }
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
+    /// Grouped sprites share a page even when a fit-only split would mix
+    /// them, and an incremental add joins its group's page.
+    #[test]
+    fn groups_share_a_page_across_full_and_incremental_packs() {
+        let tmp = std::env::temp_dir().join(format!(
+            "mj_atlas_groups_test_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&tmp);
+        let input = tmp.join("in");
+        let out = tmp.join("out");
+        for (d, dir) in ["hud", "boss"].into_iter().enumerate() {
+            std::fs::create_dir_all(input.join(dir)).unwrap();
+            for i in 0..3u8 {
+                let color = [i * 40, d as u8 * 100, 0, 255];
+                write_png(&input.join(dir).join(format!("{}.png", i)), 32, 32, color);
+            }
+        }
+        write_png(&input.join("loose.png"), 32, 32, [0, 0, 255, 255]);
+
+        // 64x64 pages hold four 32x32 sprites.
+        let mut opts = make_opts(&input, &out, None);
+        opts.max_size = 64;
+        opts.incremental = true;
+        opts.groups = ["hud=dir:hud", "boss=glob:boss/*.png"]
+            .iter()
+            .map(|r| groups::GroupRule::parse(r).unwrap())
+            .collect();
+
+        let page_of = |results: &[AtlasResult], name: &str| {
+            results
+                .iter()
+                .position(|r| r.sprites.iter().any(|s| s.name == name))
+                .unwrap()
+        };
+
+        let first = pack_and_save(&opts);
+        assert_eq!(first.len(), 2);
+        for i in 0..3 {
+            assert_eq!(page_of(&first, &format!("hud/{}.png", i)), 0);
+            assert_eq!(page_of(&first, &format!("boss/{}.png", i)), 1);
+        }
+
+        write_png(&input.join("hud/3.png"), 32, 32, [0, 200, 0, 255]);
+        let (second, path) = execute_with_path(&opts).unwrap();
+        assert_eq!(path, PackPath::Partial);
+        assert_eq!(page_of(&second, "hud/3.png"), 0);
+
+        persist_manifest(&opts, &second).unwrap();
+        let m = manifest::Manifest::try_load(&manifest::Manifest::path_for(&opts))
+            .unwrap()
+            .unwrap();
+        assert_eq!(m.sprites["hud/3.png"].group.as_deref(), Some("hud"));
+        assert_eq!(m.atlases[0].groups, vec!["hud".to_string()]);
+        assert!(m.sprites["loose.png"].group.is_none());
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
+    /// Definition-file metadata reaches the manifest, the JSON and Godot
+    /// outputs; editing the file invalidates the cache; a typo fails.
+    #[test]
+    fn animation_definitions_flow_into_outputs() {
+        let tmp = std::env::temp_dir().join(format!(
+            "mj_atlas_anim_test_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&tmp);
+        let input = tmp.join("in");
+        let out = tmp.join("out");
+        std::fs::create_dir_all(&input).unwrap();
+        for i in 1..=3u8 {
+            write_png(&input.join(format!("walk_{}.png", i)), 8, 8, [i * 50, 0, 0, 255]);
+        }
+        let defs = tmp.join("anims.toml");
+        std::fs::write(
+            &defs,
+            "version = 1\n[[animation]]\nname = \"walk\"\nfps = 12\nmode = \"pingpong\"\n\
+             frames = [\"walk_1.png\", { sprite = \"walk_2.png\", duration = 2.0, events = [\"step\"] }, \
+             { sprite = \"walk_3.png\", events = ['say \"hi\"'] }]\n",
+        )
+        .unwrap();
+
+        let mut opts = make_opts(&input, &out, None);
+        opts.incremental = true;
+        opts.animations = Some(defs.clone());
+        opts.format = crate::output::Format::GodotTres;
+        pack_and_save(&opts);
+
+        let m = manifest::Manifest::try_load(&manifest::Manifest::path_for(&opts))
+            .unwrap()
+            .unwrap();
+        let walk = &m.animations["walk"];
+        assert_eq!(walk.fps, 12.0);
+        assert_eq!(walk.durations, vec![1.0, 2.0, 1.0]);
+        assert_eq!(walk.events[0].name, "step");
+
+        let sf = std::fs::read_to_string(out.join("atlas.sprites/walk_frames.tres")).unwrap();
+        assert!(sf.contains("\"speed\": 12.0"), "{}", sf);
+        assert!(sf.contains("\"duration\": 2.0"), "{}", sf);
+        // Event names are escaped like any other .tres string literal.
+        assert!(
+            sf.contains(
+                "metadata/events = [{\"frame\": 1, \"name\": \"step\"}, \
+                 {\"frame\": 2, \"name\": \"say \\\"hi\\\"\"}]"
+            ),
+            "{}",
+            sf
+        );
+        // Ping-pong unrolled: 1 2 3 2.
+        assert_eq!(sf.matches("SubResource(\"3\")").count(), 2, "{}", sf);
+
+        opts.format = crate::output::Format::JsonHash;
+        let results = execute(&opts).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&crate::output::json::to_json_hash(&results[0], &opts).unwrap())
+                .unwrap();
+        assert_eq!(json["animationInfo"]["walk"]["mode"], "pingpong");
+        assert_eq!(json["animations"]["walk"][1], "walk_2.png");
+
+        // Same options + edited definitions → not a cache hit.
+        pack_and_save(&opts);
+        std::fs::write(
+            &defs,
+            "version = 1\n[[animation]]\nname = \"walk\"\nfps = 8\n",
+        )
+        .unwrap();
+        let (_, path) = execute_with_path(&opts).unwrap();
+        assert_eq!(path, PackPath::Full);
+
+        std::fs::write(
+            &defs,
+            "version = 1\n[[animation]]\nname = \"walk\"\nframes = [\"walk_9.png\"]\n",
+        )
+        .unwrap();
+        let err = execute(&opts).unwrap_err();
+        assert!(err.to_string().contains("walk_9.png"), "{}", err);
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
+    /// Bleeding covers every transparent texel and survives a partial
+    /// repack; premultiplication is applied once and recorded.
+    #[test]
+    fn alpha_bleed_and_premultiply() {
+        let tmp = std::env::temp_dir().join(format!(
+            "mj_atlas_alpha_test_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&tmp);
+        let input = tmp.join("in");
+        std::fs::create_dir_all(&input).unwrap();
+        write_png(&input.join("red.png"), 8, 8, [200, 0, 0, 255]);
+
+        let mut opts = make_opts(&input, &tmp.join("bleed"), None);
+        opts.max_size = 64;
+        opts.padding = 2;
+        opts.incremental = true;
+        opts.alpha_bleed = true;
+        let plain_hash = manifest::compute_options_hash(&PackOptions {
+            alpha_bleed: false,
+            ..opts.clone()
+        });
+        assert_ne!(manifest::compute_options_hash(&opts), plain_hash);
+
+        let first = pack_and_save(&opts);
+        let page = &first[0].atlas_image;
+        assert!(page.pixels().filter(|p| p[3] == 0).all(|p| p.0 == [200, 0, 0, 0]));
+
+        // In-place edit: stale bled texels from the page on disk get recolored.
+        write_png(&input.join("red.png"), 8, 8, [0, 0, 200, 255]);
+        // Same byte size; move the mtime so the fast pre-check sees the edit.
+        std::fs::File::options()
+            .write(true)
+            .open(input.join("red.png"))
+            .unwrap()
+            .set_modified(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap();
+        let (second, path) = execute_with_path(&opts).unwrap();
+        assert_eq!(path, PackPath::Partial);
+        let page = &second[0].atlas_image;
+        assert!(page.pixels().filter(|p| p[3] == 0).all(|p| p.0 == [0, 0, 200, 0]));
+        let mut rebled = page.clone();
+        alpha::bleed(&mut rebled);
+        assert_eq!(&rebled, page);
+
+        let mut opts = make_opts(&input, &tmp.join("pma"), None);
+        opts.alpha_bleed = true;
+        opts.premultiply_alpha = true;
+        assert!(execute(&opts).is_err());
+        opts.alpha_bleed = false;
+        write_png(&input.join("red.png"), 8, 8, [200, 100, 0, 128]);
+        let results = pack_and_save(&opts);
+        let red = &results[0].sprites[0];
+        assert_eq!(results[0].atlas_image.get_pixel(red.x, red.y).0, [100, 50, 0, 128]);
+        let json: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(results[0].data_path.with_extension("json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(json["meta"]["premultipliedAlpha"], true);
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
+    /// Variant pages are resampled from the master's content rects, so their
+    /// gutters must be bled again rather than left black.
+    #[test]
+    fn alpha_bleed_covers_scale_variant_pages() {
+        let tmp = std::env::temp_dir().join(format!(
+            "mj_atlas_variant_bleed_test_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&tmp);
+        let input = tmp.join("in");
+        std::fs::create_dir_all(&input).unwrap();
+        write_png(&input.join("red.png"), 8, 8, [200, 0, 0, 255]);
+
+        let mut opts = make_opts(&input, &tmp.join("out"), None);
+        opts.max_size = 64;
+        opts.padding = 2;
+        opts.alpha_bleed = true;
+        opts.scale_variants = vec![variants::ScaleVariant::parse("@h=0.5").unwrap()];
+
+        let results = pack_and_save(&opts);
+        let page = &results[0].variants[0].atlas_image;
+        assert!(page.pixels().any(|p| p[3] == 0), "variant page has gutters");
+        assert!(page.pixels().filter(|p| p[3] == 0).all(|p| p.0 == [200, 0, 0, 0]));
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
+    /// Four masks share one rect, one per channel; the channel assignment
+    /// survives a cache hit and unpack restores each mask.
+    #[test]
+    fn channel_pack_stacks_masks() {
+        let tmp = std::env::temp_dir().join(format!(
+            "mj_atlas_channel_test_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&tmp);
+        let input = tmp.join("in");
+        let out = tmp.join("out");
+        std::fs::create_dir_all(&input).unwrap();
+        let masks = [
+            ("a.png", [255, 255, 255, 255], 255),
+            ("b.png", [128, 128, 128, 255], 128),
+            ("c.png", [255, 255, 255, 64], 64),
+            ("d.png", [0, 0, 0, 255], 0),
+            ("e.png", [255, 255, 255, 255], 255),
+        ];
+        for (name, color, _) in &masks {
+            // `e` is smaller, so it sorts into a slot of its own.
+            let size = if *name == "e.png" { 4 } else { 8 };
+            write_png(&input.join(name), size, size, *color);
+        }
+
+        let mut opts = make_opts(&input, &out, None);
+        opts.incremental = true;
+        opts.channel_pack = true;
+        let results = pack_and_save(&opts);
+        assert_eq!(results[0].outer_rects.len(), 2);
+        let sprite = |results: &[AtlasResult], name: &str| {
+            results[0].sprites.iter().find(|s| s.name == name).unwrap().clone()
+        };
+        let a = sprite(&results, "a.png");
+        let px = results[0].atlas_image.get_pixel(a.x, a.y).0;
+        for (i, (name, _, value)) in masks[..4].iter().enumerate() {
+            let s = sprite(&results, name);
+            assert_eq!((s.x, s.y), (a.x, a.y));
+            assert_eq!(s.channel, Some(alpha::Channel::ALL[i]));
+            assert_eq!(px[i], *value);
+        }
+        assert_eq!(sprite(&results, "e.png").channel, Some(alpha::Channel::R));
+
+        let (cached, path) = execute_with_path(&opts).unwrap();
+        assert_eq!(path, PackPath::Skipped);
+        assert_eq!(sprite(&cached, "c.png").channel, Some(alpha::Channel::B));
+
+        let restored = tmp.join("restored");
+        crate::cmd::unpack::unpack(&results[0].data_path.with_extension("json"), &restored, false)
+            .unwrap();
+        let b = image::open(restored.join("b.png")).unwrap().into_rgba8();
+        assert_eq!(b.get_pixel(3, 3).0, [255, 255, 255, 128]);
+
+        opts.quantize = true;
+        assert!(execute(&opts).is_err());
+
+        let _ = std::fs::remove_dir_all(&tmp);
     }
-    rotated
 }
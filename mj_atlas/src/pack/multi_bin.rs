@@ -1,6 +1,12 @@
-use crunch::{Item, PackedItem, Packer, Rect, Rotation};
+use crunch::{Item, PackedItem, Rotation};
 
 use crate::error::{AppError, Result};
+use crate::pack::heuristics::Strategy;
+
+/// `(name, width, height)` of one item to pack.
+pub type PackItem = (String, usize, usize);
+/// `(bin_width, bin_height, packed_items)` for each output page.
+pub type Bins = Vec<(usize, usize, Vec<PackedItem<String>>)>;
 
 /// Pack items into one or more bins, splitting automatically when needed.
 /// Returns a vec of (bin_width, bin_height, packed_items) for each bin.
@@ -9,6 +15,7 @@ pub fn pack_multi_bin(
     max_size: usize,
     pot: bool,
     allow_rotation: bool,
+    strategy: Strategy,
 ) -> Result<Vec<(usize, usize, Vec<PackedItem<String>>)>> {
     if items.is_empty() {
         return Ok(vec![]);
@@ -48,30 +55,22 @@ pub fn pack_multi_bin(
     let mut bins = Vec::new();
 
     while !remaining.is_empty() {
-        let mut packer = Packer::with_items(remaining.clone());
-
         if pot {
-            match packer.pack_into_po2(max_size) {
-                Ok(packed) => {
-                    bins.push((packed.w, packed.h, packed.items));
-                    return Ok(bins);
-                }
-                Err(()) => {
-                    // Try packing into max_size to see how many fit
-                }
+            if let Some(result) = pack_into_po2(&remaining, max_size, strategy) {
+                bins.push(result);
+                return Ok(bins);
             }
+            // Otherwise pack into max_size to see how many fit
         } else {
             // Try to fit all into an auto-sized rect
-            if let Some(result) = try_auto_size(&remaining, max_size) {
+            if let Some(result) = try_auto_size(&remaining, max_size, strategy) {
                 bins.push(result);
                 return Ok(bins);
             }
         }
 
         // Can't fit all — pack as many as possible into max_size
-        let rect = Rect::of_size(max_size, max_size);
-        let mut packer = Packer::with_items(remaining.clone());
-        let packed = match packer.pack(rect) {
+        let packed = match strategy.pack(&remaining, max_size, max_size) {
             Ok(all) => all,
             Err(partial) => partial,
         };
@@ -100,10 +99,118 @@ pub fn pack_multi_bin(
     Ok(bins)
 }
 
+/// Like [`pack_multi_bin`], but every item list in `groups` lands on a single
+/// page whenever it fits in `max_size`. Groups are placed in order — each
+/// joins the current page if the combined set still fits, otherwise opens a
+/// new page — then `ungrouped` items fill the last page or spill onto new
+/// ones. A group that can't fit one page on its own is split across pages
+/// (with a warning) rather than failing the pack.
+pub fn pack_grouped(
+    groups: Vec<(String, Vec<PackItem>)>,
+    ungrouped: Vec<PackItem>,
+    max_size: usize,
+    pot: bool,
+    allow_rotation: bool,
+    strategy: Strategy,
+) -> Result<Bins> {
+    let rot = if allow_rotation {
+        Rotation::Allowed
+    } else {
+        Rotation::None
+    };
+    let fits = |items: &[PackItem]| {
+        let items: Vec<Item<String>> = items
+            .iter()
+            .map(|(name, w, h)| Item::new(name.clone(), *w, *h, rot))
+            .collect();
+        strategy.pack(&items, max_size, max_size).is_ok()
+    };
+
+    let mut pages: Vec<Vec<PackItem>> = Vec::new();
+    for (group, items) in groups {
+        if items.is_empty() {
+            continue;
+        }
+        if !fits(&items) {
+            log::warn!(
+                "group '{}' ({} sprites) does not fit in one {}x{} page; splitting it across pages",
+                group,
+                items.len(),
+                max_size,
+                max_size
+            );
+            for (_, _, packed) in pack_multi_bin(items.clone(), max_size, pot, allow_rotation, strategy)? {
+                let names: std::collections::HashSet<&str> =
+                    packed.iter().map(|p| p.data.as_str()).collect();
+                pages.push(
+                    items
+                        .iter()
+                        .filter(|i| names.contains(i.0.as_str()))
+                        .cloned()
+                        .collect(),
+                );
+            }
+            continue;
+        }
+        match pages.last_mut() {
+            Some(page) if fits(&[page.as_slice(), items.as_slice()].concat()) => {
+                page.extend(items)
+            }
+            _ => pages.push(items),
+        }
+    }
+
+    if !ungrouped.is_empty() {
+        match pages.last_mut() {
+            Some(page) if fits(&[page.as_slice(), ungrouped.as_slice()].concat()) => {
+                page.extend(ungrouped)
+            }
+            _ => pages.push(ungrouped),
+        }
+    }
+
+    // Each page is now known to fit; pack it for real (tight / POT sizing).
+    // A spill page of ungrouped items may still split, which is fine.
+    let mut bins = Vec::with_capacity(pages.len());
+    for page in pages {
+        bins.extend(pack_multi_bin(page, max_size, pot, allow_rotation, strategy)?);
+    }
+    Ok(bins)
+}
+
+/// Smallest power-of-two page (square, then 2:1, then 1:2 per size step)
+/// that holds every item — the search crunch's `pack_into_po2` does, for any
+/// strategy.
+fn pack_into_po2(
+    items: &[Item<String>],
+    max_size: usize,
+    strategy: Strategy,
+) -> Option<(usize, usize, Vec<PackedItem<String>>)> {
+    let min_area: usize = items.iter().map(|i| i.w * i.h).sum();
+
+    let mut size = 2;
+    while size * size * 2 < min_area {
+        size *= 2;
+    }
+
+    while size <= max_size {
+        for (w, h) in [(size, size), (size * 2, size), (size, size * 2)] {
+            if w <= max_size && h <= max_size && w * h >= min_area {
+                if let Ok(packed) = strategy.pack(items, w, h) {
+                    return Some((w, h, packed));
+                }
+            }
+        }
+        size *= 2;
+    }
+    None
+}
+
 /// Try to fit all items into an auto-sized non-POT atlas.
 fn try_auto_size(
     items: &[Item<String>],
     max_size: usize,
+    strategy: Strategy,
 ) -> Option<(usize, usize, Vec<PackedItem<String>>)> {
     let total_area: usize = items.iter().map(|i| i.w * i.h).sum();
     let side = (total_area as f64).sqrt().ceil() as usize;
@@ -121,8 +228,7 @@ fn try_auto_size(
             ((try_size * 3 / 2).min(max_size), try_size),
             (try_size, (try_size * 3 / 2).min(max_size)),
         ] {
-            let mut packer = Packer::with_items(items.iter().cloned());
-            if let Ok(packed) = packer.pack(Rect::of_size(w, h)) {
+            if let Ok(packed) = strategy.pack(items, w, h) {
                 let (actual_w, actual_h) = tight_bounds(&packed);
                 return Some((actual_w, actual_h, packed));
             }
@@ -0,0 +1,458 @@
+//! Multi-resolution scale variants (`@1x` / `@2x` / `@0.5x`, …).
+//!
+//! The pack runs ONCE at the master (input) resolution. Every variant atlas is
+//! then derived from the master layout by scaling each sprite's content rect
+//! with an exact rational factor, so a sprite's rect in a variant is always
+//! `master_rect * scale` — no rounding, no independent layout.
+//!
+//! Exactness is guaranteed by snapping the master layout to a **grid**: the
+//! least common multiple of every variant's scale denominator. With variants
+//! `0.5` and `0.25` the grid is 4, so every position, layout size and trim
+//! offset in the master atlas is a multiple of 4 and scales cleanly.
+//! Concretely, when variants are enabled:
+//!
+//!   - source canvases are grown (transparent right/bottom) to a grid multiple
+//!   - trim bounds are widened outward to grid boundaries
+//!   - `extrude + padding` is rounded up to a grid multiple (the extra goes
+//!     into padding), and so is `spacing`
+//!
+//! The canvas padding is layout-only: published frames are clipped back to
+//! the true source size ([`clip_to_source`]), so a 13×7 sprite still reports
+//! a 13×7 `sourceSize`. Variants scale the grid-aligned rect behind each frame
+//! ([`layout_rect`]) and round their own source sizes up.
+//!
+//! Variant pixels are produced by cropping each sprite's content rect out of
+//! the master atlas and resampling it, then re-extruding at
+//! `ceil(extrude * scale)`. Since the master atlas is the only input, the same
+//! code path serves full packs, partial (UV-stable) repacks and cache hits —
+//! incremental UV stability carries over to every variant automatically.
+//! Rendered pages then get the same alpha finishing as the master (see
+//! `alpha::finish_variant_page`).
+
+use crate::error::{AppError, Result};
+use crate::pack::trim::TrimResult;
+use crate::pack::{alpha, extrude, manifest, AtlasResult, PackOptions, PackedSprite};
+use image::RgbaImage;
+
+/// One requested output variant, e.g. `@1x=0.5`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScaleVariant {
+    /// Appended to the output name: `atlas` + `@1x` ⇒ `atlas@1x.png`.
+    pub suffix: String,
+    /// Scale relative to the master, as a reduced fraction `num / den`.
+    pub num: u32,
+    pub den: u32,
+}
+
+impl ScaleVariant {
+    /// Parse the CLI form `SUFFIX=SCALE`. `SCALE` may be a decimal (`0.5`,
+    /// `0.75`, `2`) or a fraction (`1/3`).
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (suffix, scale) = spec.split_once('=').ok_or_else(|| {
+            AppError::InvalidParam(format!(
+                "scale variant '{}' must look like SUFFIX=SCALE (e.g. @1x=0.5)",
+                spec
+            ))
+        })?;
+        let suffix = suffix.trim();
+        if suffix.is_empty() || suffix.contains(['/', '\\']) {
+            return Err(AppError::InvalidParam(format!(
+                "scale variant '{}': suffix must be a non-empty file-name fragment",
+                spec
+            )));
+        }
+        let (num, den) = parse_ratio(scale.trim()).ok_or_else(|| {
+            AppError::InvalidParam(format!(
+                "scale variant '{}': '{}' is not a positive decimal or fraction",
+                spec, scale
+            ))
+        })?;
+        Ok(Self {
+            suffix: suffix.to_string(),
+            num,
+            den,
+        })
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    /// Scale a master-space length. Exact when `v` is a multiple of the grid.
+    pub fn apply(&self, v: u32) -> u32 {
+        (v as u64 * self.num as u64 / self.den as u64) as u32
+    }
+
+    /// Scale rounding up — used for atlas dimensions and extrude width, where
+    /// the master value is not necessarily grid-aligned.
+    pub fn apply_ceil(&self, v: u32) -> u32 {
+        (v as u64 * self.num as u64).div_ceil(self.den as u64) as u32
+    }
+
+    /// Canonical `suffix=num/den` form — stable input for the options hash.
+    pub fn canonical(&self) -> String {
+        format!("{}={}/{}", self.suffix, self.num, self.den)
+    }
+}
+
+fn parse_ratio(s: &str) -> Option<(u32, u32)> {
+    let (num, den) = if let Some((n, d)) = s.split_once('/') {
+        (n.trim().parse::<u64>().ok()?, d.trim().parse::<u64>().ok()?)
+    } else {
+        let (int_part, frac_part) = s.split_once('.').unwrap_or((s, ""));
+        if frac_part.len() > 6 || !frac_part.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let den = 10u64.pow(frac_part.len() as u32);
+        let int: u64 = if int_part.is_empty() { 0 } else { int_part.parse().ok()? };
+        let frac: u64 = if frac_part.is_empty() { 0 } else { frac_part.parse().ok()? };
+        (int * den + frac, den)
+    };
+    if num == 0 || den == 0 {
+        return None;
+    }
+    let g = gcd(num, den);
+    let (num, den) = (num / g, den / g);
+    if num > u32::MAX as u64 || den > u32::MAX as u64 {
+        return None;
+    }
+    Some((num as u32, den as u32))
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Layout grid for a variant set: lcm of all denominators (1 when empty).
+pub fn layout_grid(variants: &[ScaleVariant]) -> u32 {
+    variants.iter().fold(1u64, |acc, v| {
+        let d = v.den as u64;
+        acc / gcd(acc, d) * d
+    }) as u32
+}
+
+fn round_up(v: u32, grid: u32) -> u32 {
+    v.div_ceil(grid) * grid
+}
+
+/// Return the options the layout actually runs with: `extrude + padding` and
+/// `spacing` rounded up to the grid. Identity when no variants are requested,
+/// so single-resolution packs are byte-for-byte unchanged.
+pub fn snapped_options(opts: &PackOptions) -> PackOptions {
+    let grid = layout_grid(&opts.scale_variants);
+    let mut snapped = opts.clone();
+    if grid > 1 {
+        snapped.padding = round_up(opts.extrude + opts.padding, grid) - opts.extrude;
+        snapped.spacing = round_up(opts.spacing, grid);
+    }
+    snapped
+}
+
+/// Grow `img` to grid-multiple dimensions (transparent fill, right/bottom).
+pub fn pad_to_grid(img: RgbaImage, grid: u32) -> RgbaImage {
+    let (w, h) = img.dimensions();
+    let (gw, gh) = (round_up(w.max(1), grid), round_up(h.max(1), grid));
+    if (gw, gh) == (w, h) {
+        return img;
+    }
+    let mut padded = RgbaImage::new(gw, gh);
+    image::imageops::overlay(&mut padded, &img, 0, 0);
+    padded
+}
+
+/// Clip a sprite's content rect (laid out on a [`pad_to_grid`] canvas) to its
+/// true `source_w × source_h`. The padding is transparent right/bottom of the
+/// source, so only the size shrinks — except for rotated sprites, where
+/// `rotate_90cw` turns the bottom rows into the leftmost atlas columns.
+pub fn clip_to_source(s: &mut PackedSprite) {
+    let (x, w, h) = clip_rect(
+        s.x,
+        (s.w, s.h),
+        s.rotated,
+        (s.trim_offset_x, s.trim_offset_y),
+        (s.source_w, s.source_h),
+    );
+    (s.x, s.w, s.h) = (x, w, h);
+    let (w, h) = if s.rotated { (h, w) } else { (w, h) };
+    s.trimmed = (s.trim_offset_x, s.trim_offset_y) != (0, 0) || (w, h) != (s.source_w, s.source_h);
+}
+
+/// [`clip_to_source`] on bare values: content `x` and atlas-orientation
+/// `(w, h)` in, clipped `(x, w, h)` out. Identity when nothing is padded.
+pub fn clip_rect(
+    x: u32,
+    (w, h): (u32, u32),
+    rotated: bool,
+    (offset_x, offset_y): (u32, u32),
+    (source_w, source_h): (u32, u32),
+) -> (u32, u32, u32) {
+    let (w, h) = if rotated { (h, w) } else { (w, h) };
+    let clipped_w = w.min(source_w.saturating_sub(offset_x));
+    let clipped_h = h.min(source_h.saturating_sub(offset_y));
+    if rotated {
+        (x + (h - clipped_h), clipped_h, clipped_w)
+    } else {
+        (x, clipped_w, clipped_h)
+    }
+}
+
+/// The grid-aligned content rect `(x, y, w, h)` a clipped sprite was laid
+/// out in — the inverse of [`clip_to_source`].
+pub fn layout_rect(s: &PackedSprite, grid: u32) -> (u32, u32, u32, u32) {
+    let (w, h) = (round_up(s.w, grid), round_up(s.h, grid));
+    let x = if s.rotated { s.x - (w - s.w) } else { s.x };
+    (x, s.y, w, h)
+}
+
+/// Re-crop a trim result so its rect sits on grid boundaries. `padded` is the
+/// grid-aligned source canvas the trim was computed on.
+pub fn snap_trim(padded: &RgbaImage, tr: TrimResult, grid: u32) -> TrimResult {
+    let (x, y, w, h) = snap_rect(
+        (tr.offset_x, tr.offset_y, tr.image.width(), tr.image.height()),
+        padded.dimensions(),
+        grid,
+    );
+    TrimResult {
+        image: image::imageops::crop_imm(padded, x, y, w, h).to_image(),
+        offset_x: x,
+        offset_y: y,
+        source_w: padded.width(),
+        source_h: padded.height(),
+        trimmed: (w, h) != padded.dimensions(),
+    }
+}
+
+/// Widen a trim rect `(x, y, w, h)` outward to grid boundaries, clamped to a
+/// grid-aligned source of `(src_w, src_h)`.
+pub fn snap_rect(
+    (x, y, w, h): (u32, u32, u32, u32),
+    (src_w, src_h): (u32, u32),
+    grid: u32,
+) -> (u32, u32, u32, u32) {
+    let x0 = x / grid * grid;
+    let y0 = y / grid * grid;
+    let x1 = round_up(x + w, grid).min(src_w);
+    let y1 = round_up(y + h, grid).min(src_h);
+    (x0, y0, x1 - x0, y1 - y0)
+}
+
+/// File naming for a variant of the master bin `bin_suffix` (`""`, `"_1"`, …):
+/// `atlas` + `@1x` + `_1` ⇒ `atlas@1x_1`.
+pub fn variant_base_name(output_name: &str, v: &ScaleVariant, bin_suffix: &str) -> String {
+    format!("{}{}{}", output_name, v.suffix, bin_suffix)
+}
+
+/// Attach one scaled `AtlasResult` per requested variant to every master
+/// result. Cached masters load their variant PNG from disk (`from_cache`);
+/// everything else is rendered from the master atlas pixels.
+pub fn attach(opts: &PackOptions, results: &mut [AtlasResult]) -> Result<()> {
+    if opts.scale_variants.is_empty() {
+        return Ok(());
+    }
+    for r in results.iter_mut() {
+        let bin_suffix = bin_suffix_of(&r.image_path, &opts.output_name);
+        let mut variants = Vec::with_capacity(opts.scale_variants.len());
+        for v in &opts.scale_variants {
+            variants.push(build_variant(opts, r, v, &bin_suffix)?);
+        }
+        r.variants = variants;
+    }
+    Ok(())
+}
+
+pub(crate) fn bin_suffix_of(image_path: &std::path::Path, output_name: &str) -> String {
+    image_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(|s| s.strip_prefix(output_name))
+        .unwrap_or("")
+        .to_string()
+}
+
+fn build_variant(
+    opts: &PackOptions,
+    master: &AtlasResult,
+    v: &ScaleVariant,
+    bin_suffix: &str,
+) -> Result<AtlasResult> {
+    let base = variant_base_name(&opts.output_name, v, bin_suffix);
+    let image_path = opts.output_dir.join(format!("{}.png", base));
+    let data_path = opts.output_dir.join(&base);
+    let width = v.apply_ceil(master.width).max(1);
+    let height = v.apply_ceil(master.height).max(1);
+
+    let grid = layout_grid(&opts.scale_variants);
+    let sprites: Vec<PackedSprite> = master
+        .sprites
+        .iter()
+        .map(|s| scale_sprite(s, v, grid))
+        .collect();
+    let outer_rects: Vec<manifest::UsedRect> = master
+        .outer_rects
+        .iter()
+        .map(|u| manifest::UsedRect {
+            name: u.name.clone(),
+            x: v.apply(u.x),
+            y: v.apply(u.y),
+            w: v.apply(u.w),
+            h: v.apply(u.h),
+            rotated: u.rotated,
+        })
+        .collect();
+
+    let atlas_image = if master.from_cache {
+        image::open(&image_path)?.into_rgba8()
+    } else {
+        let mut img = render(master, v, grid, opts.extrude, width, height);
+        alpha::finish_variant_page(opts, &mut img);
+        img
+    };
+
+    Ok(AtlasResult {
+        image_path,
+        data_path,
+        width,
+        height,
+        sprites,
+        animations: master.animations.clone(),
+        animation_meta: master.animation_meta.clone(),
+        duplicates_removed: master.duplicates_removed,
+        atlas_image,
+        outer_rects,
+        // Variants never take additive placements — the master owns layout.
+        free_rects: Vec::new(),
+        from_cache: master.from_cache,
+        scale: v.scale(),
+        variants: Vec::new(),
+    })
+}
+
+/// Scale the master's layout rect exactly, then clip it to the scaled source
+/// size (rounded up, since the true source need not be grid-aligned).
+fn scale_sprite(s: &PackedSprite, v: &ScaleVariant, grid: u32) -> PackedSprite {
+    let f = v.scale() as f32;
+    let scale_pts = |pts: &Vec<[f32; 2]>| pts.iter().map(|p| [p[0] * f, p[1] * f]).collect();
+    let (x, y, w, h) = layout_rect(s, grid);
+    let mut scaled = PackedSprite {
+        name: s.name.clone(),
+        x: v.apply(x),
+        y: v.apply(y),
+        w: v.apply(w),
+        h: v.apply(h),
+        rotated: s.rotated,
+        trimmed: s.trimmed,
+        trim_offset_x: v.apply(s.trim_offset_x),
+        trim_offset_y: v.apply(s.trim_offset_y),
+        source_w: v.apply_ceil(s.source_w),
+        source_h: v.apply_ceil(s.source_h),
+        alias_of: s.alias_of.clone(),
+        vertices: s.vertices.as_ref().map(scale_pts),
+        vertices_uv: s.vertices_uv.as_ref().map(scale_pts),
+        triangles: s.triangles.clone(),
+        channel: s.channel,
+    };
+    clip_to_source(&mut scaled);
+    scaled
+}
+
+/// Resample every canonical sprite's content rect out of the master atlas and
+/// paint it at its scaled position, re-extruding at the scaled width.
+fn render(
+    master: &AtlasResult,
+    v: &ScaleVariant,
+    grid: u32,
+    extrude: u32,
+    width: u32,
+    height: u32,
+) -> RgbaImage {
+    let mut out = RgbaImage::new(width, height);
+    let v_extrude = v.apply_ceil(extrude);
+    for s in master.sprites.iter().filter(|s| s.alias_of.is_none()) {
+        let (x, y, lw, lh) = layout_rect(s, grid);
+        let (w, h) = (v.apply(lw), v.apply(lh));
+        if w == 0 || h == 0 {
+            continue;
+        }
+        // The layout rect is already in atlas orientation, so the crop is
+        // taken as-is; rotation only matters when we rebuild from sources.
+        let crop = image::imageops::crop_imm(&master.atlas_image, x, y, lw, lh).to_image();
+        let scaled = if (w, h) == (lw, lh) {
+            crop
+        } else {
+            image::imageops::resize(&crop, w, h, image::imageops::FilterType::Lanczos3)
+        };
+        let extruded = extrude::extrude_edges(&scaled, v_extrude);
+        image::imageops::overlay(
+            &mut out,
+            &extruded,
+            v.apply(x) as i64 - v_extrude as i64,
+            v.apply(y) as i64 - v_extrude as i64,
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_decimal_and_fraction_scales() {
+        let v = ScaleVariant::parse("@1x=0.5").unwrap();
+        assert_eq!((v.suffix.as_str(), v.num, v.den), ("@1x", 1, 2));
+        let v = ScaleVariant::parse("@sd=0.75").unwrap();
+        assert_eq!((v.num, v.den), (3, 4));
+        let v = ScaleVariant::parse("@third=1/3").unwrap();
+        assert_eq!((v.num, v.den), (1, 3));
+        let v = ScaleVariant::parse("@4x=2").unwrap();
+        assert_eq!((v.num, v.den), (2, 1));
+        assert!(ScaleVariant::parse("@1x").is_err());
+        assert!(ScaleVariant::parse("=0.5").is_err());
+        assert!(ScaleVariant::parse("@0x=0").is_err());
+        assert!(ScaleVariant::parse("@x=abc").is_err());
+    }
+
+    #[test]
+    fn grid_is_lcm_of_denominators() {
+        let vs = vec![
+            ScaleVariant::parse("@1x=0.5").unwrap(),
+            ScaleVariant::parse("@q=0.25").unwrap(),
+        ];
+        assert_eq!(layout_grid(&vs), 4);
+        let vs = vec![
+            ScaleVariant::parse("@a=1/3").unwrap(),
+            ScaleVariant::parse("@b=0.5").unwrap(),
+        ];
+        assert_eq!(layout_grid(&vs), 6);
+        assert_eq!(layout_grid(&[]), 1);
+    }
+
+    #[test]
+    fn snapped_options_round_margins_to_grid() {
+        let mut opts = crate::pack::tests::make_opts(
+            std::path::Path::new("/in"),
+            std::path::Path::new("/out"),
+            None,
+        );
+        opts.extrude = 1;
+        opts.padding = 0;
+        opts.spacing = 1;
+        opts.scale_variants = vec![ScaleVariant::parse("@q=0.25").unwrap()];
+        let s = snapped_options(&opts);
+        assert_eq!(s.extrude + s.padding, 4);
+        assert_eq!(s.spacing, 4);
+        // No variants ⇒ options pass through untouched.
+        opts.scale_variants.clear();
+        let s = snapped_options(&opts);
+        assert_eq!((s.padding, s.spacing), (0, 1));
+    }
+
+    #[test]
+    fn snap_rect_widens_outward_and_clamps() {
+        assert_eq!(snap_rect((3, 5, 6, 2), (16, 16), 4), (0, 4, 12, 4));
+        assert_eq!(snap_rect((13, 0, 3, 3), (16, 16), 4), (12, 0, 4, 4));
+    }
+}
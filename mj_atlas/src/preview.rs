@@ -99,6 +99,43 @@ impl Project {
     }
 }
 
+impl Project {
+    /// GUI view of one atlas from a `mj_atlas build` project file. Sprites
+    /// are the atlas's resolved include/exclude selection at load time.
+    fn from_atlas_job(job: &crate::project::AtlasJob) -> Self {
+        let o = &job.opts;
+        Self {
+            version: 1,
+            output_name: job.name.clone(),
+            output_dir: o.output_dir.display().to_string(),
+            sprites: o
+                .explicit_sprites
+                .iter()
+                .flatten()
+                .map(|p| p.display().to_string())
+                .collect(),
+            settings: ProjectSettings {
+                max_size: o.max_size,
+                spacing: o.spacing,
+                padding: o.padding,
+                extrude: o.extrude,
+                trim: o.trim,
+                trim_threshold: o.trim_threshold,
+                rotate: o.rotate,
+                pot: o.pot,
+                polygon: o.polygon,
+                tolerance: o.tolerance,
+                quantize: o.quantize,
+                quantize_quality: o.quantize_quality,
+                format_idx: FORMAT_NAMES
+                    .iter()
+                    .position(|(name, _)| *name == o.format.as_str())
+                    .unwrap_or(0),
+            },
+        }
+    }
+}
+
 // ─── Sprite info parsed from output ───
 
 struct SpriteInfo {
@@ -163,6 +200,11 @@ enum Dialog {
     License,
     ExportAs,
     UnsavedChanges(Box<MenuAction>),
+    /// A build project with several atlases was opened — pick one to edit.
+    PickBuildAtlas {
+        path: PathBuf,
+        atlases: Vec<Project>,
+    },
 }
 
 // ─── App modes ───
@@ -1237,8 +1279,14 @@ impl MJAtlasApp {
             MenuAction::OpenProject => {
                 if let Some(path) = rfd::FileDialog::new()
                     .add_filter("mj_atlas project", &["tpproj"])
+                    .add_filter("mj_atlas build project", &["toml", "json"])
                     .pick_file()
                 {
+                    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+                    if ext != "tpproj" {
+                        self.open_build_project(path);
+                        return;
+                    }
                     match Project::load(&path) {
                         Ok(project) => {
                             let count = project.sprites.len();
@@ -1316,6 +1364,40 @@ impl MJAtlasApp {
         }
     }
 
+    /// Open a `mj_atlas build` project. Single-atlas projects go straight
+    /// to the packer; otherwise the user picks which atlas to edit. The
+    /// packer state gets no project path, so Save asks for a `.tpproj`
+    /// instead of overwriting the build file.
+    fn open_build_project(&mut self, path: PathBuf) {
+        let project_dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        let loaded = crate::project::BuildProject::load(&path)
+            .and_then(|bp| bp.resolve(&project_dir, &[]));
+        match loaded {
+            Ok(jobs) => {
+                let mut atlases: Vec<Project> = jobs.iter().map(Project::from_atlas_job).collect();
+                if atlases.len() == 1 {
+                    self.open_build_atlas(&path, atlases.remove(0));
+                } else {
+                    self.open_dialog = Some(Dialog::PickBuildAtlas { path, atlases });
+                }
+            }
+            Err(e) => self.toast(format!("Failed to open build project: {}", e), ToastKind::Error),
+        }
+    }
+
+    fn open_build_atlas(&mut self, path: &Path, project: Project) {
+        let mut state = PackerState::from_project(project, PathBuf::new());
+        state.project_path = None;
+        let msg = format!(
+            "Opened atlas '{}' from {} ({} sprites)",
+            state.project.output_name,
+            path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            state.project.sprites.len()
+        );
+        self.mode = AppMode::Packer(state);
+        self.toast(msg, ToastKind::Success);
+    }
+
     fn is_packer_dirty(&self) -> bool {
         matches!(&self.mode, AppMode::Packer(s) if s.dirty)
     }
@@ -1572,6 +1654,31 @@ impl MJAtlasApp {
                             });
                         });
                 }
+                Dialog::PickBuildAtlas { path, atlases } => {
+                    egui::Window::new("Open Atlas")
+                        .collapsible(false)
+                        .resizable(false)
+                        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                        .show(ctx, |ui| {
+                            ui.label(format!("{} declares {} atlases:", path.display(), atlases.len()));
+                            ui.add_space(8.0);
+                            for (i, atlas) in atlases.iter().enumerate() {
+                                let label = format!(
+                                    "  {} ({} sprites)  ",
+                                    atlas.output_name,
+                                    atlas.sprites.len()
+                                );
+                                if ui.button(label).clicked() {
+                                    deferred_action = Some(DeferredDialogAction::OpenBuildAtlas(i));
+                                }
+                                ui.add_space(4.0);
+                            }
+                            ui.add_space(4.0);
+                            if ui.button("Cancel").clicked() {
+                                close_dialog = true;
+                            }
+                        });
+                }
                 Dialog::ExportAs => {
                     egui::Window::new("Export As...")
                         .collapsible(false)
@@ -1622,6 +1729,11 @@ impl MJAtlasApp {
                     }
                     self.handle_menu_action(action);
                 }
+                DeferredDialogAction::OpenBuildAtlas(idx) => {
+                    if let Some(Dialog::PickBuildAtlas { path, mut atlases }) = self.open_dialog.take() {
+                        self.open_build_atlas(&path, atlases.swap_remove(idx));
+                    }
+                }
             }
         }
     }
@@ -2017,6 +2129,13 @@ impl MJAtlasApp {
                 tolerance: s.tolerance,
                 polygon_shape: pack::PolygonShape::Concave,
                 max_vertices: 0,
+                scale_variants: Vec::new(),
+                groups: Vec::new(),
+                animations: None,
+                algorithm: crate::pack::heuristics::Algorithm::default(),
+                alpha_bleed: false,
+                premultiply_alpha: false,
+                channel_pack: false,
             };
 
             let (tx, rx) = std::sync::mpsc::channel();
@@ -2912,6 +3031,8 @@ impl MJAtlasApp {
 enum DeferredDialogAction {
     SaveThenDo(MenuAction),
     Do(MenuAction),
+    /// Index into the open `Dialog::PickBuildAtlas` list.
+    OpenBuildAtlas(usize),
 }
 
 #[derive(Clone)]
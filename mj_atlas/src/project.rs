@@ -0,0 +1,527 @@
+//! Declarative multi-atlas build projects (`mj_atlas build <project>`).
+//!
+//! A build project is a versioned TOML or JSON file describing every atlas a
+//! game ships. Each `[[atlas]]` entry picks its sprites from an input root
+//! via include / exclude globs and carries its own pack options; a
+//! `[defaults]` table supplies options shared by all entries.
+//!
+//! ```toml
+//! version = 1
+//!
+//! [defaults]
+//! max_size = 2048
+//! trim = true
+//! pot = true
+//! output_dir = "build/atlases"
+//!
+//! [[atlas]]
+//! name = "ui"
+//! input = "art/ui"
+//! exclude = ["**/_wip/**"]
+//! options = { format = "godot-tres" }
+//!
+//! [[atlas]]
+//! name = "characters"
+//! input = "art/characters"
+//! include = ["hero/**", "enemies/*.png"]
+//! options = { rotate = true, scale_variants = ["@1x=0.5"] }
+//! ```
+//!
+//! Relative paths resolve against the project file's directory. `name` is
+//! both the report key and the output base name (`ui.png`, `ui.json`, …).
+//! Globs match sprite paths relative to `input` (`/`-separated): `*` and `?`
+//! stay within one path segment, `**` crosses segments, `{a,b}` alternates,
+//! and a pattern without `/` matches the file name at any depth.
+//!
+//! `build` packs every atlas in parallel, each through the incremental
+//! pipeline (manifest sidecar next to the atlas), and emits one aggregated
+//! report. The GUI opens the same file: File → Open Project loads one of
+//! its atlases into the packer.
+
+use crate::error::{AppError, Result};
+use crate::glob;
+use crate::output::Format;
+use crate::pack::{self, PackOptions, PackPath, PolygonShape};
+use rayon::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use walkdir::WalkDir;
+
+/// Current build-project schema version.
+pub const PROJECT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BuildProject {
+    pub version: u32,
+    /// Options applied to every atlas unless the atlas overrides them.
+    #[serde(default)]
+    pub defaults: AtlasOptions,
+    /// `[[atlas]]` in TOML, `"atlas": [...]` in JSON.
+    #[serde(rename = "atlas", default)]
+    pub atlases: Vec<AtlasSpec>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AtlasSpec {
+    /// Report key and output base name.
+    pub name: String,
+    /// Sprite root directory. Sprite names are paths relative to it.
+    pub input: String,
+    /// Globs selecting sprites under `input`. Empty = every image.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Globs removing sprites selected by `include`.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub options: AtlasOptions,
+}
+
+/// Pack options as written in a project file. Every field is optional so
+/// `[defaults]` and per-atlas `options` can be layered; anything left unset
+/// falls back to the `pack` CLI default (except `incremental`, which
+/// defaults to on for builds).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AtlasOptions {
+    pub output_dir: Option<String>,
+    pub format: Option<String>,
+    pub max_size: Option<usize>,
+    pub spacing: Option<u32>,
+    pub padding: Option<u32>,
+    pub extrude: Option<u32>,
+    pub trim: Option<bool>,
+    pub trim_threshold: Option<u8>,
+    pub rotate: Option<bool>,
+    pub pot: Option<bool>,
+    pub incremental: Option<bool>,
+    pub quantize: Option<bool>,
+    pub quantize_quality: Option<u8>,
+    pub polygon: Option<bool>,
+    pub tolerance: Option<f32>,
+    pub polygon_shape: Option<String>,
+    pub max_vertices: Option<u32>,
+    pub scale_variants: Option<Vec<String>>,
+    /// Grouping rules (`NAME=dir:PATH`, `NAME=glob:PATTERN`, `NAME=tag:TAG`).
+    pub groups: Option<Vec<String>>,
+    /// Animation definition file, relative to the project file.
+    pub animations: Option<String>,
+    /// Packing heuristic (`maxrects`, `skyline`, …, or `optimize`).
+    pub algorithm: Option<String>,
+    pub alpha_bleed: Option<bool>,
+    pub premultiply_alpha: Option<bool>,
+    pub channel_pack: Option<bool>,
+}
+
+impl AtlasOptions {
+    /// Field-wise `self.or(base)`.
+    fn layered_over(&self, base: &AtlasOptions) -> AtlasOptions {
+        macro_rules! pick {
+            ($($f:ident),*) => {
+                AtlasOptions { $($f: self.$f.clone().or_else(|| base.$f.clone())),* }
+            };
+        }
+        pick!(
+            output_dir, format, max_size, spacing, padding, extrude, trim, trim_threshold,
+            rotate, pot, incremental, quantize, quantize_quality, polygon, tolerance,
+            polygon_shape, max_vertices, scale_variants, groups, animations, algorithm,
+            alpha_bleed, premultiply_alpha, channel_pack
+        )
+    }
+}
+
+/// One atlas of a project, resolved to concrete pack options.
+#[derive(Debug, Clone)]
+pub struct AtlasJob {
+    pub name: String,
+    pub opts: PackOptions,
+}
+
+impl BuildProject {
+    /// Load a project file. `.toml` parses as TOML, anything else as JSON.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let is_toml = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("toml"));
+        let project: BuildProject = if is_toml {
+            toml::from_str(&content).map_err(|e| {
+                AppError::Custom(format!("project parse error ({}): {}", path.display(), e))
+            })?
+        } else {
+            serde_json::from_str(&content).map_err(|e| {
+                AppError::Custom(format!("project parse error ({}): {}", path.display(), e))
+            })?
+        };
+        project.validate()?;
+        Ok(project)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.version != PROJECT_VERSION {
+            return Err(AppError::InvalidParam(format!(
+                "unsupported project version {} (expected {})",
+                self.version, PROJECT_VERSION
+            )));
+        }
+        if self.atlases.is_empty() {
+            return Err(AppError::InvalidParam("project declares no atlases".into()));
+        }
+        let mut seen = std::collections::HashSet::new();
+        for spec in &self.atlases {
+            if spec.name.is_empty() || spec.name.contains(['/', '\\']) {
+                return Err(AppError::InvalidParam(format!(
+                    "atlas name '{}' must be a non-empty file name",
+                    spec.name
+                )));
+            }
+            if !seen.insert(spec.name.as_str()) {
+                return Err(AppError::InvalidParam(format!(
+                    "duplicate atlas name '{}'",
+                    spec.name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve every atlas (or only those named in `only`) into pack options
+    /// with an explicit sprite list. `project_dir` anchors relative paths.
+    pub fn resolve(&self, project_dir: &Path, only: &[String]) -> Result<Vec<AtlasJob>> {
+        for name in only {
+            if !self.atlases.iter().any(|a| &a.name == name) {
+                return Err(AppError::InvalidParam(format!(
+                    "no atlas named '{}' in project",
+                    name
+                )));
+            }
+        }
+
+        let mut jobs = Vec::new();
+        for spec in &self.atlases {
+            if !only.is_empty() && !only.contains(&spec.name) {
+                continue;
+            }
+            jobs.push(spec.resolve(&self.defaults, project_dir)?);
+        }
+
+        // Two atlases writing the same files would clobber each other's
+        // manifest and silently break incremental caching for both.
+        let mut outputs = std::collections::HashMap::new();
+        for job in &jobs {
+            let key = job.opts.output_dir.join(&job.opts.output_name);
+            if let Some(other) = outputs.insert(key, &job.name) {
+                return Err(AppError::InvalidParam(format!(
+                    "atlases '{}' and '{}' write to the same output",
+                    other, job.name
+                )));
+            }
+        }
+        Ok(jobs)
+    }
+}
+
+impl AtlasSpec {
+    fn resolve(&self, defaults: &AtlasOptions, project_dir: &Path) -> Result<AtlasJob> {
+        let o = self.options.layered_over(defaults);
+        let invalid = |what: String| AppError::InvalidParam(format!("atlas '{}': {}", self.name, what));
+
+        let format = match &o.format {
+            Some(f) => Format::parse(f).ok_or_else(|| invalid(format!("unknown format '{}'", f)))?,
+            None => Format::JsonHash,
+        };
+        let polygon_shape = match &o.polygon_shape {
+            Some(s) => PolygonShape::parse(s)
+                .ok_or_else(|| invalid(format!("unknown polygon_shape '{}'", s)))?,
+            None => PolygonShape::Concave,
+        };
+        let algorithm = match &o.algorithm {
+            Some(a) => pack::heuristics::Algorithm::parse(a)
+                .ok_or_else(|| invalid(format!("unknown algorithm '{}'", a)))?,
+            None => pack::heuristics::Algorithm::default(),
+        };
+        let scale_variants = o
+            .scale_variants
+            .iter()
+            .flatten()
+            .map(|v| pack::variants::ScaleVariant::parse(v).map_err(|e| invalid(e.to_string())))
+            .collect::<Result<Vec<_>>>()?;
+
+        let groups = o
+            .groups
+            .iter()
+            .flatten()
+            .map(|g| pack::groups::GroupRule::parse(g).map_err(|e| invalid(e.to_string())))
+            .collect::<Result<Vec<_>>>()?;
+
+        let input_dir = project_dir.join(&self.input);
+        if !input_dir.is_dir() {
+            return Err(invalid(format!("input '{}' is not a directory", input_dir.display())));
+        }
+        let include = compile_globs(&self.include).map_err(|e| invalid(e.to_string()))?;
+        let exclude = compile_globs(&self.exclude).map_err(|e| invalid(e.to_string()))?;
+        let sprites = select_sprites(&input_dir, &include, &exclude)?;
+        if sprites.is_empty() {
+            return Err(AppError::NoImages(format!(
+                "{} (atlas '{}' include/exclude matched nothing)",
+                input_dir.display(),
+                self.name
+            )));
+        }
+
+        let opts = PackOptions {
+            input_dir,
+            output_name: self.name.clone(),
+            output_dir: project_dir.join(o.output_dir.as_deref().unwrap_or(".")),
+            max_size: o.max_size.unwrap_or(4096),
+            spacing: o.spacing.unwrap_or(0),
+            padding: o.padding.unwrap_or(0),
+            extrude: o.extrude.unwrap_or(0),
+            trim: o.trim.unwrap_or(false),
+            trim_threshold: o.trim_threshold.unwrap_or(0),
+            rotate: o.rotate.unwrap_or(false),
+            pot: o.pot.unwrap_or(false),
+            recursive: true,
+            explicit_sprites: Some(sprites),
+            incremental: o.incremental.unwrap_or(true),
+            force: false,
+            format,
+            quantize: o.quantize.unwrap_or(false),
+            quantize_quality: o.quantize_quality.unwrap_or(85),
+            polygon: o.polygon.unwrap_or(false),
+            tolerance: o.tolerance.unwrap_or(2.0),
+            polygon_shape,
+            max_vertices: o.max_vertices.unwrap_or(0),
+            scale_variants,
+            groups,
+            animations: o.animations.as_ref().map(|a| project_dir.join(a)),
+            algorithm,
+            alpha_bleed: o.alpha_bleed.unwrap_or(false),
+            premultiply_alpha: o.premultiply_alpha.unwrap_or(false),
+            channel_pack: o.channel_pack.unwrap_or(false),
+        };
+        Ok(AtlasJob {
+            name: self.name.clone(),
+            opts,
+        })
+    }
+}
+
+fn select_sprites(input_dir: &Path, include: &[Regex], exclude: &[Regex]) -> Result<Vec<PathBuf>> {
+    let mut sprites = Vec::new();
+    for entry in WalkDir::new(input_dir).follow_links(true) {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path();
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+        if !pack::IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+            continue;
+        }
+        let rel = path
+            .strip_prefix(input_dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let included = include.is_empty() || include.iter().any(|g| g.is_match(&rel));
+        if included && !exclude.iter().any(|g| g.is_match(&rel)) {
+            sprites.push(path.to_path_buf());
+        }
+    }
+    sprites.sort();
+    Ok(sprites)
+}
+
+fn compile_globs(patterns: &[String]) -> Result<Vec<Regex>> {
+    patterns.iter().map(|p| glob::to_regex(p)).collect()
+}
+
+/// Outcome of building one atlas.
+struct AtlasBuild {
+    name: String,
+    duration_ms: u64,
+    outcome: Result<(Vec<pack::AtlasResult>, PackPath)>,
+}
+
+fn build_one(job: &AtlasJob) -> AtlasBuild {
+    let started = Instant::now();
+    let outcome = (|| {
+        let (results, path) = pack::execute_with_path(&job.opts)?;
+        for atlas_result in &results {
+            atlas_result.save_to_disk(&job.opts, job.opts.format)?;
+        }
+        pack::persist_manifest(&job.opts, &results)?;
+        Ok((results, path))
+    })();
+    AtlasBuild {
+        name: job.name.clone(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        outcome,
+    }
+}
+
+/// `mj_atlas build`: pack every atlas of the project and report. Atlases
+/// are independent, so one failure doesn't stop the others — the command
+/// fails at the end if any atlas did.
+pub fn build(
+    project_path: &Path,
+    only: &[String],
+    force: bool,
+    jobs: Option<usize>,
+    json: bool,
+) -> Result<()> {
+    let started = Instant::now();
+    let project = BuildProject::load(project_path)?;
+    let project_dir = project_path.parent().unwrap_or(Path::new("."));
+    let mut atlas_jobs = project.resolve(project_dir, only)?;
+    for job in &mut atlas_jobs {
+        job.opts.force = force;
+    }
+    log::info!(
+        "build: {} atlas(es) from {}",
+        atlas_jobs.len(),
+        project_path.display()
+    );
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs.unwrap_or(0))
+        .build()
+        .map_err(|e| AppError::Custom(format!("build: thread pool: {}", e)))?;
+    let builds: Vec<AtlasBuild> = pool.install(|| atlas_jobs.par_iter().map(build_one).collect());
+
+    let failed = builds.iter().filter(|b| b.outcome.is_err()).count();
+    let skipped = builds
+        .iter()
+        .filter(|b| matches!(b.outcome, Ok((_, PackPath::Skipped))))
+        .count();
+
+    if json {
+        let report = serde_json::json!({
+            "status": if failed == 0 { "ok" } else { "error" },
+            "project": project_path.display().to_string(),
+            "built": builds.len() - failed,
+            "failed": failed,
+            "skipped": skipped,
+            "duration_ms": started.elapsed().as_millis() as u64,
+            "atlases": builds.iter().map(|b| match &b.outcome {
+                Ok((results, path)) => serde_json::json!({
+                    "name": b.name,
+                    "status": "ok",
+                    "path": path.as_str(),
+                    "total_sprites": results.iter().map(|r| r.sprites.len()).sum::<usize>(),
+                    "duration_ms": b.duration_ms,
+                    "files": crate::output::files_summary(results),
+                }),
+                Err(e) => serde_json::json!({
+                    "name": b.name,
+                    "status": "error",
+                    "error": e.to_string(),
+                    "duration_ms": b.duration_ms,
+                }),
+            }).collect::<Vec<_>>(),
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        for b in &builds {
+            match &b.outcome {
+                Ok((results, path)) => log::info!(
+                    "build: {} — {} ({} atlas(es), {} sprites, {}ms)",
+                    b.name,
+                    path.as_str(),
+                    results.len(),
+                    results.iter().map(|r| r.sprites.len()).sum::<usize>(),
+                    b.duration_ms
+                ),
+                Err(e) => log::error!("build: {} — FAILED: {}", b.name, e),
+            }
+        }
+        log::info!(
+            "build: {} ok ({} unchanged), {} failed",
+            builds.len() - failed,
+            skipped,
+            failed
+        );
+    }
+
+    if failed > 0 {
+        return Err(AppError::Custom(format!(
+            "build: {} of {} atlas(es) failed",
+            failed,
+            builds.len()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pack::tests::write_png;
+
+    #[test]
+    fn resolves_layered_options_and_sprite_selection() {
+        let tmp = std::env::temp_dir().join(format!("mj_atlas_project_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&tmp);
+        std::fs::create_dir_all(tmp.join("art/ui/_wip")).unwrap();
+        write_png(&tmp.join("art/ui/button.png"), 8, 8, [255, 0, 0, 255]);
+        write_png(&tmp.join("art/ui/_wip/draft.png"), 8, 8, [0, 255, 0, 255]);
+        let path = tmp.join("atlases.toml");
+        std::fs::write(
+            &path,
+            r#"
+version = 1
+
+[defaults]
+trim = true
+max_size = 1024
+output_dir = "build"
+
+[[atlas]]
+name = "ui"
+input = "art/ui"
+exclude = ["**/_wip/**"]
+options = { format = "godot-tres", max_size = 512 }
+
+[[atlas]]
+name = "all"
+input = "art"
+"#,
+        )
+        .unwrap();
+
+        let project = BuildProject::load(&path).unwrap();
+        let jobs = project.resolve(&tmp, &[]).unwrap();
+        assert_eq!(jobs.len(), 2);
+        let ui = &jobs[0].opts;
+        assert_eq!(ui.format, Format::GodotTres);
+        assert_eq!(ui.max_size, 512);
+        assert!(ui.trim && ui.incremental);
+        assert_eq!(ui.output_dir, tmp.join("build"));
+        assert_eq!(ui.explicit_sprites, Some(vec![tmp.join("art/ui/button.png")]));
+        assert_eq!(jobs[1].opts.explicit_sprites.as_ref().unwrap().len(), 2);
+
+        assert!(project.resolve(&tmp, &["nope".into()]).is_err());
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn rejects_unknown_fields_and_duplicate_names() {
+        let bad_field = r#"{"version": 1, "atlas": [{"name": "a", "input": ".", "options": {"trimm": true}}]}"#;
+        assert!(serde_json::from_str::<BuildProject>(bad_field).is_err());
+        let dup: BuildProject = serde_json::from_str(
+            r#"{"version": 1, "atlas": [{"name": "a", "input": "x"}, {"name": "a", "input": "y"}]}"#,
+        )
+        .unwrap();
+        assert!(dup.validate().is_err());
+    }
+}
@@ -0,0 +1,406 @@
+//! `mj_atlas watch` — keep an atlas up to date while sprites are being edited.
+//!
+//! The incremental pipeline (`Manifest::try_load` → `diff_inputs` → partial
+//! repack via `try_fit`) is cheap enough to run on every save, so watch mode
+//! is just a loop around it:
+//!
+//!   1. Pack once at startup (honoring `--force` for this first run only).
+//!   2. Wait for a filesystem change under the input directory. Our own
+//!      outputs (`<output>.png`, `.json`, `.manifest.json`, `.log`, the
+//!      `.sprites/` dir, variants) are ignored so a pack can't retrigger itself.
+//!   3. Keep collecting changes until the tree has been quiet for the
+//!      debounce window — an editor saving ten frames packs once, not ten times.
+//!   4. Run the incremental pack and report the outcome as one event.
+//!
+//! Events go to stdout: one JSON object per line with `--json` (easy to
+//! consume from an editor plugin or the Godot SDK for hot-reload), INFO log
+//! lines otherwise. A failed run reports an `error` event and watching
+//! continues — a half-saved PNG shouldn't kill the session.
+//!
+//! The `<output>.log` sidecar is rewritten after every run, so it always
+//! describes the most recent pack. The hfrog mirror is NOT triggered from
+//! watch mode; run a regular `pack` to publish.
+
+use crate::cmd::diff;
+use crate::error::{AppError, Result};
+use crate::pack::manifest::Manifest;
+use crate::pack::{self, PackOptions};
+use crate::runlog;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+pub fn run(
+    opts: &PackOptions,
+    debounce: Duration,
+    json: bool,
+    log_path: &Path,
+    header: &[String],
+) -> Result<()> {
+    // Watch mode is built on the manifest; there is nothing to watch for
+    // without it.
+    let mut opts = PackOptions {
+        incremental: true,
+        ..opts.clone()
+    };
+
+    // Canonical roots: notify reports absolute paths rooted at whatever we
+    // asked it to watch, so canonicalizing up front makes prefix checks exact.
+    let input_root = opts.input_dir.canonicalize()?;
+    std::fs::create_dir_all(&opts.output_dir)?;
+    let filter = OutputFilter {
+        output_root: opts.output_dir.canonicalize()?,
+        output_name: opts.output_name.clone(),
+        variant_suffixes: opts
+            .scale_variants
+            .iter()
+            .map(|v| v.suffix.clone())
+            .collect(),
+    };
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(watch_error)?;
+    let mode = if opts.recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    watcher.watch(&input_root, mode).map_err(watch_error)?;
+    log::info!(
+        "watch: watching {} (debounce {}ms, Ctrl-C to stop)",
+        input_root.display(),
+        debounce.as_millis()
+    );
+
+    let mut run_idx = 0usize;
+    report(run_once(&opts, run_idx, &[]), run_idx, json);
+    runlog::flush(log_path, header);
+    opts.force = false;
+
+    while let Some(changed) = next_batch(&rx, debounce, |p| filter.is_relevant(p)) {
+        run_idx += 1;
+        let trigger: Vec<String> = changed
+            .iter()
+            .map(|p| {
+                p.strip_prefix(&input_root)
+                    .unwrap_or(p)
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+        report(run_once(&opts, run_idx, &trigger), run_idx, json);
+        runlog::flush(log_path, header);
+    }
+
+    // The sender lives inside the watcher, so this only happens if the
+    // backend shut down on its own.
+    Err(AppError::Custom("watch: file watcher stopped unexpectedly".into()))
+}
+
+fn watch_error(e: notify::Error) -> AppError {
+    AppError::Custom(format!("watch: {}", e))
+}
+
+/// Recognizes files written by the pack itself.
+struct OutputFilter {
+    output_root: PathBuf,
+    output_name: String,
+    variant_suffixes: Vec<String>,
+}
+
+impl OutputFilter {
+    /// A path is ours only if its top-level name under the output dir is a
+    /// generated artifact: `atlas.png`, `atlas_1.json`, `atlas@1x.png`,
+    /// `atlas.tpsheet`, `atlas.manifest.json`, `atlas.log`, `atlas.sprites/...`.
+    /// Inputs that merely share the prefix (`atlas_icon.png`, `atlas_ui/`)
+    /// still trigger a pack.
+    fn is_relevant(&self, path: &Path) -> bool {
+        match path.strip_prefix(&self.output_root) {
+            Ok(rel) => match rel.components().next() {
+                Some(first) => !self.is_artifact(&first.as_os_str().to_string_lossy()),
+                None => true,
+            },
+            Err(_) => true,
+        }
+    }
+
+    fn is_artifact(&self, name: &str) -> bool {
+        let Some(rest) = name.strip_prefix(&self.output_name) else {
+            return false;
+        };
+        if rest == ".manifest.json" || rest == ".log" {
+            return true;
+        }
+        std::iter::once(rest)
+            .chain(
+                self.variant_suffixes
+                    .iter()
+                    .filter_map(|suffix| rest.strip_prefix(suffix.as_str())),
+            )
+            .any(is_page_artifact)
+    }
+}
+
+/// `<bin>.png|json|tpsheet` or `.sprites`, where `<bin>` is empty or `_<n>`
+/// — what's left of an atlas file name after the output name and variant
+/// suffix.
+fn is_page_artifact(rest: &str) -> bool {
+    if rest == ".sprites" {
+        return true;
+    }
+    let Some((bin, ext)) = rest.rsplit_once('.') else {
+        return false;
+    };
+    let bin_ok = bin.is_empty()
+        || bin
+            .strip_prefix('_')
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+    bin_ok && matches!(ext, "png" | "json" | "tpsheet")
+}
+
+/// Block until at least one relevant change arrives, then keep absorbing
+/// changes until `debounce` passes with none. Returns the changed paths
+/// (sorted, deduplicated), or `None` once the channel is closed.
+fn next_batch(
+    rx: &Receiver<notify::Result<Event>>,
+    debounce: Duration,
+    is_relevant: impl Fn(&Path) -> bool,
+) -> Option<Vec<PathBuf>> {
+    let mut changed: BTreeSet<PathBuf> = BTreeSet::new();
+    while changed.is_empty() {
+        absorb(rx.recv().ok()?, &mut changed, &is_relevant);
+    }
+    loop {
+        match rx.recv_timeout(debounce) {
+            Ok(event) => absorb(event, &mut changed, &is_relevant),
+            Err(RecvTimeoutError::Timeout) => break,
+            // Deliver what we have; the next call reports the closed channel.
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    Some(changed.into_iter().collect())
+}
+
+fn absorb(
+    event: notify::Result<Event>,
+    changed: &mut BTreeSet<PathBuf>,
+    is_relevant: &impl Fn(&Path) -> bool,
+) {
+    let event = match event {
+        Ok(e) => e,
+        Err(e) => {
+            log::warn!("watch: {}", e);
+            return;
+        }
+    };
+    // Reads (including our own hashing of the sprites) and metadata-only
+    // touches can't change pack output — size + pixel hashes decide that.
+    if matches!(
+        event.kind,
+        EventKind::Access(_) | EventKind::Modify(notify::event::ModifyKind::Metadata(_))
+    ) {
+        return;
+    }
+    changed.extend(event.paths.into_iter().filter(|p| is_relevant(p)));
+}
+
+/// Run one incremental pack and describe it as a `pack` event.
+fn run_once(opts: &PackOptions, run_idx: usize, trigger: &[String]) -> Result<serde_json::Value> {
+    let started = Instant::now();
+    let manifest_path = Manifest::path_for(opts);
+    let before = Manifest::try_load(&manifest_path)?;
+
+    let (results, path) = pack::execute_with_path(opts)?;
+    for atlas_result in &results {
+        atlas_result.save_to_disk(opts, opts.format)?;
+    }
+    pack::persist_manifest(opts, &results)?;
+
+    let after = Manifest::try_load(&manifest_path)?
+        .ok_or_else(|| AppError::Custom(format!("{} not loadable", manifest_path.display())))?;
+
+    // With no previous manifest everything is new and there's nothing whose
+    // UVs could have moved — `uv_stable` is null rather than a vacuous true.
+    let (added, modified, removed, moved, uv_stable) = match &before {
+        Some(before) => {
+            let d = diff::compute(before, &after);
+            let mut modified = d.pixel_change;
+            modified.extend(d.resized.iter().map(|r| r.name.clone()));
+            modified.sort();
+            let moved: Vec<String> = d.moved.iter().map(|m| m.name.clone()).collect();
+            (d.added, modified, d.removed, moved, serde_json::json!(d.uv_stable))
+        }
+        None => {
+            let mut added: Vec<String> = after.sprites.keys().cloned().collect();
+            added.sort();
+            (added, Vec::new(), Vec::new(), Vec::new(), serde_json::Value::Null)
+        }
+    };
+
+    Ok(serde_json::json!({
+        "event": "pack",
+        "run": run_idx,
+        "status": "ok",
+        "trigger": trigger,
+        "path": path.as_str(),
+        "added": added,
+        "modified": modified,
+        "removed": removed,
+        "moved": moved,
+        "uv_stable": uv_stable,
+        "atlases": results.len(),
+        "total_sprites": results.iter().map(|r| r.sprites.len()).sum::<usize>(),
+        "duration_ms": started.elapsed().as_millis() as u64,
+        "files": crate::output::files_summary(&results),
+    }))
+}
+
+fn report(outcome: Result<serde_json::Value>, run_idx: usize, json: bool) {
+    match outcome {
+        Ok(event) => {
+            if json {
+                println!("{}", event);
+            } else {
+                let count = |key: &str| event[key].as_array().map_or(0, |a| a.len());
+                log::info!(
+                    "watch: run {} — {} ({} added, {} modified, {} removed, UV {}) in {}ms",
+                    run_idx,
+                    event["path"].as_str().unwrap_or("?"),
+                    count("added"),
+                    count("modified"),
+                    count("removed"),
+                    match event["uv_stable"].as_bool() {
+                        Some(true) => "stable",
+                        Some(false) => "BROKEN",
+                        None => "n/a",
+                    },
+                    event["duration_ms"]
+                );
+            }
+        }
+        Err(e) => {
+            log::error!("watch: run {} failed: {}", run_idx, e);
+            if json {
+                println!(
+                    "{}",
+                    serde_json::json!({
+                        "event": "error",
+                        "run": run_idx,
+                        "status": "error",
+                        "error": e.to_string(),
+                    })
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pack::tests::{make_opts, write_png};
+
+    fn modify_event(path: &Path) -> notify::Result<Event> {
+        Ok(Event::new(EventKind::Modify(notify::event::ModifyKind::Any)).add_path(path.to_path_buf()))
+    }
+
+    #[test]
+    fn burst_is_debounced_and_outputs_are_ignored() {
+        let filter = OutputFilter {
+            output_root: PathBuf::from("/proj/sprites"),
+            output_name: "atlas".into(),
+            variant_suffixes: Vec::new(),
+        };
+        let (tx, rx) = std::sync::mpsc::channel();
+        tx.send(modify_event(Path::new("/proj/sprites/atlas.png"))).unwrap();
+        tx.send(modify_event(Path::new("/proj/sprites/atlas.manifest.json"))).unwrap();
+        tx.send(modify_event(Path::new("/proj/sprites/atlas.sprites/a.tres"))).unwrap();
+        for _ in 0..5 {
+            tx.send(modify_event(Path::new("/proj/sprites/walk_01.png"))).unwrap();
+        }
+        tx.send(Ok(Event::new(EventKind::Access(notify::event::AccessKind::Any))
+            .add_path(PathBuf::from("/proj/sprites/idle.png"))))
+        .unwrap();
+        tx.send(modify_event(Path::new("/proj/sprites/sub/atlas_icon.png"))).unwrap();
+        drop(tx);
+
+        let batch = next_batch(&rx, Duration::from_millis(10), |p| filter.is_relevant(p)).unwrap();
+        assert_eq!(
+            batch,
+            vec![
+                PathBuf::from("/proj/sprites/sub/atlas_icon.png"),
+                PathBuf::from("/proj/sprites/walk_01.png"),
+            ]
+        );
+        assert!(next_batch(&rx, Duration::from_millis(10), |p| filter.is_relevant(p)).is_none());
+    }
+
+    #[test]
+    fn only_generated_artifacts_are_ignored() {
+        let filter = OutputFilter {
+            output_root: PathBuf::from("/proj/sprites"),
+            output_name: "atlas".into(),
+            variant_suffixes: vec!["@sd".into()],
+        };
+        let root = Path::new("/proj/sprites");
+        for ours in [
+            "atlas.png",
+            "atlas.json",
+            "atlas_1.png",
+            "atlas_12.tpsheet",
+            "atlas@sd.png",
+            "atlas@sd_1.json",
+            "atlas.manifest.json",
+            "atlas.log",
+            "atlas.sprites/a.tres",
+            "atlas@sd.sprites/a.tres",
+        ] {
+            assert!(!filter.is_relevant(&root.join(ours)), "{ours}");
+        }
+        for input in [
+            "atlas_icon.png",
+            "atlas_ui/button.png",
+            "atlas_.png",
+            "atlas@hd.png",
+            "atlas2.png",
+            "walk_01.png",
+        ] {
+            assert!(filter.is_relevant(&root.join(input)), "{input}");
+        }
+    }
+
+    #[test]
+    fn run_once_reports_partial_repack_and_changes() {
+        let tmp = std::env::temp_dir().join(format!("mj_atlas_watch_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&tmp);
+        let input = tmp.join("in");
+        let out = tmp.join("out");
+        std::fs::create_dir_all(&input).unwrap();
+        write_png(&input.join("a.png"), 16, 16, [255, 0, 0, 255]);
+        write_png(&input.join("b.png"), 8, 8, [0, 255, 0, 255]);
+
+        let mut opts = make_opts(&input, &out, None);
+        opts.incremental = true;
+
+        let first = run_once(&opts, 0, &[]).unwrap();
+        assert_eq!(first["path"], "full");
+        assert_eq!(first["added"], serde_json::json!(["a.png", "b.png"]));
+        assert!(first["uv_stable"].is_null());
+
+        let idle = run_once(&opts, 1, &[]).unwrap();
+        assert_eq!(idle["path"], "skipped");
+
+        write_png(&input.join("b.png"), 8, 8, [0, 0, 255, 255]);
+        write_png(&input.join("c.png"), 4, 4, [9, 9, 9, 255]);
+        let second = run_once(&opts, 2, &["b.png".into(), "c.png".into()]).unwrap();
+        assert_eq!(second["path"], "partial");
+        assert_eq!(second["added"], serde_json::json!(["c.png"]));
+        assert_eq!(second["modified"], serde_json::json!(["b.png"]));
+        assert_eq!(second["uv_stable"], true);
+        assert_eq!(second["trigger"], serde_json::json!(["b.png", "c.png"]));
+
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+}